@@ -0,0 +1,176 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ezkl::circuit::layouts::{conv, conv_im2col};
+use ezkl::circuit::region::RegionSettings;
+use ezkl::circuit::*;
+use ezkl::pfsys::create_keys;
+use ezkl::pfsys::create_proof_circuit;
+use ezkl::pfsys::srs::gen_srs;
+use ezkl::pfsys::TranscriptType;
+use ezkl::tensor::*;
+use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+use halo2_proofs::poly::kzg::multiopen::ProverSHPLONK;
+use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2curves::bn256::{Bn256, Fr};
+use rand::rngs::OsRng;
+use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+
+const K: usize = 17;
+const KERNEL_SIZE: usize = 3;
+const IN_CHANNELS: usize = 2;
+const OUT_CHANNELS: usize = 2;
+
+#[derive(Clone, Debug)]
+struct MyCircuit {
+    image: ValTensor<Fr>,
+    kernel: ValTensor<Fr>,
+    bias: ValTensor<Fr>,
+    im2col: bool,
+}
+
+impl MyCircuit {
+    fn layout_conv(
+        &self,
+        config: &BaseConfig<Fr>,
+        region: &mut region::RegionCtx<Fr>,
+    ) -> Result<ValTensor<Fr>, CircuitError> {
+        let values = [self.image.clone(), self.kernel.clone(), self.bias.clone()];
+        let layout = if self.im2col {
+            conv_im2col::<Fr>
+        } else {
+            conv::<Fr>
+        };
+        layout(
+            config,
+            region,
+            &values,
+            &[(1, 1); 2],
+            &[1; 2],
+            1,
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+        )
+    }
+
+    /// Number of rows the conv occupies when laid out in a dummy region
+    fn num_rows(&self) -> usize {
+        let config = BaseConfig::dummy(K, 2);
+        let mut region = region::RegionCtx::new_dummy(0, 2, RegionSettings::all_true(65536, 4));
+        self.layout_conv(&config, &mut region).unwrap();
+        region.row()
+    }
+}
+
+impl Circuit<Fr> for MyCircuit {
+    type Config = BaseConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let len = 10;
+
+        let a = VarTensor::new_advice(cs, K, 1, len * len);
+
+        let b = VarTensor::new_advice(cs, K, 1, len * len);
+
+        let output = VarTensor::new_advice(cs, K, 1, (len + 1) * len);
+
+        Self::Config::configure(cs, &[a, b], &output, CheckMode::UNSAFE)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "",
+            |region| {
+                let mut region = region::RegionCtx::new(region, 0, 1, 1024, 2);
+                self.layout_conv(&config, &mut region).unwrap();
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn runcnvrl(c: &mut Criterion) {
+    let mut group = c.benchmark_group("accum_conv_im2col");
+
+    let params = gen_srs::<KZGCommitmentScheme<_>>(K as u32);
+
+    for size in [4, 8, 16].iter() {
+        let mut image =
+            Tensor::from((0..IN_CHANNELS * size * size).map(|_| Value::known(Fr::random(OsRng))));
+        image.reshape(&[1, IN_CHANNELS, *size, *size]).unwrap();
+        let mut kernel = Tensor::from(
+            (0..OUT_CHANNELS * IN_CHANNELS * KERNEL_SIZE * KERNEL_SIZE).map(|_| Fr::random(OsRng)),
+        );
+        kernel
+            .reshape(&[OUT_CHANNELS, IN_CHANNELS, KERNEL_SIZE, KERNEL_SIZE])
+            .unwrap();
+        kernel.set_visibility(&ezkl::graph::Visibility::Private);
+
+        let mut bias = Tensor::from((0..OUT_CHANNELS).map(|_| Fr::random(OsRng)));
+        bias.set_visibility(&ezkl::graph::Visibility::Private);
+
+        for im2col in [false, true] {
+            let circuit = MyCircuit {
+                image: ValTensor::from(image.clone()),
+                kernel: ValTensor::try_from(kernel.clone()).unwrap(),
+                bias: ValTensor::try_from(bias.clone()).unwrap(),
+                im2col,
+            };
+            let name = if im2col { "im2col" } else { "naive" };
+
+            let pk = create_keys::<KZGCommitmentScheme<Bn256>, MyCircuit>(&circuit, &params, true)
+                .unwrap();
+
+            // the rows each layout occupies, reported by criterion as rows proven per second
+            group.throughput(Throughput::Elements(circuit.num_rows() as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &_| {
+                b.iter(|| {
+                    let prover = create_proof_circuit::<
+                        KZGCommitmentScheme<_>,
+                        MyCircuit,
+                        ProverSHPLONK<_>,
+                        VerifierSHPLONK<_>,
+                        SingleStrategy<_>,
+                        _,
+                        EvmTranscript<_, _, _, _>,
+                        EvmTranscript<_, _, _, _>,
+                    >(
+                        circuit.clone(),
+                        vec![],
+                        &params,
+                        &pk,
+                        CheckMode::UNSAFE,
+                        ezkl::Commitments::KZG,
+                        TranscriptType::EVM,
+                        None,
+                        None,
+                    );
+                    prover.unwrap();
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().with_plots();
+  targets = runcnvrl
+}
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ezkl::fieldutils::IntegerRep;
+use ezkl::graph::utilities::{instances_to_integer_rep_tensor, integer_rep_tensor_to_instances};
+use ezkl::tensor::Tensor;
+use halo2curves::bn256::Fr;
+use rand::Rng;
+
+fn runinstanceconversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("instance_conversion");
+
+    let mut rng = rand::thread_rng();
+
+    for &len in [1_000, 100_000, 1_000_000].iter() {
+        let output = Tensor::<IntegerRep>::from((0..len).map(|_| rng.gen_range(-32768..32768)));
+        let instances = integer_rep_tensor_to_instances::<Fr>(&output);
+
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("to_instances", len), &len, |b, &_| {
+            b.iter(|| integer_rep_tensor_to_instances::<Fr>(black_box(&output)));
+        });
+        group.bench_with_input(BenchmarkId::new("from_instances", len), &len, |b, &_| {
+            b.iter(|| instances_to_integer_rep_tensor(black_box(&instances), &[len]).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().with_plots();
+  targets = runinstanceconversion
+}
+criterion_main!(benches);
@@ -0,0 +1,189 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ezkl::circuit::poly::PolyOp;
+use ezkl::circuit::region::RegionCtx;
+use ezkl::circuit::table::Range;
+use ezkl::circuit::{ops::lookup::LookupOp, BaseConfig as Config, CheckMode, Op};
+use ezkl::fieldutils::IntegerRep;
+use ezkl::pfsys::create_proof_circuit;
+use ezkl::pfsys::TranscriptType;
+use ezkl::pfsys::{create_keys, srs::gen_srs};
+use ezkl::tensor::*;
+use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2curves::bn256::{Bn256, Fr};
+use rand::Rng;
+use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+
+const LEN: usize = 8;
+const K: usize = 16;
+static mut BITS: Range = (-128, 128);
+
+fn table_relu() -> LookupOp {
+    LookupOp::ThresholdedReLU {
+        scale: 1.0.into(),
+        a: 0.0.into(),
+    }
+}
+
+// relu either through the sign-bit decomposition, which only needs small range checks, or
+// through a lookup table that has to cover every input in BITS
+#[derive(Clone)]
+struct ReLUCircuit {
+    pub input: ValTensor<Fr>,
+    pub use_table: bool,
+}
+
+#[derive(Clone, Default)]
+struct ReLUParams {
+    use_table: bool,
+}
+
+impl Circuit<Fr> for ReLUCircuit {
+    type Config = Config<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ReLUParams;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn params(&self) -> Self::Params {
+        ReLUParams {
+            use_table: self.use_table,
+        }
+    }
+
+    fn configure_with_params(cs: &mut ConstraintSystem<Fr>, params: Self::Params) -> Self::Config {
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+            .collect::<Vec<_>>();
+
+        let mut config = Config::default();
+
+        if params.use_table {
+            config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    unsafe { BITS },
+                    K,
+                    &table_relu(),
+                )
+                .unwrap();
+        } else {
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+        }
+
+        config
+    }
+
+    fn configure(_cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+        unimplemented!("call configure_with_params instead")
+    }
+
+    fn synthesize(
+        &self,
+        mut config: Self::Config,
+        mut layouter: impl Layouter<Fr>, // layouter is our 'write buffer' for the circuit
+    ) -> Result<(), Error> {
+        config.layout_tables(&mut layouter).unwrap();
+        config.layout_range_checks(&mut layouter).unwrap();
+        layouter.assign_region(
+            || "",
+            |region| {
+                let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                let op: Box<dyn Op<Fr>> = if self.use_table {
+                    Box::new(table_relu())
+                } else {
+                    Box::new(PolyOp::LeakyReLU {
+                        slope: 0.0.into(),
+                        scale: 1,
+                    })
+                };
+                config
+                    .layout(&mut region, &[self.input.clone()], op)
+                    .unwrap();
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn runrelutradeoff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("relu_tradeoff");
+
+    let mut rng = rand::thread_rng();
+    let params = gen_srs::<KZGCommitmentScheme<_>>(17);
+    for &bits in [8, 12, 16].iter() {
+        let max = 1 << (bits - 1);
+        unsafe {
+            BITS = (-max, max);
+        };
+
+        let input: Tensor<Value<Fr>> =
+            Tensor::<IntegerRep>::from((0..LEN).map(|_| rng.gen_range(-max..max))).into();
+
+        for use_table in [false, true] {
+            let circuit = ReLUCircuit {
+                input: ValTensor::from(input.clone()),
+                use_table,
+            };
+            let name = if use_table { "table" } else { "sign_bit" };
+
+            let pk =
+                create_keys::<KZGCommitmentScheme<Bn256>, ReLUCircuit>(&circuit, &params, true)
+                    .unwrap();
+
+            group.throughput(Throughput::Elements(LEN as u64));
+            group.bench_with_input(BenchmarkId::new(name, bits), &bits, |b, &_| {
+                b.iter(|| {
+                    let prover = create_proof_circuit::<
+                        KZGCommitmentScheme<_>,
+                        ReLUCircuit,
+                        ProverSHPLONK<_>,
+                        VerifierSHPLONK<_>,
+                        SingleStrategy<_>,
+                        _,
+                        EvmTranscript<_, _, _, _>,
+                        EvmTranscript<_, _, _, _>,
+                    >(
+                        circuit.clone(),
+                        vec![],
+                        &params,
+                        &pk,
+                        CheckMode::UNSAFE,
+                        ezkl::Commitments::KZG,
+                        TranscriptType::EVM,
+                        None,
+                        None,
+                    );
+                    prover.unwrap();
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().with_plots();
+  targets = runrelutradeoff
+}
+criterion_main!(benches);
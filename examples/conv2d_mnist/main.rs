@@ -208,6 +208,7 @@ where
                     let op = PolyOp::Conv {
                         padding: vec![(PADDING, PADDING); 2],
                         stride: vec![STRIDE; 2],
+                        dilation: vec![1; 2],
                         group: 1,
                         data_format: DataFormat::NCHW,
                         kernel_format: KernelFormat::OIHW,
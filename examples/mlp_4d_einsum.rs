@@ -13,6 +13,11 @@ use halo2curves::bn256::Fr as F;
 use std::marker::PhantomData;
 
 const K: usize = 15;
+
+const L0_KERNEL: [IntegerRep; 16] = [10, 0, 0, -1, 0, 10, 1, 0, 0, 1, 10, 0, 1, 0, 0, 10];
+const L0_BIAS: [IntegerRep; 4] = [0, 0, 0, 1];
+const INPUT: [IntegerRep; 4] = [-30, -21, 11, 40];
+
 // A columnar ReLu MLP
 #[derive(Clone)]
 struct MyConfig {
@@ -31,6 +36,8 @@ struct MyCircuit<
     input: ValTensor<F>,
     l0_params: [Tensor<F>; 2],
     l2_params: [Tensor<F>; 2],
+    // the expected output of the first layer, relu(l0 . x + b)
+    l0_output: Vec<IntegerRep>,
     _marker: PhantomData<F>,
 }
 
@@ -158,6 +165,14 @@ impl<const LEN: usize, const LOOKUP_MIN: IntegerRep, const LOOKUP_MAX: IntegerRe
                     println!("3");
                     println!("offset: {}", region.row());
                     println!("x shape: {:?}", x.dims());
+                    // the cells hold values whenever the witness is known, as under the MockProver
+                    let l0_output = x.int_values().unwrap();
+                    if l0_output.iter().all(|v| v.is_some()) {
+                        assert_eq!(
+                            l0_output.iter().flatten().copied().collect::<Vec<_>>(),
+                            self.l0_output
+                        );
+                    }
                     x.reshape(&[x.dims()[0], 1]).unwrap();
                     let x = config
                         .layer_config
@@ -233,15 +248,12 @@ pub fn runmlp() {
     #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
     env_logger::init();
     // parameters
-    let mut l0_kernel: Tensor<F> = Tensor::<IntegerRep>::new(
-        Some(&[10, 0, 0, -1, 0, 10, 1, 0, 0, 1, 10, 0, 1, 0, 0, 10]),
-        &[4, 4],
-    )
-    .unwrap()
-    .map(integer_rep_to_felt);
+    let mut l0_kernel: Tensor<F> = Tensor::<IntegerRep>::new(Some(&L0_KERNEL), &[4, 4])
+        .unwrap()
+        .map(integer_rep_to_felt);
     l0_kernel.set_visibility(&ezkl::graph::Visibility::Private);
 
-    let mut l0_bias: Tensor<F> = Tensor::<IntegerRep>::new(Some(&[0, 0, 0, 1]), &[4, 1])
+    let mut l0_bias: Tensor<F> = Tensor::<IntegerRep>::new(Some(&L0_BIAS), &[4, 1])
         .unwrap()
         .map(integer_rep_to_felt);
     l0_bias.set_visibility(&ezkl::graph::Visibility::Private);
@@ -254,9 +266,21 @@ pub fn runmlp() {
     .map(integer_rep_to_felt);
     l2_kernel.set_visibility(&ezkl::graph::Visibility::Private);
     // input data, with 1 padding to allow for bias
-    let input: Tensor<Value<F>> = Tensor::<IntegerRep>::new(Some(&[-30, -21, 11, 40]), &[4, 1])
+    let input: Tensor<Value<F>> = Tensor::<IntegerRep>::new(Some(&INPUT), &[4, 1])
         .unwrap()
         .into();
+    let l0_output = L0_KERNEL
+        .chunks(INPUT.len())
+        .zip(L0_BIAS)
+        .map(|(row, b)| {
+            let acc = row
+                .iter()
+                .zip(INPUT)
+                .map(|(w, x)| w * x)
+                .sum::<IntegerRep>();
+            (acc + b).max(0)
+        })
+        .collect();
     let mut l2_bias: Tensor<F> = Tensor::<IntegerRep>::new(Some(&[0, 0, 0, 1]), &[4, 1])
         .unwrap()
         .map(integer_rep_to_felt);
@@ -266,6 +290,7 @@ pub fn runmlp() {
         input: input.into(),
         l0_params: [l0_kernel, l0_bias],
         l2_params: [l2_kernel, l2_bias],
+        l0_output,
         _marker: PhantomData,
     };
 
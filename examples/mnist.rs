@@ -0,0 +1,347 @@
+use ezkl::circuit::region::RegionCtx;
+use ezkl::circuit::{
+    ops::hybrid::HybridOp, ops::lookup::LookupOp, ops::poly::PolyOp, BaseConfig as PolyConfig,
+    CheckMode,
+};
+use ezkl::fieldutils::{integer_rep_to_felt, IntegerRep};
+use ezkl::tensor::*;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+use halo2curves::bn256::Fr as F;
+use serde::Deserialize;
+use std::marker::PhantomData;
+
+// the trained parameters of the conv2d_mnist example, kernels are HWIO and the dense weights are
+// laid out as [features, classes]
+#[path = "conv2d_mnist/params.rs"]
+mod params;
+
+const K: usize = 17;
+const NUM_INNER_COLS: usize = 1;
+
+const IMAGE_SIZE: usize = 28;
+const KERNEL_SIZE: usize = 5;
+const STRIDE: usize = 2;
+const OUT_CHANNELS: usize = 4;
+const CLASSES: usize = 10;
+const LEN: usize = OUT_CHANNELS * ((IMAGE_SIZE - KERNEL_SIZE) / STRIDE + 1).pow(2);
+
+// pixels are quantized to 4 bits and the parameters to a scale of 32, so that the activations
+// after the requantizing divide are at the same scale as the input
+const PARAM_SCALE: f32 = 32.;
+const PIXEL_DIVISOR: IntegerRep = 16;
+const LOOKUP_RANGE: (IntegerRep, IntegerRep) = (-8192, 8192);
+
+/// A sample image from the MNIST test set with its label.
+#[derive(Deserialize)]
+struct Sample {
+    label: usize,
+    image: Vec<u8>,
+}
+
+#[derive(Clone)]
+struct MnistConfig {
+    layer_config: PolyConfig<F>,
+    public_output: Column<Instance>,
+}
+
+// conv -> relu -> divide -> dense -> argmax, with the predicted class as the only public output
+#[derive(Clone)]
+struct MnistCircuit {
+    input: ValTensor<F>,
+    conv_params: [ValTensor<F>; 2],
+    dense_params: [ValTensor<F>; 2],
+}
+
+impl Circuit<F> for MnistCircuit {
+    type Config = MnistConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = PhantomData<F>;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = (0..6)
+            .map(|_| VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN))
+            .collect::<Vec<_>>();
+
+        let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+        let mut layer_config = PolyConfig::configure(
+            cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+
+        layer_config
+            .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+            .unwrap();
+
+        layer_config
+            .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+            .unwrap();
+
+        layer_config
+            .configure_lookup(
+                cs,
+                &advices[0],
+                &advices[2],
+                &advices[1],
+                LOOKUP_RANGE,
+                K,
+                &LookupOp::Div {
+                    denom: PARAM_SCALE.into(),
+                },
+            )
+            .unwrap();
+
+        // the argmax gadget checks its claim against a sorted copy of the logits
+        layer_config
+            .configure_dynamic_lookup(
+                cs,
+                advices[0..3].try_into().unwrap(),
+                advices[3..6].try_into().unwrap(),
+            )
+            .unwrap();
+        layer_config
+            .configure_shuffles(
+                cs,
+                advices[0..3].try_into().unwrap(),
+                advices[3..6].try_into().unwrap(),
+            )
+            .unwrap();
+
+        let public_output: Column<Instance> = cs.instance_column();
+        cs.enable_equality(public_output);
+
+        MnistConfig {
+            layer_config,
+            public_output,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        mut config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.layer_config.layout_tables(&mut layouter).unwrap();
+
+        config
+            .layer_config
+            .layout_range_checks(&mut layouter)
+            .unwrap();
+
+        let predicted = layouter.assign_region(
+            || "mnist",
+            |region| {
+                let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 1024, 2);
+
+                let x = config
+                    .layer_config
+                    .layout(
+                        &mut region,
+                        &[
+                            self.input.clone(),
+                            self.conv_params[0].clone(),
+                            self.conv_params[1].clone(),
+                        ],
+                        Box::new(PolyOp::Conv {
+                            padding: vec![(0, 0); 2],
+                            stride: vec![STRIDE; 2],
+                            dilation: vec![1; 2],
+                            group: 1,
+                            data_format: DataFormat::NHWC,
+                            kernel_format: KernelFormat::HWIO,
+                        }),
+                    )
+                    .unwrap()
+                    .unwrap();
+
+                let x = config
+                    .layer_config
+                    .layout(
+                        &mut region,
+                        &[x],
+                        Box::new(PolyOp::LeakyReLU {
+                            slope: 0.0.into(),
+                            scale: 1,
+                        }),
+                    )
+                    .unwrap()
+                    .unwrap();
+
+                let mut x = config
+                    .layer_config
+                    .layout(
+                        &mut region,
+                        &[x],
+                        Box::new(LookupOp::Div {
+                            denom: PARAM_SCALE.into(),
+                        }),
+                    )
+                    .unwrap()
+                    .unwrap();
+
+                // NHWC flattens in the order the dense weights were trained with
+                x.flatten();
+                let x = config
+                    .layer_config
+                    .layout(
+                        &mut region,
+                        &[x, self.dense_params[0].clone()],
+                        Box::new(PolyOp::Einsum {
+                            equation: "i,ij->j".to_string(),
+                        }),
+                    )
+                    .unwrap()
+                    .unwrap();
+
+                let logits = config
+                    .layer_config
+                    .layout(
+                        &mut region,
+                        &[x, self.dense_params[1].clone()],
+                        Box::new(PolyOp::Add),
+                    )
+                    .unwrap()
+                    .unwrap();
+
+                let predicted = config
+                    .layer_config
+                    .layout(
+                        &mut region,
+                        &[logits],
+                        Box::new(HybridOp::ReduceArgMax { dim: 0 }),
+                    )
+                    .unwrap()
+                    .unwrap();
+
+                if let Ok(class) = predicted.int_evals() {
+                    println!("predicted digit: {}", class[0]);
+                }
+
+                Ok(predicted)
+            },
+        )?;
+
+        match predicted.get_inner_tensor().unwrap()[0].clone() {
+            ValType::PrevAssigned(v) => {
+                layouter.constrain_instance(v.cell(), config.public_output, 0)?
+            }
+            _ => panic!("the prediction should be assigned"),
+        };
+
+        Ok(())
+    }
+}
+
+fn quantize(x: f32, scale: f32) -> F {
+    integer_rep_to_felt((x * scale).round() as IntegerRep)
+}
+
+fn load_params() -> ([ValTensor<F>; 2], [ValTensor<F>; 2]) {
+    let params = params::Params::new();
+
+    let mut kernels = Tensor::<F>::from(
+        params
+            .kernels
+            .into_iter()
+            .flatten()
+            .flatten()
+            .flatten()
+            .map(|x| quantize(x, PARAM_SCALE)),
+    );
+    kernels
+        .reshape(&[KERNEL_SIZE, KERNEL_SIZE, 1, OUT_CHANNELS])
+        .unwrap();
+    kernels.set_visibility(&ezkl::graph::Visibility::Private);
+
+    let mut conv_bias = Tensor::<F>::from((0..OUT_CHANNELS).map(|_| integer_rep_to_felt(0)));
+    conv_bias.set_visibility(&ezkl::graph::Visibility::Private);
+
+    let mut weights = Tensor::<F>::from(
+        params
+            .weights
+            .into_iter()
+            .flatten()
+            .map(|x| quantize(x, PARAM_SCALE)),
+    );
+    weights.reshape(&[LEN, CLASSES]).unwrap();
+    weights.set_visibility(&ezkl::graph::Visibility::Private);
+
+    // the logits are at the scale of the activations times the weight scale
+    let mut biases = Tensor::<F>::from(
+        params
+            .biases
+            .into_iter()
+            .map(|x| quantize(x, PARAM_SCALE * PIXEL_DIVISOR as f32)),
+    );
+    biases.set_visibility(&ezkl::graph::Visibility::Private);
+
+    (
+        [kernels.try_into().unwrap(), conv_bias.try_into().unwrap()],
+        [weights.try_into().unwrap(), biases.try_into().unwrap()],
+    )
+}
+
+fn load_samples() -> Vec<Sample> {
+    let samples = std::fs::read_to_string("examples/data/mnist_samples.json").unwrap();
+    serde_json::from_str(&samples).unwrap()
+}
+
+pub fn runmnist() {
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    env_logger::init();
+
+    let (conv_params, dense_params) = load_params();
+
+    for sample in load_samples() {
+        // the flattened image, quantized to 4 bits
+        let pixels: Tensor<IntegerRep> = Tensor::new(
+            Some(
+                &sample
+                    .image
+                    .iter()
+                    .map(|p| *p as IntegerRep / PIXEL_DIVISOR)
+                    .collect::<Vec<_>>(),
+            ),
+            &[IMAGE_SIZE * IMAGE_SIZE],
+        )
+        .unwrap();
+
+        let mut input: ValTensor<F> = pixels
+            .map(|p| Value::known(integer_rep_to_felt::<F>(p)))
+            .into();
+        input.reshape(&[1, IMAGE_SIZE, IMAGE_SIZE, 1]).unwrap();
+
+        let circuit = MnistCircuit {
+            input,
+            conv_params: conv_params.clone(),
+            dense_params: dense_params.clone(),
+        };
+
+        println!("expected digit: {}", sample.label);
+
+        // the circuit is only satisfied if the argmax gadget picks the expected class
+        let expected = vec![integer_rep_to_felt::<F>(sample.label as IntegerRep)];
+        let prover = MockProver::run(K as u32, &circuit, vec![expected]).unwrap();
+        prover.assert_satisfied();
+
+        let wrong = vec![integer_rep_to_felt::<F>(
+            ((sample.label + 1) % CLASSES) as IntegerRep,
+        )];
+        let prover = MockProver::run(K as u32, &circuit, vec![wrong]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+pub fn main() {
+    runmnist()
+}
@@ -4,8 +4,8 @@ use crate::circuit::modules::poseidon::{
     PoseidonChip,
 };
 use crate::circuit::modules::Module;
-use crate::circuit::CheckMode;
 use crate::circuit::InputType;
+use crate::circuit::{CheckMode, TableStrategy};
 use crate::commands::*;
 use crate::fieldutils::{felt_to_integer_rep, integer_rep_to_felt, IntegerRep};
 use crate::graph::TestDataSource;
@@ -206,6 +206,9 @@ struct PyRunArgs {
     /// bool: Should the circuit use range checks for inputs and outputs (set to false if the input is a felt)
     #[pyo3(get, set)]
     pub ignore_range_check_inputs_outputs: bool,
+    /// str: how lookup tables are sized, accepts `shared`, `per-layer`
+    #[pyo3(get, set)]
+    pub table_strategy: TableStrategy,
 }
 
 /// default instantiation of PyRunArgs
@@ -238,6 +241,7 @@ impl From<PyRunArgs> for RunArgs {
             decomp_base: py_run_args.decomp_base,
             decomp_legs: py_run_args.decomp_legs,
             ignore_range_check_inputs_outputs: py_run_args.ignore_range_check_inputs_outputs,
+            table_strategy: py_run_args.table_strategy,
         }
     }
 }
@@ -262,6 +266,7 @@ impl Into<PyRunArgs> for RunArgs {
             decomp_base: self.decomp_base,
             decomp_legs: self.decomp_legs,
             ignore_range_check_inputs_outputs: self.ignore_range_check_inputs_outputs,
+            table_strategy: self.table_strategy,
         }
     }
 }
@@ -2150,6 +2155,15 @@ impl pyo3_stub_gen::PyStubType for CheckMode {
     }
 }
 
+impl pyo3_stub_gen::PyStubType for TableStrategy {
+    fn type_output() -> TypeInfo {
+        TypeInfo {
+            name: "str".to_string(),
+            import: HashSet::new(),
+        }
+    }
+}
+
 impl pyo3_stub_gen::PyStubType for ContractType {
     fn type_output() -> TypeInfo {
         TypeInfo {
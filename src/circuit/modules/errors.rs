@@ -13,6 +13,9 @@ pub enum ModuleError {
     /// A constant was not previously assigned
     #[error("constant was not previously assigned")]
     ConstantNotAssigned,
+    /// The module has no instance column to expose its output in
+    #[error("the module has no instance column")]
+    MissingInstance,
     /// Input length is wrong
     #[error("input length is wrong {0}")]
     InputWrongLength(usize),
@@ -32,6 +32,61 @@ impl FloorPlanner for ModulePlanner {
         circuit.synthesize(config, layouter)
     }
 }
+
+/// Lays out a circuit with the floor planner `P` instead of the circuit's own.
+///
+/// halo2 runs a single floor planner over the whole of [Circuit::synthesize], so the planner
+/// can't differ between layers: lookup tables, range checks and the layer regions all share
+/// the same columns and are placed by the same layouter. The planner is exposed as a type
+/// parameter here instead. [ModulePlanner] stays the best choice for graph circuits, table
+/// heavy or not, as it keeps the rows of each module (e.g. poseidon hashing of the inputs)
+/// apart from the model's; with no modules any planner that places one region after the other
+/// gives the same layout.
+#[derive(Clone, Debug)]
+pub struct WithPlanner<C, P> {
+    /// the circuit being laid out
+    pub circuit: C,
+    _planner: PhantomData<P>,
+}
+
+impl<C, P> WithPlanner<C, P> {
+    /// Wraps `circuit` to be laid out with `P`.
+    pub fn new(circuit: C) -> Self {
+        Self {
+            circuit,
+            _planner: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: Circuit<F>, P: FloorPlanner> Circuit<F> for WithPlanner<C, P> {
+    type Config = C::Config;
+    type FloorPlanner = P;
+    type Params = C::Params;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.circuit.without_witnesses())
+    }
+
+    fn params(&self) -> Self::Params {
+        self.circuit.params()
+    }
+
+    fn configure_with_params(
+        meta: &mut halo2_proofs::plonk::ConstraintSystem<F>,
+        params: Self::Params,
+    ) -> Self::Config {
+        C::configure_with_params(meta, params)
+    }
+
+    fn configure(meta: &mut halo2_proofs::plonk::ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(config, layouter)
+    }
+}
 ///
 pub type ModuleIdx = usize;
 ///
@@ -104,6 +104,53 @@ impl<S: Spec<Fp, WIDTH, RATE> + Sync, const WIDTH: usize, const RATE: usize>
             instance,
         )
     }
+
+    /// The blinded commitment to `message`, the hash of the message with `blinding` appended.
+    /// The plain hash of a low entropy input (e.g. a quantized image) can be inverted by
+    /// hashing candidate inputs, a random blinding factor known only to the prover hides it.
+    pub fn run_blinded(message: Vec<Fp>, blinding: Fp) -> Result<Vec<Vec<Fp>>, ModuleError> {
+        let mut message = message;
+        message.push(blinding);
+        Self::run(message)
+    }
+
+    /// Lays out the blinded commitment to `input`, see [PoseidonChip::run_blinded]. The blinding
+    /// factor is private advice, only the commitment is constrained to the instance at
+    /// `row_offset`. Returns the assigned input, so later layers can be wired to the committed
+    /// values.
+    pub fn layout_blinded(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        input: &ValTensor<Fp>,
+        blinding: Value<Fp>,
+        row_offset: usize,
+        constants: &mut ConstantsMap<Fp>,
+    ) -> Result<ValTensor<Fp>, ModuleError> {
+        if self.config.instance.is_none() {
+            return Err(ModuleError::MissingInstance);
+        }
+        let values = input.get_inner_tensor().map_err(|e| {
+            log::error!("blinded commitments need a witnessed input: {:?}", e);
+            Error::Synthesis
+        })?;
+        let message = Tensor::from(
+            values
+                .iter()
+                .cloned()
+                .chain(std::iter::once(ValType::Value(blinding))),
+        );
+
+        let assigned = self.layout(layouter, &[message.into()], row_offset, constants)?;
+        // drop the blinding factor
+        let assigned = assigned
+            .get_slice(&[0..input.len()])
+            .and_then(|mut x| x.reshape(input.dims()).map(|_| x))
+            .map_err(|e| {
+                log::error!("reshape failed: {:?}", e);
+                Error::Synthesis
+            })?;
+        Ok(assigned)
+    }
 }
 
 impl<S: Spec<Fp, WIDTH, RATE> + Sync, const WIDTH: usize, const RATE: usize> Module<Fp>
@@ -404,7 +451,7 @@ mod tests {
     use crate::circuit::modules::ModulePlanner;
 
     use super::{
-        spec::{PoseidonSpec, POSEIDON_RATE, POSEIDON_WIDTH},
+        spec::{ConfigurablePoseidonSpec, PoseidonSpec, POSEIDON_RATE, POSEIDON_WIDTH},
         *,
     };
 
@@ -421,13 +468,15 @@ mod tests {
     const RATE: usize = POSEIDON_RATE;
     const R: usize = 240;
 
-    struct HashCircuit<S: Spec<Fp, WIDTH, RATE>> {
+    struct HashCircuit<S, const W: usize = WIDTH, const RT: usize = RATE> {
         message: ValTensor<Fp>,
         _spec: PhantomData<S>,
     }
 
-    impl<S: Spec<Fp, WIDTH, RATE>> Circuit<Fp> for HashCircuit<S> {
-        type Config = PoseidonConfig<WIDTH, RATE>;
+    impl<S: Spec<Fp, W, RT> + Sync, const W: usize, const RT: usize> Circuit<Fp>
+        for HashCircuit<S, W, RT>
+    {
+        type Config = PoseidonConfig<W, RT>;
         type FloorPlanner = ModulePlanner;
         type Params = ();
 
@@ -441,16 +490,16 @@ mod tests {
             }
         }
 
-        fn configure(meta: &mut ConstraintSystem<Fp>) -> PoseidonConfig<WIDTH, RATE> {
-            PoseidonChip::<PoseidonSpec, WIDTH, RATE>::configure(meta, ())
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> PoseidonConfig<W, RT> {
+            PoseidonChip::<S, W, RT>::configure(meta, ())
         }
 
         fn synthesize(
             &self,
-            config: PoseidonConfig<WIDTH, RATE>,
+            config: PoseidonConfig<W, RT>,
             mut layouter: impl Layouter<Fp>,
         ) -> Result<(), Error> {
-            let chip: PoseidonChip<PoseidonSpec, WIDTH, RATE> = PoseidonChip::new(config);
+            let chip: PoseidonChip<S, W, RT> = PoseidonChip::new(config);
             chip.layout(
                 &mut layouter,
                 &[self.message.clone()],
@@ -515,6 +564,171 @@ mod tests {
         assert_eq!(prover.verify(), Ok(()))
     }
 
+    #[test]
+    fn poseidon_hash_of_quantized_input() {
+        // the input [1, 2, 3, 4] quantized at scale 7, as in the python bindings test
+        let message = [1.0, 2.0, 3.0, 4.0]
+            .iter()
+            .map(|x| {
+                crate::fieldutils::integer_rep_to_felt(
+                    crate::graph::quantize_float(x, 0.0, 7).unwrap(),
+                )
+            })
+            .collect::<Vec<Fp>>();
+        let output = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(message.clone()).unwrap();
+        assert_eq!(
+            format!("{:?}", output[0][0]),
+            "0x2369898875588bf49b6539376b09705ea69aee318a58e6fcc1e68fc3e7ad81ab"
+        );
+
+        let message: Tensor<ValType<Fp>> =
+            message.into_iter().map(|m| Value::known(m).into()).into();
+
+        let k = 9;
+        let circuit = HashCircuit::<PoseidonSpec> {
+            message: message.into(),
+            _spec: PhantomData,
+        };
+        // the witnessed hash is exposed as the instance, so a verifier holding the commitment
+        // to the input can check the proof was generated for it
+        let prover = halo2_proofs::dev::MockProver::run(k, &circuit, output.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let wrong_commitment = vec![vec![output[0][0] + Fp::ONE]];
+        let prover = halo2_proofs::dev::MockProver::run(k, &circuit, wrong_commitment).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    fn configured_poseidon_matches_reference<
+        S: Spec<Fp, W, RT> + Sync,
+        const W: usize,
+        const RT: usize,
+    >() {
+        let rng = rand::rngs::OsRng;
+
+        let message = (0..5).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+        // the out of circuit sponge from halo2_gadgets, run with the same parameters
+        let reference =
+            halo2_gadgets::poseidon::primitives::Hash::<_, S, VariableLength, W, RT>::init()
+                .hash(message.clone());
+        let output = PoseidonChip::<S, W, RT>::run(message.clone()).unwrap();
+        assert_eq!(output, vec![vec![reference]]);
+
+        let message: Tensor<ValType<Fp>> =
+            message.into_iter().map(|m| Value::known(m).into()).into();
+
+        let k = 9;
+        let circuit = HashCircuit::<S, W, RT> {
+            message: message.into(),
+            _spec: PhantomData,
+        };
+        let prover = halo2_proofs::dev::MockProver::run(k, &circuit, output).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn configured_poseidon_hash() {
+        // fewer rounds than the secure default
+        configured_poseidon_matches_reference::<ConfigurablePoseidonSpec<2, 1, 4, 8>, 2, 1>();
+        // a wider state absorbs more inputs per permutation
+        configured_poseidon_matches_reference::<ConfigurablePoseidonSpec<3, 2, 8, 56>, 3, 2>();
+
+        // reducing the rounds changes the hash
+        let message = vec![Fp::ONE, Fp::ONE];
+        let reduced =
+            PoseidonChip::<ConfigurablePoseidonSpec<2, 1, 4, 8>, WIDTH, RATE>::run(message.clone())
+                .unwrap();
+        let default = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(message).unwrap();
+        assert_ne!(reduced, default);
+    }
+
+    struct BlindedHashCircuit {
+        message: ValTensor<Fp>,
+        blinding: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for BlindedHashCircuit {
+        type Config = PoseidonConfig<WIDTH, RATE>;
+        type FloorPlanner = ModulePlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                message: self.message.clone(),
+                blinding: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> PoseidonConfig<WIDTH, RATE> {
+            PoseidonChip::<PoseidonSpec, WIDTH, RATE>::configure(meta, ())
+        }
+
+        fn synthesize(
+            &self,
+            config: PoseidonConfig<WIDTH, RATE>,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip: PoseidonChip<PoseidonSpec, WIDTH, RATE> = PoseidonChip::new(config);
+            let assigned = chip.layout_blinded(
+                &mut layouter,
+                &self.message,
+                self.blinding,
+                0,
+                &mut HashMap::new(),
+            )?;
+            assert_eq!(assigned.dims(), self.message.dims());
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn poseidon_blinded_commitment() {
+        let message = [1.0, 2.0, 3.0, 4.0]
+            .iter()
+            .map(|x| {
+                crate::fieldutils::integer_rep_to_felt(
+                    crate::graph::quantize_float(x, 0.0, 7).unwrap(),
+                )
+            })
+            .collect::<Vec<Fp>>();
+        let blinding = Fp::from(0x5eed_u64);
+
+        // the commitment is the plain poseidon hash of the input followed by the blinding factor
+        let mut preimage = message.clone();
+        preimage.push(blinding);
+        let external = halo2_gadgets::poseidon::primitives::Hash::<
+            _,
+            PoseidonSpec,
+            VariableLength,
+            WIDTH,
+            RATE,
+        >::init()
+        .hash(preimage);
+        let commitment =
+            PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run_blinded(message.clone(), blinding)
+                .unwrap();
+        assert_eq!(commitment, vec![vec![external]]);
+        let unblinded = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(message.clone()).unwrap();
+        assert_ne!(commitment, unblinded);
+
+        let mut message: Tensor<ValType<Fp>> =
+            message.into_iter().map(|m| Value::known(m).into()).into();
+        message.reshape(&[2, 2]).unwrap();
+        let circuit = BlindedHashCircuit {
+            message: message.into(),
+            blinding: Value::known(blinding),
+        };
+
+        let k = 9;
+        let prover = halo2_proofs::dev::MockProver::run(k, &circuit, commitment).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // the proof doesn't open to the unblinded hash of the same input
+        let prover = halo2_proofs::dev::MockProver::run(k, &circuit, unblinded).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
     #[test]
     #[ignore]
     fn hash_for_a_range_of_input_sizes() {
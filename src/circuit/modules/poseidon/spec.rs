@@ -18,6 +18,48 @@ pub const POSEIDON_RATE: usize = 1;
 
 pub(crate) type Mds<Fp, const T: usize> = [[Fp; T]; T];
 
+/// A Poseidon specification with a configurable width and number of rounds, the round constants
+/// and MDS matrix are generated for the chosen parameters. Fewer rounds make the hash cheaper to
+/// prove but void its security margin, so this should only be used in non-adversarial settings
+/// (e.g. testing or benchmarking). [PoseidonSpec] is the secure default.
+///
+/// `RATE` must be `WIDTH - 1` and `FULL_ROUNDS` must be even, as required by the Pow5 chip.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigurablePoseidonSpec<
+    const WIDTH: usize,
+    const RATE: usize,
+    const FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+>;
+
+impl<
+        const WIDTH: usize,
+        const RATE: usize,
+        const FULL_ROUNDS: usize,
+        const PARTIAL_ROUNDS: usize,
+    > Spec<Fp, WIDTH, RATE> for ConfigurablePoseidonSpec<WIDTH, RATE, FULL_ROUNDS, PARTIAL_ROUNDS>
+{
+    fn full_rounds() -> usize {
+        FULL_ROUNDS
+    }
+
+    fn partial_rounds() -> usize {
+        PARTIAL_ROUNDS
+    }
+
+    fn sbox(val: Fp) -> Fp {
+        val.pow_vartime([5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fp; WIDTH]>, Mds<Fp, WIDTH>, Mds<Fp, WIDTH>) {
+        generate_constants::<_, Self, WIDTH, RATE>()
+    }
+}
+
 impl Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE> for PoseidonSpec {
     fn full_rounds() -> usize {
         8
@@ -21,9 +21,12 @@ use crate::{
         ops::base::BaseOp,
         table::{Range, RangeCheck, Table},
     },
-    tensor::{Tensor, TensorType, ValTensor, VarTensor},
+    tensor::{ops::accumulated::AccumulationOrder, Tensor, TensorType, ValTensor, VarTensor},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
 };
-use std::{collections::BTreeMap, marker::PhantomData};
 
 use super::{lookup::LookupOp, region::RegionCtx, CircuitError, Op};
 use halo2curves::ff::{Field, PrimeField};
@@ -108,6 +111,80 @@ impl<'source> FromPyObject<'source> for CheckMode {
     }
 }
 
+/// How the lookup tables of a model are sized.
+///
+/// * [TableStrategy::Shared]: every table spans the single `lookup_range` of the run args, so
+///   the tables share their input columns. This is the cheapest in columns, but every table is
+///   as wide as the widest layer needs, and the number of rows (or columns) needed to hold it
+///   grows with it.
+/// * [TableStrategy::PerLayer]: each table spans only the inputs its layer saw during
+///   calibration, so layers are sized independently and narrow activations get narrow tables.
+///   Tables over different ranges can't share inputs, so this costs an input column per table.
+///   Layers applying the same op (with the same parameters) still share a table, which spans
+///   the inputs of all of them.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Default, Copy,
+)]
+pub enum TableStrategy {
+    /// one range for every table
+    #[default]
+    Shared,
+    /// a range per table, from the inputs each layer saw
+    PerLayer,
+}
+
+impl std::fmt::Display for TableStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableStrategy::Shared => write!(f, "shared"),
+            TableStrategy::PerLayer => write!(f, "per-layer"),
+        }
+    }
+}
+
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+impl ToFlags for TableStrategy {
+    /// Convert the struct to a subcommand string
+    fn to_flags(&self) -> Vec<String> {
+        vec![format!("{}", self)]
+    }
+}
+
+impl From<String> for TableStrategy {
+    fn from(value: String) -> Self {
+        Self::from_str(value.as_str()).unwrap()
+    }
+}
+
+impl FromStr for TableStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "shared" => Ok(TableStrategy::Shared),
+            "per-layer" => Ok(TableStrategy::PerLayer),
+            _ => Err("Invalid value for TableStrategy".to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Converts TableStrategy into a PyObject (Required for TableStrategy to be compatible with Python)
+impl IntoPy<PyObject> for TableStrategy {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_string().to_object(py)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Obtains TableStrategy from PyObject (Required for TableStrategy to be compatible with Python)
+impl<'source> FromPyObject<'source> for TableStrategy {
+    fn extract_bound(ob: &pyo3::Bound<'source, pyo3::PyAny>) -> PyResult<Self> {
+        let trystr = String::extract_bound(ob)?;
+        TableStrategy::from_str(&trystr).map_err(PyValueError::new_err)
+    }
+}
+
 /// A struct representing the selectors for the dynamic lookup tables
 #[derive(Clone, Debug, Default)]
 pub struct DynamicLookups {
@@ -268,8 +345,11 @@ pub struct BaseConfig<F: PrimeField + TensorType + PartialOrd> {
     /// Activate sanity checks
     pub check_mode: CheckMode,
     _marker: PhantomData<F>,
-    /// shared table inputs
-    pub shared_table_inputs: Vec<TableColumn>,
+    /// Table input columns, shared by every lookup table over the same range. Tables over
+    /// different ranges (e.g. a narrow activation and a wide divide) get their own inputs.
+    pub shared_table_inputs: BTreeMap<Range, Vec<TableColumn>>,
+    /// The order dot products accumulate in, when it isn't one row of the inner columns at a time
+    accumulation_order: Option<AccumulationOrder>,
 }
 
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
@@ -282,11 +362,40 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
             shuffles: Shuffles::dummy(col_size, num_inner_cols),
             range_checks: RangeChecks::dummy(col_size, num_inner_cols),
             check_mode: CheckMode::SAFE,
-            shared_table_inputs: vec![],
+            shared_table_inputs: BTreeMap::new(),
+            accumulation_order: None,
             _marker: PhantomData,
         }
     }
 
+    /// The order in which the dot products of this config accumulate their terms. Unless it is
+    /// set with [BaseConfig::set_accumulation_order] a row of the inner columns is summed at a
+    /// time. Float references of affine and conv layers should accumulate in this order, see
+    /// [crate::tensor::ops::accumulated::affine].
+    pub fn accumulation_order(&self) -> AccumulationOrder {
+        self.accumulation_order
+            .unwrap_or(match self.custom_gates.output.num_inner_cols() {
+                1 => AccumulationOrder::Sequential,
+                n => AccumulationOrder::Blocked(n),
+            })
+    }
+
+    /// Sets the order in which dot products accumulate their terms. Blocks smaller than the
+    /// number of inner columns are padded with zeros, so that each row of the dot product gate
+    /// sums a single block, at the cost of more rows.
+    pub fn set_accumulation_order(&mut self, order: AccumulationOrder) -> Result<(), CircuitError> {
+        let num_inner_cols = self.custom_gates.output.num_inner_cols();
+        if order.block_size() > num_inner_cols {
+            return Err(CircuitError::DimMismatch(format!(
+                "blocks of {} products don't fit in {} inner columns",
+                order.block_size(),
+                num_inner_cols
+            )));
+        }
+        self.accumulation_order = Some(order);
+        Ok(())
+    }
+
     /// Configures [BaseOp]s for a given [ConstraintSystem].
     /// # Arguments
     /// * `meta` - The [ConstraintSystem] to configure the operations in.
@@ -415,13 +524,19 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
             dynamic_lookups: DynamicLookups::default(),
             shuffles: Shuffles::default(),
             range_checks: RangeChecks::default(),
-            shared_table_inputs: vec![],
+            shared_table_inputs: BTreeMap::new(),
+            accumulation_order: None,
             check_mode,
             _marker: PhantomData,
         }
     }
 
     /// Configures and creates lookup selectors
+    ///
+    /// Each op gets its own table over `lookup_range`, so a circuit can mix tables of different
+    /// widths. A table is split over as many columns as it needs to fit in `2^logrows` rows
+    /// (minus the blinding rows), so wide ranges cost extra columns rather than a larger `k`.
+    /// Tables over the same range share their input columns.
     #[allow(clippy::too_many_arguments)]
     pub fn configure_lookup(
         &mut self,
@@ -447,8 +562,13 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
         }
 
         let table = if !self.static_lookups.tables.contains_key(nl) {
-            let table =
-                Table::<F>::configure(cs, lookup_range, logrows, nl, &mut self.shared_table_inputs);
+            let table = Table::<F>::configure(
+                cs,
+                lookup_range,
+                logrows,
+                nl,
+                self.shared_table_inputs.entry(lookup_range).or_default(),
+            );
             self.static_lookups.tables.insert(nl.clone(), table.clone());
             table
         } else {
@@ -924,17 +1044,16 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
 
     /// layout_tables must be called before layout.
     pub fn layout_tables(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitError> {
-        for (i, table) in self.static_lookups.tables.values_mut().enumerate() {
+        // the first table over each range assigns the shared input columns
+        let mut assigned_inputs = BTreeSet::new();
+        for table in self.static_lookups.tables.values_mut() {
             if !table.is_assigned {
                 debug!(
                     "laying out table for {}",
                     crate::circuit::ops::Op::<F>::as_string(&table.nonlinearity)
                 );
-                if i == 0 {
-                    table.layout(layouter, false)?;
-                } else {
-                    table.layout(layouter, true)?;
-                }
+                let preassigned_input = !assigned_inputs.insert(table.range);
+                table.layout(layouter, preassigned_input)?;
             }
         }
         Ok(())
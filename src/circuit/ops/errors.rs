@@ -82,6 +82,9 @@ pub enum CircuitError {
     /// Table lookup error
     #[error("value ({0}) out of range: ({1}, {2})")]
     TableOOR(IntegerRep, IntegerRep, IntegerRep),
+    /// Lookup input out of the range of the configured table
+    #[error("lookup input ({1}) for {0} is outside of the table range ({2}, {3}): increase the lookup range or rescale the input")]
+    LookupOOR(String, IntegerRep, IntegerRep, IntegerRep),
     /// Loookup not configured
     #[error("lookup not configured: {0}")]
     LookupNotConfigured(String),
@@ -9,6 +9,11 @@ use halo2curves::ff::PrimeField;
 use serde::{Deserialize, Serialize};
 // import run args from model
 
+// softmax ops serialized before the temperature was added are untempered
+fn unit_temperature() -> utils::F32 {
+    utils::F32(1.0)
+}
+
 #[allow(missing_docs)]
 /// An enum representing the operations that consist of both lookups and arithmetic operations.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -45,6 +50,10 @@ pub enum HybridOp {
     },
     Div {
         denom: utils::F32,
+        /// clamps the quotients to the domain of the next layer, e.g. its lookup range, so a
+        /// mis-scaled layer saturates instead of overflowing the next table
+        #[serde(default)]
+        clamp: Option<crate::circuit::table::Range>,
     },
     ReduceMax {
         axes: Vec<usize>,
@@ -53,14 +62,14 @@ pub enum HybridOp {
         dim: usize,
     },
     SumPool {
-        padding: Vec<(usize, usize)>,
+        padding: Vec<(isize, isize)>,
         stride: Vec<usize>,
         kernel_shape: Vec<usize>,
         normalized: bool,
         data_format: DataFormat,
     },
     MaxPool {
-        padding: Vec<(usize, usize)>,
+        padding: Vec<(isize, isize)>,
         stride: Vec<usize>,
         pool_dims: Vec<usize>,
         data_format: DataFormat,
@@ -77,6 +86,8 @@ pub enum HybridOp {
         input_scale: utils::F32,
         output_scale: utils::F32,
         axes: Vec<usize>,
+        #[serde(default = "unit_temperature")]
+        temperature: utils::F32,
     },
     Output {
         decomp: bool,
@@ -150,7 +161,10 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 "RECIP (input_scale={}, output_scale={})",
                 input_scale, output_scale
             ),
-            HybridOp::Div { denom } => format!("DIV (denom={})", denom),
+            HybridOp::Div { denom, clamp } => match clamp {
+                Some(range) => format!("DIV (denom={}, clamp={:?})", denom, range),
+                None => format!("DIV (denom={})", denom),
+            },
             HybridOp::SumPool {
                 padding,
                 stride,
@@ -177,10 +191,11 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 input_scale,
                 output_scale,
                 axes,
+                temperature,
             } => {
                 format!(
-                    "SOFTMAX (input_scale={}, output_scale={}, axes={:?})",
-                    input_scale, output_scale, axes
+                    "SOFTMAX (input_scale={}, output_scale={}, axes={:?}, temperature={})",
+                    input_scale, output_scale, axes, temperature
                 )
             }
             HybridOp::Output { decomp } => {
@@ -262,8 +277,8 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 integer_rep_to_felt(input_scale.0 as IntegerRep),
                 integer_rep_to_felt(output_scale.0 as IntegerRep),
             )?,
-            HybridOp::Div { denom, .. } => {
-                if denom.0.fract() == 0.0 {
+            HybridOp::Div { denom, clamp } => {
+                let quotient = if denom.0.fract() == 0.0 {
                     layouts::div(
                         config,
                         region,
@@ -277,6 +292,10 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                         values.try_into()?,
                         &LookupOp::Div { denom: *denom },
                     )?
+                };
+                match clamp {
+                    Some(range) => layouts::clamp(config, region, &[quotient], *range)?,
+                    None => quotient,
                 }
             }
             HybridOp::Gather { dim, constant_idx } => {
@@ -317,6 +336,7 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 input_scale,
                 output_scale,
                 axes,
+                temperature,
             } => layouts::softmax_axes(
                 config,
                 region,
@@ -324,6 +344,7 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 *input_scale,
                 *output_scale,
                 axes,
+                *temperature,
             )?,
             HybridOp::Output { decomp } => {
                 layouts::output(config, region, values[..].try_into()?, *decomp)?
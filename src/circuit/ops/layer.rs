@@ -0,0 +1,756 @@
+use halo2_proofs::{circuit::Layouter, plonk::Error as PlonkError};
+use halo2curves::ff::PrimeField;
+
+use super::{base::BaseOp, layouts, lookup::LookupOp, region::RegionCtx, CircuitError, Op};
+use crate::circuit::{utils, BaseConfig};
+use crate::tensor::{val::create_zero_tensor, DataFormat, KernelFormat, TensorType, ValTensor};
+
+/// A layer bundles an operation with its parameters, so that the shape it produces can be worked
+/// out before anything is assigned. Chaining layers in a [LayerChain] checks every connection
+/// up front, rather than failing halfway through a layout.
+pub trait Layer<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    /// Returns the shape of the output for an input of shape `input_shape`, or an error if the
+    /// layer can't consume an input of that shape.
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError>;
+
+    /// A short lowercase name for the kind of layer, e.g. `affine`, used to name its region.
+    fn name(&self) -> String {
+        "layer".to_string()
+    }
+
+    /// Lays out the layer on `input`.
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError>;
+}
+
+/// A fully connected layer mapping a `[rows, in]` input to `[rows, out]`.
+#[derive(Clone, Debug)]
+pub struct AffineLayer<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    /// the weights, of shape `[out, in]`
+    pub weights: ValTensor<F>,
+    /// an optional bias of length `out`
+    pub bias: Option<ValTensor<F>>,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Layer<F> for AffineLayer<F> {
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+        let w = self.weights.dims();
+        if input_shape.len() != 2 || w.len() != 2 || input_shape[1] != w[1] {
+            return Err(CircuitError::DimMismatch(format!(
+                "affine with weights {:?} can't consume an input of shape {:?}",
+                w, input_shape
+            )));
+        }
+        if let Some(bias) = &self.bias {
+            if bias.len() != w[0] {
+                return Err(CircuitError::DimMismatch("affine bias".to_string()));
+            }
+        }
+        Ok(vec![input_shape[0], w[0]])
+    }
+
+    fn name(&self) -> String {
+        "affine".to_string()
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        let mut values = vec![input.clone(), self.weights.clone()];
+        values.extend(self.bias.clone());
+        layouts::affine(config, region, &values)
+    }
+}
+
+/// A (possibly dilated or grouped) convolution, see [layouts::dilated_conv].
+#[derive(Clone, Debug)]
+pub struct ConvLayer<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    /// the kernel, laid out as `kernel_format`
+    pub kernel: ValTensor<F>,
+    /// an optional bias with one entry per output channel
+    pub bias: Option<ValTensor<F>>,
+    /// padding for each spatial dimension, negative values crop
+    pub padding: Vec<(isize, isize)>,
+    /// stride for each spatial dimension
+    pub stride: Vec<usize>,
+    /// dilation for each spatial dimension
+    pub dilation: Vec<usize>,
+    /// the number of groups the channels are split into
+    pub group: usize,
+    /// the format of the input
+    pub data_format: DataFormat,
+    /// the format of the kernel
+    pub kernel_format: KernelFormat,
+}
+
+impl<
+        F: PrimeField
+            + TensorType
+            + PartialOrd
+            + std::hash::Hash
+            + std::marker::Send
+            + std::marker::Sync,
+    > Layer<F> for ConvLayer<F>
+{
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+        let kernel_dims = self.kernel.dims();
+        if input_shape.len() < 2 || kernel_dims.len() < 2 {
+            return Err(CircuitError::DimMismatch(format!(
+                "conv with kernel {:?} can't consume an input of shape {:?}",
+                kernel_dims, input_shape
+            )));
+        }
+        let (in_axis, out_axis) = self.kernel_format.get_channel_dims(kernel_dims.len());
+        let channel_axis = self.data_format.get_channel_dim(input_shape.len());
+        let image_axes = spatial_axes(input_shape.len(), channel_axis, self.data_format);
+        let kernel_axes = (0..kernel_dims.len())
+            .filter(|axis| *axis != in_axis && *axis != out_axis)
+            .collect::<Vec<_>>();
+
+        if image_axes.len() != kernel_axes.len()
+            || self.dilation.len() != kernel_axes.len()
+            || input_shape[channel_axis] != kernel_dims[in_axis] * self.group
+        {
+            return Err(CircuitError::DimMismatch(format!(
+                "conv with kernel {:?} can't consume an input of shape {:?}",
+                kernel_dims, input_shape
+            )));
+        }
+
+        let spans = kernel_axes
+            .iter()
+            .zip(&self.dilation)
+            .map(|(axis, d)| d * (kernel_dims[*axis] - 1) + 1)
+            .collect::<Vec<_>>();
+
+        let mut output_shape = input_shape.to_vec();
+        output_shape[channel_axis] = kernel_dims[out_axis];
+        windowed_dims(
+            "conv",
+            &mut output_shape,
+            &image_axes,
+            &spans,
+            &self.padding,
+            &self.stride,
+        )?;
+        Ok(output_shape)
+    }
+
+    fn name(&self) -> String {
+        "conv".to_string()
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        let mut values = vec![input.clone(), self.kernel.clone()];
+        values.extend(self.bias.clone());
+        layouts::dilated_conv(
+            config,
+            region,
+            &values,
+            &self.padding,
+            &self.stride,
+            &self.dilation,
+            self.group,
+            self.data_format,
+            self.kernel_format,
+        )
+    }
+}
+
+/// A 1D convolution over an unbatched `[channels, length]` sequence, mapping it to
+/// `[out_channels, output_length]`. The sequence is laid out as a batch of one through
+/// [ConvLayer].
+#[derive(Clone, Debug)]
+pub struct Conv1dLayer<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    /// the kernel, of shape `[out_channels, in_channels, kernel_size]`
+    pub kernel: ValTensor<F>,
+    /// an optional bias with one entry per output channel
+    pub bias: Option<ValTensor<F>>,
+    /// padding at either end of the sequence, negative values crop
+    pub padding: (isize, isize),
+    /// the stride along the sequence
+    pub stride: usize,
+    /// the dilation of the kernel
+    pub dilation: usize,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Conv1dLayer<F> {
+    fn batched(&self) -> ConvLayer<F> {
+        ConvLayer {
+            kernel: self.kernel.clone(),
+            bias: self.bias.clone(),
+            padding: vec![self.padding],
+            stride: vec![self.stride],
+            dilation: vec![self.dilation],
+            group: 1,
+            data_format: DataFormat::NCHW,
+            kernel_format: KernelFormat::OIHW,
+        }
+    }
+}
+
+impl<
+        F: PrimeField
+            + TensorType
+            + PartialOrd
+            + std::hash::Hash
+            + std::marker::Send
+            + std::marker::Sync,
+    > Layer<F> for Conv1dLayer<F>
+{
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+        if input_shape.len() != 2 || self.kernel.dims().len() != 3 {
+            return Err(CircuitError::DimMismatch(format!(
+                "conv1d with kernel {:?} can't consume an input of shape {:?}",
+                self.kernel.dims(),
+                input_shape
+            )));
+        }
+        let output_shape = self
+            .batched()
+            .output_shape(&[1, input_shape[0], input_shape[1]])?;
+        Ok(output_shape[1..].to_vec())
+    }
+
+    fn name(&self) -> String {
+        "conv1d".to_string()
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        let output_shape = self.output_shape(input.dims())?;
+        let mut batched = input.clone();
+        batched.reshape(&[1, input.dims()[0], input.dims()[1]])?;
+        let mut output = self.batched().layout(config, region, &batched)?;
+        output.reshape(&output_shape)?;
+        Ok(output)
+    }
+}
+
+/// How a [PoolLayer] reduces each window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolKind {
+    /// sums each window, dividing by the window size if `normalized`
+    Sum {
+        /// whether to average rather than sum
+        normalized: bool,
+    },
+    /// takes the max of each window
+    Max,
+}
+
+/// A pooling layer, see [layouts::sumpool] and [layouts::max_pool].
+#[derive(Clone, Debug)]
+pub struct PoolLayer {
+    /// how each window is reduced
+    pub kind: PoolKind,
+    /// padding for each spatial dimension, negative values crop
+    pub padding: Vec<(isize, isize)>,
+    /// stride for each spatial dimension
+    pub stride: Vec<usize>,
+    /// the window size for each spatial dimension
+    pub kernel_shape: Vec<usize>,
+    /// the format of the input
+    pub data_format: DataFormat,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Layer<F> for PoolLayer {
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+        if input_shape.len() < 2 {
+            return Err(CircuitError::DimMismatch(format!(
+                "pool can't consume an input of shape {:?}",
+                input_shape
+            )));
+        }
+        let channel_axis = self.data_format.get_channel_dim(input_shape.len());
+        let image_axes = spatial_axes(input_shape.len(), channel_axis, self.data_format);
+        if image_axes.len() != self.kernel_shape.len() {
+            return Err(CircuitError::DimMismatch(format!(
+                "pool with kernel shape {:?} can't consume an input of shape {:?}",
+                self.kernel_shape, input_shape
+            )));
+        }
+
+        let mut output_shape = input_shape.to_vec();
+        windowed_dims(
+            "pool",
+            &mut output_shape,
+            &image_axes,
+            &self.kernel_shape,
+            &self.padding,
+            &self.stride,
+        )?;
+        Ok(output_shape)
+    }
+
+    fn name(&self) -> String {
+        match self.kind {
+            PoolKind::Sum { .. } => "sumpool".to_string(),
+            PoolKind::Max => "maxpool".to_string(),
+        }
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        match self.kind {
+            PoolKind::Sum { normalized } => layouts::sumpool(
+                config,
+                region,
+                &[input.clone()],
+                &self.padding,
+                &self.stride,
+                &self.kernel_shape,
+                normalized,
+                self.data_format,
+            ),
+            PoolKind::Max => layouts::max_pool(
+                config,
+                region,
+                &[input.clone()],
+                &self.padding,
+                &self.stride,
+                &self.kernel_shape,
+                self.data_format,
+            ),
+        }
+    }
+}
+
+/// A layer applying an elementwise op, e.g. a [super::lookup::LookupOp] or a ReLU, which keeps
+/// the shape of its input.
+#[derive(Clone, Debug)]
+pub struct EltwiseLayer<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    /// the elementwise op
+    pub op: Box<dyn Op<F>>,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Layer<F> for EltwiseLayer<F> {
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+        Ok(input_shape.to_vec())
+    }
+
+    fn name(&self) -> String {
+        self.op.as_string().to_lowercase()
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        self.op
+            .layout(config, region, &[input.clone()])?
+            .ok_or(CircuitError::MissingLayout(self.op.as_string()))
+    }
+}
+
+/// A GRU cell unrolled over the rows of a `[timesteps, in]` input, outputting the hidden state
+/// after every step as a `[timesteps, hidden]` tensor. The hidden state starts at zero and is
+/// threaded between steps as assigned cells. With the gates ordered update, reset, candidate:
+///
+/// ```text
+/// z = sigmoid(W_z x + b_z + U_z h + c_z)
+/// r = sigmoid(W_r x + b_r + U_r h + c_r)
+/// n = tanh(W_n x + b_n + r * (U_n h + c_n))
+/// h' = n + z * (h - n)
+/// ```
+///
+/// The inputs, weights and hidden state share the fixed point multiplier `scale`, and the biases
+/// are at `scale` squared to match the matmul outputs. Products are brought back to `scale` with
+/// a [LookupOp::Div] by `scale`, so the cell needs that lookup configured along with
+/// [LookupOp::Sigmoid] and [LookupOp::Tanh] at `scale`.
+#[derive(Clone, Debug)]
+pub struct GruCell<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    /// the input weights of the update, reset and candidate gates, each `[hidden, in]`
+    pub input_weights: [ValTensor<F>; 3],
+    /// the hidden weights of the update, reset and candidate gates, each `[hidden, hidden]`
+    pub hidden_weights: [ValTensor<F>; 3],
+    /// the biases of the input projections, each of length `hidden`
+    pub input_bias: [ValTensor<F>; 3],
+    /// the biases of the hidden projections, each of length `hidden`
+    pub hidden_bias: [ValTensor<F>; 3],
+    /// the fixed point multiplier shared by the inputs, weights and hidden state
+    pub scale: utils::F32,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> GruCell<F> {
+    /// Lays out a single step, mapping an input of shape `[1, in]` and a hidden state of shape
+    /// `[1, hidden]` to the next hidden state.
+    pub fn step(
+        &self,
+        config: &BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        x: &ValTensor<F>,
+        h: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        let sigmoid = LookupOp::Sigmoid { scale: self.scale };
+        let tanh = LookupOp::Tanh { scale: self.scale };
+
+        let (wx, uh) = self.project(config, region, x, h, 0)?;
+        let pre = layouts::pairwise(config, region, &[wx, uh], BaseOp::Add)?;
+        let z = self.requantize(config, region, pre, Some(&sigmoid))?;
+
+        let (wx, uh) = self.project(config, region, x, h, 1)?;
+        let pre = layouts::pairwise(config, region, &[wx, uh], BaseOp::Add)?;
+        let r = self.requantize(config, region, pre, Some(&sigmoid))?;
+
+        // the reset gate scales the hidden projection, so bring that back to `scale` first
+        let (wx, uh) = self.project(config, region, x, h, 2)?;
+        let uh = self.requantize(config, region, uh, None)?;
+        let reset = layouts::pairwise(config, region, &[r, uh], BaseOp::Mult)?;
+        let pre = layouts::pairwise(config, region, &[wx, reset], BaseOp::Add)?;
+        let n = self.requantize(config, region, pre, Some(&tanh))?;
+
+        let diff = layouts::pairwise(config, region, &[h.clone(), n.clone()], BaseOp::Sub)?;
+        let update = layouts::pairwise(config, region, &[z, diff], BaseOp::Mult)?;
+        let update = self.requantize(config, region, update, None)?;
+        layouts::pairwise(config, region, &[n, update], BaseOp::Add)
+    }
+
+    /// the input and hidden projections of `gate`, both at `scale` squared
+    fn project(
+        &self,
+        config: &BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        x: &ValTensor<F>,
+        h: &ValTensor<F>,
+        gate: usize,
+    ) -> Result<(ValTensor<F>, ValTensor<F>), CircuitError> {
+        let wx = layouts::affine(
+            config,
+            region,
+            &[
+                x.clone(),
+                self.input_weights[gate].clone(),
+                self.input_bias[gate].clone(),
+            ],
+        )?;
+        let uh = layouts::affine(
+            config,
+            region,
+            &[
+                h.clone(),
+                self.hidden_weights[gate].clone(),
+                self.hidden_bias[gate].clone(),
+            ],
+        )?;
+        Ok((wx, uh))
+    }
+
+    /// divides `value` from `scale` squared down to `scale`, then applies `activation`
+    fn requantize(
+        &self,
+        config: &BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        value: ValTensor<F>,
+        activation: Option<&LookupOp>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        let div = LookupOp::Div { denom: self.scale };
+        let value = layouts::nonlinearity(config, region, &[value], &div)?;
+        match activation {
+            Some(activation) => layouts::nonlinearity(config, region, &[value], activation),
+            None => Ok(value),
+        }
+    }
+}
+
+impl<
+        F: PrimeField
+            + TensorType
+            + PartialOrd
+            + std::hash::Hash
+            + std::marker::Send
+            + std::marker::Sync,
+    > Layer<F> for GruCell<F>
+{
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+        let hidden = self.hidden_weights[0].dims().first().copied().unwrap_or(0);
+        let features = self.input_weights[0].dims().get(1).copied().unwrap_or(0);
+        let consistent = (0..3).all(|gate| {
+            self.input_weights[gate].dims() == [hidden, features]
+                && self.hidden_weights[gate].dims() == [hidden, hidden]
+                && self.input_bias[gate].len() == hidden
+                && self.hidden_bias[gate].len() == hidden
+        });
+        if !consistent || input_shape.len() != 2 || input_shape[1] != features {
+            return Err(CircuitError::DimMismatch(format!(
+                "gru cell with {} features and a hidden size of {} can't consume an input of shape {:?}",
+                features, hidden, input_shape
+            )));
+        }
+        Ok(vec![input_shape[0], hidden])
+    }
+
+    fn name(&self) -> String {
+        "gru".to_string()
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        let output_shape = self.output_shape(input.dims())?;
+        let (timesteps, hidden) = (output_shape[0], output_shape[1]);
+        let features = input.dims()[1];
+
+        let mut h = create_zero_tensor(hidden);
+        h.reshape(&[1, hidden])?;
+        let mut states: Option<ValTensor<F>> = None;
+        for t in 0..timesteps {
+            let x = input.get_slice(&[t..t + 1, 0..features])?;
+            h = self.step(config, region, &x, &h)?;
+            states = Some(match states {
+                Some(states) => states.concat_axis(h.clone(), &0)?,
+                None => h.clone(),
+            });
+        }
+
+        states.ok_or(CircuitError::DimMismatch(
+            "gru cell needs at least one timestep".to_string(),
+        ))
+    }
+}
+
+/// A sequence of layers, each consuming the output of the previous one. Each layer can carry a
+/// label, which names its region when the chain is laid out with [LayerChain::layout_in_regions].
+pub struct LayerChain<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    layers: Vec<Box<dyn Layer<F>>>,
+    labels: Vec<Option<String>>,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Default for LayerChain<F> {
+    fn default() -> Self {
+        Self {
+            layers: vec![],
+            labels: vec![],
+        }
+    }
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> LayerChain<F> {
+    /// Creates an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a layer to the chain.
+    pub fn with(mut self, layer: impl Layer<F> + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self.labels.push(None);
+        self
+    }
+
+    /// Appends a layer to the chain, laid out in a region named `label`.
+    pub fn with_label(mut self, label: &str, layer: impl Layer<F> + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self.labels.push(Some(label.to_string()));
+        self
+    }
+
+    /// The name of each layer's region: its label, or for unlabelled layers the kind of layer
+    /// and its position in the chain, e.g. `affine_l0` or `relu_l1`.
+    pub fn region_names(&self) -> Vec<String> {
+        self.layers
+            .iter()
+            .zip(&self.labels)
+            .enumerate()
+            .map(|(i, (layer, label))| {
+                label
+                    .clone()
+                    .unwrap_or_else(|| format!("{}_l{}", layer.name(), i))
+            })
+            .collect()
+    }
+
+    /// Infers the output shape of every layer for an input of shape `input_shape`, erroring on
+    /// the first layer that can't consume the output of the one before it.
+    pub fn output_shapes(&self, input_shape: &[usize]) -> Result<Vec<Vec<usize>>, CircuitError> {
+        let mut shapes: Vec<Vec<usize>> = vec![];
+        for layer in &self.layers {
+            let input_shape = shapes.last().map_or(input_shape, |s| s.as_slice());
+            let shape = layer.output_shape(input_shape)?;
+            shapes.push(shape);
+        }
+        Ok(shapes)
+    }
+
+    /// Validates the whole chain against the shape of `input` and then lays out each layer in
+    /// turn.
+    pub fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        self.output_shapes(input.dims())?;
+        let mut output = input.clone();
+        for layer in &self.layers {
+            output = layer.layout(config, region, &output)?;
+        }
+        Ok(output)
+    }
+
+    /// Like [LayerChain::layout], but lays out each layer in its own region, named as in
+    /// [LayerChain::region_names], so that profilers and [halo2_proofs::dev::MockProver]
+    /// failures point at the layer. The outputs of one layer are copy constrained into the next.
+    pub fn layout_in_regions(
+        &self,
+        config: &mut BaseConfig<F>,
+        layouter: &mut impl Layouter<F>,
+        input: &ValTensor<F>,
+        decomp_base: usize,
+        decomp_legs: usize,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        self.output_shapes(input.dims())?;
+        let num_inner_cols = config.custom_gates.output.num_inner_cols();
+        let mut output = input.clone();
+        for (layer, name) in self.layers.iter().zip(self.region_names()) {
+            output = layouter.assign_region(
+                || name.clone(),
+                |region| {
+                    let mut region =
+                        RegionCtx::new(region, 0, num_inner_cols, decomp_base, decomp_legs);
+                    layer.layout(config, &mut region, &output).map_err(|e| {
+                        log::error!("{}: {}", name, e);
+                        PlonkError::Synthesis
+                    })
+                },
+            )?;
+        }
+        Ok(output)
+    }
+}
+
+/// An ensemble of [LayerChain]s run on the same input, whose outputs are averaged: summed, then
+/// divided by the number of members with [layouts::div], which rounds half away from zero. Every
+/// member has to produce an output of the same shape and at the same scale.
+pub struct Ensemble<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
+    members: Vec<LayerChain<F>>,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Default for Ensemble<F> {
+    fn default() -> Self {
+        Self { members: vec![] }
+    }
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Ensemble<F> {
+    /// Creates an ensemble with no members.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a member to the ensemble.
+    pub fn with(mut self, member: LayerChain<F>) -> Self {
+        self.members.push(member);
+        self
+    }
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Layer<F> for Ensemble<F> {
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+        let mut output_shape: Option<Vec<usize>> = None;
+        for member in &self.members {
+            let shape = member
+                .output_shapes(input_shape)?
+                .pop()
+                .unwrap_or(input_shape.to_vec());
+            match &output_shape {
+                Some(s) if *s != shape => {
+                    return Err(CircuitError::DimMismatch(format!(
+                        "ensemble members produce outputs of shapes {:?} and {:?}",
+                        s, shape
+                    )))
+                }
+                _ => output_shape = Some(shape),
+            }
+        }
+        output_shape.ok_or(CircuitError::DimMismatch(
+            "an ensemble needs at least one member".to_string(),
+        ))
+    }
+
+    fn name(&self) -> String {
+        "ensemble".to_string()
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        input: &ValTensor<F>,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        self.output_shape(input.dims())?;
+        let mut total: Option<ValTensor<F>> = None;
+        for member in &self.members {
+            let output = member.layout(config, region, input)?;
+            total = Some(match total {
+                Some(total) => layouts::pairwise(config, region, &[total, output], BaseOp::Add)?,
+                None => output,
+            });
+        }
+        // output_shape errors on an empty ensemble
+        let total = total.unwrap();
+        layouts::div(config, region, &[total], F::from(self.members.len() as u64))
+    }
+}
+
+/// the axes of an image that are neither batch nor channel
+fn spatial_axes(ndims: usize, channel_axis: usize, data_format: DataFormat) -> Vec<usize> {
+    (0..ndims)
+        .filter(|axis| *axis != channel_axis && (data_format.has_no_batch() || *axis != 0))
+        .collect()
+}
+
+/// slides windows of size `spans` over the `axes` of `shape`, replacing each with the number of
+/// windows that fit
+fn windowed_dims(
+    name: &str,
+    shape: &mut [usize],
+    axes: &[usize],
+    spans: &[usize],
+    padding: &[(isize, isize)],
+    stride: &[usize],
+) -> Result<(), CircuitError> {
+    if padding.len() != axes.len() || stride.len() != axes.len() || stride.contains(&0) {
+        return Err(CircuitError::DimMismatch(format!(
+            "{} expects a padding and a positive stride for each of its {} spatial dimensions",
+            name,
+            axes.len()
+        )));
+    }
+    for (i, axis) in axes.iter().enumerate() {
+        let padded = shape[*axis] as isize + padding[i].0 + padding[i].1;
+        if padded < spans[i] as isize {
+            return Err(CircuitError::DimMismatch(format!(
+                "{} window of size {} doesn't fit in a dimension of size {}",
+                name, spans[i], padded
+            )));
+        }
+        shape[*axis] = (padded as usize - spans[i]) / stride[i] + 1;
+    }
+    Ok(())
+}
@@ -119,7 +119,301 @@ pub fn diff_less_than<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>
     Ok(())
 }
 
-/// Div accumulated layout
+/// Constrains `|a_i - b_i| <= tol` elementwise, e.g. to check that a student model's outputs
+/// stay close to a teacher's on the same input. Rather than comparing an absolute value, the
+/// differences are range checked against `(-tol, tol)`, which needs to have been configured with
+/// [BaseConfig::configure_range_check]. Returns the differences `a - b`.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::within_tolerance;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+/// let teacher = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///    Some(&[10, -4, 7]),
+///   &[3],
+/// ).unwrap());
+/// let student = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///    Some(&[9, -2, 7]),
+///   &[3],
+/// ).unwrap());
+/// let result = within_tolerance::<Fp>(&dummy_config, &mut dummy_region, &[teacher.clone(), student.clone()], 2).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[1, -2, 0]), &[3]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // a difference of 2 is outside a tolerance of 1
+/// assert!(within_tolerance::<Fp>(&dummy_config, &mut dummy_region, &[teacher, student], 1).is_err());
+/// ```
+pub fn within_tolerance<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    tol: IntegerRep,
+) -> Result<ValTensor<F>, CircuitError> {
+    if tol < 0 {
+        return Err(CircuitError::InvalidMinMaxRange(-tol, tol));
+    }
+    let diff = pairwise(config, region, values, BaseOp::Sub)?;
+    range_check(config, region, &[diff], &(-tol, tol))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Determines how [signed_div] rounds quotients that aren't exact.
+pub enum RoundingMode {
+    /// Round towards negative infinity
+    Floor,
+    /// Round towards positive infinity
+    Ceil,
+    /// Round towards zero, i.e. truncate like integer division
+    TowardZero,
+    /// Round to the nearest integer and halfway cases away from zero, like [f64::round]
+    #[default]
+    HalfAwayFromZero,
+}
+
+impl RoundingMode {
+    /// Divides `a` by the positive divisor `d` with this rounding mode.
+    pub fn div(&self, a: IntegerRep, d: IntegerRep) -> IntegerRep {
+        match self {
+            RoundingMode::Floor => a.div_euclid(d),
+            RoundingMode::Ceil => -(-a).div_euclid(d),
+            RoundingMode::TowardZero => a / d,
+            RoundingMode::HalfAwayFromZero => {
+                if a >= 0 {
+                    (2 * a + d).div_euclid(2 * d)
+                } else {
+                    -(d - 2 * a).div_euclid(2 * d)
+                }
+            }
+        }
+    }
+}
+
+/// Signed division by a positive divisor, the divisor can be a constant or a witnessed tensor
+/// that broadcasts to the shape of the numerator. The quotient `q` of `a / d` is witnessed and
+/// constrained through its remainder `r = a - q * d`, which pins `q` down for each rounding mode:
+/// * [RoundingMode::Floor]: `0 <= r < d`
+/// * [RoundingMode::Ceil]: `0 <= -r < d`
+/// * [RoundingMode::TowardZero]: `0 <= s * r < d`
+/// * [RoundingMode::HalfAwayFromZero]: `0 <= 2 * s * r + d < 2 * d`
+///
+/// where `s` is `-1` for negative numerators and `1` otherwise. Witnessed divisors are also
+/// constrained to be positive. If `saturate` is set the quotients are clamped to that range
+/// after they have been checked.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::{signed_div, RoundingMode};
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///    Some(&[-7, -6, -5, 5, 6, 7]),
+///   &[6],
+/// ).unwrap());
+/// let d = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///    Some(&[4]),
+///   &[1],
+/// ).unwrap());
+///
+/// let result = signed_div::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), d.clone()], RoundingMode::Floor, None).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-2, -2, -2, 1, 1, 1]), &[6]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// let result = signed_div::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), d.clone()], RoundingMode::TowardZero, None).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-1, -1, -1, 1, 1, 1]), &[6]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// let result = signed_div::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), d.clone()], RoundingMode::HalfAwayFromZero, None).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-2, -2, -1, 1, 2, 2]), &[6]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // saturating clamps the quotients to the range
+/// let result = signed_div::<Fp>(&dummy_config, &mut dummy_region, &[x, d], RoundingMode::Ceil, Some((-1, 1))).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-1, -1, -1, 1, 1, 1]), &[6]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn signed_div<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    rounding: RoundingMode,
+    saturate: Option<crate::circuit::table::Range>,
+) -> Result<ValTensor<F>, CircuitError> {
+    let input = values[0].clone();
+    let input_dims = input.dims().to_vec();
+
+    let mut divisor = values[1].clone();
+    let divisor_is_constant = divisor.get_const_indices().len() == divisor.len();
+    divisor.expand(&input_dims)?;
+
+    let is_assigned = !input.any_unknowns()? && !divisor.any_unknowns()?;
+
+    let mut claimed_output: ValTensor<F> = if is_assigned {
+        let input_evals = input.int_evals()?;
+        let divisor_evals = divisor.int_evals()?;
+        input_evals
+            .iter()
+            .zip(divisor_evals.iter())
+            .map(|(a, d)| {
+                if *d <= 0 {
+                    return Err(TensorError::InvalidArgument(format!(
+                        "signed div expects positive divisors, got {}",
+                        d
+                    )));
+                }
+                Ok(Value::known(integer_rep_to_felt(rounding.div(*a, *d))))
+            })
+            .collect::<Result<Tensor<Value<F>>, TensorError>>()?
+            .into()
+    } else {
+        Tensor::new(
+            Some(&vec![Value::<F>::unknown(); input.len()]),
+            &[input.len()],
+        )?
+        .into()
+    };
+    claimed_output.reshape(&input_dims)?;
+    // implicitly check if the prover provided output is within range
+    let claimed_output = identity(config, region, &[claimed_output], true)?;
+
+    if !divisor_is_constant {
+        let divisor_sign = sign(config, region, &[divisor.clone()])?;
+        let mut ones = create_constant_tensor(F::ONE, divisor_sign.len());
+        ones.reshape(divisor_sign.dims())?;
+        enforce_equality(config, region, &[divisor_sign, ones])?;
+    }
+
+    let product = pairwise(
+        config,
+        region,
+        &[claimed_output.clone(), divisor.clone()],
+        BaseOp::Mult,
+    )?;
+    let remainder = pairwise(config, region, &[input.clone(), product], BaseOp::Sub)?;
+
+    let (normalized, bound) = match rounding {
+        RoundingMode::Floor => (remainder, divisor),
+        RoundingMode::Ceil => {
+            let minus_one = create_constant_tensor(-F::ONE, 1);
+            let negated = pairwise(config, region, &[remainder, minus_one], BaseOp::Mult)?;
+            (negated, divisor)
+        }
+        RoundingMode::TowardZero => {
+            let s = nonzero_sign(config, region, &input)?;
+            let signed = pairwise(config, region, &[remainder, s], BaseOp::Mult)?;
+            (signed, divisor)
+        }
+        RoundingMode::HalfAwayFromZero => {
+            let s = nonzero_sign(config, region, &input)?;
+            let signed = pairwise(config, region, &[remainder, s], BaseOp::Mult)?;
+            let two = create_constant_tensor(F::from(2), 1);
+            let doubled = pairwise(config, region, &[signed, two.clone()], BaseOp::Mult)?;
+            let shifted = pairwise(config, region, &[doubled, divisor.clone()], BaseOp::Add)?;
+            let doubled_divisor = pairwise(config, region, &[divisor, two], BaseOp::Mult)?;
+            (shifted, doubled_divisor)
+        }
+    };
+
+    enforce_in_bound(config, region, normalized, bound)?;
+
+    match saturate {
+        Some(range) => clamp(config, region, &[claimed_output], range),
+        None => Ok(claimed_output),
+    }
+}
+
+/// Clamps each element to the inclusive `range`, the clamped values are constrained through
+/// max and min comparisons against the bounds.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::clamp;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///    Some(&[-100, -3, 0, 63, 64, 100]),
+///   &[2, 3],
+/// ).unwrap());
+///
+/// let result = clamp::<Fp>(&dummy_config, &mut dummy_region, &[x], (-64, 63)).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-64, -3, 0, 63, 63, 63]), &[2, 3]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn clamp<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    range: crate::circuit::table::Range,
+) -> Result<ValTensor<F>, CircuitError> {
+    let x = values[0].clone();
+    let mut low = create_constant_tensor(integer_rep_to_felt(range.0), x.len());
+    low.reshape(x.dims())?;
+    let mut high = create_constant_tensor(integer_rep_to_felt(range.1), x.len());
+    high.reshape(x.dims())?;
+
+    let clamped = max_comp(config, region, &[x, low])?;
+    min_comp(config, region, &[clamped, high])
+}
+
+/// Constrains `0 <= x < bound` elementwise through comparisons, which decompose into the
+/// region's `base` and `legs` and so only need the `(-1, 1)` and `(0, base - 1)` range checks
+fn enforce_in_bound<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    x: ValTensor<F>,
+    bound: ValTensor<F>,
+) -> Result<(), CircuitError> {
+    let zero = create_constant_tensor(F::ZERO, 1);
+    let is_non_negative = greater_equal(config, region, &[x.clone(), zero])?;
+    let is_below_bound = less(config, region, &[x, bound])?;
+    let in_range = and(config, region, &[is_non_negative, is_below_bound])?;
+    let comparison_unit = create_constant_tensor(F::ONE, in_range.len());
+    enforce_equality(config, region, &[in_range, comparison_unit])?;
+    Ok(())
+}
+
+/// 1 for non-negative elements and -1 for negative ones, unlike [sign] zero maps to 1
+fn nonzero_sign<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    x: &ValTensor<F>,
+) -> Result<ValTensor<F>, CircuitError> {
+    let zero = create_constant_tensor(F::ZERO, 1);
+    let is_non_negative = greater_equal(config, region, &[x.clone(), zero])?;
+    let two = create_constant_tensor(F::from(2), 1);
+    let doubled = pairwise(config, region, &[is_non_negative, two], BaseOp::Mult)?;
+    let one = create_constant_tensor(F::ONE, 1);
+    pairwise(config, region, &[doubled, one], BaseOp::Sub)
+}
+
+/// Div accumulated layout, the quotient `q` of `x / d` is rounded to the nearest integer with
+/// halfway cases rounded away from zero, as [crate::tensor::ops::nonlinearities::const_div] does.
+/// `q` is constrained through its remainder, `0 <= 2 * (x - q * d) + d - n < 2 * d` where `n` is
+/// 1 for negative `x` and 0 otherwise. The bound is checked with comparisons decomposed into the
+/// region's `base` and `legs`, unless the region opts into [RegionCtx::remainder_tables], which
+/// range checks the remainder against a `(0, 2 * d - 1)` table instead: cheaper per element, but
+/// every divisor needs its own table. Use [signed_div] for other rounding modes.
 pub(crate) fn div<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
@@ -133,20 +427,29 @@ pub(crate) fn div<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     let input = value[0].clone();
     let input_dims = input.dims();
 
+    let d = felt_to_integer_rep(div);
+    if d <= 0 {
+        return Err(TensorError::InvalidArgument(format!(
+            "div expects a positive divisor, got {}",
+            d
+        ))
+        .into());
+    }
     let divisor = create_constant_tensor(div, 1);
 
-    let is_assigned = !input.any_unknowns()? && !divisor.any_unknowns()?;
+    let is_assigned = !input.any_unknowns()?;
 
     let mut claimed_output: ValTensor<F> = if is_assigned {
         let input_evals = input.int_evals()?;
-        tensor::ops::nonlinearities::const_div(
-            &input_evals.clone(),
-            felt_to_integer_rep(div) as f64,
-        )
-        .par_iter()
-        .map(|x| Value::known(integer_rep_to_felt(*x)))
-        .collect::<Tensor<Value<F>>>()
-        .into()
+        input_evals
+            .par_iter()
+            .map(|x| {
+                Value::known(integer_rep_to_felt(
+                    RoundingMode::HalfAwayFromZero.div(*x, d),
+                ))
+            })
+            .collect::<Tensor<Value<F>>>()
+            .into()
     } else {
         Tensor::new(
             Some(&vec![Value::<F>::unknown(); input.len()]),
@@ -164,8 +467,22 @@ pub(crate) fn div<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
         &[claimed_output.clone(), divisor.clone()],
         BaseOp::Mult,
     )?;
-
-    diff_less_than(config, region, &[input.clone(), product.clone()], div)?;
+    let remainder = pairwise(config, region, &[input.clone(), product], BaseOp::Sub)?;
+
+    let two = create_constant_tensor(F::from(2), 1);
+    let doubled = pairwise(config, region, &[remainder, two], BaseOp::Mult)?;
+    let shifted = pairwise(config, region, &[doubled, divisor], BaseOp::Add)?;
+    // negative halves sit at the top of the remainder's range rather than the bottom
+    let zero = create_constant_tensor(F::ZERO, 1);
+    let is_negative = less(config, region, &[input, zero])?;
+    let normalized = pairwise(config, region, &[shifted, is_negative], BaseOp::Sub)?;
+
+    if region.remainder_tables() {
+        range_check(config, region, &[normalized], &(0, 2 * d - 1))?;
+    } else {
+        let bound = create_constant_tensor(integer_rep_to_felt(2 * d), 1);
+        enforce_in_bound(config, region, normalized, bound)?;
+    }
 
     Ok(claimed_output)
 }
@@ -397,6 +714,64 @@ pub fn dot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
     values: &[ValTensor<F>; 2],
+) -> Result<ValTensor<F>, CircuitError> {
+    accumulated_dot(config, region, values, None)
+}
+
+/// Dot product of two tensors plus a single bias element (`a · b + bias`).
+/// The bias is assigned as the initial value of the accumulator so that it is added by the same
+/// accumulation constraint as the products, with no separate addition gate.
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+/// use ezkl::circuit::layouts::dot_with_bias;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[5, 2, 3, 0, 4, -1, 3, 1, 6]),
+///     &[1, 3, 3],
+/// ).unwrap());
+/// let y = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[5, 5, 10, -4, 2, -1, 2, 0, 1]),
+///     &[1, 3, 3],
+/// ).unwrap());
+/// let b = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[-6]),
+///     &[1],
+/// ).unwrap());
+/// assert_eq!(dot_with_bias::<Fp>(&dummy_config, &mut dummy_region, &[x, y, b]).unwrap().int_evals().unwrap()[0], 80);
+/// ```
+pub fn dot_with_bias<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 3],
+) -> Result<ValTensor<F>, CircuitError> {
+    if values[2].len() != 1 {
+        return Err(TensorError::DimMismatch("dot with bias".to_string()).into());
+    }
+    accumulated_dot(
+        config,
+        region,
+        &[values[0].clone(), values[1].clone()],
+        Some(&values[2]),
+    )
+}
+
+/// Lays out an accumulated dot product. When `init` is set it is assigned to the output column
+/// in the row ahead of the first products and every product row is constrained with
+/// [BaseOp::Dot], which adds the previous accumulator value, instead of [BaseOp::DotInit].
+fn accumulated_dot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    init: Option<&ValTensor<F>>,
 ) -> Result<ValTensor<F>, CircuitError> {
     if values[0].len() != values[1].len() {
         return Err(TensorError::DimMismatch("dot".to_string()).into());
@@ -411,9 +786,33 @@ pub fn dot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     let mut inputs = vec![];
     let block_width = config.custom_gates.output.num_inner_cols();
 
+    let block_size = config.accumulation_order().block_size();
+
     let mut assigned_len = 0;
     for (i, input) in values.iter_mut().enumerate() {
+        if block_size < block_width {
+            // each row of the gate only sums one block of the accumulation order
+            let spread = input
+                .get_inner_tensor()?
+                .chunks(block_size)
+                .flat_map(|block| {
+                    let mut block = block.to_vec();
+                    block.resize(block_width, ValType::Constant(F::ZERO));
+                    block
+                })
+                .collect::<Vec<_>>();
+            *input = Tensor::new(Some(&spread), &[spread.len()])?.into();
+        }
         input.pad_to_zero_rem(block_width, ValType::Constant(F::ZERO))?;
+        if init.is_some() {
+            // the row holding the initial accumulator value has no products
+            let zeros: ValTensor<F> = Tensor::new(
+                Some(&vec![ValType::Constant(F::ZERO); block_width]),
+                &[block_width],
+            )?
+            .into();
+            *input = zeros.concat(input.clone())?;
+        }
         let inp = {
             let (res, len) = region
                 .assign_with_duplication_unconstrained(&config.custom_gates.inputs[i], input)?;
@@ -426,13 +825,27 @@ pub fn dot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     // Now we can assign the dot product
     // time this step
     let accumulated_dot = accumulated::dot(&[inputs[0].clone(), inputs[1].clone()], block_width)?;
+    let accumulated_dot: ValTensor<F> = match init {
+        Some(init) => {
+            let init_value = init.get_inner()?[0];
+            let mut transcript = vec![init.get_inner_tensor()?[0].clone()];
+            transcript.extend(
+                accumulated_dot
+                    .iter()
+                    .skip(1)
+                    .map(|acc| ValType::Value(*acc + init_value)),
+            );
+            Tensor::new(Some(&transcript), &[transcript.len()])?.into()
+        }
+        None => accumulated_dot.into(),
+    };
     let (output, output_assigned_len) = region.assign_with_duplication_constrained(
         &config.custom_gates.output,
-        &accumulated_dot.into(),
+        &accumulated_dot,
         &config.check_mode,
     )?;
 
-    // enable the selectors
+    // enable the selectors, dummy layouts only count the gates each layer adds
     if !region.is_dummy() {
         (0..output_assigned_len)
             .map(|i| {
@@ -444,16 +857,19 @@ pub fn dot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
                 if z == 0 && i > 0 {
                     return Ok(());
                 }
-                let selector = if i == 0 {
-                    config.custom_gates.selectors.get(&(BaseOp::DotInit, x, 0))
-                } else {
-                    config.custom_gates.selectors.get(&(BaseOp::Dot, x, 0))
+                let selector = match (i, init) {
+                    // the initial accumulator value is not constrained by a gate
+                    (0, Some(_)) => return Ok(()),
+                    (0, None) => config.custom_gates.selectors.get(&(BaseOp::DotInit, x, 0)),
+                    _ => config.custom_gates.selectors.get(&(BaseOp::Dot, x, 0)),
                 };
                 region.enable(selector, z)?;
 
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
+    } else {
+        region.count_gates(accumulated_dot.len() - init.is_some() as usize);
     }
 
     let last_elem = output.last()?;
@@ -862,7 +1278,296 @@ pub fn einsum<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     Ok(output)
 }
 
+/// Dense (affine) layer layout: `x @ w^T (+ b)`.
+/// The bias is optional, when it is omitted no bias assignment or addition constraint is generated.
+/// When it is present each output is laid out with [dot_with_bias], so the bias is added within the
+/// accumulation constraint of the dot product instead of by a separate addition.
+/// The bias is the starting value of each output's accumulator, to which its products are added
+/// in [BaseConfig::accumulation_order], a float reference computed with
+/// [crate::tensor::ops::accumulated::affine] in that order rounds the same way.
+/// # Arguments
+/// * `values` - `[x, w]` or `[x, w, b]` with `x` of shape `[N, IN]`, `w` of shape `[OUT, IN]` and `b` of shape `[OUT]`
+/// # Example
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::affine;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let x = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap();
+/// let w = Tensor::<IntegerRep>::new(Some(&[1, 0, -1, 2, 1, 0]), &[2, 3]).unwrap();
+///
+/// // bias free layer matches a plain matmul with the transposed weights
+/// let result = affine::<Fp>(
+///     &dummy_config,
+///     &mut dummy_region,
+///     &[ValTensor::from_integer_rep_tensor(x.clone()), ValTensor::from_integer_rep_tensor(w.clone())],
+/// ).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-2, 4, -2, 13]), &[2, 2]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // with a bias
+/// let b = Tensor::<IntegerRep>::new(Some(&[1, -1]), &[2]).unwrap();
+/// let result = affine::<Fp>(
+///     &dummy_config,
+///     &mut dummy_region,
+///     &[ValTensor::from_integer_rep_tensor(x), ValTensor::from_integer_rep_tensor(w), ValTensor::from_integer_rep_tensor(b)],
+/// ).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-1, 3, -1, 12]), &[2, 2]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn affine<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>],
+) -> Result<ValTensor<F>, CircuitError> {
+    if values.len() != 2 && values.len() != 3 {
+        return Err(CircuitError::DimMismatch(format!(
+            "affine expects 2 or 3 inputs, got {}",
+            values.len()
+        )));
+    }
+
+    let bias = match values.get(2) {
+        Some(bias) => bias,
+        None => return einsum(config, region, &values[..2], "ij,kj->ik"),
+    };
+
+    let (mut x, mut w) = (values[0].clone(), values[1].clone());
+    if x.dims().len() != 2 || w.dims().len() != 2 || x.dims()[1] != w.dims()[1] {
+        return Err(CircuitError::DimMismatch("affine".to_string()));
+    }
+    let (num_rows, out_features) = (x.dims()[0], w.dims()[0]);
+    if bias.len() != out_features {
+        return Err(CircuitError::DimMismatch("affine bias".to_string()));
+    }
+
+    // assign once so that every dot product is copy constrained to the same cells
+    let mut assigned_len = vec![];
+    if !w.all_prev_assigned() {
+        w = region.assign(&config.custom_gates.inputs[0], &w)?;
+        assigned_len.push(w.len());
+    }
+    if !x.all_prev_assigned() {
+        x = region.assign(&config.custom_gates.inputs[1], &x)?;
+        assigned_len.push(x.len());
+    }
+    if !assigned_len.is_empty() {
+        region.increment(*assigned_len.iter().max().unwrap());
+    }
+
+    let mut output: Tensor<ValType<F>> = Tensor::new(None, &[num_rows * out_features])?;
+
+    let inner_loop_function = |idx: usize, region: &mut RegionCtx<F>| {
+        let (i, k) = (idx / out_features, idx % out_features);
+        let mut row = x.get_slice(&[i..i + 1])?;
+        row.flatten();
+        let mut col = w.get_slice(&[k..k + 1])?;
+        col.flatten();
+        let b = bias.get_single_elem(k)?;
+
+        let res = dot_with_bias(config, region, &[row, col, b])?;
+        Ok(res.get_inner_tensor()?[0].clone())
+    };
+
+    region.flush()?;
+    region.apply_in_loop(&mut output, inner_loop_function)?;
+    output.reshape(&[num_rows, out_features])?;
+
+    Ok(output.into())
+}
+
+/// Folds the bias of a dense layer into its weights, for [affine] layers without a bias input.
+/// `x` of shape `[N, IN]` is padded with a bias column of ones at index `IN` and `b` of shape
+/// `[OUT]` becomes the matching column of `w`, so that `affine([x | 1, w | b])` computes the same
+/// outputs as `affine([x, w, b])` whatever the size of the layer.
+/// # Example
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::{affine, fold_bias};
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap());
+/// let w = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, 0, -1, 2, 1, 0]), &[2, 3]).unwrap());
+/// let b = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, -1]), &[2]).unwrap());
+///
+/// let folded = fold_bias::<Fp>(&[x, w, b]).unwrap();
+/// assert_eq!(folded[0].dims(), &[2, 4]);
+/// assert_eq!(folded[1].int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[1, 0, -1, 1, 2, 1, 0, -1]), &[2, 4]).unwrap());
+///
+/// let result = affine::<Fp>(&dummy_config, &mut dummy_region, &folded).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-1, 3, -1, 12]), &[2, 2]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn fold_bias<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    values: &[ValTensor<F>; 3],
+) -> Result<[ValTensor<F>; 2], CircuitError> {
+    let (x, w, b) = (&values[0], &values[1], &values[2]);
+    if x.dims().len() != 2 || w.dims().len() != 2 || x.dims()[1] != w.dims()[1] {
+        return Err(CircuitError::DimMismatch("fold bias".to_string()));
+    }
+    let (num_rows, out_features) = (x.dims()[0], w.dims()[0]);
+    if b.len() != out_features {
+        return Err(CircuitError::DimMismatch("fold bias".to_string()));
+    }
+
+    let mut ones = create_constant_tensor(F::ONE, num_rows);
+    ones.reshape(&[num_rows, 1])?;
+    let mut bias_column = b.clone();
+    bias_column.reshape(&[out_features, 1])?;
+
+    Ok([x.concat_axis(ones, &1)?, w.concat_axis(bias_column, &1)?])
+}
+
 #[derive(Debug, Clone, Copy)]
+/// A dense layer over several input feature tensors, as if they were concatenated along their
+/// last axis in the order given. The inputs are joined without being reassigned, so features that
+/// are already assigned are copy constrained straight into the [affine] dot products. `params` are
+/// the weights of shape `[OUT, IN]`, where `IN` is the total number of input features, and an
+/// optional bias of shape `[OUT]`.
+/// # Example
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::concat_affine;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let a = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, 2]), &[2]).unwrap());
+/// let b = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[3, 4]), &[2]).unwrap());
+/// let w = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, 0, 0, 1, 0, 1, -1, 0]), &[2, 4]).unwrap());
+/// let bias = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, -1]), &[2]).unwrap());
+/// let result = concat_affine::<Fp>(&dummy_config, &mut dummy_region, &[a, b], &[w, bias]).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[6, -2]), &[2]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn concat_affine<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    inputs: &[ValTensor<F>],
+    params: &[ValTensor<F>],
+) -> Result<ValTensor<F>, CircuitError> {
+    let (first, rest) = inputs.split_first().ok_or(CircuitError::DimMismatch(
+        "concat affine inputs".to_string(),
+    ))?;
+    if params.len() != 1 && params.len() != 2 {
+        return Err(CircuitError::DimMismatch(format!(
+            "concat affine expects weights and an optional bias, got {} params",
+            params.len()
+        )));
+    }
+
+    let rank = first.dims().len();
+    if rank != 1 && rank != 2 {
+        return Err(CircuitError::DimMismatch("concat affine".to_string()));
+    }
+    if rest
+        .iter()
+        .any(|t| t.dims().len() != rank || (rank == 2 && t.dims()[0] != first.dims()[0]))
+    {
+        return Err(CircuitError::DimMismatch("concat affine".to_string()));
+    }
+
+    let mut x = first.clone();
+    for t in rest {
+        x = x.concat_axis(t.clone(), &(rank - 1))?;
+    }
+    if rank == 1 {
+        x.reshape(&[1, x.len()])?;
+    }
+
+    let mut values = vec![x];
+    values.extend(params.iter().cloned());
+    let mut output = affine(config, region, &values)?;
+    if rank == 1 {
+        output.reshape(&[output.len()])?;
+    }
+
+    Ok(output)
+}
+
+/// Verifies a linear solve `A x = b` rather than computing it: the solution `x` is witnessed,
+/// e.g. from a solver run outside the circuit, and `A x` is recomputed with a matmul and copy
+/// constrained to `b`. `A` is `[n, m]`, `x` has `m` elements and `b` has `n`. With `A` and `x` at
+/// some scale, `b` is expected at twice that scale, as for any matmul output. Returns the
+/// assigned solution.
+/// # Example
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::linear_solve;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let a = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[2, 1, 1, 3]), &[2, 2]).unwrap());
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, 2]), &[2]).unwrap());
+/// let b = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[4, 7]), &[2]).unwrap());
+/// let result = linear_solve::<Fp>(&dummy_config, &mut dummy_region, &[a.clone(), x, b.clone()]).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[1, 2]), &[2]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // the shapes have to line up
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[3]).unwrap());
+/// assert!(linear_solve::<Fp>(&dummy_config, &mut dummy_region, &[a, x, b]).is_err());
+/// ```
+pub fn linear_solve<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 3],
+) -> Result<ValTensor<F>, CircuitError> {
+    let [a, x, b] = values;
+    if a.dims().len() != 2 || x.len() != a.dims()[1] || b.len() != a.dims()[0] {
+        return Err(CircuitError::DimMismatch(format!(
+            "linear solve of a {:?} system with a solution of {} and a target of {} elements",
+            a.dims(),
+            x.len(),
+            b.len()
+        )));
+    }
+
+    let mut x = x.clone();
+    x.reshape(&[x.len()])?;
+    if !x.all_prev_assigned() {
+        x = region.assign(&config.custom_gates.inputs[1], &x)?;
+        region.increment(x.len());
+    }
+
+    let mut ax = einsum(config, region, &[a.clone(), x.clone()], "ij,j->i")?;
+    let mut b = b.clone();
+    b.reshape(&[b.len()])?;
+    ax.reshape(&[ax.len()])?;
+    enforce_equality(config, region, &[ax, b])?;
+
+    Ok(x)
+}
+
 /// Determines how to handle collisions in sorting.
 pub enum SortCollisionMode {
     /// Do not sort (no rule)
@@ -983,6 +1688,283 @@ fn _select_topk<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     Ok(sorted.get_slice(&[0..k])?)
 }
 
+/// Returns the `k` largest values of a tensor along with their indices in the flattened tensor,
+/// largest first. Both are read off a sort of the input that is proven to be a permutation of it,
+/// so every selected value is range checked to be at least as large as every value that is not
+/// selected. Ties are broken deterministically in favour of the smallest index, so the indices
+/// are unique for any input.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::topk_indices;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[3, 9, 1, 9, 4]),
+///     &[5],
+/// ).unwrap());
+/// let (values, indices) = topk_indices::<Fp>(&dummy_config, &mut dummy_region, &[x], 3).unwrap();
+/// assert_eq!(values.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[9, 9, 4]), &[3]).unwrap());
+/// assert_eq!(indices.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[1, 3, 4]), &[3]).unwrap());
+/// ```
+pub fn topk_indices<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    k: usize,
+) -> Result<(ValTensor<F>, ValTensor<F>), CircuitError> {
+    let mut input = values[0].clone();
+    input.flatten();
+    if k == 0 || k > input.len() {
+        return Err(TensorError::InvalidArgument(format!(
+            "cannot select the top {} of {} values",
+            k,
+            input.len()
+        ))
+        .into());
+    }
+
+    // reversing an ascending sort that puts the largest index first among equal values gives a
+    // descending sort that puts the smallest index first
+    let (mut sorted, mut indices) = _sort_ascending(
+        config,
+        region,
+        &[input],
+        SortCollisionMode::LargestIndexFirst,
+    )?;
+    sorted.reverse()?;
+    indices.reverse()?;
+
+    Ok((sorted.get_slice(&[0..k])?, indices.get_slice(&[0..k])?))
+}
+
+/// Proves a classification with a margin: the largest of the flattened `values[0]` exceeds the
+/// runner-up by at least `margin`, so the top class holds with that much confidence. The top
+/// two are read off a sort proven by [topk_indices], and `top - second - margin` is checked to
+/// lie in `range`, a configured range check starting at 0. The upper end of the range bounds
+/// the gaps that can be proven. Returns the top two values, largest first.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::margin;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let logits = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[3, 12, -1, 7]),
+///     &[4],
+/// ).unwrap());
+/// let top = margin::<Fp>(&dummy_config, &mut dummy_region, &[logits.clone()], 5, &(0, 1023)).unwrap();
+/// assert_eq!(top.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[12, 7]), &[2]).unwrap());
+///
+/// // 12 only beats 7 by 5
+/// assert!(margin::<Fp>(&dummy_config, &mut dummy_region, &[logits], 6, &(0, 1023)).is_err());
+/// ```
+pub fn margin<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    margin: IntegerRep,
+    range: &crate::circuit::table::Range,
+) -> Result<ValTensor<F>, CircuitError> {
+    if range.0 != 0 {
+        return Err(TensorError::InvalidArgument(format!(
+            "margin expects a range check starting at 0, got {:?}",
+            range
+        ))
+        .into());
+    }
+
+    let (top, _) = topk_indices(config, region, values, 2)?;
+    let first = top.get_slice(&[0..1])?;
+    let second = top.get_slice(&[1..2])?;
+
+    let gap = pairwise(config, region, &[first, second], BaseOp::Sub)?;
+    let margin = create_constant_tensor(integer_rep_to_felt(margin), 1);
+    let slack = pairwise(config, region, &[gap, margin], BaseOp::Sub)?;
+    range_check(config, region, &[slack], range)?;
+
+    Ok(top)
+}
+
+/// Constrains the flattened `values[0]` to be monotonically non-decreasing, `x[i + 1] >= x[i]`,
+/// by range checking every consecutive difference in `range`, a configured range check starting
+/// at 0. The upper end of the range bounds the steps that can be proven. Returns the assigned
+/// values, so they can be exposed as instances.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::monotonic;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let cdf = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[0, 3, 3, 10, 16]),
+///     &[5],
+/// ).unwrap());
+/// let result = monotonic::<Fp>(&dummy_config, &mut dummy_region, &[cdf], &(0, 1023)).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[0, 3, 3, 10, 16]), &[5]).unwrap());
+///
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[0, 3, 2, 10, 16]),
+///     &[5],
+/// ).unwrap());
+/// assert!(monotonic::<Fp>(&dummy_config, &mut dummy_region, &[x], &(0, 1023)).is_err());
+/// ```
+pub fn monotonic<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    range: &crate::circuit::table::Range,
+) -> Result<ValTensor<F>, CircuitError> {
+    if range.0 != 0 {
+        return Err(TensorError::InvalidArgument(format!(
+            "monotonic expects a range check starting at 0, got {:?}",
+            range
+        ))
+        .into());
+    }
+
+    let mut input = values[0].clone();
+    input.flatten();
+    if !input.all_prev_assigned() {
+        input = region.assign(&config.custom_gates.inputs[0], &input)?;
+        region.increment(input.len());
+    }
+
+    let len = input.len();
+    if len > 1 {
+        let next = input.get_slice(&[1..len])?;
+        let prev = input.get_slice(&[0..len - 1])?;
+        let steps = pairwise(config, region, &[next, prev], BaseOp::Sub)?;
+        range_check(config, region, &[steps], range)?;
+    }
+
+    input.reshape(values[0].dims())?;
+    Ok(input)
+}
+
+/// Proves a k-nearest-neighbour classification of the query `values[0]` of shape `[d]` against
+/// the dataset `values[1]` of shape `[n, d]` labelled by `values[2]` of shape `[n]`, with labels
+/// in `0..num_classes`. Squared euclidean distances to every point are read off an einsum of the
+/// differences, the `k` nearest points are selected by [topk_indices] on the negated distances,
+/// and their labels are gathered and counted per class. Returns the class with the most votes,
+/// ties going to the smallest class: each class is scored as
+/// `votes * num_classes + (num_classes - 1 - class)`, so no two scores are equal and the argmax
+/// of the scores is pinned down by its value alone.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::knn;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let tensor = |values: &[IntegerRep], dims: &[usize]| ValTensor::from_integer_rep_tensor(
+///     Tensor::<IntegerRep>::new(Some(values), dims).unwrap(),
+/// );
+/// let query = tensor(&[0, 0], &[2]);
+/// let points = tensor(&[1, 0, 0, 2, 5, 5, -1, -1, 4, 4], &[5, 2]);
+/// let labels = tensor(&[1, 0, 2, 1, 2], &[5]);
+///
+/// // the three nearest points are labelled 1, 1 and 0
+/// let result = knn::<Fp>(&dummy_config, &mut dummy_region, &[query.clone(), points.clone(), labels.clone()], 3, 3).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[1]), &[1]).unwrap());
+///
+/// // the nearest point alone is labelled 1
+/// let result = knn::<Fp>(&dummy_config, &mut dummy_region, &[query.clone(), points.clone(), labels], 1, 3).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[1]), &[1]).unwrap());
+///
+/// // the two nearest points are labelled 2 and 1, a tie which goes to class 1
+/// let labels = tensor(&[2, 0, 2, 1, 2], &[5]);
+/// let result = knn::<Fp>(&dummy_config, &mut dummy_region, &[query, points, labels], 2, 3).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[1]), &[1]).unwrap());
+/// ```
+pub fn knn<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 3],
+    k: usize,
+    num_classes: usize,
+) -> Result<ValTensor<F>, CircuitError> {
+    let (mut query, points, labels) = (values[0].clone(), &values[1], &values[2]);
+    if query.dims().len() != 1
+        || points.dims().len() != 2
+        || points.dims()[1] != query.dims()[0]
+        || labels.dims() != [points.dims()[0]]
+    {
+        return Err(TensorError::DimMismatch(format!(
+            "knn expects a query [d], points [n, d] and labels [n], got {:?}, {:?} and {:?}",
+            query.dims(),
+            points.dims(),
+            labels.dims()
+        ))
+        .into());
+    }
+    query.reshape(&[1, query.len()])?;
+
+    let diffs = pairwise(config, region, &[points.clone(), query], BaseOp::Sub)?;
+    let distances = einsum(config, region, &[diffs.clone(), diffs], "ij,ij->i")?;
+    let minus_one = create_constant_tensor(-F::ONE, 1);
+    let negated = pairwise(config, region, &[distances, minus_one], BaseOp::Mult)?;
+    let (_, nearest) = topk_indices(config, region, &[negated], k)?;
+
+    let mut neighbours = gather(config, region, &[labels.clone(), nearest], 0)?;
+    neighbours.reshape(&[1, k])?;
+    let mut classes: ValTensor<F> = Tensor::from(
+        (0..num_classes).map(|c| ValType::Constant(integer_rep_to_felt(c as IntegerRep))),
+    )
+    .into();
+    classes.reshape(&[num_classes, 1])?;
+
+    let is_class = equals(config, region, &[classes, neighbours])?;
+    let mut votes = sum_axes(config, region, &[is_class], &[1])?;
+    votes.reshape(&[num_classes])?;
+
+    // break ties towards the smallest class, so that exactly one class has the highest score
+    let num_classes_felt =
+        create_constant_tensor(integer_rep_to_felt(num_classes as IntegerRep), 1);
+    let scaled = pairwise(config, region, &[votes, num_classes_felt], BaseOp::Mult)?;
+    let tie_breaks: ValTensor<F> = Tensor::from(
+        (0..num_classes)
+            .rev()
+            .map(|c| ValType::Constant(integer_rep_to_felt(c as IntegerRep))),
+    )
+    .into();
+    let scores = pairwise(config, region, &[scaled, tie_breaks], BaseOp::Add)?;
+    let mut predicted = argmax_axes(config, region, &[scores], 0)?;
+    predicted.reshape(&[1])?;
+    Ok(predicted)
+}
+
 /// Returns top K values.
 /// # Examples
 /// ```
@@ -1182,7 +2164,7 @@ pub(crate) fn dynamic_lookup<F: PrimeField + TensorType + PartialOrd + std::hash
                 }
 
                 let table_selector = config.dynamic_lookups.table_selectors[lookup_block];
-                region.enable(Some(&table_selector), z)?;
+                region.enable_table(Some(&table_selector), z)?;
 
                 Ok(())
             })
@@ -1202,11 +2184,13 @@ pub(crate) fn dynamic_lookup<F: PrimeField + TensorType + PartialOrd + std::hash
                     .get(&(lookup_block, (x, y)))
                     .ok_or(CircuitError::MissingSelectors(format!("{:?}", (x, y))))?;
 
-                region.enable(Some(lookup_selector), z)?;
+                region.enable_lookup(Some(lookup_selector), z)?;
 
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
+    } else {
+        region.count_lookups(lookup_len);
     }
 
     region.increment_dynamic_lookup_col_coord(table_len + flush_len_0);
@@ -1320,7 +2304,9 @@ pub(crate) fn shuffles<F: PrimeField + TensorType + PartialOrd + std::hash::Hash
         .into()
     };
 
-    region.assign_shuffle(&config.shuffles.outputs[2], &claimed_index_output)?;
+    // the claimed indices are returned assigned, so that callers can constrain them further
+    let (claimed_index_output, _) =
+        region.assign_shuffle(&config.shuffles.outputs[2], &claimed_index_output)?;
 
     // the incrementing index is the set of numbered values for the input tensor 0...n, and is FIXED
     let incrementing_index: ValTensor<F> =
@@ -1336,7 +2322,7 @@ pub(crate) fn shuffles<F: PrimeField + TensorType + PartialOrd + std::hash::Hash
                     .cartesian_coord(region.combined_dynamic_shuffle_coord() + i + flush_len_ref);
                 shuffle_block = x;
                 let ref_selector = config.shuffles.output_selectors[shuffle_block];
-                region.enable(Some(&ref_selector), z)?;
+                region.enable_table(Some(&ref_selector), z)?;
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
@@ -1354,11 +2340,13 @@ pub(crate) fn shuffles<F: PrimeField + TensorType + PartialOrd + std::hash::Hash
                     .get(&(shuffle_block, (x, y)))
                     .ok_or(CircuitError::MissingSelectors(format!("{:?}", (x, y))))?;
 
-                region.enable(Some(input_selector), z)?;
+                region.enable_lookup(Some(input_selector), z)?;
 
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
+    } else {
+        region.count_lookups(output_len);
     }
 
     region.increment_shuffle_col_coord(output_len + flush_len_ref);
@@ -2069,6 +3057,7 @@ pub fn sum<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
 
     // Now we can assign the dot product
     let accumulated_sum = accumulated::sum(&input, block_width)?;
+    let num_gates = accumulated_sum.len();
 
     let (output, output_assigned_len) = region.assign_with_duplication_constrained(
         &config.custom_gates.output,
@@ -2095,6 +3084,8 @@ pub fn sum<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
 
             region.enable(selector, z)?;
         }
+    } else {
+        region.count_gates(num_gates);
     }
 
     let last_elem = output.last()?;
@@ -2161,6 +3152,7 @@ pub fn prod<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
 
     // Now we can assign the dot product
     let accumulated_prod = accumulated::prod(&input, block_width)?;
+    let num_gates = accumulated_prod.len();
 
     let (output, output_assigned_len) = region.assign_with_duplication_constrained(
         &config.custom_gates.output,
@@ -2193,6 +3185,8 @@ pub fn prod<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
+    } else {
+        region.count_gates(num_gates);
     }
 
     let last_elem = output.last()?;
@@ -2560,6 +3554,8 @@ pub(crate) fn pairwise<F: PrimeField + TensorType + PartialOrd + std::hash::Hash
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
+    } else {
+        region.count_gates(assigned_len);
     }
     region.increment(assigned_len);
 
@@ -2660,6 +3656,19 @@ pub(crate) fn expand<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
 /// let result = greater::<Fp>(&dummy_config, &mut dummy_region, &[a,b]).unwrap();
 /// let expected = Tensor::<IntegerRep>::new(Some(&[0, 1, 1, 0, 0, 0]), &[2, 3]).unwrap();
 /// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // with 2 legs of base 16 values fit in (-256, 256), but their differences may not
+/// let a = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[200, -200]), &[2]).unwrap());
+/// let b = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(Some(&[-200, 200]), &[2]).unwrap());
+/// let mut narrow_region = RegionCtx::new_dummy(0, 2, RegionSettings::all_true(16, 2));
+/// assert!(greater::<Fp>(&dummy_config, &mut narrow_region, &[a.clone(), b.clone()]).is_err());
+///
+/// // a wider decomposition for comparisons handles them
+/// let settings = RegionSettings::all_true(16, 2).with_comparison_legs(3);
+/// let mut wide_region = RegionCtx::new_dummy(0, 2, settings);
+/// let result = greater::<Fp>(&dummy_config, &mut wide_region, &[a, b]).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[1, 0]), &[2]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
 /// ```
 pub fn greater<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
@@ -2674,7 +3683,8 @@ pub fn greater<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     rhs.expand(&broadcasted_shape)?;
 
     let diff = pairwise(config, region, &[lhs, rhs], BaseOp::Sub)?;
-    let sign = sign(config, region, &[diff])?;
+    let legs = region.comparison_legs();
+    let sign = sign_with_legs(config, region, &[diff], legs)?;
     let eq = equals(config, region, &[sign, create_unit_tensor(1)])?;
     Ok(eq)
 }
@@ -3204,7 +4214,7 @@ pub fn sumpool<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
     values: &[ValTensor<F>],
-    padding: &[(usize, usize)],
+    padding: &[(isize, isize)],
     stride: &[usize],
     kernel_shape: &[usize],
     normalized: bool,
@@ -3276,6 +4286,7 @@ pub fn sumpool<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
 }
 
 /// Applies  max pooling over a ND tensor of shape B x C x D1 x D2 x ... x DN.
+/// Padded cells are treated as `-inf`, so they are never selected as the max.
 /// # Examples
 /// ```
 /// use ezkl::tensor::Tensor;
@@ -3300,12 +4311,20 @@ pub fn sumpool<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
 /// let expected: Tensor<IntegerRep> = Tensor::<IntegerRep>::new(Some(&[5, 4, 4, 6]), &[1, 1, 2, 2]).unwrap();
 /// assert_eq!(pooled.int_evals().unwrap(), expected);
 ///
+/// // padding never wins the max, even when every input is negative
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[-5, -2, -3, -7]),
+///     &[1, 1, 2, 2],
+/// ).unwrap());
+/// let pooled = max_pool::<Fp>(&dummy_config, &mut dummy_region, &[x], &vec![(1, 1); 2], &vec![2;2], &vec![2;2], DataFormat::default()).unwrap();
+/// let expected: Tensor<IntegerRep> = Tensor::<IntegerRep>::new(Some(&[-5, -2, -3, -7]), &[1, 1, 2, 2]).unwrap();
+/// assert_eq!(pooled.int_evals().unwrap(), expected);
 /// ```
 pub fn max_pool<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
     values: &[ValTensor<F>; 1],
-    padding: &[(usize, usize)],
+    padding: &[(isize, isize)],
     stride: &[usize],
     pool_dims: &[usize],
     data_format: DataFormat,
@@ -3319,13 +4338,12 @@ pub fn max_pool<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
         image.reshape(&dims)?;
     }
 
+    let (image, padding) = crop_negative_padding(&image, padding)?;
+
     let image_dims = image.dims();
 
     let (batch, input_channels) = (image_dims[0], image_dims[1]);
 
-    let mut padded_image = image.clone();
-    padded_image.pad(padding.to_vec(), 2)?;
-
     let slides = image_dims[2..]
         .iter()
         .enumerate()
@@ -3355,20 +4373,28 @@ pub fn max_pool<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
         let coord = &cartesian_coord[idx];
         let (b, i) = (coord[0], coord[1]);
 
+        // padded cells behave as -inf and can never be the max, so rather than padding the image
+        // the window is clipped to the cells of the image it overlaps
         let mut slice = vec![b..b + 1, i..i + 1];
-        slice.extend(
-            coord[2..]
-                .iter()
-                .zip(stride.iter())
-                .zip(pool_dims.iter())
-                .map(|((c, s), k)| {
-                    let start = c * s;
-                    let end = start + k;
-                    start..end
-                }),
-        );
+        for (axis, ((c, s), k)) in coord[2..]
+            .iter()
+            .zip(stride.iter())
+            .zip(pool_dims.iter())
+            .enumerate()
+        {
+            let (pad_start, d) = (padding[axis].0, image_dims[axis + 2]);
+            let start = (c * s).max(pad_start).min(pad_start + d) - pad_start;
+            let end = (c * s + k).max(pad_start).min(pad_start + d) - pad_start;
+            if start == end {
+                return Err(TensorError::DimError(
+                    "max_pool window lies entirely in the padding".to_string(),
+                )
+                .into());
+            }
+            slice.push(start..end);
+        }
 
-        let slice = padded_image.get_slice(&slice)?;
+        let slice = image.get_slice(&slice)?;
         let max_w = max(config, region, &[slice])?;
 
         Ok::<_, CircuitError>(max_w.get_inner_tensor()?[0].clone())
@@ -3708,6 +4734,42 @@ pub fn deconv<
     Ok(output)
 }
 
+/// Splits signed padding over the spatial axes of an NCHW `image` into cropping and padding:
+/// negative entries crop that many elements off the corresponding border, as ONNX Pad does with
+/// negative pads. Returns the cropped image and the remaining non-negative padding.
+fn crop_negative_padding<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    image: &ValTensor<F>,
+    padding: &[(isize, isize)],
+) -> Result<(ValTensor<F>, Vec<(usize, usize)>), CircuitError> {
+    let non_negative = padding
+        .iter()
+        .map(|(before, after)| ((*before).max(0) as usize, (*after).max(0) as usize))
+        .collect::<Vec<_>>();
+    if padding
+        .iter()
+        .all(|(before, after)| *before >= 0 && *after >= 0)
+    {
+        return Ok((image.clone(), non_negative));
+    }
+
+    let dims = image.dims();
+    let mut slices = vec![0..dims[0], 0..dims[1]];
+    for ((before, after), d) in padding.iter().zip(&dims[2..]) {
+        let start = before.min(&0).unsigned_abs();
+        let end = d.saturating_sub(after.min(&0).unsigned_abs());
+        if start >= end {
+            return Err(TensorError::DimMismatch(format!(
+                "cannot crop ({}, {}) from a dimension of size {}",
+                before, after, d
+            ))
+            .into());
+        }
+        slices.push(start..end);
+    }
+
+    Ok((image.get_slice(&slices)?, non_negative))
+}
+
 /// Applies convolution over a ND tensor of shape C x H x D1...DN (and adds a bias).
 /// ```
 /// use ezkl::tensor::Tensor;
@@ -3782,7 +4844,20 @@ pub fn deconv<
 /// let expected = Tensor::<IntegerRep>::new(Some(&[14, 20, 26]), &[1, 1, 3]).unwrap();
 /// assert_eq!(result.int_evals().unwrap(), expected);
 ///
-/// // Test case 5: 3D convolution with NCHW format
+/// // Test case 5: 1D convolution over a length-6 sequence
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, 2, 3, 4, 5, 6]),
+///     &[1, 1, 6],  // NCL format
+/// ).unwrap());
+/// let k = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, 2, 3]),
+///     &[1, 1, 3],  // OIL format
+/// ).unwrap());
+/// let result = conv::<Fp>(&dummy_config, &mut dummy_region, &[x, k], &vec![(0, 0)], &vec![1], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[14, 20, 26, 32]), &[1, 1, 4]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // Test case 6: 3D convolution with NCHW format
 /// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
 ///     Some(&[1, 2, 3, 4, 5, 6, 7, 8]),
 ///     &[1, 1, 2, 2, 2],  // NCDHW format
@@ -3794,6 +4869,22 @@ pub fn deconv<
 /// let result = conv::<Fp>(&dummy_config, &mut dummy_region, &[x, k], &vec![(0, 0); 3], &vec![1; 3], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
 /// let expected = Tensor::<IntegerRep>::new(Some(&[3, 7, 11, 15]), &[1, 1, 2, 2, 1]).unwrap();
 /// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // Test case 7: negative padding crops a border off the 4x4 image, leaving its central 2x2
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&(0..16).collect::<Vec<_>>()),
+///     &[1, 1, 4, 4],
+/// ).unwrap());
+/// let k = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, 2, 3, 4]),
+///     &[1, 1, 2, 2],
+/// ).unwrap());
+/// let result = conv::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), k.clone()], &vec![(-1, -1); 2], &vec![1; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[5 + 2 * 6 + 3 * 9 + 4 * 10]), &[1, 1, 1, 1]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // cropping more than the image holds is an error
+/// assert!(conv::<Fp>(&dummy_config, &mut dummy_region, &[x, k], &vec![(-2, -2); 2], &vec![1; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).is_err());
 /// ```
 ///
 pub fn conv<
@@ -3802,8 +4893,78 @@ pub fn conv<
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
     values: &[ValTensor<F>],
-    padding: &[(usize, usize)],
+    padding: &[(isize, isize)],
+    stride: &[usize],
+    num_groups: usize,
+    data_format: DataFormat,
+    kernel_format: KernelFormat,
+) -> Result<ValTensor<F>, CircuitError> {
+    dilated_conv(
+        config,
+        region,
+        values,
+        padding,
+        stride,
+        &vec![1; stride.len()],
+        num_groups,
+        data_format,
+        kernel_format,
+    )
+}
+
+/// Convolution with dilated kernels: consecutive kernel taps along spatial axis `i` sample input
+/// positions `dilation[i]` apart, so a kernel of size `k` spans `dilation[i] * (k - 1) + 1`
+/// inputs. A dilation of 1 along every axis is the regular [conv].
+/// Each output is the dot product of its window and the kernel of its output channel, both
+/// flattened in row-major order (input channels, then the kernel taps) and accumulated in
+/// [BaseConfig::accumulation_order], starting from the bias.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::dilated_conv;
+/// use ezkl::tensor::{val::ValTensor, DataFormat, KernelFormat};
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// // x[r][c] = 5r + c
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&(0..25).collect::<Vec<_>>()),
+///     &[1, 1, 5, 5],
+/// ).unwrap());
+/// let k = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+///     &[1, 1, 3, 3],
+/// ).unwrap());
+///
+/// // with dilation 2 the 3x3 kernel spans the whole 5x5 input and only samples the even rows
+/// // and columns
+/// let result = dilated_conv::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), k.clone()], &vec![(0, 0); 2], &vec![1; 2], &vec![2; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// let expected = (0..3)
+///     .flat_map(|i| (0..3).map(move |j| (3 * i + j + 1) * (10 * i + 2 * j)))
+///     .sum::<IntegerRep>();
+/// assert_eq!(result.dims(), &[1, 1, 1, 1]);
+/// assert_eq!(result.int_evals().unwrap()[0], expected);
+///
+/// // padding 1 leaves room for a 3x3 output
+/// let result = dilated_conv::<Fp>(&dummy_config, &mut dummy_region, &[x, k], &vec![(1, 1); 2], &vec![1; 2], &vec![2; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[368, 521, 320, 519, 732, 447, 224, 305, 176]), &[1, 1, 3, 3]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn dilated_conv<
+    F: PrimeField + TensorType + PartialOrd + std::hash::Hash + std::marker::Send + std::marker::Sync,
+>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>],
+    padding: &[(isize, isize)],
     stride: &[usize],
+    dilation: &[usize],
     num_groups: usize,
     data_format: DataFormat,
     kernel_format: KernelFormat,
@@ -3821,6 +4982,14 @@ pub fn conv<
         .into());
     }
 
+    if dilation.len() != stride.len() || dilation.iter().any(|&d| d == 0) {
+        return Err(TensorError::DimMismatch(
+            "expected a positive dilation for each spatial dimension of conv".to_string(),
+        )
+        .into());
+    }
+    let is_dilated = dilation.iter().any(|&d| d != 1);
+
     // Assign tensors
     let mut assigned_len = vec![];
     if !working_kernel.all_prev_assigned() {
@@ -3842,6 +5011,8 @@ pub fn conv<
         working_image.reshape(&dim)?;
     }
 
+    let (working_image, padding) = crop_negative_padding(&working_image, padding)?;
+
     let image_dims = working_image.dims();
     let kernel_dims = working_kernel.dims();
 
@@ -3854,13 +5025,24 @@ pub fn conv<
     let input_channels = image_dims[1];
     let output_channels = kernel_dims[0];
 
+    // a bias is either shared by all output channels or has one value per channel
+    if has_bias && values[2].len() != 1 && values[2].len() != output_channels {
+        return Err(TensorError::DimMismatch(format!(
+            "conv bias of length {} for {} output channels",
+            values[2].len(),
+            output_channels
+        ))
+        .into());
+    }
+
     // Calculate slides for each spatial dimension
     let slides = image_dims[2..]
         .iter()
         .enumerate()
         .map(|(i, d)| {
             let d = padding[i].0 + d + padding[i].1;
-            d.checked_sub(kernel_dims[i + 2])
+            let kernel_span = dilation[i] * (kernel_dims[i + 2] - 1) + 1;
+            d.checked_sub(kernel_span)
                 .ok_or_else(|| TensorError::Overflow("conv".to_string()))?
                 .checked_div(stride[i])
                 .ok_or_else(|| TensorError::Overflow("conv".to_string()))?
@@ -3917,11 +5099,27 @@ pub fn conv<
         let mut slices = vec![batch..batch + 1, start_channel..end_channel];
         for (i, stride) in stride.iter().enumerate() {
             let coord = cartesian_coord_per_group[batch_offset + i] * stride;
-            let kernel_dim = kernel_dims[2 + i];
-            slices.push(coord..(coord + kernel_dim));
+            let kernel_span = dilation[i] * (kernel_dims[2 + i] - 1) + 1;
+            slices.push(coord..(coord + kernel_span));
         }
 
         let mut local_image = padded_image.get_slice(&slices)?;
+        if is_dilated {
+            // keep only the positions the kernel taps land on
+            let window = local_image.get_inner_tensor()?;
+            let taps = window
+                .dims()
+                .iter()
+                .enumerate()
+                .map(|(axis, d)| {
+                    let step = if axis < 2 { 1 } else { dilation[axis - 2] };
+                    (0..*d).step_by(step)
+                })
+                .multi_cartesian_product()
+                .map(|coord| window.get(&coord))
+                .collect::<Vec<_>>();
+            local_image = Tensor::from(taps.into_iter()).into();
+        }
         local_image.flatten();
 
         let start_kernel_index = group * output_channels_per_group + i;
@@ -3929,18 +5127,19 @@ pub fn conv<
         let mut local_kernel = working_kernel.get_slice(&[start_kernel_index..end_kernel_index])?;
         local_kernel.flatten();
 
-        let mut res = einsum(config, region, &[local_image, local_kernel], "i,i->")?;
-
-        if has_bias {
+        let res = if has_bias {
             let bias_index = if values[2].len() > 1 {
                 start_kernel_index
             } else {
                 0
             };
 
+            // the bias is the initial value of the accumulation, so it costs no extra gate
             let bias = values[2].get_single_elem(bias_index)?;
-            res = pairwise(config, region, &[res, bias], BaseOp::Add)?;
-        }
+            dot_with_bias(config, region, &[local_image, local_kernel, bias])?
+        } else {
+            einsum(config, region, &[local_image, local_kernel], "i,i->")?
+        };
         region.flush()?;
 
         Ok(res.get_inner_tensor()?[0].clone())
@@ -3961,6 +5160,236 @@ pub fn conv<
     Ok(final_output)
 }
 
+/// Convolution layout that lowers the kernel application to a single matrix product (im2col).
+/// Every receptive field of the padded image is unfolded into one row of a
+/// `[batch * output_positions, in_channels * kernel_size]` matrix, which is then multiplied
+/// against the kernel flattened to `[out_channels, in_channels * kernel_size]`.
+/// This lays out all of the dot products with one [einsum] call rather than one per output cell
+/// and produces exactly the same output as [conv]. Grouped convolutions are not supported.
+/// # Arguments
+/// * `config` - BaseConfig
+/// * `region` - RegionCtx
+/// * `values` - &[ValTensor<F>] - [image, kernel] or [image, kernel, bias]
+/// * `padding` - &[(isize, isize)] - padding for each spatial dimension, negative values crop
+/// * `stride` - &[usize] - stride for each spatial dimension
+/// * `num_groups` - usize - must be 1
+/// * `data_format` - DataFormat - the format of the image
+/// * `kernel_format` - KernelFormat - the format of the kernel
+/// # Returns
+/// * `ValTensor<F>` - the convolved image in `data_format`
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::{conv, conv_im2col};
+/// use ezkl::tensor::{val::ValTensor, DataFormat, KernelFormat};
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[5, 2, 3, 0, 4, -1, 3, 1, 6, 1, -2, 0, 7, 3, 2, -4, 1, 5]),
+///     &[1, 2, 3, 3],
+/// ).unwrap());
+/// let k = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[5, 1, 1, 1, 2, 0, -1, 3, 1, 1, 0, 2, -3, 1, 4, 1]),
+///     &[2, 2, 2, 2],
+/// ).unwrap());
+/// let b = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, -1]),
+///     &[2],
+/// ).unwrap());
+///
+/// // stride 1, no padding
+/// let naive = conv::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), k.clone(), b.clone()], &vec![(0, 0); 2], &vec![1; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// let result = conv_im2col::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), k.clone(), b.clone()], &vec![(0, 0); 2], &vec![1; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// assert_eq!(result.dims(), &[1, 2, 2, 2]);
+/// assert_eq!(result.int_evals().unwrap(), naive.int_evals().unwrap());
+///
+/// // stride 2, padding 1
+/// let naive = conv::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), k.clone(), b.clone()], &vec![(1, 1); 2], &vec![2; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// let result = conv_im2col::<Fp>(&dummy_config, &mut dummy_region, &[x, k, b], &vec![(1, 1); 2], &vec![2; 2], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// assert_eq!(result.dims(), &[1, 2, 2, 2]);
+/// assert_eq!(result.int_evals().unwrap(), naive.int_evals().unwrap());
+///
+/// // 1D convolution without bias
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, 2, 3, 4, 5, 6]),
+///     &[1, 1, 6],
+/// ).unwrap());
+/// let k = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, 2, 3]),
+///     &[1, 1, 3],
+/// ).unwrap());
+/// let result = conv_im2col::<Fp>(&dummy_config, &mut dummy_region, &[x.clone(), k.clone()], &vec![(0, 0)], &vec![1], 1, DataFormat::NCHW, KernelFormat::OIHW).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[14, 20, 26, 32]), &[1, 1, 4]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // padding for two spatial dims doesn't fit a 1D convolution
+/// let result = conv_im2col::<Fp>(&dummy_config, &mut dummy_region, &[x, k], &vec![(0, 0); 2], &vec![1], 1, DataFormat::NCHW, KernelFormat::OIHW);
+/// assert!(result.is_err());
+/// ```
+///
+pub fn conv_im2col<
+    F: PrimeField + TensorType + PartialOrd + std::hash::Hash + std::marker::Send + std::marker::Sync,
+>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>],
+    padding: &[(isize, isize)],
+    stride: &[usize],
+    num_groups: usize,
+    data_format: DataFormat,
+    kernel_format: KernelFormat,
+) -> Result<ValTensor<F>, CircuitError> {
+    let has_bias = values.len() == 3;
+    let (mut working_image, mut working_kernel) = (values[0].clone(), values[1].clone());
+
+    data_format.to_canonical(&mut working_image)?;
+    kernel_format.to_canonical(&mut working_kernel)?;
+
+    if num_groups != 1 {
+        return Err(TensorError::DimMismatch(format!(
+            "im2col conv does not support grouped convolutions, got groups={}",
+            num_groups
+        ))
+        .into());
+    }
+
+    if stride.iter().any(|&s| s == 0) {
+        return Err(TensorError::DimMismatch(
+            "non-positive stride is not supported for conv".to_string(),
+        )
+        .into());
+    }
+
+    // the image is [batch, channels, spatial...] once a missing batch dim is added, and the
+    // kernel [out_channels, in_channels, spatial...]
+    let spatial_dims =
+        (working_image.dims().len() + data_format.has_no_batch() as usize).saturating_sub(2);
+    if spatial_dims == 0
+        || working_kernel.dims().len() != spatial_dims + 2
+        || padding.len() != spatial_dims
+        || stride.len() != spatial_dims
+    {
+        return Err(TensorError::DimMismatch(format!(
+            "im2col conv expected a kernel, padding and stride for {} spatial dims, got kernel {:?}, {} paddings and {} strides",
+            spatial_dims,
+            working_kernel.dims(),
+            padding.len(),
+            stride.len()
+        ))
+        .into());
+    }
+
+    // Assign tensors
+    let mut assigned_len = vec![];
+    if !working_kernel.all_prev_assigned() {
+        working_kernel = region.assign(&config.custom_gates.inputs[0], &working_kernel)?;
+        assigned_len.push(working_kernel.len());
+    }
+    if !working_image.all_prev_assigned() {
+        working_image = region.assign(&config.custom_gates.inputs[1], &working_image)?;
+        assigned_len.push(working_image.len());
+    }
+
+    if !assigned_len.is_empty() {
+        region.increment(*assigned_len.iter().max().unwrap());
+    }
+
+    if data_format.has_no_batch() {
+        let mut dim = working_image.dims().to_vec();
+        dim.insert(0, 1);
+        working_image.reshape(&dim)?;
+    }
+
+    let (working_image, padding) = crop_negative_padding(&working_image, padding)?;
+
+    let image_dims = working_image.dims().to_vec();
+    let kernel_dims = working_kernel.dims().to_vec();
+
+    // Apply padding
+    let mut padded_image = working_image.clone();
+    padded_image.pad(padding.to_vec(), 2)?;
+
+    // Extract dimensions
+    let batch_size = image_dims[0];
+    let input_channels = image_dims[1];
+    let output_channels = kernel_dims[0];
+
+    if kernel_dims[1] != input_channels {
+        return Err(TensorError::DimMismatch(format!(
+            "im2col conv expected kernel with {} input channels, got {}",
+            input_channels, kernel_dims[1]
+        ))
+        .into());
+    }
+
+    // Calculate slides for each spatial dimension
+    let slides = image_dims[2..]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            let d = padding[i].0 + d + padding[i].1;
+            d.checked_sub(kernel_dims[i + 2])
+                .ok_or_else(|| TensorError::Overflow("conv".to_string()))?
+                .checked_div(stride[i])
+                .ok_or_else(|| TensorError::Overflow("conv".to_string()))?
+                .checked_add(1)
+                .ok_or_else(|| TensorError::Overflow("conv".to_string()))
+        })
+        .collect::<Result<Vec<_>, TensorError>>()?;
+
+    let patch_len = kernel_dims[1..].iter().product::<usize>();
+    let num_positions = slides.iter().product::<usize>();
+
+    // Unfold every receptive field into a row of the patch matrix
+    let mut iterations = vec![0..batch_size];
+    for slide in slides.iter() {
+        iterations.push(0..*slide);
+    }
+
+    let mut patches = Vec::with_capacity(batch_size * num_positions * patch_len);
+    for coord in iterations.into_iter().multi_cartesian_product() {
+        let mut slices = vec![coord[0]..coord[0] + 1, 0..input_channels];
+        for (i, stride) in stride.iter().enumerate() {
+            let start = coord[1 + i] * stride;
+            slices.push(start..(start + kernel_dims[2 + i]));
+        }
+        let patch = padded_image.get_slice(&slices)?;
+        patches.extend(patch.get_inner_tensor()?.iter().cloned());
+    }
+
+    let unfolded: ValTensor<F> =
+        Tensor::new(Some(&patches), &[batch_size * num_positions, patch_len])?.into();
+    working_kernel.reshape(&[output_channels, patch_len])?;
+
+    // [batch * positions, patch_len] x [out_channels, patch_len]^T
+    let mut output = einsum(config, region, &[unfolded, working_kernel], "ik,jk->ij")?;
+
+    if has_bias {
+        let mut bias = values[2].clone();
+        bias.reshape(&[1, bias.len()])?;
+        output = pairwise(config, region, &[output, bias], BaseOp::Add)?;
+    }
+
+    // [batch, positions, out_channels] -> [batch, out_channels, slides...]
+    output.reshape(&[batch_size, num_positions, output_channels])?;
+    output.move_axis(2, 1)?;
+    let mut dims = vec![batch_size, output_channels];
+    dims.extend(slides.iter().cloned());
+    output.reshape(&dims)?;
+
+    // Convert output back to requested format
+    data_format.from_canonical(&mut output)?;
+
+    Ok(output)
+}
+
 /// Power accumulated layout
 pub(crate) fn pow<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
@@ -4232,10 +5661,12 @@ pub(crate) fn range_check<F: PrimeField + TensorType + PartialOrd + std::hash::H
                     .input
                     .cartesian_coord(region.linear_coord() + i);
                 let selector = config.range_checks.selectors.get(&(*range, x, y));
-                region.enable(selector, z)?;
+                region.enable_lookup(selector, z)?;
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
+    } else {
+        region.count_lookups(assigned_len);
     }
 
     let is_assigned = !w.any_unknowns()?;
@@ -4302,6 +5733,24 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
 
     let is_dummy = region.is_dummy();
 
+    if !is_dummy && region.check_range() && config.check_mode.is_safe() && !w.any_unknowns()? {
+        // surface out of range inputs here rather than as an unsatisfied lookup
+        let table = config
+            .static_lookups
+            .tables
+            .get(nl)
+            .ok_or(CircuitError::LookupNotConfigured(Op::<F>::as_string(nl)))?;
+        let (min, max) = (table.range.0, table.largest());
+        if let Some(v) = w.int_evals()?.iter().find(|v| **v < min || **v > max) {
+            return Err(CircuitError::LookupOOR(
+                Op::<F>::as_string(nl),
+                *v,
+                min,
+                max,
+            ));
+        }
+    }
+
     let table_index: ValTensor<F> = w
         .get_inner_tensor()?
         .par_enum_map(|i, e| {
@@ -4337,10 +5786,12 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
                     .input
                     .cartesian_coord(region.linear_coord() + i);
                 let selector = config.static_lookups.selectors.get(&(nl.clone(), x, y));
-                region.enable(selector, z)?;
+                region.enable_lookup(selector, z)?;
                 Ok(())
             })
             .collect::<Result<Vec<_>, CircuitError>>()?;
+    } else {
+        region.count_lookups(assigned_len);
     }
 
     region.increment(assigned_len);
@@ -4359,6 +5810,70 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
     Ok(output)
 }
 
+/// Lookup based nonlinearity whose inputs are first clamped to `range`, the input range the
+/// lookup table was configured with. Inputs outside the table saturate to its edges instead of
+/// failing the lookup, eg. a divide of a large accumulation returns `range.1 / denom`. The clamp
+/// is constrained through the same comparisons as [max_comp] and [min_comp].
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::saturating_nonlinearity;
+/// use ezkl::circuit::ops::lookup::LookupOp;
+/// use ezkl::tensor::val::ValTensor;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///    Some(&[1000, -1000, 40, 129]),
+///    &[4],
+/// ).unwrap());
+///
+/// let div = LookupOp::Div { denom: 4.0.into() };
+/// let result = saturating_nonlinearity::<Fp>(&dummy_config, &mut dummy_region, &[x], &div, (-128, 128)).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[32, -32, 10, 32]), &[4]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn saturating_nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    nl: &LookupOp,
+    range: crate::circuit::table::Range,
+) -> Result<ValTensor<F>, CircuitError> {
+    let x = values[0].clone();
+
+    let mut low = create_constant_tensor(integer_rep_to_felt(range.0), x.len());
+    low.reshape(x.dims())?;
+    let mut high = create_constant_tensor(integer_rep_to_felt(range.1), x.len());
+    high.reshape(x.dims())?;
+
+    let clamped = max_comp(config, region, &[x, low])?;
+    let clamped = min_comp(config, region, &[clamped, high])?;
+
+    nonlinearity(config, region, &[clamped], nl)
+}
+
+/// Lookup based reciprocal `round(scale^2 / x)` that also constrains every input to be strictly
+/// positive, the only domain on which the reciprocal table is meaningful.
+pub(crate) fn positive_reciprocal<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    scale: utils::F32,
+) -> Result<ValTensor<F>, CircuitError> {
+    let input_sign = sign(config, region, values)?;
+    let mut ones = create_constant_tensor(F::ONE, input_sign.len());
+    ones.reshape(input_sign.dims())?;
+    enforce_equality(config, region, &[input_sign, ones])?;
+
+    nonlinearity(config, region, values, &LookupOp::Reciprocal { scale })
+}
+
 /// Argmax
 pub(crate) fn argmax<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
@@ -5538,9 +7053,20 @@ pub(crate) fn sign<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     region: &mut RegionCtx<F>,
     values: &[ValTensor<F>; 1],
 ) -> Result<ValTensor<F>, CircuitError> {
-    let mut decomp = decompose(config, region, values, &region.base(), &region.legs())?.0;
+    let legs = region.legs();
+    sign_with_legs(config, region, values, legs)
+}
+
+/// [sign] over a decomposition into `legs` digits rather than the region's default
+pub(crate) fn sign_with_legs<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    legs: usize,
+) -> Result<ValTensor<F>, CircuitError> {
+    let mut decomp = decompose(config, region, values, &region.base(), &legs)?.0;
     // get every n elements now, which correspond to the sign bit
-    decomp.get_every_n(region.legs() + 1)?;
+    decomp.get_every_n(legs + 1)?;
     decomp.reshape(values[0].dims())?;
 
     Ok(decomp)
@@ -5556,6 +7082,54 @@ pub(crate) fn abs<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     pairwise(config, region, &[values[0].clone(), sign], BaseOp::Mult)
 }
 
+/// ReLU without a lookup table. The input is decomposed into a sign and range-checked digits
+/// (the magnitude), which constrains the sign to be consistent with the input. The output is the
+/// input masked by whether the sign is positive, so the cost is a couple of range-checked
+/// decompositions per element rather than a table over every representable input. This is
+/// usually cheaper than a [LookupOp::ThresholdedReLU] table once the lookup range is wide, but
+/// requires range checks over `(-1, 1)` and `(0, base - 1)` to be configured.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+/// use ezkl::circuit::layouts::relu;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///    Some(&[-1000, -1, 0, 1, 1000, 65535]),
+///    &[2, 3],
+/// ).unwrap());
+/// let result = relu::<Fp>(&dummy_config, &mut dummy_region, &[x]).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[0, 0, 0, 1, 1000, 65535]), &[2, 3]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn relu<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+) -> Result<ValTensor<F>, CircuitError> {
+    let sign = sign(config, region, values)?;
+
+    let mut unit = create_unit_tensor(sign.len());
+    unit.reshape(sign.dims())?;
+
+    let relu_mask = equals(config, region, &[sign, unit])?;
+
+    pairwise(
+        config,
+        region,
+        &[values[0].clone(), relu_mask],
+        BaseOp::Mult,
+    )
+}
+
 pub(crate) fn leaky_relu<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
@@ -5563,6 +7137,10 @@ pub(crate) fn leaky_relu<F: PrimeField + TensorType + PartialOrd + std::hash::Ha
     alpha: &utils::F32,
     input_scale: &i32,
 ) -> Result<ValTensor<F>, CircuitError> {
+    if alpha.0 == 0. {
+        return relu(config, region, values);
+    }
+
     let sign = sign(config, region, values)?;
 
     let mut unit = create_unit_tensor(sign.len());
@@ -5577,10 +7155,6 @@ pub(crate) fn leaky_relu<F: PrimeField + TensorType + PartialOrd + std::hash::Ha
         BaseOp::Mult,
     )?;
 
-    if alpha.0 == 0. {
-        return Ok(positive);
-    }
-
     if input_scale < &0 {
         return Err(CircuitError::NegativeScale("leaky_relu".to_string()));
     }
@@ -5611,6 +7185,87 @@ pub(crate) fn leaky_relu<F: PrimeField + TensorType + PartialOrd + std::hash::Ha
     pairwise(config, region, &[rescaled_positive, neg_part], BaseOp::Add)
 }
 
+/// PReLU: a leaky relu whose negative slope is chosen per channel.
+/// `values[1]` holds one slope per channel of `values[0]` (axis 1, or axis 0 for 1D inputs),
+/// already quantized at `slope_scale`. The positive branch passes through, rescaled by
+/// `2^slope_scale` so both branches share the output scale of `input_scale + slope_scale`.
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+/// use ezkl::tensor::ValTensor;
+/// use ezkl::circuit::layouts::prelu;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// // two channels of two values each
+/// let x = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[-4, 3, -4, 3]),
+///     &[1, 2, 2],
+/// ).unwrap());
+/// // slopes of 0.25 and 0.5 at scale 2
+/// let slopes = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1, 2]),
+///     &[2],
+/// ).unwrap());
+/// let result = prelu::<Fp>(&dummy_config, &mut dummy_region, &[x, slopes], 2).unwrap();
+/// let expected = Tensor::<IntegerRep>::new(Some(&[-4, 12, -8, 12]), &[1, 2, 2]).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected);
+/// ```
+pub fn prelu<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    slope_scale: i32,
+) -> Result<ValTensor<F>, CircuitError> {
+    if slope_scale < 0 {
+        return Err(CircuitError::NegativeScale("prelu".to_string()));
+    }
+
+    let input = values[0].clone();
+    let channel_axis = if input.dims().len() > 1 { 1 } else { 0 };
+    let num_channels = input.dims()[channel_axis];
+
+    let mut slopes = values[1].clone();
+    if slopes.len() != num_channels {
+        return Err(TensorError::DimMismatch("prelu".to_string()).into());
+    }
+    // broadcast the slopes along every axis but the channel axis
+    let mut slope_dims = vec![1; input.dims().len()];
+    slope_dims[channel_axis] = num_channels;
+    slopes.reshape(&slope_dims)?;
+
+    let sign = sign(config, region, &[input.clone()])?;
+
+    let mut unit = create_unit_tensor(sign.len());
+    unit.reshape(sign.dims())?;
+
+    let relu_mask = equals(config, region, &[sign, unit])?;
+
+    let positive = pairwise(
+        config,
+        region,
+        &[input.clone(), relu_mask.clone()],
+        BaseOp::Mult,
+    )?;
+
+    let scale_constant = create_constant_tensor(F::from(2_u64.pow(slope_scale as u32)), 1);
+
+    let rescaled_positive = pairwise(config, region, &[positive, scale_constant], BaseOp::Mult)?;
+
+    let neg_mask = not(config, region, &[relu_mask])?;
+
+    let negative = pairwise(config, region, &[input, neg_mask], BaseOp::Mult)?;
+
+    let neg_part = pairwise(config, region, &[negative, slopes], BaseOp::Mult)?;
+
+    pairwise(config, region, &[rescaled_positive, neg_part], BaseOp::Add)
+}
+
 fn multi_dim_axes_op<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
@@ -5721,12 +7376,20 @@ pub(crate) fn softmax_axes<F: PrimeField + TensorType + PartialOrd + std::hash::
     input_scale: utils::F32,
     output_scale: utils::F32,
     axes: &[usize],
+    temperature: utils::F32,
 ) -> Result<ValTensor<F>, CircuitError> {
     let soft_max_at_scale = move |config: &BaseConfig<F>,
                                   region: &mut RegionCtx<F>,
                                   values: &[ValTensor<F>; 1]|
           -> Result<ValTensor<F>, CircuitError> {
-        softmax(config, region, values, input_scale, output_scale)
+        softmax_with_temperature(
+            config,
+            region,
+            values,
+            input_scale,
+            output_scale,
+            temperature,
+        )
     };
 
     let output = multi_dim_axes_op(config, region, values, axes, soft_max_at_scale)?;
@@ -5787,6 +7450,28 @@ pub(crate) fn percent<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>
 /// // doubles the scale of the input
 /// let expected = Tensor::<IntegerRep>::new(Some(&[350012, 350012, 352768, 350012, 350012, 344500]), &[2, 3]).unwrap();
 /// assert_eq!(result.int_evals().unwrap(), expected);
+///
+/// // large logits are rebased by their (constrained) max before the exp lookup, so the table
+/// // inputs stay non-positive and the result matches that of the rebased logits
+/// let mut large_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+/// let large = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[1_000_000, 1_000_128, 1_000_256]),
+///     &[3],
+/// ).unwrap());
+/// let result = softmax::<Fp>(&dummy_config, &mut large_region, &[large], 128.0.into(), (128.0 * 128.0).into()).unwrap();
+/// assert_eq!(large_region.max_lookup_inputs(), 0);
+/// assert!(large_region.min_lookup_inputs() >= -256);
+/// let rebased = ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(&[-256, -128, 0]),
+///     &[3],
+/// ).unwrap());
+/// let expected = softmax::<Fp>(&dummy_config, &mut dummy_region, &[rebased], 128.0.into(), (128.0 * 128.0).into()).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), expected.int_evals().unwrap());
+/// // the probabilities increase with the logits and sum to ~1 at the combined input and output scale
+/// let probs = result.int_evals().unwrap();
+/// assert!(probs[0] < probs[1] && probs[1] < probs[2]);
+/// let one = 128 * 128 * 128;
+/// assert!((probs.iter().sum::<IntegerRep>() - one).abs() < one / 100);
 /// ```
 pub fn softmax<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
@@ -5795,10 +7480,81 @@ pub fn softmax<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     input_scale: utils::F32,
     output_scale: utils::F32,
 ) -> Result<ValTensor<F>, CircuitError> {
+    softmax_with_temperature(
+        config,
+        region,
+        values,
+        input_scale,
+        output_scale,
+        1.0.into(),
+    )
+}
+
+/// Applies softmax to the logits divided by `temperature`, a temperature above 1 smooths the
+/// output distribution and one below 1 sharpens it. The temperature is applied in fixed point at
+/// `input_scale`, as a constrained divide of the rebased logits by `temperature * input_scale`.
+/// A temperature of 1 is the plain [softmax].
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::{softmax, softmax_with_temperature};
+/// use ezkl::tensor::val::ValTensor;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let logits = |values: &[IntegerRep]| ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(values),
+///     &[3],
+/// ).unwrap());
+/// let x = logits(&[0, 128, 256]);
+///
+/// let t1 = softmax_with_temperature::<Fp>(&dummy_config, &mut dummy_region, &[x.clone()], 128.0.into(), 128.0.into(), 1.0.into()).unwrap();
+/// let plain = softmax::<Fp>(&dummy_config, &mut dummy_region, &[x.clone()], 128.0.into(), 128.0.into()).unwrap();
+/// assert_eq!(t1.int_evals().unwrap(), plain.int_evals().unwrap());
+///
+/// // a temperature of 2 is the softmax of the halved logits
+/// let t2 = softmax_with_temperature::<Fp>(&dummy_config, &mut dummy_region, &[x], 128.0.into(), 128.0.into(), 2.0.into()).unwrap();
+/// let halved = softmax::<Fp>(&dummy_config, &mut dummy_region, &[logits(&[0, 64, 128])], 128.0.into(), 128.0.into()).unwrap();
+/// assert_eq!(t2.int_evals().unwrap(), halved.int_evals().unwrap());
+///
+/// // which is smoother: the most likely class loses mass to the least likely one
+/// let (p1, p2) = (t1.int_evals().unwrap(), t2.int_evals().unwrap());
+/// assert!(p2[2] < p1[2]);
+/// assert!(p2[0] > p1[0]);
+/// ```
+pub fn softmax_with_temperature<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    input_scale: utils::F32,
+    output_scale: utils::F32,
+    temperature: utils::F32,
+) -> Result<ValTensor<F>, CircuitError> {
+    if temperature.0 <= 0.0 {
+        return Err(TensorError::InvalidArgument(format!(
+            "softmax temperature must be positive, got {}",
+            temperature
+        ))
+        .into());
+    }
     // get the max then subtract it
     let max_val = max(config, region, values)?;
     // rebase the input to 0
-    let sub = pairwise(config, region, &[values[0].clone(), max_val], BaseOp::Sub)?;
+    let mut sub = pairwise(config, region, &[values[0].clone(), max_val], BaseOp::Sub)?;
+    if temperature.0 != 1.0 {
+        // sub / t == (sub * input_scale) / (t * input_scale), so fractional temperatures keep
+        // the precision of the input scale
+        let scale = create_constant_tensor(integer_rep_to_felt(input_scale.0 as IntegerRep), 1);
+        let scaled = pairwise(config, region, &[sub, scale], BaseOp::Mult)?;
+        let denom = (temperature.0 * input_scale.0).round() as IntegerRep;
+        sub = div(config, region, &[scaled], integer_rep_to_felt(denom.max(1)))?;
+    }
     // elementwise exponential
     let ex = nonlinearity(
         config,
@@ -5813,6 +7569,136 @@ pub fn softmax<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     percent(config, region, &[ex.clone()], input_scale, output_scale)
 }
 
+/// Proves that the probabilities `values[0]` are normalized: their sums along `axes` equal
+/// `scale`, the fixed point representation of one, up to the rounding of the fixed point
+/// softmax. The difference of each sum and `scale` is checked to lie in `range`, a configured
+/// range check that should contain 0, e.g. `(-tolerance, tolerance)`. Returns the sums.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::sum_to_one;
+/// use ezkl::tensor::val::ValTensor;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// // two distributions at a scale of 32, the second one lost a unit to rounding
+/// let probs = |values: &[IntegerRep]| ValTensor::from_integer_rep_tensor(Tensor::<IntegerRep>::new(
+///     Some(values),
+///     &[2, 2],
+/// ).unwrap());
+/// let sums = sum_to_one::<Fp>(&dummy_config, &mut dummy_region, &[probs(&[16, 16, 29, 2])], &[1], 32, &(-1, 1)).unwrap();
+/// assert_eq!(sums.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[32, 31]), &[2, 1]).unwrap());
+///
+/// // but not two
+/// assert!(sum_to_one::<Fp>(&dummy_config, &mut dummy_region, &[probs(&[16, 16, 28, 2])], &[1], 32, &(-1, 1)).is_err());
+/// ```
+pub fn sum_to_one<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    axes: &[usize],
+    scale: IntegerRep,
+    range: &crate::circuit::table::Range,
+) -> Result<ValTensor<F>, CircuitError> {
+    if range.0 > 0 || range.1 < 0 {
+        return Err(TensorError::InvalidArgument(format!(
+            "sum_to_one expects a range check containing 0, got {:?}",
+            range
+        ))
+        .into());
+    }
+
+    let sums = sum_axes(config, region, values, axes)?;
+    let one = create_constant_tensor(integer_rep_to_felt(scale), 1);
+    let residual = pairwise(config, region, &[sums.clone(), one], BaseOp::Sub)?;
+    range_check(config, region, &[residual], range)?;
+
+    Ok(sums)
+}
+
+/// Scaled dot-product attention `softmax(Q K^T / sqrt(d)) V` of queries `values[0]` of shape
+/// `[n, d]`, keys `values[1]` of shape `[m, d]` and values `values[2]` of shape `[m, d_v]`, all
+/// at `scale`. The scores are requantized back to `scale` and the divide by `sqrt(d)` is applied
+/// as the temperature of the (max rebased) [softmax_with_temperature] over each row. The
+/// attention weights come out at `scale^3`, so the weighted sum of the values is divided back
+/// down to `scale`. Returns the `[n, d_v]` output at `scale`.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::fieldutils::IntegerRep;
+/// use ezkl::circuit::ops::layouts::attention;
+/// use ezkl::tensor::val::ValTensor;
+/// use halo2curves::bn256::Fr as Fp;
+/// use ezkl::circuit::region::RegionCtx;
+/// use ezkl::circuit::region::RegionSettings;
+/// use ezkl::circuit::BaseConfig;
+///
+/// let dummy_config = BaseConfig::dummy(12, 2);
+/// let mut dummy_region = RegionCtx::new_dummy(0,2,RegionSettings::all_true(65536, 4));
+///
+/// let tensor = |values: &[IntegerRep], dims: &[usize]| ValTensor::from_integer_rep_tensor(
+///     Tensor::<IntegerRep>::new(Some(values), dims).unwrap(),
+/// );
+/// // at a scale of 32, a zero query attends uniformly, averaging the values [1, 2], [0, 1] and
+/// // [-1, 0]
+/// let q = tensor(&[0, 0], &[1, 2]);
+/// let k = tensor(&[32, 0, 0, 32, -32, 32], &[3, 2]);
+/// let v = tensor(&[32, 64, 0, 32, -32, 0], &[3, 2]);
+/// let result = attention::<Fp>(&dummy_config, &mut dummy_region, &[q, k.clone(), v.clone()], 32.0.into()).unwrap();
+/// assert_eq!(result.int_evals().unwrap(), Tensor::<IntegerRep>::new(Some(&[0, 32]), &[1, 2]).unwrap());
+///
+/// // the keys and values must agree on the number of tokens
+/// let q = tensor(&[0, 0], &[1, 2]);
+/// assert!(attention::<Fp>(&dummy_config, &mut dummy_region, &[q, k, tensor(&[32, 64], &[1, 2])], 32.0.into()).is_err());
+/// ```
+pub fn attention<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 3],
+    scale: utils::F32,
+) -> Result<ValTensor<F>, CircuitError> {
+    let (q, k, v) = (&values[0], &values[1], &values[2]);
+    if q.dims().len() != 2
+        || k.dims().len() != 2
+        || v.dims().len() != 2
+        || q.dims()[1] != k.dims()[1]
+        || k.dims()[0] != v.dims()[0]
+    {
+        return Err(TensorError::DimMismatch(format!(
+            "attention expects queries [n, d], keys [m, d] and values [m, d_v], got {:?}, {:?} and {:?}",
+            q.dims(),
+            k.dims(),
+            v.dims()
+        ))
+        .into());
+    }
+    let d = q.dims()[1];
+    let s = scale.0.round() as IntegerRep;
+
+    // Q K^T is at scale^2
+    let scores = einsum(config, region, &[q.clone(), k.clone()], "ij,kj->ik")?;
+    let scores = div(config, region, &[scores], integer_rep_to_felt(s))?;
+
+    let weights = softmax_axes(
+        config,
+        region,
+        &[scores],
+        scale,
+        (scale.0 * scale.0).into(),
+        &[1],
+        (d as f32).sqrt().into(),
+    )?;
+
+    let output = einsum(config, region, &[weights, v.clone()], "ik,kj->ij")?;
+    div(config, region, &[output], integer_rep_to_felt(s * s * s))
+}
+
 /// Checks that the percent error between the expected public output and the actual output value
 /// is within the percent error expressed by the `tol` input, where `tol == 1.0` means the percent
 /// error tolerance is 1 percent.
@@ -5861,3 +7747,41 @@ pub fn output<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     // regular equality constraint
     return enforce_equality(config, region, &[values[0].clone(), values[1].clone()]);
 }
+
+/// Selective disclosure of outputs: only the (flat) indices in `disclosed` of `values[0]` are
+/// constrained to equal `values[1]`, usually an instance column of length `disclosed.len()`
+/// holding the revealed values in the order they are listed in `disclosed`. The remaining outputs are still assigned (and range checked if `decomp` is set) but stay in
+/// private advice. Returns the full output.
+pub fn output_subset<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    disclosed: &[usize],
+    decomp: bool,
+) -> Result<ValTensor<F>, CircuitError> {
+    let mut output = values[0].clone();
+
+    if disclosed.iter().any(|i| *i >= output.len()) {
+        return Err(
+            TensorError::IndexOutOfBounds(*disclosed.iter().max().unwrap(), output.len()).into(),
+        );
+    }
+
+    if !output.all_prev_assigned() {
+        // range check the outputs
+        output = layouts::identity(config, region, &[output], decomp)?;
+    }
+
+    let inner = output.get_inner_tensor()?;
+    let revealed: ValTensor<F> =
+        Tensor::from(disclosed.iter().map(|i| inner.get_flat_index(*i))).into();
+
+    let mut comparators = values[1].clone();
+    if !comparators.all_prev_assigned() {
+        comparators = layouts::identity(config, region, &[comparators], decomp)?;
+    }
+
+    enforce_equality(config, region, &[revealed, comparators])?;
+
+    Ok(output)
+}
@@ -9,6 +9,73 @@ use crate::{
 
 use super::Op;
 use halo2curves::ff::PrimeField;
+use std::sync::Arc;
+
+/// An elementwise function applied through a lookup table, for activations that don't ship as a
+/// [LookupOp]. Wrap an implementation with [LookupOp::custom] and configure and lay it out like
+/// any other lookup.
+///
+/// The table is built by evaluating [CustomNonlinearity::f] at every integer of the lookup range
+/// the op is configured with, so `f` must be a pure function that is defined on that whole
+/// range. Inputs and outputs are the fixed point integers the circuit holds, any scaling is up to
+/// the implementation, and outputs must fit in the field. Grow the lookup range if the inputs of
+/// a layer can leave it, the prover fails on out of range inputs.
+pub trait CustomNonlinearity: std::fmt::Debug + Send + Sync {
+    /// A name that identifies the function, including its parameters. Lookups with the same name
+    /// share a table.
+    fn name(&self) -> String;
+    /// The function the table holds.
+    fn f(&self, x: IntegerRep) -> IntegerRep;
+}
+
+/// A [CustomNonlinearity] that can be used as a [LookupOp]. Ops are compared by name. As the
+/// function is code, circuit settings that require a custom lookup fail to serialize.
+#[derive(Clone, Debug)]
+pub struct CustomLookup(pub Arc<dyn CustomNonlinearity>);
+
+// custom lookups are Rust code and can't be written to or read from settings files
+impl Serialize for CustomLookup {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom(format!(
+            "custom lookup {} can't be serialized",
+            self.0.name()
+        )))
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomLookup {
+    fn deserialize<D: serde::Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+        Err(serde::de::Error::custom(
+            "custom lookups can't be deserialized",
+        ))
+    }
+}
+
+impl PartialEq for CustomLookup {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl Eq for CustomLookup {}
+
+impl std::hash::Hash for CustomLookup {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.name().hash(state)
+    }
+}
+
+impl PartialOrd for CustomLookup {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CustomLookup {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.name().cmp(&other.0.name())
+    }
+}
 
 #[allow(missing_docs)]
 /// An enum representing the operations that can be used to express more complex operations via accumulation
@@ -35,9 +102,20 @@ pub enum LookupOp {
     Erf { scale: utils::F32 },
     Pow { scale: utils::F32, a: utils::F32 },
     HardSwish { scale: utils::F32 },
+    Mish { scale: utils::F32 },
+    Swish { scale: utils::F32 },
+    ThresholdedReLU { scale: utils::F32, a: utils::F32 },
+    Reciprocal { scale: utils::F32 },
+    Softplus { scale: utils::F32 },
+    Custom(CustomLookup),
 }
 
 impl LookupOp {
+    /// Wraps a [CustomNonlinearity] as a lookup op.
+    pub fn custom(nl: impl CustomNonlinearity + 'static) -> Self {
+        LookupOp::Custom(CustomLookup(Arc::new(nl)))
+    }
+
     /// Returns the range of values that can be represented by the table
     pub fn bit_range(max_len: usize) -> Range {
         let range = (max_len - 1) as f64 / 2_f64;
@@ -69,6 +147,12 @@ impl LookupOp {
             LookupOp::ATanh { scale } => format!("atanh_{}", scale),
             LookupOp::Tanh { scale } => format!("tanh_{}", scale),
             LookupOp::HardSwish { scale } => format!("hardswish_{}", scale),
+            LookupOp::Mish { scale } => format!("mish_{}", scale),
+            LookupOp::Swish { scale } => format!("swish_{}", scale),
+            LookupOp::Softplus { scale } => format!("softplus_{}", scale),
+            LookupOp::ThresholdedReLU { scale, a } => format!("thresholded_relu_{}_{}", scale, a),
+            LookupOp::Reciprocal { scale } => format!("reciprocal_{}", scale),
+            LookupOp::Custom(nl) => format!("custom_{}", nl.0.name()),
         }
     }
 
@@ -141,6 +225,24 @@ impl LookupOp {
                 LookupOp::HardSwish { scale } => {
                     Ok::<_, TensorError>(tensor::ops::nonlinearities::hardswish(&x, scale.into()))
                 }
+                LookupOp::Mish { scale } => {
+                    Ok::<_, TensorError>(tensor::ops::nonlinearities::mish(&x, scale.into()))
+                }
+                LookupOp::Swish { scale } => {
+                    Ok::<_, TensorError>(tensor::ops::nonlinearities::swish(&x, scale.into()))
+                }
+                LookupOp::Softplus { scale } => {
+                    Ok::<_, TensorError>(tensor::ops::nonlinearities::softplus(&x, scale.into()))
+                }
+                LookupOp::ThresholdedReLU { scale, a } => Ok::<_, TensorError>(
+                    tensor::ops::nonlinearities::thresholded_relu(&x, scale.into(), a.into()),
+                ),
+                LookupOp::Reciprocal { scale } => {
+                    Ok::<_, TensorError>(tensor::ops::nonlinearities::reciprocal(&x, scale.into()))
+                }
+                LookupOp::Custom(nl) => Ok::<_, TensorError>(
+                    x.par_enum_map(|_, x_i| Ok::<_, TensorError>(nl.0.f(x_i)))?,
+                ),
             }?;
 
         let output = res.map(|x| integer_rep_to_felt(x));
@@ -179,6 +281,14 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
             LookupOp::Sinh { scale } => format!("SINH(scale={})", scale),
             LookupOp::ASinh { scale } => format!("ASINH(scale={})", scale),
             LookupOp::HardSwish { scale } => format!("HARDSWISH(scale={})", scale),
+            LookupOp::Mish { scale } => format!("MISH(scale={})", scale),
+            LookupOp::Swish { scale } => format!("SWISH(scale={})", scale),
+            LookupOp::Softplus { scale } => format!("SOFTPLUS(scale={})", scale),
+            LookupOp::ThresholdedReLU { scale, a } => {
+                format!("THRESHOLDED_RELU(scale={}, threshold={})", scale, a)
+            }
+            LookupOp::Reciprocal { scale } => format!("RECIPROCAL(scale={})", scale),
+            LookupOp::Custom(nl) => format!("CUSTOM({})", nl.0.name()),
         }
     }
 
@@ -188,6 +298,14 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
         region: &mut RegionCtx<F>,
         values: &[ValTensor<F>],
     ) -> Result<Option<ValTensor<F>>, CircuitError> {
+        if let LookupOp::Reciprocal { scale } = self {
+            return Ok(Some(layouts::positive_reciprocal(
+                config,
+                region,
+                values[..].try_into()?,
+                *scale,
+            )?));
+        }
         Ok(Some(layouts::nonlinearity(
             config,
             region,
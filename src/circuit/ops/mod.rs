@@ -20,6 +20,8 @@ pub mod chip;
 pub mod errors;
 ///
 pub mod hybrid;
+/// Layers pairing ops with their parameters, with shape inference
+pub mod layer;
 /// Layouts for specific functions (composed of base ops)
 pub mod layouts;
 ///
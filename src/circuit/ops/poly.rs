@@ -19,6 +19,9 @@ pub enum PolyOp {
         slope: utils::F32,
         scale: i32,
     },
+    PReLU {
+        scale: i32,
+    },
     GatherElements {
         dim: usize,
         constant_idx: Option<Tensor<usize>>,
@@ -41,8 +44,9 @@ pub enum PolyOp {
         equation: String,
     },
     Conv {
-        padding: Vec<(usize, usize)>,
+        padding: Vec<(isize, isize)>,
         stride: Vec<usize>,
+        dilation: Vec<usize>,
         group: usize,
         data_format: DataFormat,
         kernel_format: KernelFormat,
@@ -124,6 +128,7 @@ impl<
     fn as_string(&self) -> String {
         match &self {
             PolyOp::LeakyReLU { slope: a, .. } => format!("LEAKYRELU (slope={})", a),
+            PolyOp::PReLU { scale } => format!("PRELU (scale={})", scale),
             PolyOp::Abs => "ABS".to_string(),
             PolyOp::Sign => "SIGN".to_string(),
             PolyOp::GatherElements { dim, constant_idx } => format!(
@@ -169,13 +174,14 @@ impl<
             PolyOp::Conv {
                 stride,
                 padding,
+                dilation,
                 group,
                 data_format,
                 kernel_format,
             } => {
                 format!(
-                    "CONV (stride={:?}, padding={:?}, group={}, data_format={:?}, kernel_format={:?})",
-                    stride, padding, group, data_format, kernel_format
+                    "CONV (stride={:?}, padding={:?}, dilation={:?}, group={}, data_format={:?}, kernel_format={:?})",
+                    stride, padding, dilation, group, data_format, kernel_format
                 )
             }
             PolyOp::DeConv {
@@ -215,6 +221,9 @@ impl<
             PolyOp::LeakyReLU { slope, scale } => {
                 layouts::leaky_relu(config, region, values[..].try_into()?, slope, scale)?
             }
+            PolyOp::PReLU { scale } => {
+                layouts::prelu(config, region, values[..].try_into()?, *scale)?
+            }
             PolyOp::MultiBroadcastTo { shape } => {
                 layouts::expand(config, region, values[..].try_into()?, shape)?
             }
@@ -249,15 +258,17 @@ impl<
             PolyOp::Conv {
                 padding,
                 stride,
+                dilation,
                 group,
                 data_format,
                 kernel_format,
-            } => layouts::conv(
+            } => layouts::dilated_conv(
                 config,
                 region,
                 values[..].try_into()?,
                 padding,
                 stride,
+                dilation,
                 *group,
                 *data_format,
                 *kernel_format,
@@ -373,6 +384,7 @@ impl<
             } => in_scales[0],
             // this corresponds to the leaky relu operation with a slope which induces a change in scale
             PolyOp::LeakyReLU { scale, .. } => in_scales[0] + *scale,
+            PolyOp::PReLU { scale } => in_scales[0] + *scale,
             PolyOp::MeanOfSquares { .. } => 2 * in_scales[0],
             PolyOp::Xor | PolyOp::Or | PolyOp::And | PolyOp::Not => 0,
             PolyOp::Iff => in_scales[1],
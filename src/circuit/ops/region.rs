@@ -12,6 +12,7 @@ use halo2_proofs::{
 use halo2curves::ff::PrimeField;
 use itertools::Itertools;
 use maybe_rayon::iter::ParallelExtend;
+use serde::{Deserialize, Serialize};
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
@@ -86,7 +87,7 @@ impl ShuffleIndex {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Some settings for a region to differentiate it across the different phases of proof generation
 pub struct RegionSettings {
     /// whether we are in witness generation mode
@@ -97,6 +98,14 @@ pub struct RegionSettings {
     pub base: usize,
     /// number of legs for decompositions
     pub legs: usize,
+    /// number of legs for decomposing the differences in comparisons, which can need more digits
+    /// than the values being compared. Defaults to `legs`
+    pub comparison_legs: Option<usize>,
+    /// whether to record the range of the values each layer outputs, for calibration
+    pub track_ranges: bool,
+    /// whether divides by a constant range check their remainders against a table per divisor
+    /// rather than decomposing them, see [RegionCtx::remainder_tables]
+    pub remainder_tables: bool,
 }
 
 #[allow(unsafe_code)]
@@ -112,6 +121,9 @@ impl RegionSettings {
             check_range,
             base,
             legs,
+            comparison_legs: None,
+            track_ranges: false,
+            remainder_tables: false,
         }
     }
 
@@ -122,6 +134,9 @@ impl RegionSettings {
             check_range: true,
             base,
             legs,
+            comparison_legs: None,
+            track_ranges: false,
+            remainder_tables: false,
         }
     }
 
@@ -132,8 +147,30 @@ impl RegionSettings {
             check_range: false,
             base,
             legs,
+            comparison_legs: None,
+            track_ranges: false,
+            remainder_tables: false,
         }
     }
+
+    /// Sets the number of legs for decomposing the differences in comparisons
+    pub fn with_comparison_legs(mut self, legs: usize) -> RegionSettings {
+        self.comparison_legs = Some(legs);
+        self
+    }
+
+    /// Records the range of the values each layer outputs, see [RegionCtx::layer_ranges]
+    pub fn with_range_tracking(mut self) -> RegionSettings {
+        self.track_ranges = true;
+        self
+    }
+
+    /// Range checks the remainders of divides by a constant against per-divisor tables, see
+    /// [RegionCtx::remainder_tables]
+    pub fn with_remainder_tables(mut self) -> RegionSettings {
+        self.remainder_tables = true;
+        self
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -147,8 +184,14 @@ pub struct RegionStatistics {
     pub max_range_size: IntegerRep,
     /// the current set of used lookups
     pub used_lookups: HashSet<LookupOp>,
+    /// the smallest and largest input seen by each used lookup (widened to include 0)
+    pub lookup_ranges: HashMap<LookupOp, Range>,
     /// the current set of used range checks
     pub used_range_checks: HashSet<Range>,
+    /// the number of rows a custom gate has been enabled on
+    pub enabled_gates: usize,
+    /// the number of rows a lookup, range check or shuffle has been enabled on
+    pub enabled_lookups: usize,
 }
 
 impl RegionStatistics {
@@ -158,8 +201,101 @@ impl RegionStatistics {
         self.min_lookup_inputs = self.min_lookup_inputs.min(other.min_lookup_inputs);
         self.max_range_size = self.max_range_size.max(other.max_range_size);
         self.used_lookups.extend(other.used_lookups.clone());
+        for (op, range) in other.lookup_ranges.iter() {
+            self.update_lookup_range(op, *range);
+        }
         self.used_range_checks
             .extend(other.used_range_checks.clone());
+        self.enabled_gates += other.enabled_gates;
+        self.enabled_lookups += other.enabled_lookups;
+    }
+
+    /// widen the range of inputs seen by `op` to include `range`
+    pub fn update_lookup_range(&mut self, op: &LookupOp, range: Range) {
+        self.lookup_ranges
+            .entry(op.clone())
+            .and_modify(|r| *r = (r.0.min(range.0), r.1.max(range.1)))
+            .or_insert(range);
+    }
+
+    /// the gate and lookup constraints enabled so far
+    pub fn constraint_count(&self) -> ConstraintCount {
+        ConstraintCount {
+            gates: self.enabled_gates,
+            lookups: self.enabled_lookups,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// The number of rows gates and lookups are enabled on, each enabled row is one instance of the
+/// gate's (or lookup's) constraints
+pub struct ConstraintCount {
+    /// custom gate constraints (e.g. the multiply-accumulate rows of a dot product)
+    pub gates: usize,
+    /// lookup, range check and shuffle constraints
+    pub lookups: usize,
+}
+
+impl std::ops::Sub for ConstraintCount {
+    type Output = ConstraintCount;
+
+    fn sub(self, other: ConstraintCount) -> ConstraintCount {
+        ConstraintCount {
+            gates: self.gates - other.gates,
+            lookups: self.lookups - other.lookups,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+/// The constraints each layer of a model adds to the circuit
+pub struct LayerConstraints {
+    /// the node index, name and constraints of each layer, in layout order
+    pub layers: Vec<(usize, String, ConstraintCount)>,
+}
+
+impl LayerConstraints {
+    /// adds `count` to the constraints of a layer, layers that are laid out several times (e.g. in
+    /// the body of a loop) accumulate their constraints
+    pub fn record(&mut self, idx: usize, name: &str, count: ConstraintCount) {
+        match self
+            .layers
+            .iter_mut()
+            .find(|(i, n, _)| *i == idx && n == name)
+        {
+            Some((_, _, total)) => {
+                total.gates += count.gates;
+                total.lookups += count.lookups;
+            }
+            None => self.layers.push((idx, name.to_string(), count)),
+        }
+    }
+
+    /// the constraints of all layers
+    pub fn total(&self) -> ConstraintCount {
+        self.layers
+            .iter()
+            .fold(ConstraintCount::default(), |acc, (_, _, c)| {
+                ConstraintCount {
+                    gates: acc.gates + c.gates,
+                    lookups: acc.lookups + c.lookups,
+                }
+            })
+    }
+}
+
+impl std::fmt::Display for LayerConstraints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, name, count) in &self.layers {
+            writeln!(
+                f,
+                "{:>5} {:<40} {:>10} gates {:>10} lookups",
+                idx, name, count.gates, count.lookups
+            )?;
+        }
+        let total = self.total();
+        write!(f, "total: {} gates, {} lookups", total.gates, total.lookups)
     }
 }
 
@@ -168,6 +304,138 @@ unsafe impl Sync for RegionStatistics {}
 #[allow(unsafe_code)]
 unsafe impl Send for RegionStatistics {}
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The cells a layer of a model occupies
+pub struct LayerFootprint {
+    /// the node index of the layer
+    pub idx: usize,
+    /// the name of the layer
+    pub name: String,
+    /// the linear coordinates of the advice cells the layer is laid out on
+    pub advice: std::ops::Range<usize>,
+    /// the number of new constants the layer assigns to fixed columns
+    pub constants: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+/// The cells each layer of a model occupies
+pub struct LayerFootprints {
+    /// the footprint of each layer, in layout order
+    pub layers: Vec<LayerFootprint>,
+}
+
+impl LayerFootprints {
+    /// adds a layer's footprint, layers that are laid out several times (e.g. in the body of a
+    /// loop) are widened to cover all of their cells
+    pub fn record(&mut self, footprint: LayerFootprint) {
+        match self
+            .layers
+            .iter_mut()
+            .find(|l| l.idx == footprint.idx && l.name == footprint.name)
+        {
+            Some(l) => {
+                l.advice = l.advice.start.min(footprint.advice.start)
+                    ..l.advice.end.max(footprint.advice.end);
+                l.constants += footprint.constants;
+            }
+            None => self.layers.push(footprint),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The smallest and largest values a layer of a model has output
+pub struct LayerRange {
+    /// the node index of the layer
+    pub idx: usize,
+    /// the name of the layer
+    pub name: String,
+    /// the smallest output value
+    pub min: IntegerRep,
+    /// the largest output value
+    pub max: IntegerRep,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+/// The range of the values each layer of a model has output
+pub struct LayerRanges {
+    /// the range of each layer, in layout order
+    pub layers: Vec<LayerRange>,
+}
+
+impl LayerRanges {
+    /// adds the range of a layer's outputs, layers that are laid out several times (e.g. in the
+    /// body of a loop or over several passes) are widened to cover all of their outputs
+    pub fn record(&mut self, range: LayerRange) {
+        match self
+            .layers
+            .iter_mut()
+            .find(|l| l.idx == range.idx && l.name == range.name)
+        {
+            Some(l) => {
+                l.min = l.min.min(range.min);
+                l.max = l.max.max(range.max);
+            }
+            None => self.layers.push(range),
+        }
+    }
+}
+
+#[cfg(feature = "timing")]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+/// Wall-clock time spent laying out each layer of a model
+pub struct LayerTimings {
+    /// the node index, name and accumulated layout time of each layer, in layout order
+    pub layers: Vec<(usize, String, std::time::Duration)>,
+}
+
+#[cfg(feature = "timing")]
+impl LayerTimings {
+    /// adds `duration` to the time spent on a layer, layers that are laid out several times (e.g.
+    /// in the body of a loop) accumulate their time
+    pub fn record(&mut self, idx: usize, name: &str, duration: std::time::Duration) {
+        match self
+            .layers
+            .iter_mut()
+            .find(|(i, n, _)| *i == idx && n == name)
+        {
+            Some((_, _, total)) => *total += duration,
+            None => self.layers.push((idx, name.to_string(), duration)),
+        }
+    }
+
+    /// the total time spent laying out layers
+    pub fn total(&self) -> std::time::Duration {
+        self.layers.iter().map(|(_, _, d)| *d).sum()
+    }
+
+    /// the layers sorted by the time spent on them, slowest first
+    pub fn slowest(&self) -> Vec<&(usize, String, std::time::Duration)> {
+        self.layers.iter().sorted_by(|a, b| b.2.cmp(&a.2)).collect()
+    }
+}
+
+#[cfg(feature = "timing")]
+impl std::fmt::Display for LayerTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.total().as_secs_f64();
+        for (idx, name, duration) in &self.layers {
+            let share = if total > 0.0 {
+                100.0 * duration.as_secs_f64() / total
+            } else {
+                0.0
+            };
+            let duration = format!("{:?}", duration);
+            writeln!(
+                f,
+                "{:>5} {:<40} {:>12} {:>6.2}%",
+                idx, name, duration, share
+            )?;
+        }
+        write!(f, "total: {:?}", self.total())
+    }
+}
+
 #[derive(Debug)]
 /// A context for a region
 pub struct RegionCtx<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> {
@@ -181,6 +449,11 @@ pub struct RegionCtx<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Ha
     settings: RegionSettings,
     assigned_constants: ConstantsMap<F>,
     max_dynamic_input_len: usize,
+    layer_constraints: LayerConstraints,
+    layer_footprints: LayerFootprints,
+    layer_ranges: LayerRanges,
+    #[cfg(feature = "timing")]
+    layer_timings: LayerTimings,
 }
 
 impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a, F> {
@@ -194,6 +467,28 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         self.settings.legs
     }
 
+    /// get the number of legs differences are decomposed into for comparisons
+    pub fn comparison_legs(&self) -> usize {
+        self.settings.comparison_legs.unwrap_or(self.settings.legs)
+    }
+
+    /// set the number of legs differences are decomposed into for comparisons
+    pub fn set_comparison_legs(&mut self, legs: usize) {
+        self.settings.comparison_legs = Some(legs);
+    }
+
+    /// whether divides by a constant `d` range check their remainders against a `(0, 2 * d - 1)`
+    /// table, which has to be configured for every divisor, rather than decomposing them into
+    /// the region's `base` and `legs`
+    pub fn remainder_tables(&self) -> bool {
+        self.settings.remainder_tables
+    }
+
+    /// set whether divides by a constant range check their remainders against per-divisor tables
+    pub fn set_remainder_tables(&mut self, remainder_tables: bool) {
+        self.settings.remainder_tables = remainder_tables;
+    }
+
     /// get the max dynamic input len
     pub fn max_dynamic_input_len(&self) -> usize {
         self.max_dynamic_input_len
@@ -261,11 +556,63 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         self.settings.check_range
     }
 
+    /// whether the range of each layer's outputs is recorded
+    pub fn track_ranges(&self) -> bool {
+        self.settings.track_ranges
+    }
+
     ///
     pub fn statistics(&self) -> &RegionStatistics {
         &self.statistics
     }
 
+    /// get the gate and lookup constraints enabled so far
+    pub fn constraint_count(&self) -> ConstraintCount {
+        self.statistics.constraint_count()
+    }
+
+    /// get the constraints each layer has added so far
+    pub fn layer_constraints(&self) -> &LayerConstraints {
+        &self.layer_constraints
+    }
+
+    /// record the constraints a layer added
+    pub fn record_layer_constraints(&mut self, idx: usize, name: &str, count: ConstraintCount) {
+        self.layer_constraints.record(idx, name, count);
+    }
+
+    /// get the cells each layer has occupied so far
+    pub fn layer_footprints(&self) -> &LayerFootprints {
+        &self.layer_footprints
+    }
+
+    /// record the cells a layer occupies
+    pub fn record_layer_footprint(&mut self, footprint: LayerFootprint) {
+        self.layer_footprints.record(footprint);
+    }
+
+    /// get the range of the values each layer has output so far
+    pub fn layer_ranges(&self) -> &LayerRanges {
+        &self.layer_ranges
+    }
+
+    /// record the range of a layer's outputs
+    pub fn record_layer_range(&mut self, range: LayerRange) {
+        self.layer_ranges.record(range);
+    }
+
+    /// get the time spent laying out each layer so far
+    #[cfg(feature = "timing")]
+    pub fn layer_timings(&self) -> &LayerTimings {
+        &self.layer_timings
+    }
+
+    /// record the time spent laying out a layer
+    #[cfg(feature = "timing")]
+    pub fn record_layer_time(&mut self, idx: usize, name: &str, duration: std::time::Duration) {
+        self.layer_timings.record(idx, name, duration);
+    }
+
     /// Create a new region context
     pub fn new(
         region: Region<'a, F>,
@@ -288,6 +635,11 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
             settings: RegionSettings::all_true(decomp_base, decomp_legs),
             assigned_constants: HashMap::new(),
             max_dynamic_input_len: 0,
+            layer_constraints: LayerConstraints::default(),
+            layer_footprints: LayerFootprints::default(),
+            layer_ranges: LayerRanges::default(),
+            #[cfg(feature = "timing")]
+            layer_timings: LayerTimings::default(),
         }
     }
 
@@ -325,6 +677,11 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
             settings,
             assigned_constants: HashMap::new(),
             max_dynamic_input_len: 0,
+            layer_constraints: LayerConstraints::default(),
+            layer_footprints: LayerFootprints::default(),
+            layer_ranges: LayerRanges::default(),
+            #[cfg(feature = "timing")]
+            layer_timings: LayerTimings::default(),
         }
     }
 
@@ -347,6 +704,11 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
             settings,
             assigned_constants: HashMap::new(),
             max_dynamic_input_len: 0,
+            layer_constraints: LayerConstraints::default(),
+            layer_footprints: LayerFootprints::default(),
+            layer_ranges: LayerRanges::default(),
+            #[cfg(feature = "timing")]
+            layer_timings: LayerTimings::default(),
         }
     }
 
@@ -459,16 +821,22 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         Ok(())
     }
 
-    /// Update the max and min from inputs
-    pub fn update_max_min_lookup_inputs(
-        &mut self,
-        inputs: &[ValTensor<F>],
-    ) -> Result<(), CircuitError> {
+    /// The smallest and largest of the inputs, widened to include 0
+    fn lookup_inputs_range(inputs: &[ValTensor<F>]) -> Result<Range, CircuitError> {
         let (mut min, mut max) = (0, 0);
         for i in inputs {
             max = max.max(i.int_evals()?.into_iter().max().unwrap_or_default());
             min = min.min(i.int_evals()?.into_iter().min().unwrap_or_default());
         }
+        Ok((min, max))
+    }
+
+    /// Update the max and min from inputs
+    pub fn update_max_min_lookup_inputs(
+        &mut self,
+        inputs: &[ValTensor<F>],
+    ) -> Result<(), CircuitError> {
+        let (min, max) = Self::lookup_inputs_range(inputs)?;
         self.statistics.max_lookup_inputs = self.statistics.max_lookup_inputs.max(max);
         self.statistics.min_lookup_inputs = self.statistics.min_lookup_inputs.min(min);
         Ok(())
@@ -508,6 +876,8 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         lookup: LookupOp,
         inputs: &[ValTensor<F>],
     ) -> Result<(), CircuitError> {
+        let range = Self::lookup_inputs_range(inputs)?;
+        self.statistics.update_lookup_range(&lookup, range);
         self.statistics.used_lookups.insert(lookup);
         self.update_max_min_lookup_inputs(inputs)
     }
@@ -558,6 +928,11 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         self.statistics.used_lookups.clone()
     }
 
+    /// get the range of inputs seen by each used lookup
+    pub fn lookup_ranges(&self) -> HashMap<LookupOp, Range> {
+        self.statistics.lookup_ranges.clone()
+    }
+
     /// get used range checks
     pub fn used_range_checks(&self) -> HashSet<Range> {
         self.statistics.used_range_checks.clone()
@@ -727,14 +1102,47 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         }
     }
 
-    /// Enable a selector
+    /// Enable a custom gate selector
     pub fn enable(&mut self, selector: Option<&Selector>, offset: usize) -> Result<(), Error> {
+        self.statistics.enabled_gates += 1;
+        self.enable_table(selector, offset)
+    }
+
+    /// Enable a lookup, range check or shuffle input selector
+    pub fn enable_lookup(
+        &mut self,
+        selector: Option<&Selector>,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.count_lookups(1);
+        self.enable_table(selector, offset)
+    }
+
+    /// Enable the selector of a dynamic lookup or shuffle table row, table rows hold the values
+    /// looked up into and add no constraints of their own
+    pub fn enable_table(
+        &mut self,
+        selector: Option<&Selector>,
+        offset: usize,
+    ) -> Result<(), Error> {
         match &self.region {
             Some(region) => selector.unwrap().enable(&mut region.borrow_mut(), offset),
             None => Ok(()),
         }
     }
 
+    /// Count custom gate constraints without enabling their selectors, for dummy layouts where
+    /// the selectors aren't configured
+    pub fn count_gates(&mut self, n: usize) {
+        self.statistics.enabled_gates += n;
+    }
+
+    /// Count lookup constraints without enabling their selectors, for dummy layouts where the
+    /// selectors aren't configured
+    pub fn count_lookups(&mut self, n: usize) {
+        self.statistics.enabled_lookups += n;
+    }
+
     /// constrain equal
     pub fn constrain_equal(
         &mut self,
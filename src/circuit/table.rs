@@ -1,4 +1,7 @@
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 use halo2curves::ff::PrimeField;
 
@@ -11,7 +14,7 @@ use maybe_rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
 use crate::{
     circuit::CircuitError,
-    fieldutils::{integer_rep_to_felt, IntegerRep},
+    fieldutils::{felt_to_integer_rep, integer_rep_to_felt, IntegerRep},
     tensor::{Tensor, TensorType},
 };
 
@@ -38,6 +41,31 @@ lazy_static::lazy_static! {
 #[cfg(any(not(feature = "ezkl"), target_arch = "wasm32"))]
 pub const LOOKUP_CACHE: &str = "";
 
+/// Whether generated lookup tables are memoized in memory across synthesize calls.
+static MEMOIZE_TABLES: AtomicBool = AtomicBool::new(false);
+
+/// In-memory memo of generated lookup tables (inputs, outputs), keyed by table name.
+static TABLE_MEMO: Mutex<BTreeMap<String, (Tensor<IntegerRep>, Tensor<IntegerRep>)>> =
+    Mutex::new(BTreeMap::new());
+
+/// Enables or disables memoizing generated lookup tables in memory.
+/// Tables are witness-independent, so when proving many inputs against the same circuit
+/// the table contents only need to be generated once and are reused across synthesize calls.
+/// Disabling memoization also drops any memoized tables.
+pub fn set_table_memoization(enabled: bool) {
+    MEMOIZE_TABLES.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        clear_table_memo();
+    }
+}
+
+/// Drops all lookup tables memoized in memory.
+pub fn clear_table_memo() {
+    if let Ok(mut memo) = TABLE_MEMO.lock() {
+        memo.clear();
+    }
+}
+
 #[derive(Debug, Clone)]
 ///
 pub struct SelectorConstructor<F: PrimeField> {
@@ -144,6 +172,57 @@ pub fn num_cols_required(range_len: IntegerRep, col_size: usize) -> usize {
     (range_len / col_size as IntegerRep) as usize + 1
 }
 
+/// Lookup table sizing recommended by [recommended_lookup_bits].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LookupRecommendation {
+    /// every value the table may be queried with
+    pub range: Range,
+    /// the number of bits needed to index the range
+    pub bits: u32,
+    /// the minimum logrows for the range to fit in a single table column
+    pub logrows: u32,
+}
+
+/// Recommends a lookup table for the activation following an affine layer, i.e. one that is
+/// queried with `b + sum_i x_i * w_i` over `fan_in` terms, where each `x_i` lies in `input_range`,
+/// each `w_i` in `weight_range` and the bias `b`, if any, in `bias_range`.
+///
+/// ```
+/// use ezkl::circuit::table::recommended_lookup_bits;
+///
+/// let rec = recommended_lookup_bits((-30, 40), (-2, 32), None, 4);
+/// assert_eq!(rec.range, (-3840, 5120));
+/// assert_eq!(rec.bits, 14);
+/// assert!(rec.logrows >= rec.bits);
+/// ```
+pub fn recommended_lookup_bits(
+    input_range: Range,
+    weight_range: Range,
+    bias_range: Option<Range>,
+    fan_in: usize,
+) -> LookupRecommendation {
+    let products = [
+        input_range.0 * weight_range.0,
+        input_range.0 * weight_range.1,
+        input_range.1 * weight_range.0,
+        input_range.1 * weight_range.1,
+    ];
+    let fan_in = fan_in as IntegerRep;
+    let bias_range = bias_range.unwrap_or((0, 0));
+    let range = (
+        fan_in * products.iter().min().unwrap() + bias_range.0,
+        fan_in * products.iter().max().unwrap() + bias_range.1,
+    );
+    let len = (range.1 - range.0 + 1) as f64;
+    LookupRecommendation {
+        range,
+        bits: len.log2().ceil() as u32,
+        logrows: (len + crate::graph::RESERVED_BLINDING_ROWS as f64)
+            .log2()
+            .ceil() as u32,
+    }
+}
+
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
     /// get largest element represented by the range
     pub fn largest(&self) -> IntegerRep {
@@ -203,6 +282,43 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
         }
     }
 
+    /// Generates the table's inputs, spanning `range.0..=largest()`, and the nonlinearity's
+    /// outputs on them.
+    fn generate(&self) -> Result<(Tensor<F>, Tensor<F>), crate::tensor::TensorError> {
+        let inputs = Tensor::from(self.range.0..=self.largest())
+            .par_enum_map(|_, x| Ok::<_, crate::tensor::TensorError>(integer_rep_to_felt(x)))?;
+        let evals = self.nonlinearity.f(&[inputs.clone()])?;
+        Ok((inputs, evals.output))
+    }
+
+    /// Returns the `(input, output)` pairs the table is populated with when laid out, in order
+    /// of increasing input. Useful for checking a table encodes the function it is meant to when
+    /// debugging activation mismatches.
+    ///
+    /// ```
+    /// use ezkl::circuit::lookup::LookupOp;
+    /// use ezkl::circuit::table::Table;
+    /// use halo2_proofs::plonk::ConstraintSystem;
+    /// use halo2curves::bn256::Fr as Fp;
+    ///
+    /// let mut cs = ConstraintSystem::<Fp>::default();
+    /// let relu = LookupOp::ThresholdedReLU { scale: 1.0.into(), a: 0.0.into() };
+    /// let table = Table::<Fp>::configure(&mut cs, (-64, 64), 8, &relu, &mut vec![]);
+    ///
+    /// let entries = table.entries().unwrap();
+    /// assert_eq!(entries.first().unwrap().0, -64);
+    /// assert_eq!(entries.last().unwrap().0, table.largest());
+    /// assert!(entries.iter().all(|(x, y)| *y == (*x).max(0)));
+    /// ```
+    pub fn entries(&self) -> Result<Vec<(IntegerRep, IntegerRep)>, CircuitError> {
+        let (inputs, evals) = self.generate()?;
+        Ok(inputs
+            .iter()
+            .zip(evals.iter())
+            .map(|(x, y)| (felt_to_integer_rep(*x), felt_to_integer_rep(*y)))
+            .collect())
+    }
+
     /// Take a linear coordinate and output the (column, row) position in the storage block.
     pub fn cartesian_coord(&self, linear_coord: usize) -> (usize, usize) {
         let x = linear_coord / self.col_size;
@@ -220,17 +336,23 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
             return Err(CircuitError::TableAlreadyAssigned);
         }
 
-        let smallest = self.range.0;
-        let largest = self.largest();
-
-        let gen_table = || -> Result<(Tensor<F>, Tensor<F>), crate::tensor::TensorError> {
-            let inputs = Tensor::from(smallest..=largest)
-                .par_enum_map(|_, x| Ok::<_, crate::tensor::TensorError>(integer_rep_to_felt(x)))?;
-            let evals = self.nonlinearity.f(&[inputs.clone()])?;
-            Ok((inputs, evals.output))
+        let memoize = MEMOIZE_TABLES.load(Ordering::Relaxed);
+        let memoized = if memoize {
+            TABLE_MEMO
+                .lock()
+                .ok()
+                .and_then(|memo| memo.get(&self.name()).cloned())
+        } else {
+            None
         };
 
-        let (inputs, evals) = if !LOOKUP_CACHE.is_empty() {
+        let (inputs, evals) = if let Some((inputs, evals)) = memoized {
+            debug!("reusing memoized lookup table {}", self.name());
+            (
+                inputs.map(|x| integer_rep_to_felt(x)),
+                evals.map(|x| integer_rep_to_felt(x)),
+            )
+        } else if !LOOKUP_CACHE.is_empty() {
             let cache = std::path::Path::new(&*LOOKUP_CACHE);
             let cache_path = cache.join(self.name());
             let input_path = cache_path.join("inputs");
@@ -253,7 +375,7 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
                     ))
                 })?;
 
-                let (inputs, evals) = gen_table()?;
+                let (inputs, evals) = self.generate()?;
                 inputs.save(&input_path)?;
                 evals.save(&output_path)?;
 
@@ -265,9 +387,20 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
                 self.nonlinearity.as_path()
             );
 
-            gen_table()?
+            self.generate()?
         };
 
+        if memoize {
+            if let Ok(mut memo) = TABLE_MEMO.lock() {
+                memo.entry(self.name()).or_insert_with(|| {
+                    (
+                        inputs.map(|x| felt_to_integer_rep(x)),
+                        evals.map(|x| felt_to_integer_rep(x)),
+                    )
+                });
+            }
+        }
+
         let chunked_inputs = inputs.chunks(self.col_size);
 
         self.is_assigned = true;
@@ -4,7 +4,7 @@ use crate::tensor::{DataFormat, KernelFormat};
 use crate::tensor::{Tensor, TensorType, ValTensor, VarTensor};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
-    dev::MockProver,
+    dev::{MockProver, VerifyFailure},
     plonk::{Circuit, ConstraintSystem, Error},
 };
 use halo2curves::bn256::Fr as F;
@@ -21,6 +21,101 @@ use std::marker::PhantomData;
 #[derive(Default)]
 struct TestParams;
 
+/// Asserts that `prover` rejects its witness and that at least one of the reported failures is
+/// an unsatisfied constraint in the gate named `gate` (see [ops::base::BaseOp::as_str]).
+fn assert_constraint_failure(prover: &MockProver<F>, gate: &str) {
+    let failures = match prover.verify() {
+        Ok(()) => panic!(
+            "expected a failure in gate {} but the circuit is satisfied",
+            gate
+        ),
+        Err(failures) => failures,
+    };
+    let gate_suffix = format!("('{}')", gate);
+    let in_gate = failures.iter().any(|failure| match failure {
+        VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+            constraint.to_string().ends_with(&gate_suffix)
+        }
+        _ => false,
+    });
+    assert!(
+        in_gate,
+        "expected a failure in gate {}, got {:?}",
+        gate, failures
+    );
+}
+
+/// Asserts that `prover` rejects its witness and that at least one of the reported failures is
+/// located in the region named `region`.
+fn assert_failure_in_region(prover: &MockProver<F>, region: &str) {
+    let failures = prover.verify().err().unwrap_or_default();
+    let region_suffix = format!("('{}')", region);
+    let in_region = failures.iter().any(|failure| match failure {
+        VerifyFailure::ConstraintNotSatisfied { location, .. } => {
+            location.to_string().contains(&region_suffix)
+        }
+        _ => false,
+    });
+    assert!(
+        in_region,
+        "expected a failure in region {}, got {:?}",
+        region, failures
+    );
+}
+
+/// Quantizes `values` at `scale`, rounding to the nearest integer.
+fn quantize(values: &[f32], scale: f32, dims: &[usize]) -> ValTensor<F> {
+    let values = values
+        .iter()
+        .map(|v| (v * scale).round() as crate::fieldutils::IntegerRep)
+        .collect::<Vec<_>>();
+    ValTensor::from_integer_rep_tensor(Tensor::new(Some(&values), dims).unwrap())
+}
+
+/// Asserts that every value of `output`, read at `scale`, is within `tolerance` of the float
+/// reference `expected`.
+fn assert_close(output: &ValTensor<F>, scale: f32, expected: &[f32], tolerance: f32) {
+    let output = output.int_evals().unwrap();
+    assert_eq!(output.len(), expected.len());
+    for (i, (value, expected)) in output.iter().zip(expected).enumerate() {
+        let value = *value as f32 / scale;
+        assert!(
+            (value - expected).abs() <= tolerance,
+            "output {}: {} vs {}",
+            i,
+            value,
+            expected
+        );
+    }
+}
+
+/// Builds a circuit for each `(input, expected)` case and checks it with a [MockProver], using
+/// `expected` as the single instance column. Failures are collected across all cases and
+/// reported together, so one bad vector doesn't hide the others.
+fn run_cases<C: Circuit<F>, I: std::fmt::Debug>(
+    k: u32,
+    build_circuit: impl Fn(&I) -> C,
+    cases: &[(I, Vec<F>)],
+) {
+    let mut failed = vec![];
+    for (i, (input, expected)) in cases.iter().enumerate() {
+        let circuit = build_circuit(input);
+        let result = MockProver::run(k, &circuit, vec![expected.clone()])
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|prover| prover.verify().map_err(|e| format!("{:?}", e)));
+        if let Err(e) = result {
+            failed.push(format!("case {} ({:?}): {}", i, input, e));
+        }
+    }
+    assert!(
+        failed.is_empty(),
+        "{} of {} cases failed:\n{}",
+        failed.len(),
+        cases.len(),
+        failed.join("\n")
+    );
+}
+
 #[cfg(test)]
 mod matmul {
 
@@ -99,20 +194,22 @@ mod matmul {
 }
 
 #[cfg(test)]
-mod matmul_col_overflow_double_col {
+mod affine_fused_bias {
     use super::*;
 
-    const K: usize = 5;
-    const LEN: usize = 6;
+    const K: usize = 8;
     const NUM_INNER_COLS: usize = 2;
+    const N: usize = 2;
+    const OUT: usize = 4;
 
+    /// Lays out the same dense layer twice: with the bias fused into the dot product accumulation,
+    /// and with the bias folded into the weights by padding the input with a column of ones.
     #[derive(Clone)]
-    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct AffineCircuit<const IN: usize> {
+        fused: [ValTensor<F>; 3],
     }
 
-    impl Circuit<F> for MatmulCircuit<F> {
+    impl<const IN: usize> Circuit<F> for AffineCircuit<IN> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -122,15 +219,16 @@ mod matmul_col_overflow_double_col {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
-            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
-            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
+            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, N * OUT * (IN + 1));
+            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, N * OUT * (IN + 1));
+            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, N * OUT * (IN + 1));
+            let _constant = VarTensor::constant_cols(cs, K, 2, false);
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
         fn synthesize(
             &self,
-            mut config: Self::Config,
+            config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
             layouter
@@ -138,15 +236,16 @@ mod matmul_col_overflow_double_col {
                     || "",
                     |region| {
                         let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 128, 2);
-                        config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "ij,jk->ik".to_string(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis)
+                        let fused = layouts::affine(&config, &mut region, &self.fused)
+                            .map_err(|_| Error::Synthesis)?;
+                        let folded =
+                            layouts::fold_bias(&self.fused).map_err(|_| Error::Synthesis)?;
+                        assert_eq!(folded[0].dims(), &[N, IN + 1]);
+                        let padded = layouts::affine(&config, &mut region, &folded)
+                            .map_err(|_| Error::Synthesis)?;
+                        assert_eq!(fused.dims(), &[N, OUT]);
+                        assert_eq!(fused.int_evals().unwrap(), padded.int_evals().unwrap());
+                        Ok(())
                     },
                 )
                 .unwrap();
@@ -154,40 +253,65 @@ mod matmul_col_overflow_double_col {
         }
     }
 
-    #[test]
-    fn matmulcircuit() {
-        // parameters
-        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        a.reshape(&[LEN, LEN]).unwrap();
+    fn run<const IN: usize>() {
+        let x = (0..N * IN).map(|i| i as i128 - 2).collect::<Vec<_>>();
+        let w = (0..OUT * IN).map(|i| 3 - i as i128).collect::<Vec<_>>();
+        let b = (0..OUT).map(|i| 2 * i as i128 - 3).collect::<Vec<_>>();
 
-        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        w.reshape(&[LEN, 1]).unwrap();
+        let tensor = |values: &[i128], dims: &[usize]| {
+            ValTensor::from_integer_rep_tensor(Tensor::new(Some(values), dims).unwrap())
+        };
 
-        let circuit = MatmulCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(w)],
-            _marker: PhantomData,
+        let circuit = AffineCircuit::<IN> {
+            fused: [
+                tensor(&x, &[N, IN]),
+                tensor(&w, &[OUT, IN]),
+                tensor(&b, &[OUT]),
+            ],
         };
 
         let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
     }
+
+    #[test]
+    fn affinefusedbiascircuit() {
+        run::<3>();
+    }
+
+    #[test]
+    fn affinefusedbiaslen8() {
+        // resizing the layer needs no changes to where the bias column goes
+        run::<8>();
+    }
 }
 
 #[cfg(test)]
-mod matmul_col_overflow {
+mod accumulation_order {
     use super::*;
+    use crate::circuit::region::RegionSettings;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ops::accumulated::{self, AccumulationOrder};
 
-    const K: usize = 5;
-    const LEN: usize = 6;
+    const K: usize = 8;
+    const N: usize = 2;
+    const IN: usize = 5;
+    const OUT: usize = 3;
 
     #[derive(Clone)]
-    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct MyConfig {
+        base_config: BaseConfig<F>,
+        instance: ValTensor<F>,
     }
 
-    impl Circuit<F> for MatmulCircuit<F> {
-        type Config = BaseConfig<F>;
+    #[derive(Clone)]
+    struct AffineCircuit<const NUM_INNER_COLS: usize> {
+        inputs: [Tensor<IntegerRep>; 3],
+        order: AccumulationOrder,
+    }
+
+    impl<const NUM_INNER_COLS: usize> Circuit<F> for AffineCircuit<NUM_INNER_COLS> {
+        type Config = MyConfig;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
 
@@ -196,10 +320,16 @@ mod matmul_col_overflow {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
-            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+            let len = N * OUT * (IN + 1) * NUM_INNER_COLS;
+            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, len);
+            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, len);
+            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, len);
+            let _constant = VarTensor::constant_cols(cs, K, 2, false);
+            let instance = ValTensor::new_instance(cs, vec![vec![N, OUT]], 0);
+            MyConfig {
+                base_config: BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+                instance,
+            }
         }
 
         fn synthesize(
@@ -207,73 +337,362 @@ mod matmul_col_overflow {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "ij,jk->ik".to_string(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
-                .unwrap();
-            Ok(())
+            config
+                .base_config
+                .set_accumulation_order(self.order)
+                .map_err(|_| Error::Synthesis)?;
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 128, 2);
+                    let output =
+                        layouts::affine(&config.base_config, &mut region, &values(&self.inputs))
+                            .map_err(|_| Error::Synthesis)?;
+                    layouts::enforce_equality(
+                        &config.base_config,
+                        &mut region,
+                        &[output, config.instance.clone()],
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
         }
     }
 
+    fn values(inputs: &[Tensor<IntegerRep>; 3]) -> Vec<ValTensor<F>> {
+        inputs
+            .iter()
+            .map(|x| ValTensor::from_integer_rep_tensor(x.clone()))
+            .collect()
+    }
+
+    fn inputs() -> [Tensor<IntegerRep>; 3] {
+        let x = (0..N * IN)
+            .map(|i| 3 * i as IntegerRep - 7)
+            .collect::<Vec<_>>();
+        let w = (0..OUT * IN)
+            .map(|i| 5 - i as IntegerRep)
+            .collect::<Vec<_>>();
+        let b = (0..OUT).map(|i| i as IntegerRep - 1).collect::<Vec<_>>();
+        [
+            Tensor::new(Some(&x), &[N, IN]).unwrap(),
+            Tensor::new(Some(&w), &[OUT, IN]).unwrap(),
+            Tensor::new(Some(&b), &[OUT]).unwrap(),
+        ]
+    }
+
+    // the rows a dummy layout of the affine layer takes in each order
+    fn rows(order: AccumulationOrder) -> usize {
+        let mut config = BaseConfig::<F>::dummy(K, 2);
+        config.set_accumulation_order(order).unwrap();
+        let mut region = RegionCtx::new_dummy(0, 2, RegionSettings::all_true(65536, 4));
+        layouts::affine(&config, &mut region, &values(&inputs())).unwrap();
+        region.row()
+    }
+
+    fn run<const NUM_INNER_COLS: usize>(order: AccumulationOrder) {
+        let inputs = inputs();
+        // sums in the field don't depend on the order, the integer outputs are the same in any
+        let expected = accumulated::affine(&inputs, order)
+            .unwrap()
+            .iter()
+            .map(|y| integer_rep_to_felt(*y))
+            .collect::<Vec<_>>();
+        let circuit = AffineCircuit::<NUM_INNER_COLS> { inputs, order };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![expected.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        let mut tampered = expected;
+        tampered[0] += F::ONE;
+        let prover = MockProver::run(K as u32, &circuit, vec![tampered]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // float sums do depend on it, a 1 is lost to rounding whenever it is added to 1e8
+    fn float_affine(order: AccumulationOrder) -> f32 {
+        let x = Tensor::<f32>::new(Some(&[1e8, 1.0, -1e8, 1.0, 1.0, 1.0]), &[1, 6]).unwrap();
+        let w = Tensor::<f32>::new(Some(&[1.0; 6]), &[1, 6]).unwrap();
+        let b = Tensor::<f32>::new(Some(&[1.0]), &[1]).unwrap();
+        accumulated::affine(&[x, w, b], order).unwrap()[0]
+    }
+
     #[test]
-    fn matmulcircuit() {
-        // parameters
-        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        a.reshape(&[LEN, LEN]).unwrap();
+    fn floatreferenceorder() {
+        // the bias starts the accumulation, added last it wouldn't be lost to rounding in the
+        // first two orders, giving results one higher
+        // 1 + 1e8 + 1 - 1e8 + 1 + 1 + 1
+        assert_eq!(float_affine(AccumulationOrder::Sequential), 3.0);
+        // 1 + (1e8 + 1) + (-1e8 + 1) + (1 + 1)
+        assert_eq!(float_affine(AccumulationOrder::Blocked(2)), 2.0);
+        // 1 + (1e8 + 1 - 1e8) + (1 + 1 + 1)
+        assert_eq!(float_affine(AccumulationOrder::Blocked(3)), 4.0);
+    }
 
-        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        w.reshape(&[LEN, 1]).unwrap();
+    #[test]
+    fn sequentialaccumulation() {
+        assert_eq!(
+            BaseConfig::<F>::dummy(12, 1).accumulation_order(),
+            AccumulationOrder::Sequential
+        );
+        run::<1>(AccumulationOrder::Sequential);
+        // a single term per row on two inner columns
+        run::<2>(AccumulationOrder::Sequential);
+    }
 
-        let circuit = MatmulCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(w)],
-            _marker: PhantomData,
+    #[test]
+    fn blockedaccumulation() {
+        assert_eq!(
+            BaseConfig::<F>::dummy(12, 2).accumulation_order(),
+            AccumulationOrder::Blocked(2)
+        );
+        run::<2>(AccumulationOrder::Blocked(2));
+    }
+
+    #[test]
+    fn configuredaccumulationorder() {
+        // one term per row takes more rows than a block of two terms per row
+        assert!(rows(AccumulationOrder::Sequential) > rows(AccumulationOrder::Blocked(2)));
+
+        // blocks can't be wider than the gate
+        let mut config = BaseConfig::<F>::dummy(K, 2);
+        assert!(config
+            .set_accumulation_order(AccumulationOrder::Blocked(3))
+            .is_err());
+        assert_eq!(config.accumulation_order(), AccumulationOrder::Blocked(2));
+        config
+            .set_accumulation_order(AccumulationOrder::Sequential)
+            .unwrap();
+        assert_eq!(config.accumulation_order(), AccumulationOrder::Sequential);
+    }
+}
+
+#[cfg(test)]
+mod affine_concat_inputs {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 8;
+    const LEN: usize = 2;
+    const OUT: usize = 3;
+
+    #[derive(Clone)]
+    struct ConcatAffineConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    /// Two feature vectors that are assigned before they reach a dense layer over both of them.
+    #[derive(Clone)]
+    struct ConcatAffineCircuit {
+        parts: [ValTensor<F>; 2],
+        params: [ValTensor<F>; 2],
+    }
+
+    impl Circuit<F> for ConcatAffineCircuit {
+        type Config = ConcatAffineConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, 2 * LEN * OUT);
+            let b = VarTensor::new_advice(cs, K, 1, 2 * LEN * OUT);
+            let output = VarTensor::new_advice(cs, K, 1, 2 * LEN * OUT);
+            let _constant = VarTensor::constant_cols(cs, K, 2, false);
+            let base_config = BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE);
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            ConcatAffineConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let output = layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let mut parts = vec![];
+                    for part in &self.parts {
+                        let assigned = region
+                            .assign(&config.base_config.custom_gates.inputs[1], part)
+                            .map_err(|_| Error::Synthesis)?;
+                        region.increment(assigned.len());
+                        parts.push(assigned);
+                    }
+                    layouts::concat_affine(&config.base_config, &mut region, &parts, &self.params)
+                        .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            assert_eq!(output.dims(), &[OUT]);
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    const A: [IntegerRep; LEN] = [3, -1];
+    const B: [IntegerRep; LEN] = [2, 5];
+    const W: [IntegerRep; OUT * 2 * LEN] = [1, 2, 3, 4, -1, 0, 2, 1, 0, -3, 1, 1];
+    const BIAS: [IntegerRep; OUT] = [1, 0, -2];
+
+    fn circuit(parts: [&[IntegerRep]; 2]) -> ConcatAffineCircuit {
+        let tensor = |values: &[IntegerRep], dims: &[usize]| {
+            ValTensor::from_integer_rep_tensor(Tensor::new(Some(values), dims).unwrap())
         };
+        ConcatAffineCircuit {
+            parts: [tensor(parts[0], &[LEN]), tensor(parts[1], &[LEN])],
+            params: [tensor(&W, &[OUT, 2 * LEN]), tensor(&BIAS, &[OUT])],
+        }
+    }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+    // out[k] = bias[k] + sum_j (a | b)[j] * w[k][j]
+    fn reference() -> Vec<F> {
+        let x = A.iter().chain(B.iter()).collect::<Vec<_>>();
+        (0..OUT)
+            .map(|k| {
+                let acc = (0..2 * LEN)
+                    .map(|j| x[j] * W[k * 2 * LEN + j])
+                    .sum::<IntegerRep>();
+                integer_rep_to_felt(acc + BIAS[k])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn affineconcatinputs() {
+        let prover = MockProver::run(K as u32, &circuit([&A, &B]), vec![reference()]).unwrap();
         prover.assert_satisfied();
     }
+
+    #[test]
+    fn affineconcatinputsorder() {
+        // the weights see the features in the order the inputs are given
+        let prover = MockProver::run(K as u32, &circuit([&B, &A]), vec![reference()]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 }
 
 #[cfg(test)]
-#[cfg(all(
-    feature = "ezkl",
-    not(all(target_arch = "wasm32", target_os = "unknown"))
-))]
-mod matmul_col_ultra_overflow_double_col {
+mod lookup_bits {
+    use crate::circuit::table::recommended_lookup_bits;
+    use crate::fieldutils::IntegerRep;
+
+    // the first layer of examples/mlp_4d_einsum.rs, which sizes its table with 14 bits
+    const L0_KERNEL: [IntegerRep; 16] = [10, 0, 0, -1, 0, 10, 1, 0, 0, 1, 10, 0, 1, 0, 0, 10];
+    const L0_BIAS: [IntegerRep; 4] = [0, 0, 0, 1];
+    const INPUT: [IntegerRep; 4] = [-30, -21, 11, 40];
+    const BITS: u32 = 14;
+
+    fn range(values: &[IntegerRep]) -> (IntegerRep, IntegerRep) {
+        (*values.iter().min().unwrap(), *values.iter().max().unwrap())
+    }
 
-    use halo2_proofs::poly::kzg::{
-        commitment::KZGCommitmentScheme,
-        multiopen::{ProverSHPLONK, VerifierSHPLONK},
-        strategy::SingleStrategy,
-    };
-    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+    #[test]
+    fn mlpfitsexamplebits() {
+        let rec = recommended_lookup_bits(
+            range(&INPUT),
+            range(&L0_KERNEL),
+            Some(range(&L0_BIAS)),
+            INPUT.len(),
+        );
+        assert_eq!(rec.range, (-1200, 1601));
+        assert!(rec.bits <= BITS);
+
+        // every activation input the example computes lies in the recommended range
+        for (row, b) in L0_KERNEL.chunks(INPUT.len()).zip(L0_BIAS) {
+            let acc = row
+                .iter()
+                .zip(INPUT)
+                .map(|(w, x)| w * x)
+                .sum::<IntegerRep>()
+                + b;
+            assert!(rec.range.0 <= acc && acc <= rec.range.1);
+        }
+    }
+}
 
+#[cfg(test)]
+mod layer_chain {
     use super::*;
+    use crate::circuit::ops::layer::{
+        AffineLayer, ConvLayer, EltwiseLayer, LayerChain, PoolKind, PoolLayer,
+    };
+    use crate::circuit::region::RegionSettings;
 
-    const K: usize = 4;
-    const LEN: usize = 10;
-    const NUM_INNER_COLS: usize = 2;
+    const K: usize = 8;
+    const LEN: usize = 64;
+
+    fn tensor(values: &[i128], dims: &[usize]) -> ValTensor<F> {
+        ValTensor::from_integer_rep_tensor(Tensor::new(Some(values), dims).unwrap())
+    }
+
+    // affine -> square -> affine, mapping [2, 3] to [2, 2]
+    fn mlp() -> LayerChain<F> {
+        LayerChain::new()
+            .with(AffineLayer {
+                weights: tensor(&[1, 0, 1, 0, 1, 0, 2, -1, 0, 1, 1, 1], &[4, 3]),
+                bias: Some(tensor(&[0, 1, -1, 2], &[4])),
+            })
+            .with(EltwiseLayer {
+                op: Box::new(PolyOp::Pow(2)),
+            })
+            .with(AffineLayer {
+                weights: tensor(&[1, -1, 0, 1, 0, 2, 1, -1], &[2, 4]),
+                bias: None,
+            })
+    }
+
+    // conv -> sumpool, mapping [1, 1, 4, 4] to [1, 2, 2, 2]
+    fn convnet() -> LayerChain<F> {
+        LayerChain::new()
+            .with(ConvLayer {
+                kernel: tensor(&[1, 0, 0, 1, 0, 1, -1, 0], &[2, 1, 2, 2]),
+                bias: None,
+                padding: vec![(0, 0); 2],
+                stride: vec![1; 2],
+                dilation: vec![1; 2],
+                group: 1,
+                data_format: DataFormat::NCHW,
+                kernel_format: KernelFormat::OIHW,
+            })
+            .with(PoolLayer {
+                kind: PoolKind::Sum { normalized: false },
+                padding: vec![(0, 0); 2],
+                stride: vec![1; 2],
+                kernel_shape: vec![2; 2],
+                data_format: DataFormat::NCHW,
+            })
+    }
 
     #[derive(Clone)]
-    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct ChainCircuit {
+        mlp_input: ValTensor<F>,
+        conv_input: ValTensor<F>,
     }
 
-    impl Circuit<F> for MatmulCircuit<F> {
+    impl Circuit<F> for ChainCircuit {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -283,9 +702,10 @@ mod matmul_col_ultra_overflow_double_col {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
-            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
-            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
@@ -298,16 +718,18 @@ mod matmul_col_ultra_overflow_double_col {
                 .assign_region(
                     || "",
                     |region| {
-                        let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 128, 2);
-                        config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "ij,jk->ik".to_string(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis)
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        for (chain, input) in
+                            [(mlp(), &self.mlp_input), (convnet(), &self.conv_input)]
+                        {
+                            let shapes = chain.output_shapes(input.dims()).unwrap();
+                            let output = chain
+                                .layout(&mut config, &mut region, input)
+                                .map_err(|_| Error::Synthesis)?;
+                            // shape inference agrees with what the layouts produce
+                            assert_eq!(output.dims(), shapes.last().unwrap().as_slice());
+                        }
+                        Ok(())
                     },
                 )
                 .unwrap();
@@ -316,84 +738,157 @@ mod matmul_col_ultra_overflow_double_col {
     }
 
     #[test]
-    #[ignore]
-    fn matmulcircuit() {
-        // get some logs fam
-        crate::logger::init_logger();
-        // parameters
-        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        a.reshape(&[LEN, LEN]).unwrap();
-
-        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        w.reshape(&[LEN, 1]).unwrap();
-
-        let circuit = MatmulCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(w)],
-            _marker: PhantomData,
+    fn layerchaincircuit() {
+        let circuit = ChainCircuit {
+            mlp_input: tensor(&[1, -2, 3, 0, 2, -1], &[2, 3]),
+            conv_input: tensor(&(0..16).collect::<Vec<_>>(), &[1, 1, 4, 4]),
         };
 
-        let params = crate::pfsys::srs::gen_srs::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
-        >(K as u32);
+        assert_eq!(
+            mlp().output_shapes(&[2, 3]).unwrap(),
+            vec![vec![2, 4], vec![2, 4], vec![2, 2]]
+        );
+        assert_eq!(
+            convnet().output_shapes(&[1, 1, 4, 4]).unwrap(),
+            vec![vec![1, 2, 3, 3], vec![1, 2, 2, 2]]
+        );
 
-        let pk = crate::pfsys::create_keys::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
-            MatmulCircuit<F>,
-        >(&circuit, &params, true)
-        .unwrap();
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
 
-        let prover = crate::pfsys::create_proof_circuit::<
-            KZGCommitmentScheme<_>,
-            _,
-            ProverSHPLONK<_>,
-            VerifierSHPLONK<_>,
-            SingleStrategy<_>,
-            _,
-            EvmTranscript<_, _, _, _>,
-            EvmTranscript<_, _, _, _>,
-        >(
-            circuit.clone(),
-            vec![],
-            &params,
-            &pk,
-            // use safe mode to verify that the proof is correct
-            CheckMode::SAFE,
-            crate::Commitments::KZG,
-            crate::pfsys::TranscriptType::EVM,
-            None,
-            None,
-        );
+    #[test]
+    fn layerchainmisconnected() {
+        // the second affine expects 3 features but the first one produces 4
+        let chain = LayerChain::new()
+            .with(AffineLayer {
+                weights: tensor(&[1; 12], &[4, 3]),
+                bias: None,
+            })
+            .with(AffineLayer {
+                weights: tensor(&[1; 6], &[2, 3]),
+                bias: None,
+            });
+        assert!(chain.output_shapes(&[2, 3]).is_err());
+
+        // the mismatch is caught before anything is assigned
+        let mut config = BaseConfig::dummy(12, 2);
+        let mut region = RegionCtx::new_dummy(0, 2, RegionSettings::all_true(128, 2));
+        let input = tensor(&[1; 6], &[2, 3]);
+        assert!(chain.layout(&mut config, &mut region, &input).is_err());
+        assert_eq!(region.linear_coord(), 0);
+
+        // as is a pooling window that doesn't fit the conv output
+        let chain = convnet().with(PoolLayer {
+            kind: PoolKind::Max,
+            padding: vec![(0, 0); 2],
+            stride: vec![1; 2],
+            kernel_shape: vec![3; 2],
+            data_format: DataFormat::NCHW,
+        });
+        assert!(chain.output_shapes(&[1, 1, 4, 4]).is_err());
+    }
 
-        assert!(prover.is_ok());
+    #[test]
+    fn layerchainregionnames() {
+        assert_eq!(mlp().region_names(), ["affine_l0", "pow_l1", "affine_l2"]);
+
+        let chain = convnet().with_label(
+            "head",
+            AffineLayer {
+                weights: tensor(&[1; 4], &[2, 2]),
+                bias: None,
+            },
+        );
+        assert_eq!(chain.region_names(), ["conv_l0", "sumpool_l1", "head"]);
     }
 }
 
 #[cfg(test)]
-#[cfg(all(
-    feature = "ezkl",
-    not(all(target_arch = "wasm32", target_os = "unknown"))
-))]
-mod matmul_col_ultra_overflow {
-
-    use halo2_proofs::poly::kzg::{
-        commitment::KZGCommitmentScheme,
-        multiopen::{ProverSHPLONK, VerifierSHPLONK},
-        strategy::SingleStrategy,
-    };
-    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
-
+mod ensemble {
     use super::*;
+    use crate::circuit::ops::layer::{AffineLayer, EltwiseLayer, Ensemble, Layer, LayerChain};
+    use crate::circuit::region::RegionSettings;
+
+    const K: usize = 10;
+    const LEN: usize = 64;
+    const SCALE: f32 = 4.0;
+    const IN: usize = 3;
+    const HIDDEN: usize = 4;
+    const OUT: usize = 2;
+
+    const X: [f32; 6] = [1.0, -0.5, 0.25, 0.75, 0.0, -1.0];
+
+    // the parameters of the two members, weights at SCALE and biases at the scale of the
+    // matmul they are added to
+    struct Mlp {
+        w1: [f32; HIDDEN * IN],
+        b1: [f32; HIDDEN],
+        w2: [f32; OUT * HIDDEN],
+        b2: [f32; OUT],
+    }
 
-    const K: usize = 4;
-    const LEN: usize = 10;
+    const MLPS: [Mlp; 2] = [
+        Mlp {
+            w1: [
+                0.5, -0.25, 1.0, 0.0, 0.75, -0.5, -1.0, 0.25, 0.5, 0.25, 0.25, 0.25,
+            ],
+            b1: [0.25, -0.5, 0.0, 0.5],
+            w2: [1.0, -0.5, 0.25, 0.0, -0.25, 0.75, 0.5, 1.0],
+            b2: [0.5, -0.25],
+        },
+        Mlp {
+            w1: [
+                -0.5, 0.5, 0.0, 1.0, 0.25, 0.25, 0.75, -0.75, -0.25, 0.0, 1.0, 0.5,
+            ],
+            b1: [0.0, 0.25, -0.25, 0.75],
+            w2: [0.25, 0.5, -1.0, 0.75, 0.0, -0.5, 0.25, 0.5],
+            b2: [-0.5, 0.25],
+        },
+    ];
+
+    // affine -> relu -> affine, with the outputs at SCALE^3
+    fn member(mlp: &Mlp) -> LayerChain<F> {
+        LayerChain::new()
+            .with(AffineLayer {
+                weights: quantize(&mlp.w1, SCALE, &[HIDDEN, IN]),
+                bias: Some(quantize(&mlp.b1, SCALE.powi(2), &[HIDDEN])),
+            })
+            .with(EltwiseLayer {
+                op: Box::new(PolyOp::LeakyReLU {
+                    slope: 0.0.into(),
+                    scale: 1,
+                }),
+            })
+            .with(AffineLayer {
+                weights: quantize(&mlp.w2, SCALE, &[OUT, HIDDEN]),
+                bias: Some(quantize(&mlp.b2, SCALE.powi(3), &[OUT])),
+            })
+    }
+
+    fn reference(mlp: &Mlp) -> Vec<f32> {
+        let mut output = vec![];
+        for x in X.chunks(IN) {
+            let hidden = mlp
+                .w1
+                .chunks(IN)
+                .zip(mlp.b1)
+                .map(|(w, b)| x.iter().zip(w).map(|(x, w)| x * w).sum::<f32>() + b)
+                .map(|h| h.max(0.0))
+                .collect::<Vec<_>>();
+            for (w, b) in mlp.w2.chunks(HIDDEN).zip(mlp.b2) {
+                output.push(hidden.iter().zip(w).map(|(h, w)| h * w).sum::<f32>() + b);
+            }
+        }
+        output
+    }
 
     #[derive(Clone)]
-    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct EnsembleCircuit {
+        input: ValTensor<F>,
     }
 
-    impl Circuit<F> for MatmulCircuit<F> {
+    impl Circuit<F> for EnsembleCircuit {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -403,10 +898,24 @@ mod matmul_col_ultra_overflow {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
-            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+                .unwrap();
+            config
         }
 
         fn synthesize(
@@ -414,96 +923,88 @@ mod matmul_col_ultra_overflow {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "ij,jk->ik".to_string(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
-                .unwrap();
-            Ok(())
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    ensemble()
+                        .layout(&mut config, &mut region, &self.input)
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
         }
     }
 
-    #[test]
-    #[ignore]
-    fn matmulcircuit() {
-        // get some logs fam
-        crate::logger::init_logger();
-        // parameters
-        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        a.reshape(&[LEN, LEN]).unwrap();
-
-        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
-        w.reshape(&[LEN, 1]).unwrap();
+    fn ensemble() -> Ensemble<F> {
+        Ensemble::new()
+            .with(member(&MLPS[0]))
+            .with(member(&MLPS[1]))
+    }
 
-        let circuit = MatmulCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(w)],
-            _marker: PhantomData,
+    #[test]
+    fn ensemblecircuit() {
+        let circuit = EnsembleCircuit {
+            input: quantize(&X, SCALE, &[2, IN]),
         };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
 
-        let params = crate::pfsys::srs::gen_srs::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
-        >(K as u32);
-
-        let pk = crate::pfsys::create_keys::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
-            MatmulCircuit<F>,
-        >(&circuit, &params, true)
-        .unwrap();
+    #[test]
+    fn ensemblereference() {
+        let mut config = BaseConfig::dummy(K, 1);
+        let mut region = RegionCtx::new_dummy(0, 1, RegionSettings::all_true(1024, 2));
+        let output = ensemble()
+            .layout(&mut config, &mut region, &quantize(&X, SCALE, &[2, IN]))
+            .unwrap();
+        assert_eq!(output.dims(), &[2, OUT]);
 
-        let prover = crate::pfsys::create_proof_circuit::<
-            KZGCommitmentScheme<_>,
-            _,
-            ProverSHPLONK<_>,
-            VerifierSHPLONK<_>,
-            SingleStrategy<_>,
-            _,
-            EvmTranscript<_, _, _, _>,
-            EvmTranscript<_, _, _, _>,
-        >(
-            circuit.clone(),
-            vec![],
-            &params,
-            &pk,
-            // use safe mode to verify that the proof is correct
-            CheckMode::SAFE,
-            crate::Commitments::KZG,
-            crate::pfsys::TranscriptType::EVM,
-            None,
-            None,
-        );
+        let (a, b) = (reference(&MLPS[0]), reference(&MLPS[1]));
+        let expected = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| (a + b) / 2.0)
+            .collect::<Vec<_>>();
+        // the only rounding is that of the final divide
+        assert_close(&output, SCALE.powi(3), &expected, 0.5 / SCALE.powi(3));
+    }
 
-        assert!(prover.is_ok());
+    #[test]
+    fn ensemblemismatchedmembers() {
+        // a member with three outputs can't be averaged with one with two
+        let wide = LayerChain::new().with(AffineLayer {
+            weights: quantize(&[1.0; 3 * IN], SCALE, &[3, IN]),
+            bias: None,
+        });
+        let ensemble = Ensemble::new().with(member(&MLPS[0])).with(wide);
+        assert!(ensemble.output_shape(&[2, IN]).is_err());
+        assert!(Ensemble::<F>::new().output_shape(&[2, IN]).is_err());
     }
 }
 
 #[cfg(test)]
-mod dot {
-    use ops::poly::PolyOp;
-
+mod linear_solve {
     use super::*;
+    use crate::circuit::ops::layouts;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::graph::Visibility;
 
-    const K: usize = 4;
+    const K: usize = 6;
     const LEN: usize = 4;
+    // [[2, 1], [1, 3]] x = [4, 7] is solved by x = [1, 2]
+    const A: [IntegerRep; 4] = [2, 1, 1, 3];
+    const B: [IntegerRep; 2] = [4, 7];
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct SolveCircuit {
+        a: ValTensor<F>,
+        x: ValTensor<F>,
+        b: ValTensor<F>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
+    impl Circuit<F> for SolveCircuit {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -516,145 +1017,290 @@ mod dot {
             let a = VarTensor::new_advice(cs, K, 1, LEN);
             let b = VarTensor::new_advice(cs, K, 1, LEN);
             let output = VarTensor::new_advice(cs, K, 1, LEN);
-
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
         fn synthesize(
             &self,
-            mut config: Self::Config,
+            config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "i,i->".to_string(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
-                .unwrap();
-            Ok(())
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    layouts::linear_solve(
+                        &config,
+                        &mut region,
+                        &[self.a.clone(), self.x.clone(), self.b.clone()],
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
         }
     }
 
-    #[test]
-    fn dotcircuit() {
-        // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
-
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
-
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
-            _marker: PhantomData,
+    fn circuit(x: [IntegerRep; 2]) -> SolveCircuit {
+        // the system is fixed by the circuit, only the solution is witnessed
+        let fixed = |values: &[IntegerRep], dims: &[usize]| {
+            let mut t: Tensor<F> = Tensor::new(
+                Some(
+                    &values
+                        .iter()
+                        .map(|v| integer_rep_to_felt(*v))
+                        .collect::<Vec<_>>(),
+                ),
+                dims,
+            )
+            .unwrap();
+            t.set_visibility(&Visibility::Fixed);
+            ValTensor::try_from(t).unwrap()
         };
+        SolveCircuit {
+            a: fixed(&A, &[2, 2]),
+            x: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&x), &[2]).unwrap()),
+            b: fixed(&B, &[2]),
+        }
+    }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+    #[test]
+    fn linearsolvecircuit() {
+        let prover = MockProver::run(K as u32, &circuit([1, 2]), vec![]).unwrap();
         prover.assert_satisfied();
     }
+
+    #[test]
+    fn linearsolvewrongsolution() {
+        // [2, 1] gives [5, 5]
+        let prover = MockProver::run(K as u32, &circuit([2, 1]), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 }
 
 #[cfg(test)]
-mod dot_col_overflow_triple_col {
+mod tiled_input {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::circuit::modules::{
+        poseidon::{
+            spec::{PoseidonSpec, POSEIDON_RATE, POSEIDON_WIDTH},
+            PoseidonChip, PoseidonConfig,
+        },
+        Module, ModulePlanner,
+    };
+    use crate::circuit::ops::layouts;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ops::check_tiling;
 
-    const K: usize = 4;
-    const LEN: usize = 50;
+    const K: usize = 11;
+    const TILE: [usize; 3] = [1, 4, 4];
+    const LEN: usize = 16;
+    const WIDTH: usize = POSEIDON_WIDTH;
+    const RATE: usize = POSEIDON_RATE;
+
+    type Poseidon = PoseidonChip<PoseidonSpec, WIDTH, RATE>;
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct MyConfig {
+        base_config: BaseConfig<F>,
+        instance: ValTensor<F>,
+        poseidon: PoseidonConfig<WIDTH, RATE>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
-        type Config = BaseConfig<F>;
-        type FloorPlanner = SimpleFloorPlanner;
-        type Params = TestParams;
+    // proves the sum of a single tile, so each tile of the input gets its own proof. the hash of
+    // the tile is public so that the proofs can be checked against a commitment to the input
+    #[derive(Clone)]
+    struct TileCircuit {
+        tile: ValTensor<F>,
+    }
+
+    impl Circuit<F> for TileCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = ModulePlanner;
+        type Params = TestParams;
 
         fn without_witnesses(&self) -> Self {
             self.clone()
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            // used for constants in the padding
-            let _fixed = cs.fixed_column();
-            cs.enable_constant(_fixed);
-
-            let a = VarTensor::new_advice(cs, K, 3, LEN);
-            let b = VarTensor::new_advice(cs, K, 3, LEN);
-            let output = VarTensor::new_advice(cs, K, 3, LEN);
-
-            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+            let instance = ValTensor::new_instance(cs, vec![vec![1]], 0);
+            let poseidon = Poseidon::configure(cs, ());
+
+            MyConfig {
+                base_config: BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+                instance,
+                poseidon,
+            }
         }
 
         fn synthesize(
             &self,
-            mut config: Self::Config,
+            config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 3, 128, 2);
-                        config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "i,i->".to_string(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
-                .unwrap();
-            Ok(())
+            let poseidon = Poseidon::new(config.poseidon.clone());
+            // the hashed cells are the ones summed, so the proof is about the committed tile
+            let tile =
+                poseidon.layout(&mut layouter, &[self.tile.clone()], 0, &mut HashMap::new())?;
+
+            layouter.assign_region(|| "_new_module", |_| Ok(()))?;
+
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let sum = layouts::sum(&config.base_config, &mut region, &[tile.clone()])
+                        .map_err(|_| Error::Synthesis)?;
+                    layouts::enforce_equality(
+                        &config.base_config,
+                        &mut region,
+                        &[sum, config.instance.clone()],
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
         }
     }
 
-    #[test]
-    fn dotcircuit() {
-        // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    fn input() -> Tensor<IntegerRep> {
+        Tensor::from_fn(&[1, 8, 8], |coord| {
+            (3 * coord[1] as IntegerRep - 2 * coord[2] as IntegerRep) % 7
+        })
+    }
 
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    fn tile_hash(tile: &Tensor<IntegerRep>) -> F {
+        Poseidon::run(tile.iter().map(|x| integer_rep_to_felt(*x)).collect()).unwrap()[0][0]
+    }
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
-            _marker: PhantomData,
+    // the input is committed to as the hash of its tile hashes, in tile order. each tile proof
+    // only covers its own tile, the commitment is what ties the proofs to a single input
+    fn commitment(tile_hashes: &[F]) -> F {
+        Poseidon::run(tile_hashes.to_vec()).unwrap()[0][0]
+    }
+
+    fn prove(tile: &Tensor<IntegerRep>, sum: IntegerRep, hash: F) -> MockProver<F> {
+        let circuit = TileCircuit {
+            tile: ValTensor::from_integer_rep_tensor(tile.clone()),
         };
+        MockProver::run(
+            K as u32,
+            &circuit,
+            vec![vec![integer_rep_to_felt(sum)], vec![hash]],
+        )
+        .unwrap()
+    }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
+    #[test]
+    fn tiledinputcircuit() {
+        let input = input();
+        let tiles = input.tiles(&TILE).unwrap();
+        assert_eq!(tiles.len(), 4);
+
+        let ranges = tiles.iter().map(|(r, _)| r.clone()).collect::<Vec<_>>();
+        check_tiling(input.dims(), &ranges).unwrap();
+        assert_eq!(Tensor::from_tiles(input.dims(), &tiles).unwrap(), input);
+
+        let hashes = tiles.iter().map(|(_, t)| tile_hash(t)).collect::<Vec<_>>();
+        let mut total = 0;
+        let mut proven_hashes = vec![];
+        for (i, (_, tile)) in tiles.iter().enumerate() {
+            let sum = tile.iter().sum::<IntegerRep>();
+            total += sum;
+
+            prove(tile, sum, hashes[i]).assert_satisfied();
+            proven_hashes.push(hashes[i]);
+
+            assert!(prove(tile, sum + 1, hashes[i]).verify().is_err());
+            // a tile can't be passed off as another tile of the input
+            let other = hashes[(i + 1) % hashes.len()];
+            assert!(prove(tile, sum, other).verify().is_err());
+        }
+        assert_eq!(total, input.iter().sum::<IntegerRep>());
+        assert_eq!(commitment(&proven_hashes), commitment(&hashes));
+    }
+
+    #[test]
+    fn tiledinputswappedtile() {
+        let input = input();
+        let tiles = input.tiles(&TILE).unwrap();
+        let hashes = tiles.iter().map(|(_, t)| tile_hash(t)).collect::<Vec<_>>();
+
+        // a tile of a different input proves fine on its own, but its hash no longer matches
+        // the commitment to the input
+        let other = input.map(|x| x + 1);
+        let (_, swapped) = other.tiles(&TILE).unwrap().remove(0);
+        let swapped_hash = tile_hash(&swapped);
+        prove(&swapped, swapped.iter().sum::<IntegerRep>(), swapped_hash).assert_satisfied();
+
+        let mut proven_hashes = hashes.clone();
+        proven_hashes[0] = swapped_hash;
+        assert_ne!(commitment(&proven_hashes), commitment(&hashes));
+    }
+
+    #[test]
+    fn tiledinputbadcoverage() {
+        let input = input();
+        let mut ranges = input
+            .tiles(&TILE)
+            .unwrap()
+            .into_iter()
+            .map(|(r, _)| r)
+            .collect::<Vec<_>>();
+
+        // proving the first tile twice leaves the last one unproven
+        let mut overlapping = ranges.clone();
+        overlapping[3] = overlapping[0].clone();
+        assert!(check_tiling(input.dims(), &overlapping).is_err());
+
+        ranges.pop();
+        assert!(check_tiling(input.dims(), &ranges).is_err());
     }
 }
 
 #[cfg(test)]
-mod dot_col_overflow {
+mod parallel_mock_provers {
     use super::*;
+    use crate::circuit::ops::layouts;
+    use crate::circuit::table::Table;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
 
-    const K: usize = 4;
-    const LEN: usize = 50;
+    const K: usize = 8;
+    const LEN: usize = 4;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn configs_are_send_and_sync() {
+        assert_send_sync::<BaseConfig<F>>();
+        assert_send_sync::<Table<F>>();
+        assert_send_sync::<VarTensor>();
+        assert_send_sync::<ValTensor<F>>();
+        assert_send_sync::<crate::graph::GraphConfig>();
+        assert_send_sync::<crate::graph::GraphCircuit>();
+    }
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+    struct MyConfig {
+        base_config: BaseConfig<F>,
+        instance: ValTensor<F>,
+    }
+
+    #[derive(Clone)]
+    struct DotCircuit {
         inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
-        type Config = BaseConfig<F>;
+    impl Circuit<F> for DotCircuit {
+        type Config = MyConfig;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
 
@@ -666,67 +1312,102 @@ mod dot_col_overflow {
             let a = VarTensor::new_advice(cs, K, 1, LEN);
             let b = VarTensor::new_advice(cs, K, 1, LEN);
             let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+            let instance = ValTensor::new_instance(cs, vec![vec![1]], 0);
 
-            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+            MyConfig {
+                base_config: BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+                instance,
+            }
         }
 
         fn synthesize(
             &self,
-            mut config: Self::Config,
+            config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "i,i->".to_string(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
-                .unwrap();
-            Ok(())
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let dot = layouts::dot(&config.base_config, &mut region, &self.inputs)
+                        .map_err(|_| Error::Synthesis)?;
+                    layouts::enforce_equality(
+                        &config.base_config,
+                        &mut region,
+                        &[dot, config.instance.clone()],
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
         }
     }
 
     #[test]
-    fn dotcircuit() {
-        // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
-
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    fn mockprovers_across_threads() {
+        let cases = (0..8)
+            .map(|t| {
+                let a = (0..LEN as IntegerRep).map(|i| i + t).collect::<Vec<_>>();
+                let b = (0..LEN as IntegerRep)
+                    .map(|i| 2 * i - t)
+                    .collect::<Vec<_>>();
+                let dot = a.iter().zip(&b).map(|(a, b)| a * b).sum::<IntegerRep>();
+                let circuit = DotCircuit {
+                    inputs: [a, b].map(|x| {
+                        ValTensor::from_integer_rep_tensor(Tensor::new(Some(&x), &[LEN]).unwrap())
+                    }),
+                };
+                (circuit, dot)
+            })
+            .collect::<Vec<_>>();
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
-            _marker: PhantomData,
-        };
+        // each thread checks its own circuit, with both the correct and a tampered instance
+        std::thread::scope(|s| {
+            let handles = cases
+                .iter()
+                .map(|(circuit, dot)| {
+                    s.spawn(move || {
+                        let prover = MockProver::run(
+                            K as u32,
+                            circuit,
+                            vec![vec![integer_rep_to_felt(*dot)]],
+                        )
+                        .unwrap();
+                        let ok = prover.verify().is_ok();
+                        let prover = MockProver::run(
+                            K as u32,
+                            circuit,
+                            vec![vec![integer_rep_to_felt(*dot + 1)]],
+                        )
+                        .unwrap();
+                        ok && prover.verify().is_err()
+                    })
+                })
+                .collect::<Vec<_>>();
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
+            for handle in handles {
+                assert!(handle.join().unwrap());
+            }
+        });
     }
 }
 
 #[cfg(test)]
-mod sum {
+mod matmul_col_overflow_double_col {
     use super::*;
 
-    const K: usize = 4;
-    const LEN: usize = 4;
+    const K: usize = 5;
+    const LEN: usize = 6;
+    const NUM_INNER_COLS: usize = 2;
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 1],
+    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
+    impl Circuit<F> for MatmulCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -736,10 +1417,9 @@ mod sum {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
-
+            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
+            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
+            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
@@ -752,12 +1432,14 @@ mod sum {
                 .assign_region(
                     || "",
                     |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 128, 2);
                         config
                             .layout(
                                 &mut region,
                                 &self.inputs.clone(),
-                                Box::new(PolyOp::Sum { axes: vec![0] }),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "ij,jk->ik".to_string(),
+                                }),
                             )
                             .map_err(|_| Error::Synthesis)
                     },
@@ -768,12 +1450,16 @@ mod sum {
     }
 
     #[test]
-    fn sumcircuit() {
+    fn matmulcircuit() {
         // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        a.reshape(&[LEN, LEN]).unwrap();
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a)],
+        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        w.reshape(&[LEN, 1]).unwrap();
+
+        let circuit = MatmulCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(w)],
             _marker: PhantomData,
         };
 
@@ -783,20 +1469,19 @@ mod sum {
 }
 
 #[cfg(test)]
-mod sum_col_overflow_double_col {
+mod matmul_col_overflow {
     use super::*;
 
-    const K: usize = 4;
-    const LEN: usize = 20;
-    const NUM_INNER_COLS: usize = 2;
+    const K: usize = 5;
+    const LEN: usize = 6;
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 1],
+    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
+    impl Circuit<F> for MatmulCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -806,10 +1491,9 @@ mod sum_col_overflow_double_col {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN);
-            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN);
-            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN);
-
+            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
@@ -822,12 +1506,14 @@ mod sum_col_overflow_double_col {
                 .assign_region(
                     || "",
                     |region| {
-                        let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 128, 2);
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
                         config
                             .layout(
                                 &mut region,
                                 &self.inputs.clone(),
-                                Box::new(PolyOp::Sum { axes: vec![0] }),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "ij,jk->ik".to_string(),
+                                }),
                             )
                             .map_err(|_| Error::Synthesis)
                     },
@@ -838,12 +1524,16 @@ mod sum_col_overflow_double_col {
     }
 
     #[test]
-    fn sumcircuit() {
+    fn matmulcircuit() {
         // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        a.reshape(&[LEN, LEN]).unwrap();
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a)],
+        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        w.reshape(&[LEN, 1]).unwrap();
+
+        let circuit = MatmulCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(w)],
             _marker: PhantomData,
         };
 
@@ -853,19 +1543,32 @@ mod sum_col_overflow_double_col {
 }
 
 #[cfg(test)]
-mod sum_col_overflow {
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod matmul_col_ultra_overflow_double_col {
+
+    use halo2_proofs::poly::kzg::{
+        commitment::KZGCommitmentScheme,
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    };
+    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+
     use super::*;
 
     const K: usize = 4;
-    const LEN: usize = 20;
+    const LEN: usize = 10;
+    const NUM_INNER_COLS: usize = 2;
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 1],
+    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
+    impl Circuit<F> for MatmulCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -875,10 +1578,9 @@ mod sum_col_overflow {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
-
+            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
+            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
+            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN * LEN * LEN);
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
@@ -891,12 +1593,14 @@ mod sum_col_overflow {
                 .assign_region(
                     || "",
                     |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 128, 2);
                         config
                             .layout(
                                 &mut region,
                                 &self.inputs.clone(),
-                                Box::new(PolyOp::Sum { axes: vec![0] }),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "ij,jk->ik".to_string(),
+                                }),
                             )
                             .map_err(|_| Error::Synthesis)
                     },
@@ -907,35 +1611,84 @@ mod sum_col_overflow {
     }
 
     #[test]
-    fn sumcircuit() {
+    #[ignore]
+    fn matmulcircuit() {
+        // get some logs fam
+        crate::logger::init_logger();
         // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        a.reshape(&[LEN, LEN]).unwrap();
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a)],
-            _marker: PhantomData,
+        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        w.reshape(&[LEN, 1]).unwrap();
+
+        let circuit = MatmulCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(w)],
+            _marker: PhantomData,
         };
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
+        let params = crate::pfsys::srs::gen_srs::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
+        >(K as u32);
+
+        let pk = crate::pfsys::create_keys::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
+            MatmulCircuit<F>,
+        >(&circuit, &params, true)
+        .unwrap();
+
+        let prover = crate::pfsys::create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            _,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            circuit.clone(),
+            vec![],
+            &params,
+            &pk,
+            // use safe mode to verify that the proof is correct
+            CheckMode::SAFE,
+            crate::Commitments::KZG,
+            crate::pfsys::TranscriptType::EVM,
+            None,
+            None,
+        );
+
+        assert!(prover.is_ok());
     }
 }
 
 #[cfg(test)]
-mod composition {
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod matmul_col_ultra_overflow {
+
+    use halo2_proofs::poly::kzg::{
+        commitment::KZGCommitmentScheme,
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    };
+    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
 
     use super::*;
 
-    const K: usize = 9;
-    const LEN: usize = 4;
+    const K: usize = 4;
+    const LEN: usize = 10;
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+    struct MatmulCircuit<F: PrimeField + TensorType + PartialOrd> {
         inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
+    impl Circuit<F> for MatmulCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -945,10 +1698,9 @@ mod composition {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
-
+            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN);
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
@@ -957,36 +1709,17 @@ mod composition {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            // lots of stacked dot products
             layouter
                 .assign_region(
                     || "",
                     |region| {
                         let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        let _ = config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "i,i->".to_string(),
-                                }),
-                            )
-                            .unwrap();
-                        let _ = config
-                            .layout(
-                                &mut region,
-                                &self.inputs.clone(),
-                                Box::new(PolyOp::Einsum {
-                                    equation: "i,i->".to_string(),
-                                }),
-                            )
-                            .unwrap();
                         config
                             .layout(
                                 &mut region,
                                 &self.inputs.clone(),
                                 Box::new(PolyOp::Einsum {
-                                    equation: "i,i->".to_string(),
+                                    equation: "ij,jk->ik".to_string(),
                                 }),
                             )
                             .map_err(|_| Error::Synthesis)
@@ -998,37 +1731,74 @@ mod composition {
     }
 
     #[test]
-    fn dotcircuit() {
+    #[ignore]
+    fn matmulcircuit() {
+        // get some logs fam
+        crate::logger::init_logger();
         // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let mut a = Tensor::from((0..LEN * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        a.reshape(&[LEN, LEN]).unwrap();
 
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let mut w = Tensor::from((0..LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        w.reshape(&[LEN, 1]).unwrap();
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
+        let circuit = MatmulCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(w)],
             _marker: PhantomData,
         };
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
+        let params = crate::pfsys::srs::gen_srs::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
+        >(K as u32);
+
+        let pk = crate::pfsys::create_keys::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
+            MatmulCircuit<F>,
+        >(&circuit, &params, true)
+        .unwrap();
+
+        let prover = crate::pfsys::create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            _,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            circuit.clone(),
+            vec![],
+            &params,
+            &pk,
+            // use safe mode to verify that the proof is correct
+            CheckMode::SAFE,
+            crate::Commitments::KZG,
+            crate::pfsys::TranscriptType::EVM,
+            None,
+            None,
+        );
+
+        assert!(prover.is_ok());
     }
 }
 
 #[cfg(test)]
-mod conv {
+mod dot {
+    use ops::poly::PolyOp;
 
     use super::*;
 
-    const K: usize = 22;
-    const LEN: usize = 100;
+    const K: usize = 4;
+    const LEN: usize = 4;
 
     #[derive(Clone)]
-    struct ConvCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: Vec<ValTensor<F>>,
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for ConvCircuit<F> {
+    impl Circuit<F> for MyCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -1038,12 +1808,9 @@ mod conv {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, (LEN + 1) * LEN);
-            let b = VarTensor::new_advice(cs, K, 1, (LEN + 1) * LEN);
-            let output = VarTensor::new_advice(cs, K, 1, (LEN + 1) * LEN);
-
-            // column for constants
-            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
 
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
@@ -1061,13 +1828,9 @@ mod conv {
                         config
                             .layout(
                                 &mut region,
-                                &self.inputs,
-                                Box::new(PolyOp::Conv {
-                                    padding: vec![(1, 1); 2],
-                                    stride: vec![2; 2],
-                                    group: 1,
-                                    data_format: DataFormat::default(),
-                                    kernel_format: KernelFormat::default(),
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "i,i->".to_string(),
                                 }),
                             )
                             .map_err(|_| Error::Synthesis)
@@ -1079,116 +1842,107 @@ mod conv {
     }
 
     #[test]
-    fn convcircuit() {
+    fn dotcircuit() {
         // parameters
-        let kernel_height = 2;
-        let kernel_width = 3;
-        let image_height = 5;
-        let image_width = 7;
-        let in_channels = 3;
-        let out_channels = 2;
-
-        let mut image =
-            Tensor::from((0..in_channels * image_height * image_width).map(|_| F::random(OsRng)));
-        image
-            .reshape(&[1, in_channels, image_height, image_width])
-            .unwrap();
-        image.set_visibility(&crate::graph::Visibility::Private);
-
-        let image = ValTensor::try_from(image).unwrap();
-
-        let mut kernels = Tensor::from(
-            (0..{ out_channels * in_channels * kernel_height * kernel_width })
-                .map(|_| F::random(OsRng)),
-        );
-        kernels
-            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
-            .unwrap();
-        kernels.set_visibility(&crate::graph::Visibility::Private);
-
-        let kernels = ValTensor::try_from(kernels).unwrap();
-        let mut bias = Tensor::from((0..{ out_channels }).map(|_| F::random(OsRng)));
-        bias.set_visibility(&crate::graph::Visibility::Private);
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let bias = ValTensor::try_from(bias).unwrap();
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let circuit = ConvCircuit::<F> {
-            inputs: [image, kernels, bias].to_vec(),
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
             _marker: PhantomData,
         };
 
         let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
     }
+}
 
-    #[test]
-    fn convcircuitnobias() {
-        // parameters
-        let kernel_height = 2;
-        let kernel_width = 2;
-        let image_height = 4;
-        let image_width = 5;
-        let in_channels = 3;
-        let out_channels = 2;
+#[cfg(test)]
+mod dot_wide_accumulation {
+    use super::*;
+    use crate::fieldutils::i64_to_felt;
 
-        let mut image =
-            Tensor::from((0..in_channels * image_height * image_width).map(|i| F::from(i as u64)));
-        image
-            .reshape(&[1, in_channels, image_height, image_width])
-            .unwrap();
-        image.set_visibility(&crate::graph::Visibility::Private);
+    const K: usize = 4;
+    const LEN: usize = 4;
+    // each product is 2.5e9, so even a single term overflows an i32 accumulator
+    const X: i64 = 50_000;
 
-        let mut kernels = Tensor::from(
-            (0..{ out_channels * in_channels * kernel_height * kernel_width })
-                .map(|i| F::from(i as u64)),
-        );
-        kernels
-            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
-            .unwrap();
-        kernels.set_visibility(&crate::graph::Visibility::Private);
+    #[derive(Clone)]
+    struct MyCircuit {
+        inputs: [ValTensor<F>; 2],
+    }
 
-        let image = ValTensor::try_from(image).unwrap();
-        let kernels = ValTensor::try_from(kernels).unwrap();
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
 
-        let circuit = ConvCircuit::<F> {
-            inputs: [image, kernels].to_vec(),
-            _marker: PhantomData,
-        };
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
-    }
-}
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
 
-#[cfg(test)]
-#[cfg(all(
-    feature = "ezkl",
-    not(all(target_arch = "wasm32", target_os = "unknown"))
-))]
-mod conv_col_ultra_overflow {
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
 
-    use halo2_proofs::poly::{
-        kzg::strategy::SingleStrategy,
-        kzg::{
-            commitment::KZGCommitmentScheme,
-            multiopen::{ProverSHPLONK, VerifierSHPLONK},
-        },
-    };
-    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        let output = layouts::dot(&config, &mut region, &self.inputs)
+                            .map_err(|_| Error::Synthesis)?;
+                        let expected = LEN as i64 * X * X;
+                        assert!(i32::try_from(X * X).is_err());
+                        assert_eq!(
+                            output.get_felt_evals().unwrap()[0],
+                            i64_to_felt::<F>(expected)
+                        );
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dotwideaccumulationcircuit() {
+        let input = || Tensor::from((0..LEN).map(|_| Value::known(i64_to_felt::<F>(X))));
+
+        let circuit = MyCircuit {
+            inputs: [ValTensor::from(input()), ValTensor::from(input())],
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
 
+#[cfg(test)]
+mod dot_col_overflow_triple_col {
     use super::*;
 
-    const K: usize = 6;
-    const LEN: usize = 10;
+    const K: usize = 4;
+    const LEN: usize = 50;
 
     #[derive(Clone)]
-    struct ConvCircuit<F: PrimeField + TensorType + PartialOrd> {
-        image: ValTensor<F>,
-        kernel: ValTensor<F>,
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for ConvCircuit<F> {
+    impl Circuit<F> for MyCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -1198,10 +1952,14 @@ mod conv_col_ultra_overflow {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * LEN);
-            let _constant = VarTensor::constant_cols(cs, K, LEN * LEN * LEN * LEN, false);
+            // used for constants in the padding
+            let _fixed = cs.fixed_column();
+            cs.enable_constant(_fixed);
+
+            let a = VarTensor::new_advice(cs, K, 3, LEN);
+            let b = VarTensor::new_advice(cs, K, 3, LEN);
+            let output = VarTensor::new_advice(cs, K, 3, LEN);
+
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
@@ -1214,17 +1972,13 @@ mod conv_col_ultra_overflow {
                 .assign_region(
                     || "",
                     |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        let mut region = RegionCtx::new(region, 0, 3, 128, 2);
                         config
                             .layout(
                                 &mut region,
-                                &[self.image.clone(), self.kernel.clone()],
-                                Box::new(PolyOp::Conv {
-                                    padding: vec![(1, 1); 2],
-                                    stride: vec![2; 2],
-                                    group: 1,
-                                    data_format: DataFormat::default(),
-                                    kernel_format: KernelFormat::default(),
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "i,i->".to_string(),
                                 }),
                             )
                             .map_err(|_| Error::Synthesis)
@@ -1236,104 +1990,36 @@ mod conv_col_ultra_overflow {
     }
 
     #[test]
-    #[ignore]
-    fn conv_circuit() {
+    fn dotcircuit() {
         // parameters
-        let kernel_height = 2;
-        let kernel_width = 2;
-        let image_height = LEN;
-        let image_width = LEN;
-        let in_channels = 3;
-        let out_channels = 2;
-
-        // get some logs fam
-        crate::logger::init_logger();
-        let mut image =
-            Tensor::from((0..in_channels * image_height * image_width).map(|i| F::from(i as u64)));
-        image
-            .reshape(&[1, in_channels, image_height, image_width])
-            .unwrap();
-        image.set_visibility(&crate::graph::Visibility::Private);
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let mut kernels = Tensor::from(
-            (0..{ out_channels * in_channels * kernel_height * kernel_width })
-                .map(|i| F::from(i as u64)),
-        );
-        kernels
-            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
-            .unwrap();
-        kernels.set_visibility(&crate::graph::Visibility::Private);
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let circuit = ConvCircuit::<F> {
-            image: ValTensor::try_from(image).unwrap(),
-            kernel: ValTensor::try_from(kernels).unwrap(),
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
             _marker: PhantomData,
         };
 
-        let params = crate::pfsys::srs::gen_srs::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
-        >(K as u32);
-
-        let pk = crate::pfsys::create_keys::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
-            ConvCircuit<F>,
-        >(&circuit, &params, true)
-        .unwrap();
-
-        let prover = crate::pfsys::create_proof_circuit::<
-            KZGCommitmentScheme<_>,
-            _,
-            ProverSHPLONK<_>,
-            VerifierSHPLONK<_>,
-            SingleStrategy<_>,
-            _,
-            EvmTranscript<_, _, _, _>,
-            EvmTranscript<_, _, _, _>,
-        >(
-            circuit.clone(),
-            vec![],
-            &params,
-            &pk,
-            // use safe mode to verify that the proof is correct
-            CheckMode::SAFE,
-            crate::Commitments::KZG,
-            crate::pfsys::TranscriptType::EVM,
-            None,
-            None,
-        );
-
-        assert!(prover.is_ok());
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
     }
 }
 
 #[cfg(test)]
-// not wasm 32 unknown
-#[cfg(all(
-    feature = "ezkl",
-    not(all(target_arch = "wasm32", target_os = "unknown"))
-))]
-mod conv_relu_col_ultra_overflow {
-
-    use halo2_proofs::poly::kzg::{
-        commitment::KZGCommitmentScheme,
-        multiopen::{ProverSHPLONK, VerifierSHPLONK},
-        strategy::SingleStrategy,
-    };
-    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
-
+mod dot_col_overflow {
     use super::*;
 
-    const K: usize = 8;
-    const LEN: usize = 15;
+    const K: usize = 4;
+    const LEN: usize = 50;
 
     #[derive(Clone)]
-    struct ConvCircuit<F: PrimeField + TensorType + PartialOrd> {
-        image: ValTensor<F>,
-        kernel: ValTensor<F>,
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for ConvCircuit<F> {
+    impl Circuit<F> for MyCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -1343,24 +2029,11 @@ mod conv_relu_col_ultra_overflow {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * 4);
-            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * 4);
-            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * 4);
-            let mut base_config =
-                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
-            // sets up a new relu table
-
-            base_config
-                .configure_range_check(cs, &a, &b, (-1, 1), K)
-                .unwrap();
-
-            base_config
-                .configure_range_check(cs, &a, &b, (0, 1), K)
-                .unwrap();
-
-            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
 
-            base_config.clone()
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
         fn synthesize(
@@ -1368,116 +2041,46 @@ mod conv_relu_col_ultra_overflow {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            config.layout_range_checks(&mut layouter).unwrap();
             layouter
                 .assign_region(
                     || "",
                     |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 2, 2);
-                        let output = config
-                            .layout(
-                                &mut region,
-                                &[self.image.clone(), self.kernel.clone()],
-                                Box::new(PolyOp::Conv {
-                                    padding: vec![(1, 1); 2],
-                                    stride: vec![2; 2],
-                                    group: 1,
-                                    data_format: DataFormat::default(),
-                                    kernel_format: KernelFormat::default(),
-                                }),
-                            )
-                            .map_err(|_| Error::Synthesis);
-                        let _output = config
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
                             .layout(
                                 &mut region,
-                                &[output.unwrap().unwrap()],
-                                Box::new(PolyOp::LeakyReLU {
-                                    slope: 0.0.into(),
-                                    scale: 1,
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "i,i->".to_string(),
                                 }),
                             )
-                            .unwrap();
-                        Ok(())
+                            .map_err(|_| Error::Synthesis)
                     },
                 )
                 .unwrap();
-
             Ok(())
         }
     }
 
     #[test]
-    #[ignore]
-    fn conv_relu_circuit() {
+    fn dotcircuit() {
         // parameters
-        let kernel_height = 2;
-        let kernel_width = 2;
-        let image_height = LEN;
-        let image_width = LEN;
-        let in_channels = 3;
-        let out_channels = 2;
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        // get some logs fam
-        crate::logger::init_logger();
-        let mut image =
-            Tensor::from((0..in_channels * image_height * image_width).map(|_| F::from(0)));
-        image
-            .reshape(&[1, in_channels, image_height, image_width])
-            .unwrap();
-        image.set_visibility(&crate::graph::Visibility::Private);
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let mut kernels = Tensor::from(
-            (0..{ out_channels * in_channels * kernel_height * kernel_width }).map(|_| F::from(0)),
-        );
-        kernels
-            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
-            .unwrap();
-        kernels.set_visibility(&crate::graph::Visibility::Private);
-
-        let circuit = ConvCircuit::<F> {
-            image: ValTensor::try_from(image).unwrap(),
-            kernel: ValTensor::try_from(kernels).unwrap(),
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
             _marker: PhantomData,
         };
 
-        let params = crate::pfsys::srs::gen_srs::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
-        >(K as u32);
-
-        let pk = crate::pfsys::create_keys::<
-            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
-            ConvCircuit<F>,
-        >(&circuit, &params, true)
-        .unwrap();
-
-        let prover = crate::pfsys::create_proof_circuit::<
-            KZGCommitmentScheme<_>,
-            _,
-            ProverSHPLONK<_>,
-            VerifierSHPLONK<_>,
-            SingleStrategy<_>,
-            _,
-            EvmTranscript<_, _, _, _>,
-            EvmTranscript<_, _, _, _>,
-        >(
-            circuit.clone(),
-            vec![],
-            &params,
-            &pk,
-            CheckMode::SAFE,
-            crate::Commitments::KZG,
-            crate::pfsys::TranscriptType::EVM,
-            // use safe mode to verify that the proof is correct
-            None,
-            None,
-        );
-
-        assert!(prover.is_ok());
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
     }
 }
 
 #[cfg(test)]
-mod add_w_shape_casting {
+mod sum {
     use super::*;
 
     const K: usize = 4;
@@ -1485,7 +2088,7 @@ mod add_w_shape_casting {
 
     #[derive(Clone)]
     struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
+        inputs: [ValTensor<F>; 1],
         _marker: PhantomData<F>,
     }
 
@@ -1517,7 +2120,11 @@ mod add_w_shape_casting {
                     |region| {
                         let mut region = RegionCtx::new(region, 0, 1, 128, 2);
                         config
-                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Add))
+                            .layout(
+                                &mut region,
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Sum { axes: vec![0] }),
+                            )
                             .map_err(|_| Error::Synthesis)
                     },
                 )
@@ -1527,14 +2134,12 @@ mod add_w_shape_casting {
     }
 
     #[test]
-    fn addcircuit() {
+    fn sumcircuit() {
         // parameters
         let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let b = Tensor::from((0..1).map(|i| Value::known(F::from(i + 1))));
-
         let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            inputs: [ValTensor::from(a)],
             _marker: PhantomData,
         };
 
@@ -1544,15 +2149,16 @@ mod add_w_shape_casting {
 }
 
 #[cfg(test)]
-mod add {
+mod sum_col_overflow_double_col {
     use super::*;
 
     const K: usize = 4;
-    const LEN: usize = 4;
+    const LEN: usize = 20;
+    const NUM_INNER_COLS: usize = 2;
 
     #[derive(Clone)]
     struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
+        inputs: [ValTensor<F>; 1],
         _marker: PhantomData<F>,
     }
 
@@ -1566,9 +2172,9 @@ mod add {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let a = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN);
+            let b = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN);
+            let output = VarTensor::new_advice(cs, K, NUM_INNER_COLS, LEN);
 
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
@@ -1582,9 +2188,13 @@ mod add {
                 .assign_region(
                     || "",
                     |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS, 128, 2);
                         config
-                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Add))
+                            .layout(
+                                &mut region,
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Sum { axes: vec![0] }),
+                            )
                             .map_err(|_| Error::Synthesis)
                     },
                 )
@@ -1594,14 +2204,12 @@ mod add {
     }
 
     #[test]
-    fn addcircuit() {
+    fn sumcircuit() {
         // parameters
         let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
-
         let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            inputs: [ValTensor::from(a)],
             _marker: PhantomData,
         };
 
@@ -1611,17 +2219,15 @@ mod add {
 }
 
 #[cfg(test)]
-mod dynamic_lookup {
+mod sum_col_overflow {
     use super::*;
 
-    const K: usize = 6;
-    const LEN: usize = 4;
-    const NUM_LOOP: usize = 5;
+    const K: usize = 4;
+    const LEN: usize = 20;
 
     #[derive(Clone)]
     struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        tables: [[ValTensor<F>; 2]; NUM_LOOP],
-        lookups: [[ValTensor<F>; 2]; NUM_LOOP],
+        inputs: [ValTensor<F>; 1],
         _marker: PhantomData<F>,
     }
 
@@ -1635,31 +2241,16 @@ mod dynamic_lookup {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 2, LEN);
-            let b = VarTensor::new_advice(cs, K, 2, LEN);
-            let c: VarTensor = VarTensor::new_advice(cs, K, 2, LEN);
-
-            let d = VarTensor::new_advice(cs, K, 1, LEN);
-            let e = VarTensor::new_advice(cs, K, 1, LEN);
-            let f: VarTensor = VarTensor::new_advice(cs, K, 1, LEN);
-
-            let _constant = VarTensor::constant_cols(cs, K, LEN * NUM_LOOP, false);
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
 
-            let mut config =
-                Self::Config::configure(cs, &[a.clone(), b.clone()], &c, CheckMode::SAFE);
-            config
-                .configure_dynamic_lookup(
-                    cs,
-                    &[a.clone(), b.clone(), c.clone()],
-                    &[d.clone(), e.clone(), f.clone()],
-                )
-                .unwrap();
-            config
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
         fn synthesize(
             &self,
-            config: Self::Config,
+            mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
             layouter
@@ -1667,22 +2258,13 @@ mod dynamic_lookup {
                     || "",
                     |region| {
                         let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        for i in 0..NUM_LOOP {
-                            layouts::dynamic_lookup(
-                                &config,
+                        config
+                            .layout(
                                 &mut region,
-                                &self.lookups[i],
-                                &self.tables[i],
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Sum { axes: vec![0] }),
                             )
-                            .map_err(|_| Error::Synthesis)?;
-                        }
-                        assert_eq!(
-                            region.dynamic_lookup_col_coord(),
-                            NUM_LOOP * self.tables[0][0].len()
-                        );
-                        assert_eq!(region.dynamic_lookup_index(), NUM_LOOP);
-
-                        Ok(())
+                            .map_err(|_| Error::Synthesis)
                     },
                 )
                 .unwrap();
@@ -1691,84 +2273,31 @@ mod dynamic_lookup {
     }
 
     #[test]
-    fn dynamiclookupcircuit() {
+    fn sumcircuit() {
         // parameters
-        let tables = (0..NUM_LOOP)
-            .map(|loop_idx| {
-                [
-                    ValTensor::from(Tensor::from(
-                        (0..LEN).map(|i| Value::known(F::from((i * loop_idx) as u64 + 1))),
-                    )),
-                    ValTensor::from(Tensor::from(
-                        (0..LEN).map(|i| Value::known(F::from((loop_idx * i * i) as u64 + 1))),
-                    )),
-                ]
-            })
-            .collect::<Vec<_>>();
-
-        let lookups = (0..NUM_LOOP)
-            .map(|loop_idx| {
-                [
-                    ValTensor::from(Tensor::from(
-                        (0..3).map(|i| Value::known(F::from((i * loop_idx) as u64 + 1))),
-                    )),
-                    ValTensor::from(Tensor::from(
-                        (0..3).map(|i| Value::known(F::from((loop_idx * i * i) as u64 + 1))),
-                    )),
-                ]
-            })
-            .collect::<Vec<_>>();
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
         let circuit = MyCircuit::<F> {
-            tables: tables.clone().try_into().unwrap(),
-            lookups: lookups.try_into().unwrap(),
+            inputs: [ValTensor::from(a)],
             _marker: PhantomData,
         };
 
         let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
-
-        let lookups = (0..NUM_LOOP)
-            .map(|loop_idx| {
-                let prev_idx = if loop_idx == 0 {
-                    NUM_LOOP - 1
-                } else {
-                    loop_idx - 1
-                };
-                [
-                    ValTensor::from(Tensor::from(
-                        (0..3).map(|i| Value::known(F::from((i * prev_idx) as u64 + 1))),
-                    )),
-                    ValTensor::from(Tensor::from(
-                        (0..3).map(|i| Value::known(F::from((prev_idx * i * i) as u64 + 1))),
-                    )),
-                ]
-            })
-            .collect::<Vec<_>>();
-
-        let circuit = MyCircuit::<F> {
-            tables: tables.try_into().unwrap(),
-            lookups: lookups.try_into().unwrap(),
-            _marker: PhantomData,
-        };
-
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        assert!(prover.verify().is_err());
     }
 }
 
 #[cfg(test)]
-mod shuffle {
+mod composition {
+
     use super::*;
 
-    const K: usize = 6;
+    const K: usize = 9;
     const LEN: usize = 4;
-    const NUM_LOOP: usize = 5;
 
     #[derive(Clone)]
     struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [[ValTensor<F>; 1]; NUM_LOOP],
-        references: [[ValTensor<F>; 1]; NUM_LOOP],
+        inputs: [ValTensor<F>; 2],
         _marker: PhantomData<F>,
     }
 
@@ -1782,128 +2311,90 @@ mod shuffle {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 2, LEN);
-            let b = VarTensor::new_advice(cs, K, 2, LEN);
-            let c: VarTensor = VarTensor::new_advice(cs, K, 2, LEN);
-
-            let d = VarTensor::new_advice(cs, K, 1, LEN);
-            let e = VarTensor::new_advice(cs, K, 1, LEN);
-            let f: VarTensor = VarTensor::new_advice(cs, K, 1, LEN);
-
-            let _constant = VarTensor::constant_cols(cs, K, LEN * NUM_LOOP, false);
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
 
-            let mut config =
-                Self::Config::configure(cs, &[a.clone(), b.clone()], &c, CheckMode::SAFE);
-            config
-                .configure_shuffles(
-                    cs,
-                    &[a.clone(), b.clone(), c.clone()],
-                    &[d.clone(), e.clone(), f.clone()],
-                )
-                .unwrap();
-            config
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
 
         fn synthesize(
             &self,
-            config: Self::Config,
+            mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
+            // lots of stacked dot products
             layouter
                 .assign_region(
                     || "",
                     |region| {
                         let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        for i in 0..NUM_LOOP {
-                            layouts::shuffles(
-                                &config,
+                        let _ = config
+                            .layout(
                                 &mut region,
-                                &self.inputs[i],
-                                &self.references[i],
-                                layouts::SortCollisionMode::Unsorted,
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "i,i->".to_string(),
+                                }),
                             )
-                            .map_err(|_| Error::Synthesis)?;
-                        }
-                        assert_eq!(
-                            region.shuffle_col_coord(),
-                            NUM_LOOP * self.references[0][0].len()
-                        );
-                        assert_eq!(region.shuffle_index(), NUM_LOOP);
-
-                        Ok(())
-                    },
-                )
+                            .unwrap();
+                        let _ = config
+                            .layout(
+                                &mut region,
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "i,i->".to_string(),
+                                }),
+                            )
+                            .unwrap();
+                        config
+                            .layout(
+                                &mut region,
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Einsum {
+                                    equation: "i,i->".to_string(),
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
                 .unwrap();
             Ok(())
         }
     }
 
     #[test]
-    fn shufflecircuit() {
+    fn dotcircuit() {
         // parameters
-        let references = (0..NUM_LOOP)
-            .map(|loop_idx| {
-                [ValTensor::from(Tensor::from((0..LEN).map(|i| {
-                    Value::known(F::from((i * loop_idx) as u64 + 1))
-                })))]
-            })
-            .collect::<Vec<_>>();
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
-        let inputs = (0..NUM_LOOP)
-            .map(|loop_idx| {
-                [ValTensor::from(Tensor::from((0..LEN).rev().map(|i| {
-                    Value::known(F::from((i * loop_idx) as u64 + 1))
-                })))]
-            })
-            .collect::<Vec<_>>();
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
 
         let circuit = MyCircuit::<F> {
-            references: references.clone().try_into().unwrap(),
-            inputs: inputs.try_into().unwrap(),
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
             _marker: PhantomData,
         };
 
         let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
-
-        let inputs = (0..NUM_LOOP)
-            .map(|loop_idx| {
-                let prev_idx = if loop_idx == 0 {
-                    NUM_LOOP - 1
-                } else {
-                    loop_idx - 1
-                };
-                [ValTensor::from(Tensor::from((0..LEN).rev().map(|i| {
-                    Value::known(F::from((i * prev_idx) as u64 + 1))
-                })))]
-            })
-            .collect::<Vec<_>>();
-
-        let circuit = MyCircuit::<F> {
-            references: references.try_into().unwrap(),
-            inputs: inputs.try_into().unwrap(),
-            _marker: PhantomData,
-        };
-
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        assert!(prover.verify().is_err());
     }
 }
 
 #[cfg(test)]
-mod add_with_overflow {
+mod conv {
+
     use super::*;
 
-    const K: usize = 4;
-    const LEN: usize = 50;
+    const K: usize = 22;
+    const LEN: usize = 100;
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
+    struct ConvCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: Vec<ValTensor<F>>,
         _marker: PhantomData<F>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
+    impl Circuit<F> for ConvCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -1913,9 +2404,12 @@ mod add_with_overflow {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let a = VarTensor::new_advice(cs, K, 1, (LEN + 1) * LEN);
+            let b = VarTensor::new_advice(cs, K, 1, (LEN + 1) * LEN);
+            let output = VarTensor::new_advice(cs, K, 1, (LEN + 1) * LEN);
+
+            // column for constants
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
 
             Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
         }
@@ -1931,7 +2425,18 @@ mod add_with_overflow {
                     |region| {
                         let mut region = RegionCtx::new(region, 0, 1, 128, 2);
                         config
-                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Add))
+                            .layout(
+                                &mut region,
+                                &self.inputs,
+                                Box::new(PolyOp::Conv {
+                                    padding: vec![(1, 1); 2],
+                                    stride: vec![2; 2],
+                                    dilation: vec![1; 2],
+                                    group: 1,
+                                    data_format: DataFormat::default(),
+                                    kernel_format: KernelFormat::default(),
+                                }),
+                            )
                             .map_err(|_| Error::Synthesis)
                     },
                 )
@@ -1941,174 +2446,4451 @@ mod add_with_overflow {
     }
 
     #[test]
-    fn addcircuit() {
+    fn convcircuit() {
         // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let kernel_height = 2;
+        let kernel_width = 3;
+        let image_height = 5;
+        let image_width = 7;
+        let in_channels = 3;
+        let out_channels = 2;
 
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let mut image =
+            Tensor::from((0..in_channels * image_height * image_width).map(|_| F::random(OsRng)));
+        image
+            .reshape(&[1, in_channels, image_height, image_width])
+            .unwrap();
+        image.set_visibility(&crate::graph::Visibility::Private);
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
+        let image = ValTensor::try_from(image).unwrap();
+
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * in_channels * kernel_height * kernel_width })
+                .map(|_| F::random(OsRng)),
+        );
+        kernels
+            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
+            .unwrap();
+        kernels.set_visibility(&crate::graph::Visibility::Private);
+
+        let kernels = ValTensor::try_from(kernels).unwrap();
+        let mut bias = Tensor::from((0..{ out_channels }).map(|_| F::random(OsRng)));
+        bias.set_visibility(&crate::graph::Visibility::Private);
+
+        let bias = ValTensor::try_from(bias).unwrap();
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [image, kernels, bias].to_vec(),
             _marker: PhantomData,
         };
 
         let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
     }
-}
 
-#[cfg(test)]
-mod add_with_overflow_and_poseidon {
-    use std::collections::HashMap;
+    #[test]
+    fn convcircuitnobias() {
+        // parameters
+        let kernel_height = 2;
+        let kernel_width = 2;
+        let image_height = 4;
+        let image_width = 5;
+        let in_channels = 3;
+        let out_channels = 2;
 
-    use halo2curves::bn256::Fr;
+        let mut image =
+            Tensor::from((0..in_channels * image_height * image_width).map(|i| F::from(i as u64)));
+        image
+            .reshape(&[1, in_channels, image_height, image_width])
+            .unwrap();
+        image.set_visibility(&crate::graph::Visibility::Private);
 
-    use crate::circuit::modules::{
-        poseidon::{
-            spec::{PoseidonSpec, POSEIDON_RATE, POSEIDON_WIDTH},
-            PoseidonChip, PoseidonConfig,
-        },
-        Module, ModulePlanner,
-    };
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * in_channels * kernel_height * kernel_width })
+                .map(|i| F::from(i as u64)),
+        );
+        kernels
+            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
+            .unwrap();
+        kernels.set_visibility(&crate::graph::Visibility::Private);
+
+        let image = ValTensor::try_from(image).unwrap();
+        let kernels = ValTensor::try_from(kernels).unwrap();
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [image, kernels].to_vec(),
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
 
+#[cfg(test)]
+mod conv_bias {
     use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
 
-    const K: usize = 15;
-    const LEN: usize = 50;
-    const WIDTH: usize = POSEIDON_WIDTH;
-    const RATE: usize = POSEIDON_RATE;
+    const K: usize = 9;
+    const LEN: usize = 128;
+    const IN_CHANNELS: usize = 2;
+    const OUT_CHANNELS: usize = 2;
+    const IMAGE: usize = 3;
+    const KERNEL: usize = 2;
+    const OUT: usize = IMAGE - KERNEL + 1;
 
-    #[derive(Debug, Clone)]
-    struct MyCircuitConfig {
-        base: BaseConfig<Fr>,
-        poseidon: PoseidonConfig<WIDTH, RATE>,
+    #[derive(Clone)]
+    struct ConvConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
     }
 
     #[derive(Clone)]
-    struct MyCircuit {
-        inputs: [ValTensor<Fr>; 2],
+    struct ConvCircuit {
+        inputs: Vec<ValTensor<F>>,
     }
 
-    impl Circuit<Fr> for MyCircuit {
-        type Config = MyCircuitConfig;
-        type FloorPlanner = ModulePlanner;
+    impl Circuit<F> for ConvCircuit {
+        type Config = ConvConfig;
+        type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
 
         fn without_witnesses(&self) -> Self {
             self.clone()
         }
 
-        fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
 
-            let base = BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE);
-            VarTensor::constant_cols(cs, K, 2, false);
+            let base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
 
-            let poseidon = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::configure(cs, ());
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
 
-            MyCircuitConfig { base, poseidon }
+            ConvConfig {
+                base_config,
+                public_output,
+            }
         }
 
         fn synthesize(
             &self,
             mut config: Self::Config,
-            mut layouter: impl Layouter<Fr>,
+            mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            let poseidon_chip: PoseidonChip<PoseidonSpec, WIDTH, RATE> =
-                PoseidonChip::new(config.poseidon.clone());
+            let output = layouter.assign_region(
+                || "conv",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    Ok(config
+                        .base_config
+                        .layout(
+                            &mut region,
+                            &self.inputs,
+                            Box::new(PolyOp::Conv {
+                                padding: vec![(0, 0); 2],
+                                stride: vec![1; 2],
+                                dilation: vec![1; 2],
+                                group: 1,
+                                data_format: DataFormat::NCHW,
+                                kernel_format: KernelFormat::OIHW,
+                            }),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
+                },
+            )?;
 
-            let assigned_inputs_a =
-                poseidon_chip.layout(&mut layouter, &self.inputs[0..1], 0, &mut HashMap::new())?;
-            let assigned_inputs_b =
-                poseidon_chip.layout(&mut layouter, &self.inputs[1..2], 1, &mut HashMap::new())?;
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+            Ok(())
+        }
+    }
 
-            layouter.assign_region(|| "_new_module", |_| Ok(()))?;
+    fn image() -> Vec<IntegerRep> {
+        (0..(IN_CHANNELS * IMAGE * IMAGE) as IntegerRep)
+            .map(|i| i - 7)
+            .collect()
+    }
 
-            let inputs = vec![assigned_inputs_a, assigned_inputs_b];
+    fn kernel() -> Vec<IntegerRep> {
+        (0..(OUT_CHANNELS * IN_CHANNELS * KERNEL * KERNEL) as IntegerRep)
+            .map(|i| 3 - i % 5)
+            .collect()
+    }
 
-            layouter.assign_region(
-                || "model",
-                |region| {
-                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                    config
-                        .base
-                        .layout(&mut region, &inputs, Box::new(PolyOp::Add))
-                        .map_err(|_| Error::Synthesis)
+    const BIAS: [IntegerRep; OUT_CHANNELS] = [10, -25];
+
+    // out[o][y][x] = bias[o] + sum_{c, ky, kx} image[c][y + ky][x + kx] * kernel[o][c][ky][kx]
+    fn reference(bias: &[IntegerRep; OUT_CHANNELS]) -> Vec<F> {
+        let (image, kernel) = (image(), kernel());
+        let mut out = vec![];
+        for o in 0..OUT_CHANNELS {
+            for y in 0..OUT {
+                for x in 0..OUT {
+                    let mut acc = bias[o];
+                    for c in 0..IN_CHANNELS {
+                        for ky in 0..KERNEL {
+                            for kx in 0..KERNEL {
+                                acc += image[(c * IMAGE + y + ky) * IMAGE + x + kx]
+                                    * kernel[((o * IN_CHANNELS + c) * KERNEL + ky) * KERNEL + kx];
+                            }
+                        }
+                    }
+                    out.push(integer_rep_to_felt(acc));
+                }
+            }
+        }
+        out
+    }
+
+    fn circuit(with_bias: bool) -> ConvCircuit {
+        let tensor = |values: &[IntegerRep], dims: &[usize]| {
+            ValTensor::from_integer_rep_tensor(Tensor::new(Some(values), dims).unwrap())
+        };
+        let mut inputs = vec![
+            tensor(&image(), &[1, IN_CHANNELS, IMAGE, IMAGE]),
+            tensor(&kernel(), &[OUT_CHANNELS, IN_CHANNELS, KERNEL, KERNEL]),
+        ];
+        if with_bias {
+            inputs.push(tensor(&BIAS, &[OUT_CHANNELS]));
+        }
+        ConvCircuit { inputs }
+    }
+
+    #[test]
+    fn convbiascircuit() {
+        let prover = MockProver::run(K as u32, &circuit(true), vec![reference(&BIAS)]).unwrap();
+        prover.assert_satisfied();
+
+        // the bias is optional
+        let prover = MockProver::run(K as u32, &circuit(false), vec![reference(&[0, 0])]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn convbiasmissing() {
+        // the unbiased reference doesn't satisfy the biased circuit
+        let prover = MockProver::run(K as u32, &circuit(true), vec![reference(&[0, 0])]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod conv_1d {
+
+    use super::*;
+    use crate::circuit::ops::layer::{Conv1dLayer, Layer};
+
+    const K: usize = 10;
+    const LEN: usize = 12;
+
+    #[derive(Clone)]
+    struct MyConfig {
+        base_config: BaseConfig<F>,
+        instance: ValTensor<F>,
+    }
+
+    #[derive(Clone)]
+    struct Conv1dCircuit {
+        signal: ValTensor<F>,
+        layer: Conv1dLayer<F>,
+    }
+
+    impl Circuit<F> for Conv1dCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            // column for constants
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+            let instance = ValTensor::new_instance(cs, vec![vec![1, 4]], 0);
+
+            MyConfig {
+                base_config: BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let output = self
+                        .layer
+                        .layout(&mut config.base_config, &mut region, &self.signal)
+                        .map_err(|_| Error::Synthesis)?;
+                    layouts::enforce_equality(
+                        &config.base_config,
+                        &mut region,
+                        &[output, config.instance.clone()],
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn conv1dcircuit() {
+        // an unbatched [C, L] = [1, 6] sequence
+        let mut signal = Tensor::from((1..=6).map(|i| F::from(i as u64)));
+        signal.reshape(&[1, 6]).unwrap();
+        signal.set_visibility(&crate::graph::Visibility::Private);
+
+        // a [COUT, CIN, K] = [1, 1, 3] kernel
+        let mut kernel = Tensor::from((1..=3).map(|i| F::from(i as u64)));
+        kernel.reshape(&[1, 1, 3]).unwrap();
+        kernel.set_visibility(&crate::graph::Visibility::Private);
+
+        let circuit = Conv1dCircuit {
+            signal: ValTensor::try_from(signal).unwrap(),
+            layer: Conv1dLayer {
+                kernel: ValTensor::try_from(kernel).unwrap(),
+                bias: None,
+                padding: (0, 0),
+                stride: 1,
+                dilation: 1,
+            },
+        };
+        assert_eq!(circuit.layer.output_shape(&[1, 6]).unwrap(), vec![1, 4]);
+
+        // each output is the dot product of a window of three with [1, 2, 3]
+        let expected = [14u64, 20, 26, 32].map(F::from).to_vec();
+        let prover = MockProver::run(K as u32, &circuit, vec![expected.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        let mut tampered = expected;
+        tampered[3] += F::ONE;
+        let prover = MockProver::run(K as u32, &circuit, vec![tampered]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod conv_col_ultra_overflow {
+
+    use halo2_proofs::poly::{
+        kzg::strategy::SingleStrategy,
+        kzg::{
+            commitment::KZGCommitmentScheme,
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        },
+    };
+    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 10;
+
+    #[derive(Clone)]
+    struct ConvCircuit<F: PrimeField + TensorType + PartialOrd> {
+        image: ValTensor<F>,
+        kernel: ValTensor<F>,
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for ConvCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * LEN);
+            let _constant = VarTensor::constant_cols(cs, K, LEN * LEN * LEN * LEN, false);
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
+                            .layout(
+                                &mut region,
+                                &[self.image.clone(), self.kernel.clone()],
+                                Box::new(PolyOp::Conv {
+                                    padding: vec![(1, 1); 2],
+                                    stride: vec![2; 2],
+                                    dilation: vec![1; 2],
+                                    group: 1,
+                                    data_format: DataFormat::default(),
+                                    kernel_format: KernelFormat::default(),
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn conv_circuit() {
+        // parameters
+        let kernel_height = 2;
+        let kernel_width = 2;
+        let image_height = LEN;
+        let image_width = LEN;
+        let in_channels = 3;
+        let out_channels = 2;
+
+        // get some logs fam
+        crate::logger::init_logger();
+        let mut image =
+            Tensor::from((0..in_channels * image_height * image_width).map(|i| F::from(i as u64)));
+        image
+            .reshape(&[1, in_channels, image_height, image_width])
+            .unwrap();
+        image.set_visibility(&crate::graph::Visibility::Private);
+
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * in_channels * kernel_height * kernel_width })
+                .map(|i| F::from(i as u64)),
+        );
+        kernels
+            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
+            .unwrap();
+        kernels.set_visibility(&crate::graph::Visibility::Private);
+
+        let circuit = ConvCircuit::<F> {
+            image: ValTensor::try_from(image).unwrap(),
+            kernel: ValTensor::try_from(kernels).unwrap(),
+            _marker: PhantomData,
+        };
+
+        let params = crate::pfsys::srs::gen_srs::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
+        >(K as u32);
+
+        let pk = crate::pfsys::create_keys::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
+            ConvCircuit<F>,
+        >(&circuit, &params, true)
+        .unwrap();
+
+        let prover = crate::pfsys::create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            _,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            circuit.clone(),
+            vec![],
+            &params,
+            &pk,
+            // use safe mode to verify that the proof is correct
+            CheckMode::SAFE,
+            crate::Commitments::KZG,
+            crate::pfsys::TranscriptType::EVM,
+            None,
+            None,
+        );
+
+        assert!(prover.is_ok());
+    }
+}
+
+#[cfg(test)]
+// not wasm 32 unknown
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod conv_relu_col_ultra_overflow {
+
+    use halo2_proofs::poly::kzg::{
+        commitment::KZGCommitmentScheme,
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    };
+    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+
+    use super::*;
+
+    const K: usize = 8;
+    const LEN: usize = 15;
+
+    #[derive(Clone)]
+    struct ConvCircuit<F: PrimeField + TensorType + PartialOrd> {
+        image: ValTensor<F>,
+        kernel: ValTensor<F>,
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for ConvCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * 4);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * 4);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN * LEN * 4);
+            let mut base_config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            // sets up a new relu table
+
+            base_config
+                .configure_range_check(cs, &a, &b, (-1, 1), K)
+                .unwrap();
+
+            base_config
+                .configure_range_check(cs, &a, &b, (0, 1), K)
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            base_config.clone()
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 2, 2);
+                        let output = config
+                            .layout(
+                                &mut region,
+                                &[self.image.clone(), self.kernel.clone()],
+                                Box::new(PolyOp::Conv {
+                                    padding: vec![(1, 1); 2],
+                                    stride: vec![2; 2],
+                                    dilation: vec![1; 2],
+                                    group: 1,
+                                    data_format: DataFormat::default(),
+                                    kernel_format: KernelFormat::default(),
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis);
+                        let _output = config
+                            .layout(
+                                &mut region,
+                                &[output.unwrap().unwrap()],
+                                Box::new(PolyOp::LeakyReLU {
+                                    slope: 0.0.into(),
+                                    scale: 1,
+                                }),
+                            )
+                            .unwrap();
+                        Ok(())
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn conv_relu_circuit() {
+        // parameters
+        let kernel_height = 2;
+        let kernel_width = 2;
+        let image_height = LEN;
+        let image_width = LEN;
+        let in_channels = 3;
+        let out_channels = 2;
+
+        // get some logs fam
+        crate::logger::init_logger();
+        let mut image =
+            Tensor::from((0..in_channels * image_height * image_width).map(|_| F::from(0)));
+        image
+            .reshape(&[1, in_channels, image_height, image_width])
+            .unwrap();
+        image.set_visibility(&crate::graph::Visibility::Private);
+
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * in_channels * kernel_height * kernel_width }).map(|_| F::from(0)),
+        );
+        kernels
+            .reshape(&[out_channels, in_channels, kernel_height, kernel_width])
+            .unwrap();
+        kernels.set_visibility(&crate::graph::Visibility::Private);
+
+        let circuit = ConvCircuit::<F> {
+            image: ValTensor::try_from(image).unwrap(),
+            kernel: ValTensor::try_from(kernels).unwrap(),
+            _marker: PhantomData,
+        };
+
+        let params = crate::pfsys::srs::gen_srs::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<_>,
+        >(K as u32);
+
+        let pk = crate::pfsys::create_keys::<
+            halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme<halo2curves::bn256::Bn256>,
+            ConvCircuit<F>,
+        >(&circuit, &params, true)
+        .unwrap();
+
+        let prover = crate::pfsys::create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            _,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            circuit.clone(),
+            vec![],
+            &params,
+            &pk,
+            CheckMode::SAFE,
+            crate::Commitments::KZG,
+            crate::pfsys::TranscriptType::EVM,
+            // use safe mode to verify that the proof is correct
+            None,
+            None,
+        );
+
+        assert!(prover.is_ok());
+    }
+}
+
+#[cfg(test)]
+mod add_w_shape_casting {
+    use super::*;
+
+    const K: usize = 4;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
+                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Add))
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn addcircuit() {
+        // parameters
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let b = Tensor::from((0..1).map(|i| Value::known(F::from(i + 1))));
+
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod add {
+    use super::*;
+
+    const K: usize = 4;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
+                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Add))
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn addcircuit() {
+        // parameters
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod dynamic_lookup {
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+    const NUM_LOOP: usize = 5;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        tables: [[ValTensor<F>; 2]; NUM_LOOP],
+        lookups: [[ValTensor<F>; 2]; NUM_LOOP],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 2, LEN);
+            let b = VarTensor::new_advice(cs, K, 2, LEN);
+            let c: VarTensor = VarTensor::new_advice(cs, K, 2, LEN);
+
+            let d = VarTensor::new_advice(cs, K, 1, LEN);
+            let e = VarTensor::new_advice(cs, K, 1, LEN);
+            let f: VarTensor = VarTensor::new_advice(cs, K, 1, LEN);
+
+            let _constant = VarTensor::constant_cols(cs, K, LEN * NUM_LOOP, false);
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &c, CheckMode::SAFE);
+            config
+                .configure_dynamic_lookup(
+                    cs,
+                    &[a.clone(), b.clone(), c.clone()],
+                    &[d.clone(), e.clone(), f.clone()],
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        for i in 0..NUM_LOOP {
+                            layouts::dynamic_lookup(
+                                &config,
+                                &mut region,
+                                &self.lookups[i],
+                                &self.tables[i],
+                            )
+                            .map_err(|_| Error::Synthesis)?;
+                        }
+                        assert_eq!(
+                            region.dynamic_lookup_col_coord(),
+                            NUM_LOOP * self.tables[0][0].len()
+                        );
+                        assert_eq!(region.dynamic_lookup_index(), NUM_LOOP);
+
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dynamiclookupcircuit() {
+        // parameters
+        let tables = (0..NUM_LOOP)
+            .map(|loop_idx| {
+                [
+                    ValTensor::from(Tensor::from(
+                        (0..LEN).map(|i| Value::known(F::from((i * loop_idx) as u64 + 1))),
+                    )),
+                    ValTensor::from(Tensor::from(
+                        (0..LEN).map(|i| Value::known(F::from((loop_idx * i * i) as u64 + 1))),
+                    )),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let lookups = (0..NUM_LOOP)
+            .map(|loop_idx| {
+                [
+                    ValTensor::from(Tensor::from(
+                        (0..3).map(|i| Value::known(F::from((i * loop_idx) as u64 + 1))),
+                    )),
+                    ValTensor::from(Tensor::from(
+                        (0..3).map(|i| Value::known(F::from((loop_idx * i * i) as u64 + 1))),
+                    )),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let circuit = MyCircuit::<F> {
+            tables: tables.clone().try_into().unwrap(),
+            lookups: lookups.try_into().unwrap(),
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let lookups = (0..NUM_LOOP)
+            .map(|loop_idx| {
+                let prev_idx = if loop_idx == 0 {
+                    NUM_LOOP - 1
+                } else {
+                    loop_idx - 1
+                };
+                [
+                    ValTensor::from(Tensor::from(
+                        (0..3).map(|i| Value::known(F::from((i * prev_idx) as u64 + 1))),
+                    )),
+                    ValTensor::from(Tensor::from(
+                        (0..3).map(|i| Value::known(F::from((prev_idx * i * i) as u64 + 1))),
+                    )),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let circuit = MyCircuit::<F> {
+            tables: tables.try_into().unwrap(),
+            lookups: lookups.try_into().unwrap(),
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod shuffle {
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+    const NUM_LOOP: usize = 5;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [[ValTensor<F>; 1]; NUM_LOOP],
+        references: [[ValTensor<F>; 1]; NUM_LOOP],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 2, LEN);
+            let b = VarTensor::new_advice(cs, K, 2, LEN);
+            let c: VarTensor = VarTensor::new_advice(cs, K, 2, LEN);
+
+            let d = VarTensor::new_advice(cs, K, 1, LEN);
+            let e = VarTensor::new_advice(cs, K, 1, LEN);
+            let f: VarTensor = VarTensor::new_advice(cs, K, 1, LEN);
+
+            let _constant = VarTensor::constant_cols(cs, K, LEN * NUM_LOOP, false);
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &c, CheckMode::SAFE);
+            config
+                .configure_shuffles(
+                    cs,
+                    &[a.clone(), b.clone(), c.clone()],
+                    &[d.clone(), e.clone(), f.clone()],
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        for i in 0..NUM_LOOP {
+                            layouts::shuffles(
+                                &config,
+                                &mut region,
+                                &self.inputs[i],
+                                &self.references[i],
+                                layouts::SortCollisionMode::Unsorted,
+                            )
+                            .map_err(|_| Error::Synthesis)?;
+                        }
+                        assert_eq!(
+                            region.shuffle_col_coord(),
+                            NUM_LOOP * self.references[0][0].len()
+                        );
+                        assert_eq!(region.shuffle_index(), NUM_LOOP);
+
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn shufflecircuit() {
+        // parameters
+        let references = (0..NUM_LOOP)
+            .map(|loop_idx| {
+                [ValTensor::from(Tensor::from((0..LEN).map(|i| {
+                    Value::known(F::from((i * loop_idx) as u64 + 1))
+                })))]
+            })
+            .collect::<Vec<_>>();
+
+        let inputs = (0..NUM_LOOP)
+            .map(|loop_idx| {
+                [ValTensor::from(Tensor::from((0..LEN).rev().map(|i| {
+                    Value::known(F::from((i * loop_idx) as u64 + 1))
+                })))]
+            })
+            .collect::<Vec<_>>();
+
+        let circuit = MyCircuit::<F> {
+            references: references.clone().try_into().unwrap(),
+            inputs: inputs.try_into().unwrap(),
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let inputs = (0..NUM_LOOP)
+            .map(|loop_idx| {
+                let prev_idx = if loop_idx == 0 {
+                    NUM_LOOP - 1
+                } else {
+                    loop_idx - 1
+                };
+                [ValTensor::from(Tensor::from((0..LEN).rev().map(|i| {
+                    Value::known(F::from((i * prev_idx) as u64 + 1))
+                })))]
+            })
+            .collect::<Vec<_>>();
+
+        let circuit = MyCircuit::<F> {
+            references: references.try_into().unwrap(),
+            inputs: inputs.try_into().unwrap(),
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod topk_indices {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 10;
+    const LEN: usize = 5;
+    const TOP: usize = 2;
+
+    #[derive(Clone)]
+    struct TopKConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    /// Selects the top 2 of a vector, with the selected indices as the public outputs.
+    #[derive(Clone)]
+    struct TopKCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for TopKCircuit {
+        type Config = TopKConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..6)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+                .unwrap();
+            base_config
+                .configure_shuffles(
+                    cs,
+                    advices[0..3].try_into().unwrap(),
+                    advices[3..6].try_into().unwrap(),
+                )
+                .unwrap();
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            TopKConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
+                .unwrap();
+            let (_, indices) = layouter.assign_region(
+                || "topk",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    layouts::topk_indices(
+                        &config.base_config,
+                        &mut region,
+                        &[self.input.clone()],
+                        TOP,
+                    )
+                    .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            for (i, value) in indices.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("indices should be assigned"),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prove(input: [IntegerRep; LEN], indices: [IntegerRep; TOP]) -> MockProver<F> {
+        let circuit = TopKCircuit {
+            input: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&input), &[LEN]).unwrap()),
+        };
+        let instances = vec![indices.iter().map(|i| integer_rep_to_felt(*i)).collect()];
+        MockProver::run(K as u32, &circuit, instances).unwrap()
+    }
+
+    #[test]
+    fn topkindicescircuit() {
+        prove([5, -2, 8, 2, 7], [2, 4]).assert_satisfied();
+        prove([-3, -1, -4, -1, -5], [1, 3]).assert_satisfied();
+    }
+
+    #[test]
+    fn topkindicesties() {
+        // equal values are selected smallest index first, so only one claim is valid
+        prove([1, 9, 4, 9, 9], [1, 3]).assert_satisfied();
+        assert!(prove([1, 9, 4, 9, 9], [3, 1]).verify().is_err());
+        assert!(prove([1, 9, 4, 9, 9], [1, 4]).verify().is_err());
+    }
+
+    #[test]
+    fn topkindiceswrongclaim() {
+        assert!(prove([5, -2, 8, 2, 7], [2, 0]).verify().is_err());
+        assert!(prove([5, -2, 8, 2, 7], [4, 2]).verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod margin {
+    use super::*;
+    use crate::fieldutils::IntegerRep;
+
+    const K: usize = 10;
+    const LEN: usize = 5;
+    // the gap above the margin is range checked to 10 bits
+    const RANGE: (IntegerRep, IntegerRep) = (0, 1023);
+
+    #[derive(Clone)]
+    struct MarginCircuit {
+        logits: ValTensor<F>,
+        margin: IntegerRep,
+    }
+
+    impl Circuit<F> for MarginCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..6)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            let mut config = Self::Config::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], RANGE, K)
+                .unwrap();
+            config
+                .configure_shuffles(
+                    cs,
+                    advices[0..3].try_into().unwrap(),
+                    advices[3..6].try_into().unwrap(),
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "margin",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    layouts::margin(
+                        &config,
+                        &mut region,
+                        &[self.logits.clone()],
+                        self.margin,
+                        &RANGE,
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn satisfied(logits: [IntegerRep; LEN], margin: IntegerRep) -> bool {
+        let circuit = MarginCircuit {
+            logits: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&logits), &[LEN]).unwrap()),
+            margin,
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+            .map(|prover| prover.verify().is_ok())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn margincircuit() {
+        // 9 beats the runner-up 4 by 5
+        assert!(satisfied([4, -3, 9, 0, 2], 3));
+        assert!(satisfied([4, -3, 9, 0, 2], 5));
+    }
+
+    #[test]
+    fn margintoolarge() {
+        assert!(!satisfied([4, -3, 9, 0, 2], 6));
+        // a tied top class has no margin at all
+        assert!(satisfied([7, 1, 7, 0, 2], 0));
+        assert!(!satisfied([7, 1, 7, 0, 2], 1));
+    }
+}
+
+#[cfg(test)]
+mod monotonic {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+
+    const K: usize = 8;
+    const LEN: usize = 5;
+    const RANGE: (IntegerRep, IntegerRep) = (0, 255);
+
+    #[derive(Clone)]
+    struct MyConfig {
+        base_config: BaseConfig<F>,
+        instance: ValTensor<F>,
+    }
+
+    // a model output constrained to be non-decreasing and exposed as instances
+    #[derive(Clone)]
+    struct MonotonicCircuit {
+        output: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MonotonicCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let instance = ValTensor::new_instance(cs, vec![vec![LEN]], 0);
+
+            let mut base_config =
+                BaseConfig::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            base_config
+                .configure_range_check(cs, &a, &b, RANGE, K)
+                .unwrap();
+            MyConfig {
+                base_config,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
+                .unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let output = layouts::monotonic(
+                        &config.base_config,
+                        &mut region,
+                        &[self.output.clone()],
+                        &RANGE,
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    layouts::enforce_equality(
+                        &config.base_config,
+                        &mut region,
+                        &[output, config.instance.clone()],
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn satisfied(output: [IntegerRep; LEN]) -> bool {
+        let circuit = MonotonicCircuit {
+            output: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&output), &[LEN]).unwrap()),
+        };
+        let instances = vec![output.iter().map(|v| integer_rep_to_felt(*v)).collect()];
+        MockProver::run(K as u32, &circuit, instances)
+            .map(|prover| prover.verify().is_ok())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn monotoniccircuit() {
+        // a cumulative distribution at scale 64
+        assert!(satisfied([0, 8, 30, 30, 64]));
+        assert!(satisfied([-5, -5, -5, -5, -5]));
+    }
+
+    #[test]
+    fn monotonicdecreasing() {
+        assert!(!satisfied([0, 8, 30, 29, 64]));
+        assert!(!satisfied([64, 30, 30, 8, 0]));
+    }
+}
+
+#[cfg(test)]
+mod knn {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::graph::Visibility;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 10;
+    const LEN: usize = 16;
+    const NEIGHBOURS: usize = 3;
+    const CLASSES: usize = 2;
+    // two clusters, around (0, 0) labelled 0 and around (6, 6) labelled 1
+    const POINTS: [IntegerRep; 12] = [0, 1, 1, 0, -1, -1, 6, 5, 5, 6, 7, 7];
+    const LABELS: [IntegerRep; 6] = [0, 0, 0, 1, 1, 1];
+
+    #[derive(Clone)]
+    struct KnnConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    /// Classifies a private query against a dataset fixed by the circuit, with the predicted
+    /// label as the public output.
+    #[derive(Clone)]
+    struct KnnCircuit {
+        query: ValTensor<F>,
+        points: ValTensor<F>,
+        labels: ValTensor<F>,
+        neighbours: usize,
+    }
+
+    impl Circuit<F> for KnnCircuit {
+        type Config = KnnConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..6)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+                .unwrap();
+            base_config
+                .configure_dynamic_lookup(
+                    cs,
+                    advices[0..3].try_into().unwrap(),
+                    advices[3..6].try_into().unwrap(),
+                )
+                .unwrap();
+            base_config
+                .configure_shuffles(
+                    cs,
+                    advices[0..3].try_into().unwrap(),
+                    advices[3..6].try_into().unwrap(),
+                )
+                .unwrap();
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            KnnConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
+                .unwrap();
+            let predicted = layouter.assign_region(
+                || "knn",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    layouts::knn(
+                        &config.base_config,
+                        &mut region,
+                        &[self.query.clone(), self.points.clone(), self.labels.clone()],
+                        self.neighbours,
+                        CLASSES,
+                    )
+                    .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            match predicted.get_inner_tensor().unwrap()[0].clone() {
+                ValType::PrevAssigned(v) => {
+                    layouter.constrain_instance(v.cell(), config.public_output, 0)
+                }
+                _ => panic!("the prediction should be assigned"),
+            }
+        }
+    }
+
+    fn prove(query: [IntegerRep; 2], label: IntegerRep) -> MockProver<F> {
+        prove_with(&LABELS, NEIGHBOURS, query, label)
+    }
+
+    fn prove_with(
+        labels: &[IntegerRep; 6],
+        neighbours: usize,
+        query: [IntegerRep; 2],
+        label: IntegerRep,
+    ) -> MockProver<F> {
+        let fixed = |values: &[IntegerRep], dims: &[usize]| {
+            let mut t: Tensor<F> = Tensor::new(
+                Some(
+                    &values
+                        .iter()
+                        .map(|v| integer_rep_to_felt(*v))
+                        .collect::<Vec<_>>(),
+                ),
+                dims,
+            )
+            .unwrap();
+            t.set_visibility(&Visibility::Fixed);
+            ValTensor::try_from(t).unwrap()
+        };
+        let circuit = KnnCircuit {
+            query: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&query), &[2]).unwrap()),
+            points: fixed(&POINTS, &[6, 2]),
+            labels: fixed(labels, &[6]),
+            neighbours,
+        };
+        MockProver::run(K as u32, &circuit, vec![vec![integer_rep_to_felt(label)]]).unwrap()
+    }
+
+    #[test]
+    fn knncircuit() {
+        prove([1, 1], 0).assert_satisfied();
+        prove([5, 5], 1).assert_satisfied();
+        // two of the three nearest points are in the second cluster
+        prove([3, 4], 1).assert_satisfied();
+    }
+
+    #[test]
+    fn knnwronglabel() {
+        assert!(prove([1, 1], 1).verify().is_err());
+        assert!(prove([5, 5], 0).verify().is_err());
+    }
+
+    #[test]
+    fn knntie() {
+        // the two nearest points to the origin are labelled 1 and 0, and the tie goes to 0
+        let labels = [1, 0, 0, 1, 1, 1];
+        prove_with(&labels, 2, [0, 0], 0).assert_satisfied();
+        assert!(prove_with(&labels, 2, [0, 0], 1).verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod tampered_output {
+    use super::*;
+    use crate::circuit::ops::layer::{Layer, LayerChain};
+    use ops::base::BaseOp;
+
+    const K: usize = 5;
+    const LEN: usize = 4;
+
+    /// Lays out `values[0] op values[1]` like [layouts::pairwise], but when `tamper` is set the
+    /// assigned output is one more than the true result.
+    fn layer(
+        config: &BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        values: &[ValTensor<F>; 2],
+        op: BaseOp,
+        tamper: bool,
+    ) -> Result<ValTensor<F>, CircuitError> {
+        if !tamper {
+            return layouts::pairwise(config, region, values, op);
+        }
+
+        let (lhs, rhs) = (values[0].get_felt_evals()?, values[1].get_felt_evals()?);
+        let tampered = match op {
+            BaseOp::Add => (lhs + rhs)?,
+            BaseOp::Mult => (lhs * rhs)?,
+            _ => return Err(CircuitError::UnsupportedOp),
+        }
+        .map(|x| Value::known(x + F::ONE));
+
+        region.assign(&config.custom_gates.inputs[0], &values[0])?;
+        region.assign(&config.custom_gates.inputs[1], &values[1])?;
+        let output = region.assign(&config.custom_gates.output, &tampered.into())?;
+        for i in 0..output.len() {
+            let (x, y, z) =
+                config.custom_gates.inputs[0].cartesian_coord(region.linear_coord() + i);
+            let selector = config.custom_gates.selectors.get(&(op.clone(), x, y));
+            region.enable(selector, z)?;
+        }
+        region.increment(output.len());
+
+        Ok(output)
+    }
+
+    /// Applies `op` with a fixed right hand side, named after the op, e.g. `add`.
+    struct PairwiseLayer {
+        op: BaseOp,
+        rhs: ValTensor<F>,
+        tamper: bool,
+    }
+
+    impl Layer<F> for PairwiseLayer {
+        fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>, CircuitError> {
+            Ok(input_shape.to_vec())
+        }
+
+        fn name(&self) -> String {
+            self.op.as_str().to_lowercase()
+        }
+
+        fn layout(
+            &self,
+            config: &mut BaseConfig<F>,
+            region: &mut RegionCtx<F>,
+            input: &ValTensor<F>,
+        ) -> Result<ValTensor<F>, CircuitError> {
+            let values = [input.clone(), self.rhs.clone()];
+            layer(config, region, &values, self.op.clone(), self.tamper)
+        }
+    }
+
+    /// Computes `(x + y) * y` as a two layer chain, each layer in its own region, optionally
+    /// writing an off-by-one result into the output cells of one of them.
+    #[derive(Clone)]
+    struct TwoLayerCircuit {
+        inputs: [ValTensor<F>; 2],
+        tampered_layer: Option<usize>,
+        second_label: Option<&'static str>,
+    }
+
+    impl TwoLayerCircuit {
+        fn chain(&self) -> LayerChain<F> {
+            let layer = |i: usize, op: BaseOp| PairwiseLayer {
+                op,
+                rhs: self.inputs[1].clone(),
+                tamper: self.tampered_layer == Some(i),
+            };
+            let chain = LayerChain::new().with(layer(0, BaseOp::Add));
+            match self.second_label {
+                Some(label) => chain.with_label(label, layer(1, BaseOp::Mult)),
+                None => chain.with(layer(1, BaseOp::Mult)),
+            }
+        }
+    }
+
+    impl Circuit<F> for TwoLayerCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            self.chain()
+                .layout_in_regions(&mut config, &mut layouter, &self.inputs[0], 128, 2)
+                .map_err(|_| Error::Synthesis)?;
+            Ok(())
+        }
+    }
+
+    fn circuit(tampered_layer: Option<usize>) -> TwoLayerCircuit {
+        let x = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let y = Tensor::from((0..LEN).map(|i| Value::known(F::from(2 * i as u64 + 3))));
+        TwoLayerCircuit {
+            inputs: [ValTensor::from(x), ValTensor::from(y)],
+            tampered_layer,
+            second_label: None,
+        }
+    }
+
+    #[test]
+    fn untamperedcircuit() {
+        let circuit = circuit(None);
+        assert_eq!(circuit.chain().region_names(), ["add_l0", "mult_l1"]);
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn tamperedfirstlayer() {
+        let prover = MockProver::run(K as u32, &circuit(Some(0)), vec![]).unwrap();
+        assert_constraint_failure(&prover, BaseOp::Add.as_str());
+        assert_failure_in_region(&prover, "add_l0");
+    }
+
+    #[test]
+    fn tamperedsecondlayer() {
+        let prover = MockProver::run(K as u32, &circuit(Some(1)), vec![]).unwrap();
+        assert_constraint_failure(&prover, BaseOp::Mult.as_str());
+        assert_failure_in_region(&prover, "mult_l1");
+    }
+
+    #[test]
+    fn tamperedlabelledlayer() {
+        let circuit = TwoLayerCircuit {
+            second_label: Some("hidden"),
+            ..circuit(Some(1))
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_constraint_failure(&prover, BaseOp::Mult.as_str());
+        assert_failure_in_region(&prover, "hidden");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a failure in gate ADD")]
+    fn tamperedwronggate() {
+        let prover = MockProver::run(K as u32, &circuit(Some(1)), vec![]).unwrap();
+        assert_constraint_failure(&prover, BaseOp::Add.as_str());
+    }
+}
+
+#[cfg(test)]
+mod output_subset {
+    use super::*;
+    use ops::base::BaseOp;
+
+    const K: usize = 4;
+    const LEN: usize = 4;
+    const DISCLOSED: [usize; 2] = [1, 3];
+
+    #[derive(Clone)]
+    struct MyConfig {
+        base_config: BaseConfig<F>,
+        instance: ValTensor<F>,
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        inputs: [ValTensor<F>; 2],
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let instance = ValTensor::new_instance(cs, vec![vec![DISCLOSED.len()]], 0);
+
+            MyConfig {
+                base_config: BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        let output = layouts::pairwise(
+                            &config.base_config,
+                            &mut region,
+                            &self.inputs,
+                            BaseOp::Add,
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                        layouts::output_subset(
+                            &config.base_config,
+                            &mut region,
+                            &[output, config.instance.clone()],
+                            &DISCLOSED,
+                            false,
+                        )
+                        .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    fn circuit() -> MyCircuit {
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(10 * (i as u64 + 1)))));
+        MyCircuit {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+        }
+    }
+
+    // a + b = [11, 22, 33, 44]
+    fn outputs() -> Vec<F> {
+        (0..LEN).map(|i| F::from(11 * (i as u64 + 1))).collect()
+    }
+
+    #[test]
+    fn outputsubsetcircuit() {
+        let outputs = outputs();
+        let public = DISCLOSED.iter().map(|i| outputs[*i]).collect::<Vec<_>>();
+        // the private outputs never enter the instance vector
+        assert!(!public.contains(&outputs[0]) && !public.contains(&outputs[2]));
+
+        let prover = MockProver::run(K as u32, &circuit(), vec![public]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn outputsubsetwrongdisclosed() {
+        let outputs = outputs();
+        let public = vec![outputs[1], outputs[3] + F::ONE];
+
+        let prover = MockProver::run(K as u32, &circuit(), vec![public]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn outputsubsetallpublic() {
+        // exposing every output shifts the disclosed values out of place
+        let prover = MockProver::run(K as u32, &circuit(), vec![outputs()]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod shaped_instance {
+    use super::*;
+    use crate::fieldutils::IntegerRep;
+    use crate::tensor::ValType;
+    use itertools::Itertools;
+
+    const K: usize = 5;
+    const SHAPE: [usize; 3] = [2, 2, 2];
+    const LEN: usize = 8;
+    // the length of a tensor ahead of the output in the same instance column
+    const OFFSET: usize = 3;
+
+    #[derive(Clone)]
+    struct ShapedConfig {
+        base_config: BaseConfig<F>,
+        instance: ValTensor<F>,
+    }
+
+    // x + x, with each output cell constrained to the instance row of its coordinate
+    #[derive(Clone)]
+    struct ShapedCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for ShapedCircuit {
+        type Config = ShapedConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            let base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            let instance = ValTensor::new_instance(cs, vec![vec![OFFSET], SHAPE.to_vec()], 0);
+
+            ShapedConfig {
+                base_config,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let output = layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    Ok(config
+                        .base_config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone(), self.input.clone()],
+                            Box::new(PolyOp::Add),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
+                },
+            )?;
+
+            let column = match &config.instance {
+                ValTensor::Instance { inner, .. } => *inner,
+                _ => panic!("expected an instance tensor"),
+            };
+            let output = output.get_inner_tensor().unwrap();
+            for coord in SHAPE.iter().map(|d| 0..*d).multi_cartesian_product() {
+                let row = config.instance.instance_row(1, &coord).unwrap();
+                match output.get(&coord) {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), column, row)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    // x[c][h][w] = 4c + 2h + w + 1
+    fn circuit() -> ShapedCircuit {
+        let input = Tensor::new(Some(&(1..=LEN as IntegerRep).collect::<Vec<_>>()), &SHAPE);
+        ShapedCircuit {
+            input: ValTensor::from_integer_rep_tensor(input.unwrap()),
+        }
+    }
+
+    #[test]
+    fn instancerowmapping() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let instance = ShapedCircuit::configure(&mut cs).instance;
+        assert_eq!(instance.instance_row(0, &[2]).unwrap(), 2);
+        assert_eq!(instance.instance_row(1, &[0, 0, 0]).unwrap(), OFFSET);
+        assert_eq!(instance.instance_row(1, &[0, 1, 0]).unwrap(), OFFSET + 2);
+        assert_eq!(instance.instance_row(1, &[1, 0, 1]).unwrap(), OFFSET + 5);
+        assert_eq!(instance.instance_row(1, &[1, 1, 1]).unwrap(), OFFSET + 7);
+        assert!(instance.instance_row(1, &[2, 0, 0]).is_err());
+        assert!(instance.instance_row(1, &[0, 0]).is_err());
+        assert!(instance.instance_row(2, &[0]).is_err());
+    }
+
+    #[test]
+    fn shapedinstancecircuit() {
+        // the output in row-major order, after the leading tensor
+        let mut instances = vec![F::ZERO; OFFSET];
+        instances.extend((1..=LEN as u64).map(|x| F::from(2 * x)));
+        let prover = MockProver::run(K as u32, &circuit(), vec![instances]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn shapedinstancetransposed() {
+        // the same values in column-major order
+        let mut instances = vec![F::ZERO; OFFSET];
+        instances.extend(
+            SHAPE
+                .iter()
+                .rev()
+                .map(|d| 0..*d)
+                .multi_cartesian_product()
+                .map(|whc| F::from(2 * (4 * whc[2] + 2 * whc[1] + whc[0] + 1) as u64)),
+        );
+        let prover = MockProver::run(K as u32, &circuit(), vec![instances]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod add_with_overflow {
+    use super::*;
+
+    const K: usize = 4;
+    const LEN: usize = 50;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
+                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Add))
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn addcircuit() {
+        // parameters
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod add_with_overflow_and_poseidon {
+    use std::collections::HashMap;
+
+    use halo2curves::bn256::Fr;
+
+    use crate::circuit::modules::{
+        poseidon::{
+            spec::{PoseidonSpec, POSEIDON_RATE, POSEIDON_WIDTH},
+            PoseidonChip, PoseidonConfig,
+        },
+        Module, ModulePlanner,
+    };
+
+    use super::*;
+
+    const K: usize = 15;
+    const LEN: usize = 50;
+    const WIDTH: usize = POSEIDON_WIDTH;
+    const RATE: usize = POSEIDON_RATE;
+
+    #[derive(Debug, Clone)]
+    struct MyCircuitConfig {
+        base: BaseConfig<Fr>,
+        poseidon: PoseidonConfig<WIDTH, RATE>,
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        inputs: [ValTensor<Fr>; 2],
+    }
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = MyCircuitConfig;
+        type FloorPlanner = ModulePlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            let base = BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE);
+            VarTensor::constant_cols(cs, K, 2, false);
+
+            let poseidon = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::configure(cs, ());
+
+            MyCircuitConfig { base, poseidon }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let poseidon_chip: PoseidonChip<PoseidonSpec, WIDTH, RATE> =
+                PoseidonChip::new(config.poseidon.clone());
+
+            let assigned_inputs_a =
+                poseidon_chip.layout(&mut layouter, &self.inputs[0..1], 0, &mut HashMap::new())?;
+            let assigned_inputs_b =
+                poseidon_chip.layout(&mut layouter, &self.inputs[1..2], 1, &mut HashMap::new())?;
+
+            layouter.assign_region(|| "_new_module", |_| Ok(()))?;
+
+            let inputs = vec![assigned_inputs_a, assigned_inputs_b];
+
+            layouter.assign_region(
+                || "model",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    config
+                        .base
+                        .layout(&mut region, &inputs, Box::new(PolyOp::Add))
+                        .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn addcircuit() {
+        let a = (0..LEN)
+            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
+            .collect::<Vec<_>>();
+        let b = (0..LEN)
+            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
+            .collect::<Vec<_>>();
+        let commitment_a = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(a.clone()).unwrap()[0][0];
+
+        let commitment_b = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(b.clone()).unwrap()[0][0];
+
+        // parameters
+        let a = Tensor::from(a.into_iter().map(Value::known));
+        let b = Tensor::from(b.into_iter().map(Value::known));
+        let circuit = MyCircuit {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+        };
+
+        let prover =
+            MockProver::run(K as u32, &circuit, vec![vec![commitment_a, commitment_b]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn addcircuit_bad_hashes() {
+        let a = (0..LEN)
+            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
+            .collect::<Vec<_>>();
+        let b = (0..LEN)
+            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
+            .collect::<Vec<_>>();
+        let commitment_a =
+            PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(a.clone()).unwrap()[0][0] + Fr::one();
+
+        let commitment_b =
+            PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(b.clone()).unwrap()[0][0] + Fr::one();
+
+        // parameters
+        let a = Tensor::from(a.into_iter().map(Value::known));
+        let b = Tensor::from(b.into_iter().map(Value::known));
+        let circuit = MyCircuit {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+        };
+
+        let prover =
+            MockProver::run(K as u32, &circuit, vec![vec![commitment_a, commitment_b]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod committed_output {
+    use super::*;
+    use crate::circuit::modules::polycommit::{PolyCommitChip, PolyCommitConfig};
+    use crate::circuit::modules::Module;
+    use crate::pfsys::{
+        check_proof_commitments_polycommit, create_keys, create_proof_circuit, srs::gen_srs,
+        swap_proof_commitments_polycommit, verify_proof_circuit, PfsysError, Snark, TranscriptType,
+    };
+    use halo2_proofs::plonk::ProvingKey;
+    use halo2_proofs::poly::commitment::Params;
+    use halo2_proofs::poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    };
+    use halo2curves::bn256::{Bn256, G1Affine};
+    use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+    use std::collections::HashMap;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct CommittedConfig {
+        // configured first, so its commitment is the first point in the proof
+        polycommit: PolyCommitConfig,
+        base_config: BaseConfig<F>,
+    }
+
+    // a + b, with the sum copied into a committed column and no instance columns at all
+    #[derive(Clone)]
+    struct CommittedCircuit {
+        inputs: [ValTensor<F>; 2],
+    }
+
+    impl Circuit<F> for CommittedCircuit {
+        type Config = CommittedConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let polycommit = PolyCommitChip::configure(cs, (K, LEN));
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            CommittedConfig {
+                polycommit,
+                base_config: BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let output = layouter.assign_region(
+                || "add",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    config
+                        .base_config
+                        .layout(&mut region, &self.inputs, Box::new(PolyOp::Add))
+                        .map_err(|_| Error::Synthesis)?
+                        .ok_or(Error::Synthesis)
+                },
+            )?;
+
+            PolyCommitChip::new(config.polycommit)
+                .layout(&mut layouter, &[output], 0, &mut HashMap::new())
+                .map_err(|_| Error::Synthesis)?;
+            Ok(())
+        }
+    }
+
+    // the circuit for `a = [1, 2, 3, 4]` and `b = 10 * a + offset`
+    fn circuit(offset: u64) -> CommittedCircuit {
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(10 * (i as u64 + 1) + offset))));
+        CommittedCircuit {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+        }
+    }
+
+    struct Setup {
+        params: ParamsKZG<Bn256>,
+        pk: ProvingKey<G1Affine>,
+    }
+
+    impl Setup {
+        fn new() -> Self {
+            let params = gen_srs::<KZGCommitmentScheme<Bn256>>(K as u32);
+            let pk = create_keys::<KZGCommitmentScheme<Bn256>, CommittedCircuit>(
+                &circuit(0),
+                &params,
+                true,
+            )
+            .unwrap();
+            Self { params, pk }
+        }
+
+        fn prove(&self, circuit: CommittedCircuit) -> Snark<F, G1Affine> {
+            create_proof_circuit::<
+                KZGCommitmentScheme<_>,
+                _,
+                ProverSHPLONK<_>,
+                VerifierSHPLONK<_>,
+                SingleStrategy<_>,
+                _,
+                EvmTranscript<_, _, _, _>,
+                EvmTranscript<_, _, _, _>,
+            >(
+                circuit,
+                vec![],
+                &self.params,
+                &self.pk,
+                CheckMode::SAFE,
+                crate::Commitments::KZG,
+                TranscriptType::EVM,
+                None,
+                None,
+            )
+            .unwrap()
+        }
+
+        fn commit(&self, outputs: &[u64]) -> Vec<G1Affine> {
+            let num_unusable_rows = (self.pk.get_vk().cs().blinding_factors() + 1) as u32;
+            PolyCommitChip::commit::<KZGCommitmentScheme<Bn256>>(
+                outputs.iter().map(|x| F::from(*x)).collect(),
+                num_unusable_rows,
+                &self.params,
+            )
+        }
+
+        fn verify(&self, snark: &Snark<F, G1Affine>) -> bool {
+            verify_proof_circuit::<
+                VerifierSHPLONK<'_, Bn256>,
+                KZGCommitmentScheme<Bn256>,
+                _,
+                _,
+                EvmTranscript<_, _, _, _>,
+            >(
+                snark,
+                &self.params,
+                self.pk.get_vk(),
+                SingleStrategy::new(&self.params),
+                self.params.n(),
+            )
+            .is_ok()
+        }
+    }
+
+    #[test]
+    fn committedoutputcircuit() {
+        let setup = Setup::new();
+        assert_eq!(setup.pk.get_vk().cs().num_instance_columns(), 0);
+        let snark = setup.prove(circuit(0));
+
+        let verify_against = |outputs: &[u64]| {
+            let snark = swap_proof_commitments_polycommit(&snark, &setup.commit(outputs)).unwrap();
+            setup.verify(&snark)
+        };
+
+        // the proof only holds against a commitment to the outputs it computed
+        assert!(verify_against(&[11, 22, 33, 44]));
+        assert!(!verify_against(&[11, 22, 33, 45]));
+    }
+
+    #[test]
+    fn committedoutputchecked() {
+        let setup = Setup::new();
+        let published = setup.commit(&[11, 22, 33, 44]);
+
+        // an honest proof opens with the published commitment
+        let snark = setup.prove(circuit(0));
+        assert!(setup.verify(&snark));
+        assert!(check_proof_commitments_polycommit(&snark, &published).is_ok());
+
+        // a valid proof of other outputs doesn't
+        let other = setup.prove(circuit(1));
+        assert!(setup.verify(&other));
+        assert!(matches!(
+            check_proof_commitments_polycommit(&other, &published),
+            Err(PfsysError::CommitmentMismatch)
+        ));
+
+        // and splicing the published commitment into it passes the check but breaks the proof
+        let tampered = swap_proof_commitments_polycommit(&other, &published).unwrap();
+        assert!(check_proof_commitments_polycommit(&tampered, &published).is_ok());
+        assert!(!setup.verify(&tampered));
+    }
+}
+
+#[cfg(test)]
+mod sub {
+    use super::*;
+
+    const K: usize = 4;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
+                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Sub))
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn subcircuit() {
+        // parameters
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod sub_assigned {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 5;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct SubConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    // out = a - b, where a is assigned by an earlier layout and b mixes witnessed values and
+    // constants
+    #[derive(Clone)]
+    struct SubCircuit {
+        a: ValTensor<F>,
+        b: ValTensor<F>,
+    }
+
+    impl SubCircuit {
+        fn new((a, b): &([IntegerRep; LEN], [IntegerRep; LEN])) -> Self {
+            let b = b
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    let x = integer_rep_to_felt::<F>(*x);
+                    if i % 2 == 0 {
+                        ValType::Value(Value::known(x))
+                    } else {
+                        ValType::Constant(x)
+                    }
+                })
+                .collect::<Tensor<_>>();
+            SubCircuit {
+                a: ValTensor::from_integer_rep_tensor(Tensor::new(Some(a), &[LEN]).unwrap()),
+                b: b.into(),
+            }
+        }
+    }
+
+    impl Circuit<F> for SubCircuit {
+        type Config = SubConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            SubConfig {
+                base_config: BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let output = layouter.assign_region(
+                || "sub",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let a = config
+                        .base_config
+                        .layout(
+                            &mut region,
+                            &[self.a.clone()],
+                            Box::new(PolyOp::Identity { out_scale: None }),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    config
+                        .base_config
+                        .layout(&mut region, &[a, self.b.clone()], Box::new(PolyOp::Sub))
+                        .map_err(|_| Error::Synthesis)?
+                        .ok_or(Error::Synthesis)
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) | ValType::AssignedConstant(v, _) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    fn cases() -> Vec<(([IntegerRep; LEN], [IntegerRep; LEN]), Vec<F>)> {
+        [
+            ([5, 3, 0, -2], [1, 4, -3, -2]),
+            ([0, 0, 0, 0], [7, -7, 1, -1]),
+            ([100, -100, 42, 1], [0, 0, 0, 0]),
+        ]
+        .into_iter()
+        .map(|(a, b)| {
+            let diff = a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| integer_rep_to_felt(a - b))
+                .collect();
+            ((a, b), diff)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn subassignedcircuit() {
+        run_cases(K as u32, SubCircuit::new, &cases());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 of 3 cases failed")]
+    fn subassignedwrongsign() {
+        let mut cases = cases();
+        // b - a instead of a - b
+        cases[0].1 = cases[0].1.iter().map(|x| -*x).collect();
+        run_cases(K as u32, SubCircuit::new, &cases);
+    }
+}
+
+#[cfg(test)]
+mod mult {
+    use super::*;
+
+    const K: usize = 4;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
+                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Mult))
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn multcircuit() {
+        // parameters
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod pow {
+    use super::*;
+
+    const K: usize = 8;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 1],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        config
+                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Pow(5)))
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn powcircuit() {
+        // parameters
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod matmul_relu {
+    use super::*;
+
+    const K: usize = 18;
+    const LEN: usize = 32;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    // A columnar ReLu MLP
+    #[derive(Clone)]
+    struct MyConfig<F: PrimeField + TensorType + PartialOrd> {
+        base_config: BaseConfig<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = MyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            let mut base_config =
+                BaseConfig::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+
+            base_config
+                .configure_range_check(cs, &a, &b, (-1, 1), K)
+                .unwrap();
+
+            base_config
+                .configure_range_check(cs, &a, &b, (0, 1023), K)
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            MyConfig { base_config }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
+                .unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    let op = PolyOp::Einsum {
+                        equation: "ij,jk->ik".to_string(),
+                    };
+                    let output = config
+                        .base_config
+                        .layout(&mut region, &self.inputs, Box::new(op))
+                        .unwrap();
+                    let _output = config
+                        .base_config
+                        .layout(
+                            &mut region,
+                            &[output.unwrap()],
+                            Box::new(PolyOp::LeakyReLU {
+                                slope: 0.0.into(),
+                                scale: 1,
+                            }),
+                        )
+                        .unwrap();
+                    Ok(())
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn matmulrelucircuit() {
+        // parameters
+        let mut a = Tensor::from((0..LEN * LEN).map(|_| Value::known(F::from(1))));
+        a.reshape(&[LEN, LEN]).unwrap();
+
+        // parameters
+        let mut b = Tensor::from((0..LEN).map(|_| Value::known(F::from(1))));
+        b.reshape(&[LEN, 1]).unwrap();
+
+        let circuit = MyCircuit {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod mlp_cases {
+    use super::*;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 12;
+    const LEN: usize = 4;
+
+    const L0_KERNEL: [i128; LEN * LEN] = [10, 0, 0, -1, 0, 10, 1, 0, 0, 1, 10, 0, 1, 0, 0, 10];
+    const L0_BIAS: [i128; LEN] = [0, 0, 0, 1];
+    const L2_KERNEL: [i128; LEN * LEN] = [0, 3, 10, -1, 0, 10, 1, 0, 0, 1, 0, 12, 1, -2, 32, 0];
+    const L2_BIAS: [i128; LEN] = [0, 0, 0, 1];
+
+    // relu(l2 . relu(l0 . x + b0) + b2)
+    #[derive(Clone)]
+    struct MLPCircuit {
+        input: ValTensor<F>,
+        l0_params: [ValTensor<F>; 2],
+        l2_params: [ValTensor<F>; 2],
+    }
+
+    #[derive(Clone)]
+    struct MLPConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    impl MLPCircuit {
+        fn new(input: &[i128; LEN]) -> Self {
+            let tensor = |values: &[i128], dims: &[usize]| {
+                ValTensor::from_integer_rep_tensor(Tensor::new(Some(values), dims).unwrap())
+            };
+            MLPCircuit {
+                input: tensor(input, &[LEN, 1]),
+                l0_params: [tensor(&L0_KERNEL, &[LEN, LEN]), tensor(&L0_BIAS, &[LEN, 1])],
+                l2_params: [tensor(&L2_KERNEL, &[LEN, LEN]), tensor(&L2_BIAS, &[LEN, 1])],
+            }
+        }
+    }
+
+    impl Circuit<F> for MLPCircuit {
+        type Config = MLPConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN * LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * LEN);
+
+            let mut base_config =
+                BaseConfig::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+
+            base_config
+                .configure_range_check(cs, &a, &b, (-1, 1), K)
+                .unwrap();
+
+            base_config
+                .configure_range_check(cs, &a, &b, (0, 1023), K)
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            MLPConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
+                .unwrap();
+            let output = layouter.assign_region(
+                || "mlp",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    let mut x = self.input.clone();
+                    for [kernel, bias] in [&self.l0_params, &self.l2_params] {
+                        x = config
+                            .base_config
+                            .layout(
+                                &mut region,
+                                &[kernel.clone(), x],
+                                Box::new(PolyOp::Einsum {
+                                    equation: "ij,jk->ik".to_string(),
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis)?
+                            .unwrap();
+                        x = config
+                            .base_config
+                            .layout(&mut region, &[x, bias.clone()], Box::new(PolyOp::Add))
+                            .map_err(|_| Error::Synthesis)?
+                            .unwrap();
+                        x = config
+                            .base_config
+                            .layout(
+                                &mut region,
+                                &[x],
+                                Box::new(PolyOp::LeakyReLU {
+                                    slope: 0.0.into(),
+                                    scale: 1,
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis)?
+                            .unwrap();
+                    }
+                    Ok(x)
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) | ValType::AssignedConstant(v, _) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    /// The same MLP evaluated over plain integers.
+    fn forward(input: &[i128; LEN]) -> Vec<F> {
+        let layer = |x: &[i128], kernel: &[i128], bias: &[i128]| {
+            kernel
+                .chunks(LEN)
+                .zip(bias)
+                .map(|(row, b)| {
+                    let y = row.iter().zip(x).map(|(w, x)| w * x).sum::<i128>() + b;
+                    y.max(0)
+                })
+                .collect::<Vec<_>>()
+        };
+        let hidden = layer(input, &L0_KERNEL, &L0_BIAS);
+        layer(&hidden, &L2_KERNEL, &L2_BIAS)
+            .into_iter()
+            .map(crate::fieldutils::integer_rep_to_felt)
+            .collect()
+    }
+
+    fn cases() -> Vec<([i128; LEN], Vec<F>)> {
+        [
+            [-30, -21, 11, 40],
+            [0, 0, 0, 0],
+            [1, 2, 3, 4],
+            [-5, -5, -5, -5],
+            [12, -7, 0, 3],
+        ]
+        .into_iter()
+        .map(|input| (input, forward(&input)))
+        .collect()
+    }
+
+    #[test]
+    fn mlpcases() {
+        run_cases(K as u32, MLPCircuit::new, &cases());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 of 5 cases failed")]
+    fn mlpcaseswrongexpected() {
+        let mut cases = cases();
+        cases[2].1[0] += F::ONE;
+        run_cases(K as u32, MLPCircuit::new, &cases);
+    }
+}
+
+#[cfg(test)]
+mod relu {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    const K: u32 = 8;
+
+    #[derive(Clone)]
+    struct ReLUCircuit<F: PrimeField + TensorType + PartialOrd> {
+        pub input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for ReLUCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, 8, 1, 3))
+                .collect::<Vec<_>>();
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K as usize)
+                .unwrap();
+
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1), K as usize)
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K as usize, 8, false);
+
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>, // layouter is our 'write buffer' for the circuit
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 2, 2);
+                        Ok(config
+                            .layout(
+                                &mut region,
+                                &[self.input.clone()],
+                                Box::new(PolyOp::LeakyReLU {
+                                    slope: 0.0.into(),
+                                    scale: 1,
+                                }),
+                            )
+                            .unwrap())
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn relucircuit() {
+        let input: Tensor<Value<F>> =
+            Tensor::new(Some(&[Value::<F>::known(F::from(1_u64)); 4]), &[4]).unwrap();
+
+        let circuit = ReLUCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod custom_nonlinearity {
+    use super::*;
+    use crate::circuit::ops::lookup::CustomNonlinearity;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 8;
+    const LEN: usize = 4;
+    const RANGE: (IntegerRep, IntegerRep) = (-16, 16);
+
+    #[derive(Debug)]
+    struct Square;
+
+    impl CustomNonlinearity for Square {
+        fn name(&self) -> String {
+            "square".to_string()
+        }
+
+        fn f(&self, x: IntegerRep) -> IntegerRep {
+            x * x
+        }
+    }
+
+    #[derive(Clone)]
+    struct SquareConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    #[derive(Clone)]
+    struct SquareCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl SquareCircuit {
+        fn new(input: &[IntegerRep; LEN]) -> Self {
+            SquareCircuit {
+                input: ValTensor::from_integer_rep_tensor(
+                    Tensor::new(Some(input), &[LEN]).unwrap(),
+                ),
+            }
+        }
+    }
+
+    impl Circuit<F> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            base_config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    RANGE,
+                    K,
+                    &LookupOp::custom(Square),
+                )
+                .unwrap();
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            SquareConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.base_config.layout_tables(&mut layouter).unwrap();
+            let output = layouter.assign_region(
+                || "square",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    Ok(config
+                        .base_config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(LookupOp::custom(Square)),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn cases() -> Vec<([IntegerRep; LEN], Vec<F>)> {
+        [[-16, -3, 0, 5], [16, 1, -1, 7]]
+            .into_iter()
+            .map(|input| {
+                (
+                    input,
+                    input.iter().map(|x| integer_rep_to_felt(x * x)).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn customsquarecircuit() {
+        // the table is keyed by the name of the function
+        assert_eq!(LookupOp::custom(Square), LookupOp::custom(Square));
+        assert_eq!(LookupOp::custom(Square).as_path(), "custom_square");
+        run_cases(K as u32, SquareCircuit::new, &cases());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 of 2 cases failed")]
+    fn customsquarewrongoutput() {
+        let mut cases = cases();
+        // -3 * -3 claimed as -9
+        cases[0].1[1] = integer_rep_to_felt(-9);
+        run_cases(K as u32, SquareCircuit::new, &cases);
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod mish_swish {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ops::nonlinearities::{mish, swish};
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 12;
+    const LEN: usize = 6;
+    const SCALE: f32 = 256.0;
+    const RANGE: (IntegerRep, IntegerRep) = (-1024, 1024);
+    const INPUT: [IntegerRep; LEN] = [-1024, -300, -1, 0, 77, 1000];
+
+    fn ops() -> [LookupOp; 2] {
+        [
+            LookupOp::Mish {
+                scale: SCALE.into(),
+            },
+            LookupOp::Swish {
+                scale: SCALE.into(),
+            },
+        ]
+    }
+
+    #[derive(Clone)]
+    struct ActivationConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    // [mish(x), swish(x)], both as lookups keyed on x
+    #[derive(Clone)]
+    struct ActivationCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for ActivationCircuit {
+        type Config = ActivationConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, 2 * LEN))
+                .collect::<Vec<_>>();
+
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            for op in ops() {
+                base_config
+                    .configure_lookup(cs, &advices[0], &advices[1], &advices[2], RANGE, K, &op)
+                    .unwrap();
+            }
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            ActivationConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.base_config.layout_tables(&mut layouter).unwrap();
+            let outputs = layouter.assign_region(
+                || "activations",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    ops()
+                        .into_iter()
+                        .map(|op| {
+                            Ok(config
+                                .base_config
+                                .layout(&mut region, &[self.input.clone()], Box::new(op))
+                                .map_err(|_| Error::Synthesis)?
+                                .unwrap())
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+
+            let cells = outputs
+                .iter()
+                .flat_map(|output| output.get_inner_tensor().unwrap().to_vec());
+            for (i, value) in cells.enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn circuit() -> ActivationCircuit {
+        ActivationCircuit {
+            input: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&INPUT), &[LEN]).unwrap()),
+        }
+    }
+
+    // the tables hold the rounded activations, so they sit within one unit of the f32 values
+    fn expected() -> Vec<F> {
+        let x = Tensor::new(Some(&INPUT), &[LEN]).unwrap();
+        let references: [fn(f32) -> f32; 2] =
+            [|x| x * (x.exp().ln_1p()).tanh(), |x| x / (1.0 + (-x).exp())];
+        let tables = [mish(&x, SCALE.into()), swish(&x, SCALE.into())];
+
+        let mut expected = vec![];
+        for (table, reference) in tables.iter().zip(references) {
+            for (y, x) in table.iter().zip(INPUT) {
+                let y_ref = reference(x as f32 / SCALE) * SCALE;
+                assert!((*y as f32 - y_ref).abs() <= 1.0, "{} vs {}", y, y_ref);
+            }
+            expected.extend(table.iter().map(|y| integer_rep_to_felt(*y)));
+        }
+        expected
+    }
+
+    #[test]
+    fn mishswishcircuit() {
+        let prover = MockProver::run(K as u32, &circuit(), vec![expected()]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn mishswishswapped() {
+        // mish and swish differ by more than rounding away from 0
+        let mut expected = expected();
+        expected.rotate_left(LEN);
+        let prover = MockProver::run(K as u32, &circuit(), vec![expected]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod gru_cell {
+    use super::*;
+    use crate::circuit::ops::layer::{GruCell, Layer};
+    use crate::circuit::region::RegionSettings;
+    use crate::fieldutils::IntegerRep;
+
+    const K: usize = 12;
+    const LEN: usize = 16;
+    const SCALE: f32 = 16.0;
+    const RANGE: (IntegerRep, IntegerRep) = (-1024, 1024);
+    const HIDDEN: usize = 2;
+
+    // the gates are ordered update, reset, candidate, and every weight is a multiple of 1/16
+    const INPUT_WEIGHTS: [[f32; 4]; 3] = [
+        [0.5, -0.25, 0.75, 0.5],
+        [-0.5, 0.25, 0.25, 1.0],
+        [1.0, -0.75, 0.5, 0.25],
+    ];
+    const HIDDEN_WEIGHTS: [[f32; 4]; 3] = [
+        [0.25, 0.5, -0.5, 0.25],
+        [0.5, 0.0, 0.25, -0.5],
+        [-0.25, 0.75, 0.5, 0.5],
+    ];
+    const INPUT_BIAS: [[f32; 2]; 3] = [[0.125, -0.25], [0.0, 0.5], [0.25, -0.125]];
+    const HIDDEN_BIAS: [[f32; 2]; 3] = [[0.0, 0.25], [-0.125, 0.0], [0.5, 0.25]];
+    // two timesteps of two features
+    const INPUT: [f32; 4] = [1.0, -0.5, 0.25, 0.75];
+
+    fn ops() -> [LookupOp; 3] {
+        [
+            LookupOp::Div {
+                denom: SCALE.into(),
+            },
+            LookupOp::Sigmoid {
+                scale: SCALE.into(),
+            },
+            LookupOp::Tanh {
+                scale: SCALE.into(),
+            },
+        ]
+    }
+
+    fn cell() -> GruCell<F> {
+        let gates =
+            |params: &[[f32; 4]; 3], dims: &[usize]| params.map(|p| quantize(&p, SCALE, dims));
+        let biases = |params: &[[f32; 2]; 3]| params.map(|p| quantize(&p, SCALE * SCALE, &[2]));
+        GruCell {
+            input_weights: gates(&INPUT_WEIGHTS, &[HIDDEN, 2]),
+            hidden_weights: gates(&HIDDEN_WEIGHTS, &[HIDDEN, HIDDEN]),
+            input_bias: biases(&INPUT_BIAS),
+            hidden_bias: biases(&HIDDEN_BIAS),
+            scale: SCALE.into(),
+        }
+    }
+
+    // the same cell in f32, returning the hidden state after each step
+    fn reference() -> Vec<f32> {
+        let project = |w: &[f32; 4], b: &[f32; 2], v: &[f32]| {
+            (0..HIDDEN)
+                .map(|i| w[2 * i] * v[0] + w[2 * i + 1] * v[1] + b[i])
+                .collect::<Vec<_>>()
+        };
+        let sigmoid = |x: f32| 1.0 / (1.0 + (-x).exp());
+
+        let mut h = vec![0.0; HIDDEN];
+        let mut states = vec![];
+        for x in INPUT.chunks(2) {
+            let gate = |g: usize| {
+                let wx = project(&INPUT_WEIGHTS[g], &INPUT_BIAS[g], x);
+                let uh = project(&HIDDEN_WEIGHTS[g], &HIDDEN_BIAS[g], &h);
+                (wx, uh)
+            };
+            let (wx, uh) = gate(0);
+            let z = (0..HIDDEN)
+                .map(|i| sigmoid(wx[i] + uh[i]))
+                .collect::<Vec<_>>();
+            let (wx, uh) = gate(1);
+            let r = (0..HIDDEN)
+                .map(|i| sigmoid(wx[i] + uh[i]))
+                .collect::<Vec<_>>();
+            let (wx, uh) = gate(2);
+            let n = (0..HIDDEN)
+                .map(|i| (wx[i] + r[i] * uh[i]).tanh())
+                .collect::<Vec<_>>();
+            h = (0..HIDDEN)
+                .map(|i| (1.0 - z[i]) * n[i] + z[i] * h[i])
+                .collect();
+            states.extend(h.iter());
+        }
+        states
+    }
+
+    #[derive(Clone)]
+    struct GruCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for GruCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            for op in ops() {
+                config
+                    .configure_lookup(cs, &advices[0], &advices[1], &advices[2], RANGE, K, &op)
+                    .unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    cell()
+                        .layout(&mut config, &mut region, &self.input)
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn grucellunrolled() {
+        let circuit = GruCircuit {
+            input: quantize(&INPUT, SCALE, &[2, 2]),
+        };
+        assert_eq!(cell().output_shape(&[2, 2]).unwrap(), vec![2, HIDDEN]);
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn grucellreference() {
+        let mut config = BaseConfig::dummy(K, 1);
+        let mut region = RegionCtx::new_dummy(0, 1, RegionSettings::all_true(128, 2));
+        let states = cell()
+            .layout(&mut config, &mut region, &quantize(&INPUT, SCALE, &[2, 2]))
+            .unwrap();
+        assert_eq!(states.dims(), &[2, HIDDEN]);
+
+        // the quantized cell tracks the f32 one to within a couple of units
+        assert_close(&states, SCALE, &reference(), 2.0 / SCALE);
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod attention {
+    use super::*;
+    use crate::circuit::region::RegionSettings;
+    use crate::fieldutils::IntegerRep;
+
+    const K: usize = 12;
+    const LEN: usize = 16;
+    const SCALE: f32 = 32.0;
+    const RANGE: (IntegerRep, IntegerRep) = (-1024, 1024);
+    const D: usize = 2;
+
+    // two queries attending over three tokens
+    const Q: [f32; 4] = [1.0, -0.5, 0.25, 0.75];
+    const KEYS: [f32; 6] = [0.5, 1.0, -1.0, 0.25, 0.75, -0.5];
+    const VALUES: [f32; 6] = [1.0, 0.0, -0.5, 0.5, 0.25, -1.0];
+
+    // softmax(Q K^T / sqrt(d)) V in f32
+    fn reference() -> Vec<f32> {
+        let mut output = vec![];
+        for q in Q.chunks(D) {
+            let scores = KEYS
+                .chunks(D)
+                .map(|k| (q[0] * k[0] + q[1] * k[1]) / (D as f32).sqrt())
+                .collect::<Vec<_>>();
+            let max = scores.iter().cloned().fold(f32::MIN, f32::max);
+            let exps = scores.iter().map(|s| (s - max).exp()).collect::<Vec<_>>();
+            let total = exps.iter().sum::<f32>();
+            for j in 0..D {
+                output.push(
+                    exps.iter()
+                        .zip(VALUES.chunks(D))
+                        .map(|(e, v)| e / total * v[j])
+                        .sum(),
+                );
+            }
+        }
+        output
+    }
+
+    #[derive(Clone)]
+    struct AttentionCircuit {
+        inputs: [ValTensor<F>; 3],
+    }
+
+    impl Circuit<F> for AttentionCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..6)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    RANGE,
+                    K,
+                    &LookupOp::Exp {
+                        scale: SCALE.into(),
+                        base: std::f64::consts::E.into(),
+                    },
+                )
+                .unwrap();
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+                .unwrap();
+            config
+                .configure_shuffles(
+                    cs,
+                    advices[0..3].try_into().unwrap(),
+                    advices[3..6].try_into().unwrap(),
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    layouts::attention(&config, &mut region, &self.inputs, SCALE.into())
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn inputs() -> [ValTensor<F>; 3] {
+        [
+            quantize(&Q, SCALE, &[2, D]),
+            quantize(&KEYS, SCALE, &[3, D]),
+            quantize(&VALUES, SCALE, &[3, D]),
+        ]
+    }
+
+    #[test]
+    fn attentioncircuit() {
+        let circuit = AttentionCircuit { inputs: inputs() };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn attentionreference() {
+        let config = BaseConfig::dummy(K, 1);
+        let mut region = RegionCtx::new_dummy(0, 1, RegionSettings::all_true(1024, 2));
+        let output = layouts::attention(&config, &mut region, &inputs(), SCALE.into()).unwrap();
+        assert_eq!(output.dims(), &[2, D]);
+        assert_close(&output, SCALE, &reference(), 2.0 / SCALE);
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod softmax_sum_to_one {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+
+    const K: usize = 12;
+    const LEN: usize = 16;
+    const INPUT_SCALE: f32 = 32.0;
+    const OUTPUT_SCALE: f32 = 1024.0;
+    const RANGE: (IntegerRep, IntegerRep) = (-1024, 1024);
+    // the recip of the sum of exps rounds to within half a unit, so each row is off by at most
+    // half its sum of exps, at most 3 * 32 / 2 here
+    const TOLERANCE: IntegerRep = 64;
+
+    #[derive(Clone)]
+    struct SoftmaxCircuit {
+        logits: ValTensor<F>,
+        // added to the first probability, to model a prover that tampers with the output
+        tamper: IntegerRep,
+    }
+
+    impl Circuit<F> for SoftmaxCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..6)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    RANGE,
+                    K,
+                    &LookupOp::Exp {
+                        scale: INPUT_SCALE.into(),
+                        base: std::f64::consts::E.into(),
+                    },
+                )
+                .unwrap();
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+                .unwrap();
+            config
+                .configure_range_check(cs, &advices[0], &advices[1], (-TOLERANCE, TOLERANCE), K)
+                .unwrap();
+            config
+                .configure_shuffles(
+                    cs,
+                    advices[0..3].try_into().unwrap(),
+                    advices[3..6].try_into().unwrap(),
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    let probs = layouts::softmax_axes(
+                        &config,
+                        &mut region,
+                        &[self.logits.clone()],
+                        INPUT_SCALE.into(),
+                        OUTPUT_SCALE.into(),
+                        &[1],
+                        1.0.into(),
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+
+                    let mut tamper = vec![0; probs.len()];
+                    tamper[0] = self.tamper;
+                    let tamper = ValTensor::from_integer_rep_tensor(
+                        Tensor::new(Some(&tamper), probs.dims()).unwrap(),
+                    );
+                    let probs = layouts::pairwise(
+                        &config,
+                        &mut region,
+                        &[probs, tamper],
+                        ops::base::BaseOp::Add,
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+
+                    layouts::sum_to_one(
+                        &config,
+                        &mut region,
+                        &[probs],
+                        &[1],
+                        (INPUT_SCALE * OUTPUT_SCALE) as IntegerRep,
+                        &(-TOLERANCE, TOLERANCE),
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn circuit(tamper: IntegerRep) -> SoftmaxCircuit {
+        let logits = [1.0, -0.5, 0.25, 0.0, 0.75, -1.0]
+            .iter()
+            .map(|x: &f32| (x * INPUT_SCALE).round() as IntegerRep)
+            .collect::<Vec<_>>();
+        SoftmaxCircuit {
+            logits: ValTensor::from_integer_rep_tensor(
+                Tensor::new(Some(&logits), &[2, 3]).unwrap(),
+            ),
+            tamper,
+        }
+    }
+
+    #[test]
+    fn normalizedsoftmaxcircuit() {
+        let prover = MockProver::run(K as u32, &circuit(0), vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn tamperedsoftmaxcircuit() {
+        // moving mass onto the first class breaks the normalization of its row
+        let prover = MockProver::run(K as u32, &circuit(4096), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod softplus {
+    use super::*;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ops::nonlinearities::softplus;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 12;
+    const LEN: usize = 6;
+    const SCALE: f32 = 256.0;
+    const RANGE: (IntegerRep, IntegerRep) = (-1024, 1024);
+    const INPUT: [IntegerRep; LEN] = [-1024, -300, -1, 0, 77, 1000];
+
+    fn op() -> LookupOp {
+        LookupOp::Softplus {
+            scale: SCALE.into(),
+        }
+    }
+
+    #[derive(Clone)]
+    struct SoftplusConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    #[derive(Clone)]
+    struct SoftplusCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for SoftplusCircuit {
+        type Config = SoftplusConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            base_config
+                .configure_lookup(cs, &advices[0], &advices[1], &advices[2], RANGE, K, &op())
+                .unwrap();
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            SoftplusConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.base_config.layout_tables(&mut layouter).unwrap();
+            let output = layouter.assign_region(
+                || "softplus",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    Ok(config
+                        .base_config
+                        .layout(&mut region, &[self.input.clone()], Box::new(op()))
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn circuit() -> SoftplusCircuit {
+        SoftplusCircuit {
+            input: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&INPUT), &[LEN]).unwrap()),
+        }
+    }
+
+    fn expected() -> Vec<F> {
+        let x = Tensor::new(Some(&INPUT), &[LEN]).unwrap();
+        let table = softplus(&x, SCALE.into());
+        for (y, x) in table.iter().zip(INPUT) {
+            let y_ref = (x as f32 / SCALE).exp().ln_1p() * SCALE;
+            assert!((*y as f32 - y_ref).abs() <= 1.0, "{} vs {}", y, y_ref);
+            // softplus is positive everywhere, and well above rounding to 0 on this range
+            assert!(*y > 0);
+        }
+        table.iter().map(|y| integer_rep_to_felt(*y)).collect()
+    }
+
+    #[test]
+    fn softpluscircuit() {
+        let prover = MockProver::run(K as u32, &circuit(), vec![expected()]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn softplusrelu() {
+        // softplus is a smoothed relu, the two differ by ln(2) at 0
+        let mut expected = expected();
+        expected[3] = integer_rep_to_felt(0);
+        let prover = MockProver::run(K as u32, &circuit(), vec![expected]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod relu_sign_bit_vs_table {
+    use super::*;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 10;
+    const LEN: usize = 8;
+    const LOOKUP_RANGE: (i128, i128) = (-64, 64);
+
+    fn table_relu() -> LookupOp {
+        LookupOp::ThresholdedReLU {
+            scale: 1.0.into(),
+            a: 0.0.into(),
+        }
+    }
+
+    #[derive(Clone)]
+    struct ReLUConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    // the same relu, either through the sign-bit decomposition or through a lookup table
+    #[derive(Clone)]
+    struct ReLUCircuit {
+        input: ValTensor<F>,
+        use_table: bool,
+    }
+
+    impl ReLUCircuit {
+        fn new(case: &([i128; LEN], bool)) -> Self {
+            let (input, use_table) = case;
+            ReLUCircuit {
+                input: ValTensor::from_integer_rep_tensor(
+                    Tensor::new(Some(input), &[LEN]).unwrap(),
+                ),
+                use_table: *use_table,
+            }
+        }
+    }
+
+    impl Circuit<F> for ReLUCircuit {
+        type Config = ReLUConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 15), K)
+                .unwrap();
+            base_config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    LOOKUP_RANGE,
+                    K,
+                    &table_relu(),
+                )
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            ReLUConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.base_config.layout_tables(&mut layouter).unwrap();
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
+                .unwrap();
+            let output = layouter.assign_region(
+                || "relu",
+                |region| {
+                    // base 16 with 2 legs covers the lookup range
+                    let mut region = RegionCtx::new(region, 0, 1, 16, 2);
+                    let op: Box<dyn Op<F>> = if self.use_table {
+                        Box::new(table_relu())
+                    } else {
+                        Box::new(PolyOp::LeakyReLU {
+                            slope: 0.0.into(),
+                            scale: 1,
+                        })
+                    };
+                    Ok(config
+                        .base_config
+                        .layout(&mut region, &[self.input.clone()], op)
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) | ValType::AssignedConstant(v, _) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    fn cases() -> Vec<(([i128; LEN], bool), Vec<F>)> {
+        [
+            [-64, -33, -8, -1, 0, 1, 8, 63],
+            [5, -5, 17, -17, 40, -40, 0, 0],
+            [-1, -2, -3, -4, -5, -6, -7, -8],
+        ]
+        .into_iter()
+        .flat_map(|input| {
+            let expected = input
+                .iter()
+                .map(|x| crate::fieldutils::integer_rep_to_felt((*x).max(0)))
+                .collect::<Vec<_>>();
+            [false, true].map(|use_table| ((input, use_table), expected.clone()))
+        })
+        .collect()
+    }
+
+    #[test]
+    fn relusignbitmatchestable() {
+        run_cases(K as u32, ReLUCircuit::new, &cases());
+    }
+
+    #[test]
+    #[should_panic(expected = "2 of 6 cases failed")]
+    fn relusignbitwrongoutput() {
+        let mut cases = cases();
+        // a negative input passed through unchanged is rejected by both implementations
+        for case in &mut cases[..2] {
+            case.1[2] = crate::fieldutils::integer_rep_to_felt(-8);
+        }
+        run_cases(K as u32, ReLUCircuit::new, &cases);
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod mixed_width_tables {
+    use super::*;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 12;
+    const LEN: usize = 4;
+    // an 8 bit relu table and a 14 bit divide table, the latter spans several columns
+    const RELU_RANGE: (i128, i128) = (-128, 128);
+    const DIV_RANGE: (i128, i128) = (-8192, 8192);
+
+    fn relu() -> LookupOp {
+        LookupOp::ThresholdedReLU {
+            scale: 1.0.into(),
+            a: 0.0.into(),
+        }
+    }
+
+    fn div() -> LookupOp {
+        LookupOp::Div { denom: 64.0.into() }
+    }
+
+    #[derive(Clone)]
+    struct MixedConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    // div(relu(x) * w)
+    #[derive(Clone)]
+    struct MixedCircuit {
+        input: ValTensor<F>,
+        weight: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MixedCircuit {
+        type Config = MixedConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+
+            for (range, nl) in [(RELU_RANGE, relu()), (DIV_RANGE, div())] {
+                base_config
+                    .configure_lookup(cs, &advices[0], &advices[1], &advices[2], range, K, &nl)
+                    .unwrap();
+            }
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            MixedConfig {
+                base_config,
+                public_output,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.base_config.layout_tables(&mut layouter).unwrap();
+            let output = layouter.assign_region(
+                || "mixed",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let x = config
+                        .base_config
+                        .layout(&mut region, &[self.input.clone()], Box::new(relu()))
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    let x = config
+                        .base_config
+                        .layout(
+                            &mut region,
+                            &[x, self.weight.clone()],
+                            Box::new(PolyOp::Mult),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    Ok(config
+                        .base_config
+                        .layout(&mut region, &[x], Box::new(div()))
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
                 },
             )?;
 
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
             Ok(())
         }
     }
 
-    #[test]
-    fn addcircuit() {
-        let a = (0..LEN)
-            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
-            .collect::<Vec<_>>();
-        let b = (0..LEN)
-            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
-            .collect::<Vec<_>>();
-        let commitment_a = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(a.clone()).unwrap()[0][0];
-
-        let commitment_b = PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(b.clone()).unwrap()[0][0];
-
-        // parameters
-        let a = Tensor::from(a.into_iter().map(Value::known));
-        let b = Tensor::from(b.into_iter().map(Value::known));
-        let circuit = MyCircuit {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
+    fn circuit() -> MixedCircuit {
+        let tensor = |values: &[i128]| {
+            ValTensor::from_integer_rep_tensor(Tensor::new(Some(values), &[LEN]).unwrap())
         };
-
-        let prover =
-            MockProver::run(K as u32, &circuit, vec![vec![commitment_a, commitment_b]]).unwrap();
-        prover.assert_satisfied();
+        MixedCircuit {
+            input: tensor(&[-100, 3, 100, 127]),
+            weight: tensor(&[60, 60, 60, 60]),
+        }
     }
 
     #[test]
-    fn addcircuit_bad_hashes() {
-        let a = (0..LEN)
-            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
-            .collect::<Vec<_>>();
-        let b = (0..LEN)
-            .map(|i| halo2curves::bn256::Fr::from(i as u64 + 1))
-            .collect::<Vec<_>>();
-        let commitment_a =
-            PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(a.clone()).unwrap()[0][0] + Fr::one();
-
-        let commitment_b =
-            PoseidonChip::<PoseidonSpec, WIDTH, RATE>::run(b.clone()).unwrap()[0][0] + Fr::one();
-
-        // parameters
-        let a = Tensor::from(a.into_iter().map(Value::known));
-        let b = Tensor::from(b.into_iter().map(Value::known));
-        let circuit = MyCircuit {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
+    fn mixedwidthtables() {
+        let config = {
+            let mut cs = ConstraintSystem::<F>::default();
+            MixedCircuit::configure(&mut cs).base_config
         };
+        // the two tables don't share their inputs
+        assert_eq!(config.shared_table_inputs.len(), 2);
+        let relu_cols = config.static_lookups.tables[&relu()].table_inputs.len();
+        let div_cols = config.static_lookups.tables[&div()].table_inputs.len();
+        assert!(div_cols > relu_cols);
+
+        // relu(x) * 60 / 64, rounded
+        let expected = [0, 3, 94, 119]
+            .into_iter()
+            .map(crate::fieldutils::integer_rep_to_felt)
+            .collect::<Vec<F>>();
+        let prover = MockProver::run(K as u32, &circuit(), vec![expected.clone()]).unwrap();
+        prover.assert_satisfied();
 
-        let prover =
-            MockProver::run(K as u32, &circuit, vec![vec![commitment_a, commitment_b]]).unwrap();
+        let mut wrong = expected;
+        wrong[2] += F::ONE;
+        let prover = MockProver::run(K as u32, &circuit(), vec![wrong]).unwrap();
         assert!(prover.verify().is_err());
     }
 }
 
 #[cfg(test)]
-mod sub {
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod saturating_div {
     use super::*;
+    use crate::fieldutils::IntegerRep;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
 
-    const K: usize = 4;
+    const K: usize = 12;
     const LEN: usize = 4;
+    const DIV_RANGE: (IntegerRep, IntegerRep) = (-128, 128);
+
+    fn div() -> LookupOp {
+        LookupOp::Div { denom: 4.0.into() }
+    }
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct SaturatingConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
-        type Config = BaseConfig<F>;
+    // div(clamp(x, DIV_RANGE))
+    #[derive(Clone)]
+    struct SaturatingCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl SaturatingCircuit {
+        fn new(input: &[IntegerRep; LEN]) -> Self {
+            SaturatingCircuit {
+                input: ValTensor::from_integer_rep_tensor(
+                    Tensor::new(Some(input), &[LEN]).unwrap(),
+                ),
+            }
+        }
+    }
+
+    impl Circuit<F> for SaturatingCircuit {
+        type Config = SaturatingConfig;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
 
@@ -2117,11 +6899,45 @@ mod sub {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN * LEN))
+                .collect::<Vec<_>>();
 
-            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+
+            // the clamp compares against the table edges through the decomposition range checks
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K)
+                .unwrap();
+            base_config
+                .configure_range_check(cs, &advices[0], &advices[1], (0, 1023), K)
+                .unwrap();
+            base_config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    DIV_RANGE,
+                    K,
+                    &div(),
+                )
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            SaturatingConfig {
+                base_config,
+                public_output,
+            }
         }
 
         fn synthesize(
@@ -2129,53 +6945,117 @@ mod sub {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        config
-                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Sub))
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
+            config.base_config.layout_tables(&mut layouter).unwrap();
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
                 .unwrap();
+            let output = layouter.assign_region(
+                || "saturating_div",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    layouts::saturating_nonlinearity(
+                        &config.base_config,
+                        &mut region,
+                        &[self.input.clone()],
+                        &div(),
+                        DIV_RANGE,
+                    )
+                    .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
             Ok(())
         }
     }
 
-    #[test]
-    fn subcircuit() {
-        // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
-
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    fn cases() -> Vec<([IntegerRep; LEN], Vec<F>)> {
+        [
+            // accumulations far outside the table saturate to its edges
+            ([1000, -1000, 40, 129], [32, -32, 10, 32]),
+            ([128, -128, -129, 0], [32, -32, -32, 0]),
+            ([-7, 6, 100000, -3], [-2, 2, 32, -1]),
+        ]
+        .into_iter()
+        .map(|(input, output)| {
+            (
+                input,
+                output
+                    .into_iter()
+                    .map(crate::fieldutils::integer_rep_to_felt)
+                    .collect(),
+            )
+        })
+        .collect()
+    }
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
-            _marker: PhantomData,
-        };
+    #[test]
+    fn saturatingdivcircuit() {
+        run_cases(K as u32, SaturatingCircuit::new, &cases());
+    }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
+    #[test]
+    #[should_panic(expected = "1 of 3 cases failed")]
+    fn saturatingdivunclamped() {
+        let mut cases = cases();
+        // 1000 / 4 without the clamp
+        cases[0].1[0] = crate::fieldutils::integer_rep_to_felt(250);
+        run_cases(K as u32, SaturatingCircuit::new, &cases);
     }
 }
 
 #[cfg(test)]
-mod mult {
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod div_negative_numerator {
     use super::*;
+    use crate::fieldutils::IntegerRep;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
 
-    const K: usize = 4;
+    const K: usize = 12;
     const LEN: usize = 4;
+    const DIV_RANGE: (IntegerRep, IntegerRep) = (-1024, 1024);
+
+    fn div() -> LookupOp {
+        LookupOp::Div {
+            denom: 128.0.into(),
+        }
+    }
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct DivConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
-        type Config = BaseConfig<F>;
+    #[derive(Clone)]
+    struct DivCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl DivCircuit {
+        fn new(input: &[IntegerRep; LEN]) -> Self {
+            DivCircuit {
+                input: ValTensor::from_integer_rep_tensor(
+                    Tensor::new(Some(input), &[LEN]).unwrap(),
+                ),
+            }
+        }
+    }
+
+    impl Circuit<F> for DivCircuit {
+        type Config = DivConfig;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
 
@@ -2184,11 +7064,35 @@ mod mult {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let a = VarTensor::new_advice(cs, K, 1, LEN);
-            let b = VarTensor::new_advice(cs, K, 1, LEN);
-            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
 
-            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+            let mut base_config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            base_config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    DIV_RANGE,
+                    K,
+                    &div(),
+                )
+                .unwrap();
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            DivConfig {
+                base_config,
+                public_output,
+            }
         }
 
         fn synthesize(
@@ -2196,53 +7100,169 @@ mod mult {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        config
-                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Mult))
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
-                .unwrap();
+            config.base_config.layout_tables(&mut layouter).unwrap();
+            let output = layouter.assign_region(
+                || "div",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    Ok(config
+                        .base_config
+                        .layout(&mut region, &[self.input.clone()], Box::new(div()))
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
             Ok(())
         }
     }
 
-    #[test]
-    fn multcircuit() {
-        // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    fn cases() -> Vec<([IntegerRep; LEN], Vec<F>)> {
+        [
+            ([-256, -128, -1024, -320], [-2, -1, -8, -3]),
+            // quotients round half away from zero, same as for positive numerators
+            ([-255, -64, -63, -1], [-2, -1, 0, 0]),
+            ([-896, 256, -1000, 0], [-7, 2, -8, 0]),
+        ]
+        .into_iter()
+        .map(|(input, output)| {
+            (
+                input,
+                output
+                    .into_iter()
+                    .map(crate::fieldutils::integer_rep_to_felt)
+                    .collect(),
+            )
+        })
+        .collect()
+    }
 
-        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    #[test]
+    fn divnegativecircuit() {
+        run_cases(K as u32, DivCircuit::new, &cases());
+    }
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
-            _marker: PhantomData,
-        };
+    #[test]
+    #[should_panic(expected = "1 of 3 cases failed")]
+    fn divnegativewrongsign() {
+        let mut cases = cases();
+        cases[0].1[0] = crate::fieldutils::integer_rep_to_felt(2);
+        run_cases(K as u32, DivCircuit::new, &cases);
+    }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
+    #[test]
+    fn divnegativehybridpaths() {
+        use crate::circuit::hybrid::HybridOp;
+        use crate::circuit::region::RegionSettings;
+
+        // a whole denominator is divided in the circuit rather than through the div lookup that
+        // fractional ones use, both have to round negative halves away from zero
+        let input = ValTensor::from_integer_rep_tensor(
+            Tensor::<IntegerRep>::new(Some(&[-64, -192, -320, 64, 192, -63]), &[6]).unwrap(),
+        );
+        let expected = &[-1, -2, -3, 1, 2, 0];
+        let mut config = BaseConfig::<F>::dummy(K, 2);
+        for settings in [
+            RegionSettings::all_true(128, 2),
+            RegionSettings::all_true(128, 2).with_remainder_tables(),
+        ] {
+            let mut region = RegionCtx::new_dummy(0, 2, settings);
+            let hybrid = config
+                .layout(
+                    &mut region,
+                    &[input.clone()],
+                    Box::new(HybridOp::Div {
+                        denom: 128.0.into(),
+                        clamp: None,
+                    }),
+                )
+                .unwrap()
+                .unwrap()
+                .int_evals()
+                .unwrap();
+            let lookup = config
+                .layout(&mut region, &[input.clone()], Box::new(div()))
+                .unwrap()
+                .unwrap()
+                .int_evals()
+                .unwrap();
+            assert_eq!(hybrid, lookup);
+            assert_eq!(hybrid.to_vec(), expected);
+        }
     }
 }
 
 #[cfg(test)]
-mod pow {
+mod signed_div {
     use super::*;
+    use crate::circuit::ops::layouts::RoundingMode;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::{create_constant_tensor, ValType};
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 12;
+    const LEN: usize = 8;
+    const NUMERATORS: [IntegerRep; LEN] = [-9, -8, -6, -1, 0, 1, 6, 9];
+    const MODES: [RoundingMode; 4] = [
+        RoundingMode::Floor,
+        RoundingMode::Ceil,
+        RoundingMode::TowardZero,
+        RoundingMode::HalfAwayFromZero,
+    ];
 
-    const K: usize = 8;
-    const LEN: usize = 4;
+    #[derive(Clone)]
+    struct SignedDivConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
+    }
+
+    #[derive(Clone, Debug)]
+    struct Case {
+        rounding: RoundingMode,
+        divisor: IntegerRep,
+        // a constant divisor is fixed in the circuit, otherwise it is witnessed
+        constant: bool,
+        saturate: Option<(IntegerRep, IntegerRep)>,
+    }
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 1],
-        _marker: PhantomData<F>,
+    struct SignedDivCircuit {
+        input: ValTensor<F>,
+        divisor: ValTensor<F>,
+        rounding: RoundingMode,
+        saturate: Option<(IntegerRep, IntegerRep)>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
-        type Config = BaseConfig<F>;
+    impl SignedDivCircuit {
+        fn new(case: &Case) -> Self {
+            let divisor = if case.constant {
+                create_constant_tensor(integer_rep_to_felt(case.divisor), 1)
+            } else {
+                ValTensor::from_integer_rep_tensor(
+                    Tensor::new(Some(&[case.divisor]), &[1]).unwrap(),
+                )
+            };
+            SignedDivCircuit {
+                input: ValTensor::from_integer_rep_tensor(
+                    Tensor::new(Some(&NUMERATORS), &[LEN]).unwrap(),
+                ),
+                divisor,
+                rounding: case.rounding,
+                saturate: case.saturate,
+            }
+        }
+    }
+
+    impl Circuit<F> for SignedDivCircuit {
+        type Config = SignedDivConfig;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
 
@@ -2255,7 +7275,24 @@ mod pow {
             let b = VarTensor::new_advice(cs, K, 1, LEN);
             let output = VarTensor::new_advice(cs, K, 1, LEN);
 
-            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+            let mut base_config =
+                BaseConfig::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            base_config
+                .configure_range_check(cs, &a, &b, (-1, 1), K)
+                .unwrap();
+            base_config
+                .configure_range_check(cs, &a, &b, (0, 1023), K)
+                .unwrap();
+
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
+
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            SignedDivConfig {
+                base_config,
+                public_output,
+            }
         }
 
         fn synthesize(
@@ -2263,57 +7300,165 @@ mod pow {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
-                        config
-                            .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Pow(5)))
-                            .map_err(|_| Error::Synthesis)
-                    },
-                )
+            config
+                .base_config
+                .layout_range_checks(&mut layouter)
                 .unwrap();
+            let output = layouter.assign_region(
+                || "signed div",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
+                    layouts::signed_div(
+                        &config.base_config,
+                        &mut region,
+                        &[self.input.clone(), self.divisor.clone()],
+                        self.rounding,
+                        self.saturate,
+                    )
+                    .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
             Ok(())
         }
     }
 
+    // an independent float reference for the quotients
+    fn expected(case: &Case) -> Vec<F> {
+        NUMERATORS
+            .iter()
+            .map(|a| {
+                let q = *a as f64 / case.divisor as f64;
+                let q = match case.rounding {
+                    RoundingMode::Floor => q.floor(),
+                    RoundingMode::Ceil => q.ceil(),
+                    RoundingMode::TowardZero => q.trunc(),
+                    RoundingMode::HalfAwayFromZero => q.round(),
+                } as IntegerRep;
+                let q = match case.saturate {
+                    Some((low, high)) => q.clamp(low, high),
+                    None => q,
+                };
+                integer_rep_to_felt(q)
+            })
+            .collect()
+    }
+
+    fn cases() -> Vec<(Case, Vec<F>)> {
+        MODES
+            .into_iter()
+            .flat_map(|rounding| {
+                [1, 2, 3, 4, 16].into_iter().flat_map(move |divisor| {
+                    [false, true].into_iter().map(move |constant| Case {
+                        rounding,
+                        divisor,
+                        constant,
+                        saturate: None,
+                    })
+                })
+            })
+            .map(|case| {
+                let expected = expected(&case);
+                (case, expected)
+            })
+            .collect()
+    }
+
     #[test]
-    fn powcircuit() {
-        // parameters
-        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    fn signeddivcircuit() {
+        run_cases(K as u32, SignedDivCircuit::new, &cases());
+    }
 
-        let circuit = MyCircuit::<F> {
-            inputs: [ValTensor::from(a)],
-            _marker: PhantomData,
-        };
+    #[test]
+    fn signeddivsaturating() {
+        let cases = MODES
+            .into_iter()
+            .map(|rounding| Case {
+                rounding,
+                divisor: 2,
+                constant: false,
+                saturate: Some((-2, 1)),
+            })
+            .map(|case| {
+                let expected = expected(&case);
+                (case, expected)
+            })
+            .collect::<Vec<_>>();
+        run_cases(K as u32, SignedDivCircuit::new, &cases);
+    }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
+    #[test]
+    #[should_panic(expected = "1 of 40 cases failed")]
+    fn signeddivwrongrounding() {
+        let mut cases = cases();
+        // -9 / 4 floors to -3, claim it truncates instead
+        let (_, expected) = cases
+            .iter_mut()
+            .find(|(case, _)| case.rounding == RoundingMode::Floor && case.divisor == 4)
+            .unwrap();
+        expected[0] = integer_rep_to_felt(-2);
+        run_cases(K as u32, SignedDivCircuit::new, &cases);
+    }
+
+    #[test]
+    #[should_panic(expected = "2 of 2 cases failed")]
+    fn signeddivnonpositivedivisor() {
+        let cases = [0, -4]
+            .into_iter()
+            .map(|divisor| Case {
+                rounding: RoundingMode::Floor,
+                divisor,
+                constant: false,
+                saturate: None,
+            })
+            .map(|case| (case, vec![F::ZERO; LEN]))
+            .collect::<Vec<_>>();
+        run_cases(K as u32, SignedDivCircuit::new, &cases);
     }
 }
 
 #[cfg(test)]
-mod matmul_relu {
+mod requantize_clamp {
     use super::*;
-
-    const K: usize = 18;
-    const LEN: usize = 32;
+    use crate::circuit::hybrid::HybridOp;
+    use crate::circuit::table::Range;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
+    use crate::tensor::ops::nonlinearities::tanh;
+    use crate::tensor::ValType;
+    use halo2_proofs::plonk::{Column, Instance};
+
+    const K: usize = 12;
+    const LEN: usize = 4;
+    const DENOM: f32 = 4.0;
+    const SCALE: f32 = 16.0;
+    // the domain of the activation table after the requantize
+    const RANGE: Range = (-64, 64);
+    // quotients of 100 and -125 overflow the table
+    const INPUT: [IntegerRep; LEN] = [400, -8, 12, -500];
 
     #[derive(Clone)]
-    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
-        inputs: [ValTensor<F>; 2],
-        _marker: PhantomData<F>,
+    struct RequantizeConfig {
+        base_config: BaseConfig<F>,
+        public_output: Column<Instance>,
     }
 
-    // A columnar ReLu MLP
+    // tanh(requantize(x))
     #[derive(Clone)]
-    struct MyConfig<F: PrimeField + TensorType + PartialOrd> {
-        base_config: BaseConfig<F>,
+    struct RequantizeCircuit {
+        input: ValTensor<F>,
+        clamp: Option<Range>,
     }
 
-    impl Circuit<F> for MyCircuit<F> {
-        type Config = MyConfig<F>;
+    impl Circuit<F> for RequantizeCircuit {
+        type Config = RequantizeConfig;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
 
@@ -2328,18 +7473,39 @@ mod matmul_relu {
 
             let mut base_config =
                 BaseConfig::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
-
             base_config
                 .configure_range_check(cs, &a, &b, (-1, 1), K)
                 .unwrap();
-
             base_config
                 .configure_range_check(cs, &a, &b, (0, 1023), K)
                 .unwrap();
+            // the remainder of the requantizing divide, which opts into a table for its divisor
+            base_config
+                .configure_range_check(cs, &a, &b, (0, 2 * DENOM as IntegerRep - 1), K)
+                .unwrap();
+            base_config
+                .configure_lookup(
+                    cs,
+                    &a,
+                    &b,
+                    &output,
+                    RANGE,
+                    K,
+                    &LookupOp::Tanh {
+                        scale: SCALE.into(),
+                    },
+                )
+                .unwrap();
 
             let _constant = VarTensor::constant_cols(cs, K, 8, false);
 
-            MyConfig { base_config }
+            let public_output = cs.instance_column();
+            cs.enable_equality(public_output);
+
+            RequantizeConfig {
+                base_config,
+                public_output,
+            }
         }
 
         fn synthesize(
@@ -2347,77 +7513,126 @@ mod matmul_relu {
             mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
+            config.base_config.layout_tables(&mut layouter).unwrap();
             config
                 .base_config
                 .layout_range_checks(&mut layouter)
                 .unwrap();
-            layouter.assign_region(
-                || "",
+            let output = layouter.assign_region(
+                || "requantize",
                 |region| {
                     let mut region = RegionCtx::new(region, 0, 1, 1024, 2);
-                    let op = PolyOp::Einsum {
-                        equation: "ij,jk->ik".to_string(),
-                    };
-                    let output = config
+                    let x = config
                         .base_config
-                        .layout(&mut region, &self.inputs, Box::new(op))
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(HybridOp::Div {
+                                denom: DENOM.into(),
+                                clamp: self.clamp,
+                            }),
+                        )
+                        .map_err(|_| Error::Synthesis)?
                         .unwrap();
-                    let _output = config
+                    Ok(config
                         .base_config
                         .layout(
                             &mut region,
-                            &[output.unwrap()],
-                            Box::new(PolyOp::LeakyReLU {
-                                slope: 0.0.into(),
-                                scale: 1,
+                            &[x],
+                            Box::new(LookupOp::Tanh {
+                                scale: SCALE.into(),
                             }),
                         )
-                        .unwrap();
-                    Ok(())
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap())
                 },
             )?;
 
+            for (i, value) in output.get_inner_tensor().unwrap().iter().enumerate() {
+                match value {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), config.public_output, i)?
+                    }
+                    _ => panic!("output should be assigned"),
+                }
+            }
             Ok(())
         }
     }
 
-    #[test]
-    fn matmulrelucircuit() {
-        // parameters
-        let mut a = Tensor::from((0..LEN * LEN).map(|_| Value::known(F::from(1))));
-        a.reshape(&[LEN, LEN]).unwrap();
-
-        // parameters
-        let mut b = Tensor::from((0..LEN).map(|_| Value::known(F::from(1))));
-        b.reshape(&[LEN, 1]).unwrap();
+    fn circuit(clamp: Option<Range>) -> RequantizeCircuit {
+        RequantizeCircuit {
+            input: ValTensor::from_integer_rep_tensor(Tensor::new(Some(&INPUT), &[LEN]).unwrap()),
+            clamp,
+        }
+    }
 
-        let circuit = MyCircuit {
-            inputs: [ValTensor::from(a), ValTensor::from(b)],
-            _marker: PhantomData,
-        };
+    // tanh of the (clamped) quotients, as the table computes it
+    fn expected(clamp: Option<Range>) -> Vec<F> {
+        let quotients = INPUT
+            .iter()
+            .map(|x| {
+                let q = (*x as f64 / DENOM as f64).round() as IntegerRep;
+                match clamp {
+                    Some((low, high)) => q.clamp(low, high),
+                    None => q,
+                }
+            })
+            .collect::<Vec<_>>();
+        let quotients = Tensor::new(Some(&quotients), &[LEN]).unwrap();
+        tanh(&quotients, SCALE.into())
+            .iter()
+            .map(|y| integer_rep_to_felt(*y))
+            .collect()
+    }
 
-        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+    #[test]
+    fn requantizeclamped() {
+        let prover =
+            MockProver::run(K as u32, &circuit(Some(RANGE)), vec![expected(Some(RANGE))]).unwrap();
         prover.assert_satisfied();
     }
+
+    #[test]
+    fn requantizeunclamped() {
+        // the quotients are right, but 100 and -125 aren't in the tanh table
+        let prover = MockProver::run(K as u32, &circuit(None), vec![expected(None)]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn requantizeclampedwrongoutput() {
+        // the clamp is constrained, the unclamped activations aren't a valid witness for it
+        let prover =
+            MockProver::run(K as u32, &circuit(Some(RANGE)), vec![expected(None)]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 }
 
 #[cfg(test)]
-mod relu {
+mod distillation {
     use super::*;
-    use halo2_proofs::{
-        circuit::{Layouter, SimpleFloorPlanner, Value},
-        dev::MockProver,
-        plonk::{Circuit, ConstraintSystem, Error},
-    };
-
-    const K: u32 = 8;
+    use crate::circuit::ops::layouts;
+    use crate::fieldutils::{integer_rep_to_felt, IntegerRep};
 
+    const K: usize = 8;
+    const LEN: usize = 32;
+    const TOL: IntegerRep = 2;
+    const INPUT: [IntegerRep; 3] = [2, -1, 3];
+    const WEIGHTS: [IntegerRep; 6] = [1, 2, 0, 0, -1, 2];
+    // the teacher computes [1, 7] on the input
+    const TEACHER_BIAS: [IntegerRep; 2] = [1, 0];
+
+    // a teacher and a student affine layer on the same input, whose outputs must agree to
+    // within TOL
     #[derive(Clone)]
-    struct ReLUCircuit<F: PrimeField + TensorType + PartialOrd> {
-        pub input: ValTensor<F>,
+    struct DistillationCircuit {
+        input: ValTensor<F>,
+        teacher: [ValTensor<F>; 2],
+        student: [ValTensor<F>; 2],
     }
 
-    impl Circuit<F> for ReLUCircuit<F> {
+    impl Circuit<F> for DistillationCircuit {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -2427,71 +7642,87 @@ mod relu {
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-            let advices = (0..3)
-                .map(|_| VarTensor::new_advice(cs, 8, 1, 3))
-                .collect::<Vec<_>>();
-
-            let mut config = BaseConfig::configure(
-                cs,
-                &[advices[0].clone(), advices[1].clone()],
-                &advices[2],
-                CheckMode::SAFE,
-            );
-
-            config
-                .configure_range_check(cs, &advices[0], &advices[1], (-1, 1), K as usize)
-                .unwrap();
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let _constant = VarTensor::constant_cols(cs, K, 8, false);
 
+            // unsafe so that a student outside the tolerance is caught by the range check
+            // constraint rather than by the witness check at layout time
+            let mut config =
+                BaseConfig::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::UNSAFE);
             config
-                .configure_range_check(cs, &advices[0], &advices[1], (0, 1), K as usize)
+                .configure_range_check(cs, &a, &b, (-TOL, TOL), K)
                 .unwrap();
-
-            let _constant = VarTensor::constant_cols(cs, K as usize, 8, false);
-
             config
         }
 
         fn synthesize(
             &self,
             mut config: Self::Config,
-            mut layouter: impl Layouter<F>, // layouter is our 'write buffer' for the circuit
+            mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
             config.layout_range_checks(&mut layouter).unwrap();
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0, 1, 2, 2);
-                        Ok(config
-                            .layout(
-                                &mut region,
-                                &[self.input.clone()],
-                                Box::new(PolyOp::LeakyReLU {
-                                    slope: 0.0.into(),
-                                    scale: 1,
-                                }),
-                            )
-                            .unwrap())
-                    },
-                )
-                .unwrap();
-
-            Ok(())
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    // assign the input once so that both models read the same cells
+                    let input =
+                        layouts::identity(&config, &mut region, &[self.input.clone()], false)
+                            .map_err(|_| Error::Synthesis)?;
+                    let affine = |region: &mut RegionCtx<F>, params: &[ValTensor<F>; 2]| {
+                        layouts::affine(
+                            &config,
+                            region,
+                            &[input.clone(), params[0].clone(), params[1].clone()],
+                        )
+                        .map_err(|_| Error::Synthesis)
+                    };
+                    let teacher = affine(&mut region, &self.teacher)?;
+                    let student = affine(&mut region, &self.student)?;
+                    layouts::within_tolerance(&config, &mut region, &[teacher, student], TOL)
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
         }
     }
 
-    #[test]
-    fn relucircuit() {
-        let input: Tensor<Value<F>> =
-            Tensor::new(Some(&[Value::<F>::known(F::from(1_u64)); 4]), &[4]).unwrap();
-
-        let circuit = ReLUCircuit::<F> {
-            input: ValTensor::from(input),
+    fn circuit(student_bias: [IntegerRep; 2]) -> DistillationCircuit {
+        let params = |bias: &[IntegerRep]| {
+            [
+                ValTensor::from_integer_rep_tensor(Tensor::new(Some(&WEIGHTS), &[2, 3]).unwrap()),
+                ValTensor::from_integer_rep_tensor(Tensor::new(Some(bias), &[2]).unwrap()),
+            ]
         };
+        let input = Tensor::from(
+            INPUT
+                .iter()
+                .map(|x| Value::known(integer_rep_to_felt::<F>(*x))),
+        );
+        let mut input: ValTensor<F> = input.into();
+        input.reshape(&[1, 3]).unwrap();
+        DistillationCircuit {
+            input,
+            teacher: params(&TEACHER_BIAS),
+            student: params(&student_bias),
+        }
+    }
 
-        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+    #[test]
+    fn distillationclose() {
+        // the student computes [0, 9], off by 1 and 2
+        let prover = MockProver::run(K as u32, &circuit([-1, 2]), vec![]).unwrap();
         prover.assert_satisfied();
     }
+
+    #[test]
+    fn distillationfar() {
+        // the student computes [4, 8], the first output is off by 3
+        let prover = MockProver::run(K as u32, &circuit([4, 1]), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 }
 
 #[cfg(test)]
@@ -2622,3 +7853,87 @@ mod lookup_ultra_overflow {
         assert!(prover.is_ok());
     }
 }
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "ezkl",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod lookup_out_of_range {
+    use super::*;
+
+    const K: usize = 6;
+
+    #[derive(Clone)]
+    struct SigmoidCircuit<F: PrimeField + TensorType + PartialOrd> {
+        pub input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for SigmoidCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, 3))
+                .collect::<Vec<_>>();
+
+            let nl = LookupOp::Sigmoid { scale: 1.0.into() };
+
+            let mut config = BaseConfig::default();
+
+            config
+                .configure_lookup(cs, &advices[0], &advices[1], &advices[2], (-8, 8), K, &nl)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                        let res = config.layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(LookupOp::Sigmoid { scale: 1.0.into() }),
+                        );
+                        match res {
+                            Err(CircuitError::LookupOOR(op, value, min, _)) => {
+                                assert!(op.starts_with("SIGMOID"));
+                                assert_eq!(value, 1000);
+                                assert_eq!(min, -8);
+                            }
+                            _ => panic!("expected an out of range lookup error"),
+                        }
+                        Ok(())
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lookupoutofrange() {
+        let a = Tensor::from([1, 1000, 2].into_iter().map(|i| Value::known(F::from(i))));
+
+        let circuit = SigmoidCircuit::<F> {
+            input: ValTensor::from(a),
+        };
+
+        let _ = MockProver::run(K as u32, &circuit, vec![]);
+    }
+}
@@ -1055,9 +1055,11 @@ pub(crate) async fn calibrate(
     max_logrows: Option<u32>,
 ) -> Result<GraphSettings, EZKLError> {
     use log::error;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use tabled::Table;
 
+    use crate::circuit::lookup::LookupOp;
+    use crate::circuit::table::Range;
     use crate::fieldutils::IntegerRep;
 
     let data = GraphData::from_str(&data)?;
@@ -1239,8 +1241,18 @@ pub(crate) async fn calibrate(
 
         let max_range_size = result.iter().map(|x| x.max_range_size).max().unwrap_or(0);
 
+        let mut lookup_ranges = BTreeMap::<LookupOp, Range>::new();
+        for (op, range) in result.iter().flat_map(|x| x.lookup_ranges.iter()) {
+            lookup_ranges
+                .entry(op.clone())
+                .and_modify(|r| *r = (r.0.min(range.0), r.1.max(range.1)))
+                .or_insert(*range);
+        }
+        let lookup_ranges = lookup_ranges.into_iter().collect::<Vec<_>>();
+
         let res = circuit.calc_min_logrows(
             (min_lookup_range, max_lookup_range),
+            &lookup_ranges,
             max_range_size,
             max_logrows,
             lookup_safety_margin,
@@ -1261,6 +1273,7 @@ pub(crate) async fn calibrate(
             let found_settings = GraphSettings {
                 run_args: found_run_args,
                 required_lookups: new_settings.required_lookups,
+                lookup_ranges: new_settings.lookup_ranges,
                 required_range_checks: new_settings.required_range_checks,
                 model_output_scales: new_settings.model_output_scales,
                 model_input_scales: new_settings.model_input_scales,
@@ -6,6 +6,8 @@ use halo2curves::ff::PrimeField;
 pub type IntegerRep = i128;
 
 /// Converts an integer rep to a PrimeField element.
+/// Negative values are encoded as `modulus - |x|` of the target field `F`, so the
+/// encoding is correct for any prime field and not just a particular curve.
 pub fn integer_rep_to_felt<F: PrimeField>(x: IntegerRep) -> F {
     if x >= 0 {
         F::from_u128(x as u128)
@@ -16,6 +18,36 @@ pub fn integer_rep_to_felt<F: PrimeField>(x: IntegerRep) -> F {
     }
 }
 
+/// Errors from converting between integers and field elements.
+#[derive(Debug, thiserror::Error)]
+pub enum FieldError {
+    /// The value is outside of the range that can be safely encoded
+    #[error("{0} is outside of the safe range [{min}, {max}] of integers that can be encoded as field elements", min = -MAX_SAFE_INTEGER_REP, max = MAX_SAFE_INTEGER_REP)]
+    OutOfRange(IntegerRep),
+}
+
+/// The largest magnitude [integer_rep_to_felt_checked] encodes. The product of any two values
+/// in the safe range still fits in an [IntegerRep], so it decodes exactly with
+/// [felt_to_integer_rep] after being multiplied in the field.
+pub const MAX_SAFE_INTEGER_REP: IntegerRep = i64::MAX as IntegerRep;
+
+/// Converts an integer rep to a PrimeField element like [integer_rep_to_felt], but errors instead
+/// of encoding values outside of `[-MAX_SAFE_INTEGER_REP, MAX_SAFE_INTEGER_REP]`, for callers
+/// that want to catch overflowing values rather than let them wrap around the field.
+pub fn integer_rep_to_felt_checked<F: PrimeField>(x: IntegerRep) -> Result<F, FieldError> {
+    if x.unsigned_abs() > MAX_SAFE_INTEGER_REP as u128 {
+        return Err(FieldError::OutOfRange(x));
+    }
+    Ok(integer_rep_to_felt(x))
+}
+
+/// Converts an i64 to a PrimeField element.
+/// Accumulation (dot products, sums, convolutions) happens in the field itself, so its width is
+/// bounded by the field modulus rather than by the integer type the inputs were quantized to.
+pub fn i64_to_felt<F: PrimeField>(x: i64) -> F {
+    integer_rep_to_felt(x as IntegerRep)
+}
+
 /// Converts a PrimeField element to an f64.
 pub fn felt_to_f64<F: PrimeField + PartialOrd + Field>(x: F) -> f64 {
     if x > F::from_u128(IntegerRep::MAX as u128) {
@@ -70,6 +102,72 @@ mod test {
         assert_eq!(res, F::from(131072));
     }
 
+    /// `modulus - 1` as little-endian bytes, computed from `F::MODULUS`
+    fn modulus_minus_one<Fi: PrimeField>() -> Vec<u8> {
+        let hex = Fi::MODULUS.trim_start_matches("0x");
+        let hex = if hex.len() % 2 == 1 {
+            format!("0{}", hex)
+        } else {
+            hex.to_string()
+        };
+        let mut bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .rev()
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        // the modulus is odd so subtracting one never borrows
+        bytes[0] -= 1;
+        bytes.resize(Fi::Repr::default().as_ref().len(), 0);
+        bytes
+    }
+
+    #[test]
+    fn integerreptofeltnegativeone() {
+        let pasta: F = integer_rep_to_felt(-1);
+        assert_eq!(
+            pasta.to_repr().as_ref(),
+            modulus_minus_one::<F>().as_slice()
+        );
+
+        use halo2curves::bn256::Fr;
+        let bn256: Fr = integer_rep_to_felt(-1);
+        assert_eq!(
+            bn256.to_repr().as_ref(),
+            modulus_minus_one::<Fr>().as_slice()
+        );
+
+        assert_ne!(modulus_minus_one::<F>(), modulus_minus_one::<Fr>());
+    }
+
+    #[test]
+    fn integerreptofeltchecked() {
+        for x in [0, 1, -1, MAX_SAFE_INTEGER_REP, -MAX_SAFE_INTEGER_REP] {
+            let res: F = integer_rep_to_felt_checked(x).unwrap();
+            assert_eq!(res, integer_rep_to_felt::<F>(x));
+        }
+
+        for x in [
+            MAX_SAFE_INTEGER_REP + 1,
+            -MAX_SAFE_INTEGER_REP - 1,
+            IntegerRep::MAX,
+            IntegerRep::MIN,
+        ] {
+            assert!(matches!(
+                integer_rep_to_felt_checked::<F>(x),
+                Err(FieldError::OutOfRange(v)) if v == x
+            ));
+        }
+    }
+
+    #[test]
+    fn i64tofelt() {
+        for x in [0, 1, -1, i32::MAX as i64 + 1, i64::MIN, i64::MAX] {
+            let res: F = i64_to_felt(x);
+            assert_eq!(res, integer_rep_to_felt::<F>(x as IntegerRep));
+            assert_eq!(felt_to_integer_rep(res), x as IntegerRep);
+        }
+    }
+
     #[test]
     fn felttointegerrep() {
         for x in -(2_i128.pow(16))..(2_i128.pow(16)) {
@@ -87,6 +87,9 @@ pub enum GraphError {
     /// Halo2 error
     #[error("[halo2] {0}")]
     Halo2Error(#[from] halo2_proofs::plonk::Error),
+    /// Proof system error
+    #[error("[pfsys] {0}")]
+    PfsysError(#[from] crate::pfsys::errors::PfsysError),
     /// System time error
     #[error("[system time] {0}")]
     SystemTimeError(#[from] std::time::SystemTimeError),
@@ -131,6 +134,20 @@ pub enum GraphError {
     /// Missing scale
     #[error("missing scale")]
     MissingScale,
+    /// A node is fed a value that doesn't have the shape it was parsed with, e.g. a model input of
+    /// the wrong shape
+    #[error("node {layer} expects an input of shape {expected:?}, got one of shape {got:?}")]
+    ShapeMismatch {
+        /// the node being laid out
+        layer: usize,
+        /// the shape the node expects
+        expected: Vec<usize>,
+        /// the shape of the value it was fed
+        got: Vec<usize>,
+    },
+    /// A node's recorded scale doesn't match the scale its op produces
+    #[error("node {0} should have scale {1} given its inputs, but has scale {2}")]
+    ScaleMismatch(usize, crate::Scale, crate::Scale),
     /// Extended k is too large
     #[error("extended k is too large to accommodate the quotient polynomial with logrows {0}")]
     ExtendedKTooLarge(u32),
@@ -158,4 +175,7 @@ pub enum GraphError {
     /// Inssuficient advice columns
     #[error("insuficcient advice columns (need {0} at least)")]
     InsufficientAdviceColumns(usize),
+    /// Calibration data doesn't fit the model
+    #[error("failed to calibrate: {0}")]
+    Calibration(String),
 }
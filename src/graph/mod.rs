@@ -17,6 +17,8 @@ pub mod vars;
 /// errors for the graph
 pub mod errors;
 
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+use crate::pfsys::Snark;
 #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
 use colored_json::ToColoredJson;
 #[cfg(all(not(not(feature = "ezkl")), unix))]
@@ -37,12 +39,20 @@ use crate::circuit::lookup::LookupOp;
 use crate::circuit::modules::ModulePlanner;
 use crate::circuit::region::{ConstantsMap, RegionSettings};
 use crate::circuit::table::{num_cols_required, Range, Table, RESERVED_BLINDING_ROWS_PAD};
-use crate::circuit::{CheckMode, InputType};
+use crate::circuit::{CheckMode, InputType, TableStrategy};
 use crate::fieldutils::{felt_to_f64, IntegerRep};
 use crate::pfsys::PrettyElements;
 use crate::tensor::{Tensor, ValTensor};
 use crate::{RunArgs, EZKL_BUF_CAPACITY};
 
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+use halo2_proofs::plonk::ProvingKey;
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+use halo2_proofs::poly::kzg::{
+    commitment::{KZGCommitmentScheme, ParamsKZG},
+    multiopen::{ProverSHPLONK, VerifierSHPLONK},
+    strategy::SingleStrategy,
+};
 use halo2_proofs::{
     circuit::Layouter,
     plonk::{Circuit, ConstraintSystem, Error as PlonkError},
@@ -63,6 +73,8 @@ use pyo3::types::PyDict;
 use pyo3::types::PyDictMethods;
 #[cfg(feature = "python-bindings")]
 use pyo3::ToPyObject;
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
 
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
@@ -136,6 +148,9 @@ pub struct GraphWitness {
     pub min_lookup_inputs: IntegerRep,
     /// max range check size
     pub max_range_size: IntegerRep,
+    /// the smallest and largest input to each lookup
+    #[serde(default)]
+    pub lookup_ranges: Vec<(LookupOp, Range)>,
     /// (optional) version of ezkl used
     pub version: Option<String>,
 }
@@ -166,6 +181,7 @@ impl GraphWitness {
             max_lookup_inputs: 0,
             min_lookup_inputs: 0,
             max_range_size: 0,
+            lookup_ranges: vec![],
             version: None,
         }
     }
@@ -445,6 +461,10 @@ pub struct GraphSettings {
     pub module_sizes: ModuleSizes,
     /// required_lookups
     pub required_lookups: Vec<LookupOp>,
+    /// the calibrated input range of each lookup, which sizes its table under
+    /// [TableStrategy::PerLayer]
+    #[serde(default)]
+    pub lookup_ranges: Vec<(LookupOp, Range)>,
     /// required range_checks
     pub required_range_checks: Vec<Range>,
     /// check mode
@@ -462,6 +482,32 @@ pub struct GraphSettings {
 }
 
 impl GraphSettings {
+    /// The range the table for `op` spans under the run args' [TableStrategy]. With
+    /// [TableStrategy::PerLayer], lookups that weren't calibrated fall back to `lookup_range`.
+    pub fn table_range(&self, op: &LookupOp) -> Range {
+        match self.run_args.table_strategy {
+            TableStrategy::Shared => self.run_args.lookup_range,
+            TableStrategy::PerLayer => self
+                .lookup_ranges
+                .iter()
+                .find(|(o, _)| o == op)
+                .map(|(_, range)| *range)
+                .unwrap_or(self.run_args.lookup_range),
+        }
+    }
+
+    /// The number of entries in all the lookup tables, i.e. the rows they fill summed over
+    /// tables (and over the columns a wide table is split into).
+    pub fn lookup_table_rows(&self) -> usize {
+        self.required_lookups
+            .iter()
+            .map(|op| {
+                let range = self.table_range(op);
+                (range.1 - range.0 + 1) as usize
+            })
+            .sum()
+    }
+
     /// Calc the number of rows required for lookup tables
     pub fn lookup_log_rows(&self) -> u32 {
         ((self.run_args.lookup_range.1 - self.run_args.lookup_range.0) as f32)
@@ -672,6 +718,60 @@ pub struct GraphCircuit {
     pub core: CoreCircuit,
     /// The witness data for the model.
     pub graph_witness: GraphWitness,
+    /// The model's most recent forward pass, see [ForwardCache]
+    #[serde(skip)]
+    pub forward_cache: ForwardCache,
+}
+
+/// What a cached forward pass depends on
+#[derive(Clone, Debug, PartialEq)]
+struct ForwardCacheKey {
+    inputs: Vec<Tensor<Fp>>,
+    run_args: RunArgs,
+    region_settings: RegionSettings,
+}
+
+#[derive(Debug, Default)]
+struct CachedForward {
+    entry: Option<(ForwardCacheKey, ForwardResult)>,
+    passes: usize,
+}
+
+/// Caches the model's forward pass for the most recent inputs. Clones of a circuit share the
+/// cache, so the activations computed for an input are reused across key generation and
+/// proving. The pass is recomputed whenever the inputs, run args or region settings change.
+#[derive(Clone, Debug, Default)]
+pub struct ForwardCache(std::sync::Arc<std::sync::Mutex<CachedForward>>);
+
+impl ForwardCache {
+    /// the number of forward passes that were computed rather than read from the cache
+    pub fn passes(&self) -> usize {
+        self.0.lock().unwrap().passes
+    }
+
+    /// drops the cached forward pass
+    pub fn clear(&self) {
+        self.0.lock().unwrap().entry = None;
+    }
+
+    fn get_or_compute(
+        &self,
+        key: ForwardCacheKey,
+        forward: impl FnOnce() -> Result<ForwardResult, GraphError>,
+    ) -> Result<ForwardResult, GraphError> {
+        if let Some((cached_key, res)) = &self.0.lock().unwrap().entry {
+            if *cached_key == key {
+                trace!("reusing the cached forward pass");
+                return Ok(res.clone());
+            }
+        }
+
+        let res = forward()?;
+        let mut cache = self.0.lock().unwrap();
+        cache.entry = Some((key, res.clone()));
+        cache.passes += 1;
+        Ok(res)
+    }
 }
 
 impl GraphCircuit {
@@ -687,6 +787,20 @@ impl GraphCircuit {
     pub fn model(&self) -> &Model {
         &self.core.model
     }
+
+    /// A digest of the model and its settings, i.e. everything key generation depends on besides
+    /// the SRS. Circuits with the same architecture share a fingerprint, so it can be used to
+    /// cache keys across runs (see [crate::pfsys::create_keys_cached]).
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    pub fn fingerprint(&self) -> Result<String, GraphError> {
+        // when the settings were generated has no bearing on the keys
+        let mut settings = self.core.settings.clone();
+        settings.timestamp = None;
+
+        let mut buffer = bincode::serialize(&self.core.model)?;
+        buffer.extend(serde_json::to_vec(&settings)?);
+        Ok(sha256::digest(buffer))
+    }
     ///
     pub fn save(&self, path: std::path::PathBuf) -> Result<(), GraphError> {
         let f = std::fs::File::create(&path).map_err(|e| {
@@ -810,6 +924,7 @@ impl GraphCircuit {
         Ok(GraphCircuit {
             core,
             graph_witness: GraphWitness::new(inputs, vec![]),
+            forward_cache: ForwardCache::default(),
         })
     }
 
@@ -838,6 +953,7 @@ impl GraphCircuit {
         Ok(GraphCircuit {
             core,
             graph_witness: GraphWitness::new(inputs, vec![]),
+            forward_cache: ForwardCache::default(),
         })
     }
 
@@ -1134,10 +1250,12 @@ impl GraphCircuit {
         Ok(min_bits)
     }
 
-    /// calculate the minimum logrows required for the circuit
+    /// calculate the minimum logrows required for the circuit. `lookup_ranges` are the input
+    /// ranges of each lookup, which size the tables under [TableStrategy::PerLayer].
     pub fn calc_min_logrows(
         &mut self,
         min_max_lookup: Range,
+        lookup_ranges: &[(LookupOp, Range)],
         max_range_size: IntegerRep,
         max_logrows: Option<u32>,
         lookup_safety_margin: f64,
@@ -1222,6 +1340,15 @@ impl GraphCircuit {
         *settings_mut = GraphCircuit::new(model, &settings_mut.run_args)?
             .settings()
             .clone();
+        settings_mut.lookup_ranges = lookup_ranges
+            .iter()
+            .map(|(op, range)| {
+                (
+                    op.clone(),
+                    Self::calc_safe_lookup_range(*range, lookup_safety_margin),
+                )
+            })
+            .collect();
 
         debug!(
             "setting lookup_range to: {:?}, setting logrows to: {}",
@@ -1341,9 +1468,15 @@ impl GraphCircuit {
             }
         }
 
-        let mut model_results =
+        let key = ForwardCacheKey {
+            inputs: inputs.to_vec(),
+            run_args: self.settings().run_args.clone(),
+            region_settings: region_settings.clone(),
+        };
+        let mut model_results = self.forward_cache.get_or_compute(key, || {
             self.model()
-                .forward(inputs, &self.settings().run_args, region_settings)?;
+                .forward(inputs, &self.settings().run_args, region_settings)
+        })?;
 
         if visibility.output.requires_processing() {
             let module_outlets = visibility.output.overwrites_inputs();
@@ -1388,6 +1521,7 @@ impl GraphCircuit {
             max_lookup_inputs: model_results.max_lookup_inputs,
             min_lookup_inputs: model_results.min_lookup_inputs,
             max_range_size: model_results.max_range_size,
+            lookup_ranges: model_results.lookup_ranges,
             version: Some(crate::version().to_string()),
         };
 
@@ -1406,6 +1540,91 @@ impl GraphCircuit {
         Ok(witness)
     }
 
+    /// Quantizes float `inputs` at the model's input scales, runs the forward pass and proves it
+    /// with KZG. Returns the proof along with the outputs dequantized at the model's output
+    /// scales. `params` and `pk` are the SRS and the proving key generated for this circuit.
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    pub fn infer_and_prove(
+        &mut self,
+        inputs: &[Tensor<f32>],
+        params: &ParamsKZG<bn256::Bn256>,
+        pk: &ProvingKey<G1Affine>,
+    ) -> Result<(Snark<Fp, G1Affine>, Vec<Tensor<f32>>), GraphError> {
+        let input_shapes = self.model().graph.input_shapes()?;
+        if inputs.len() != input_shapes.len() {
+            return Err(GraphError::InvalidDims(
+                0,
+                format!(
+                    "expected {} inputs, got {}",
+                    input_shapes.len(),
+                    inputs.len()
+                ),
+            ));
+        }
+
+        let mut quantized = inputs
+            .iter()
+            .zip(self.model().graph.get_input_scales())
+            .zip(input_shapes)
+            .map(|((input, scale), shape)| {
+                let mut t = input
+                    .iter()
+                    .map(|x| {
+                        let x = quantize_float(&(*x as f64), 0.0, scale)?;
+                        Ok(crate::fieldutils::integer_rep_to_felt(x))
+                    })
+                    .collect::<Result<Tensor<Fp>, GraphError>>()?;
+                t.reshape(&shape)?;
+                Ok(t)
+            })
+            .collect::<Result<Vec<_>, GraphError>>()?;
+
+        let run_args = &self.settings().run_args;
+        let region_settings = RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs);
+        let witness = self.forward::<KZGCommitmentScheme<bn256::Bn256>>(
+            &mut quantized,
+            Some(pk.get_vk()),
+            Some(params),
+            region_settings,
+        )?;
+
+        self.load_graph_witness(&witness)?;
+        let public_inputs = self.prepare_public_inputs(&witness)?;
+        let proof_split_commits: Option<crate::pfsys::ProofSplitCommit> = witness.clone().into();
+
+        let snark = crate::pfsys::create_proof_circuit::<
+            KZGCommitmentScheme<bn256::Bn256>,
+            _,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            self.clone(),
+            vec![public_inputs],
+            params,
+            pk,
+            self.settings().check_mode,
+            crate::Commitments::KZG,
+            crate::pfsys::TranscriptType::EVM,
+            proof_split_commits,
+            None,
+        )?;
+
+        // polycommit commitments are computed outside the circuit and swapped in
+        let commitments = witness.get_polycommitments();
+        let snark = if commitments.is_empty() {
+            snark
+        } else {
+            crate::pfsys::swap_proof_commitments_polycommit(&snark, &commitments)?
+        };
+
+        let outputs = witness.get_float_outputs(&self.model().graph.get_output_scales()?);
+        Ok((snark, outputs))
+    }
+
     /// Create a new circuit from a set of input data and [RunArgs].
     #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
     pub fn from_run_args(
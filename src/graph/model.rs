@@ -1,21 +1,24 @@
 use super::errors::GraphError;
 use super::extract_const_quantized_values;
+use super::multiplier_to_scale;
 use super::node::*;
 use super::vars::*;
 use super::GraphSettings;
 use crate::circuit::hybrid::HybridOp;
 use crate::circuit::region::ConstantsMap;
+use crate::circuit::region::LayerFootprint;
+use crate::circuit::region::LayerRange;
 use crate::circuit::region::RegionCtx;
 use crate::circuit::region::RegionSettings;
 use crate::circuit::table::Range;
 use crate::circuit::Input;
 use crate::circuit::InputType;
 use crate::circuit::Unknown;
-use crate::fieldutils::IntegerRep;
+use crate::fieldutils::{felt_to_integer_rep, integer_rep_to_felt, IntegerRep};
 use crate::tensor::ValType;
 use crate::{
     circuit::{lookup::LookupOp, BaseConfig as PolyConfig, CheckMode, Op},
-    tensor::{Tensor, ValTensor},
+    tensor::{Tensor, ValTensor, VarTensor},
     RunArgs,
 };
 use halo2curves::bn256::Fr as Fp;
@@ -23,6 +26,8 @@ use halo2curves::bn256::Fr as Fp;
 #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
 use super::input::GraphData;
 #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+use super::OpHandlers;
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
 use colored::Colorize;
 use halo2_proofs::{
     circuit::{Layouter, Value},
@@ -70,6 +75,8 @@ pub struct ForwardResult {
     pub min_lookup_inputs: IntegerRep,
     /// The max range check size
     pub max_range_size: IntegerRep,
+    /// The smallest and largest input to each lookup operation.
+    pub lookup_ranges: Vec<(LookupOp, Range)>,
 }
 
 impl From<DummyPassRes> for ForwardResult {
@@ -79,6 +86,7 @@ impl From<DummyPassRes> for ForwardResult {
             max_lookup_inputs: res.max_lookup_inputs,
             min_lookup_inputs: res.min_lookup_inputs,
             max_range_size: res.max_range_size,
+            lookup_ranges: res.lookup_ranges,
         }
     }
 }
@@ -95,6 +103,40 @@ pub struct ModelConfig {
 /// Representation of execution graph
 pub type NodeGraph = BTreeMap<usize, NodeType>;
 
+/// The range of the activations a calibration pass observed for one layer of a model.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LayerCalibration {
+    /// the node index of the layer
+    pub idx: usize,
+    /// the name of the layer
+    pub name: String,
+    /// the smallest activation observed
+    pub min: f32,
+    /// the largest activation observed
+    pub max: f32,
+    /// the largest scale at which the layer's activations stay within the table domain, `None`
+    /// if the layer only ever output zeros
+    pub scale: Option<crate::Scale>,
+}
+
+/// Quantization parameters derived from calibration data by [Model::calibrate].
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct QuantParams {
+    /// the largest input scale at which every layer's activations stay within the table domain
+    pub input_scale: crate::Scale,
+    /// the observed range and derived scale of each layer, in layout order
+    pub layers: Vec<LayerCalibration>,
+}
+
+/// The assigned tensors of a model laid out by [Model::layout_with_intermediates].
+#[derive(Clone, Debug)]
+pub struct LaidOutModel {
+    /// the outputs of the model
+    pub outputs: Vec<ValTensor<Fp>>,
+    /// the outputs of every node (including the inputs), keyed by node index
+    pub intermediates: BTreeMap<usize, Vec<ValTensor<Fp>>>,
+}
+
 /// A struct for loading from an Onnx file and converting a computational graph to a circuit.
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub struct DummyPassRes {
@@ -116,6 +158,8 @@ pub struct DummyPassRes {
     pub total_const_size: usize,
     /// lookup ops
     pub lookup_ops: HashSet<LookupOp>,
+    /// the smallest and largest input to each lookup op
+    pub lookup_ranges: Vec<(LookupOp, Range)>,
     /// range checks
     pub range_checks: HashSet<Range>,
     /// max lookup inputs
@@ -126,6 +170,16 @@ pub struct DummyPassRes {
     pub max_range_size: IntegerRep,
     /// outputs
     pub outputs: Vec<Tensor<Fp>>,
+    /// gate and lookup constraints added by each layer
+    pub layer_constraints: crate::circuit::region::LayerConstraints,
+    /// cells occupied by each layer
+    pub layer_footprints: crate::circuit::region::LayerFootprints,
+    /// range of the values each layer output, only tracked in witness generation passes with
+    /// [RegionSettings::with_range_tracking], as used by [Model::calibrate]
+    pub layer_ranges: crate::circuit::region::LayerRanges,
+    /// time spent laying out each layer
+    #[cfg(feature = "timing")]
+    pub layer_timings: crate::circuit::region::LayerTimings,
 }
 
 /// A struct for loading from an Onnx file and converting a computational graph to a circuit.
@@ -137,6 +191,12 @@ pub struct Model {
     pub visibility: VarVisibility,
 }
 
+impl std::fmt::Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.summary())
+    }
+}
+
 ///
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum OutputMapping {
@@ -485,14 +545,31 @@ impl Model {
     /// * `run_args` - [RunArgs]
     #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
     pub fn new(reader: &mut dyn std::io::Read, run_args: &RunArgs) -> Result<Self, GraphError> {
+        Self::new_with_op_handlers(reader, run_args, &OpHandlers::default())
+    }
+
+    /// Creates a `Model` from a specified path to an Onnx file, building the nodes of the ops
+    /// `handlers` covers with those handlers instead of the native conversion.
+    /// # Arguments
+    /// * `reader` - A reader for an Onnx file.
+    /// * `run_args` - [RunArgs]
+    /// * `handlers` - [OpHandlers]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    pub fn new_with_op_handlers(
+        reader: &mut dyn std::io::Read,
+        run_args: &RunArgs,
+        handlers: &OpHandlers,
+    ) -> Result<Self, GraphError> {
         let visibility = VarVisibility::from_args(run_args)?;
 
-        let graph = Self::load_onnx_model(reader, run_args, &visibility)?;
+        let graph = Self::load_onnx_model(reader, run_args, &visibility, handlers)?;
 
         let om = Model { graph, visibility };
 
         debug!("\n {}", om.table_nodes());
 
+        om.verify_scales()?;
+
         Ok(om)
     }
 
@@ -533,23 +610,9 @@ impl Model {
         Ok(result)
     }
 
-    /// Generate model parameters for the circuit
-    pub fn gen_params(
-        &self,
-        run_args: &RunArgs,
-        check_mode: CheckMode,
-    ) -> Result<GraphSettings, GraphError> {
-        let instance_shapes = self.instance_shapes()?;
-        #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
-        debug!(
-            "{} {} {}",
-            "model has".blue(),
-            instance_shapes.len().to_string().blue(),
-            "instances".blue()
-        );
-
-        let inputs: Vec<ValTensor<Fp>> = self
-            .graph
+    /// Placeholder inputs for a dummy layout of the model, unknown unless the inputs are fixed
+    fn dummy_inputs(&self) -> Result<Vec<ValTensor<Fp>>, GraphError> {
+        self.graph
             .input_shapes()?
             .iter()
             .map(|shape| {
@@ -568,7 +631,25 @@ impl Model {
                 t.reshape(shape)?;
                 Ok(t)
             })
-            .collect::<Result<Vec<_>, GraphError>>()?;
+            .collect()
+    }
+
+    /// Generate model parameters for the circuit
+    pub fn gen_params(
+        &self,
+        run_args: &RunArgs,
+        check_mode: CheckMode,
+    ) -> Result<GraphSettings, GraphError> {
+        let instance_shapes = self.instance_shapes()?;
+        #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+        debug!(
+            "{} {} {}",
+            "model has".blue(),
+            instance_shapes.len().to_string().blue(),
+            "instances".blue()
+        );
+
+        let inputs = self.dummy_inputs()?;
 
         let res = self.dummy_layout(
             run_args,
@@ -585,6 +666,9 @@ impl Model {
             num_rows: res.num_rows,
             total_assignments: res.linear_coord,
             required_lookups: res.lookup_ops.into_iter().collect(),
+            // the dummy inputs say nothing about the ranges the lookups see in practice, these
+            // are calibrated from data
+            lookup_ranges: vec![],
             max_dynamic_input_len: res.max_dynamic_input_len,
             required_range_checks: res.range_checks.into_iter().collect(),
             model_output_scales: self.graph.get_output_scales()?,
@@ -633,6 +717,125 @@ impl Model {
         Ok(res.into())
     }
 
+    /// Runs a forward pass on already quantized inputs and returns the quantized outputs. The pass
+    /// is a dummy layout of the model, the same arithmetic witness generation runs, so every
+    /// divide, rebase and lookup rounds exactly as the circuit constrains it and the outputs are
+    /// the values the circuit's output cells hold.
+    /// # Arguments
+    /// * `model_inputs` - The quantized inputs to the model.
+    /// * `run_args` - [RunArgs]
+    pub fn forward_quantized(
+        &self,
+        model_inputs: &[Tensor<IntegerRep>],
+        run_args: &RunArgs,
+    ) -> Result<Vec<Tensor<IntegerRep>>, GraphError> {
+        let inputs = model_inputs
+            .iter()
+            .map(|x| x.map(integer_rep_to_felt::<Fp>))
+            .collect::<Vec<_>>();
+        let region_settings = RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs);
+        let res = self.forward(&inputs, run_args, region_settings)?;
+        Ok(res
+            .outputs
+            .iter()
+            .map(|output| output.map(felt_to_integer_rep))
+            .collect())
+    }
+
+    /// Post training quantization calibration: runs the model over `samples` and derives scales
+    /// from the range of the activations each layer outputs. A layer's scale is the largest at
+    /// which all of its activations fit in the table domain `run_args.lookup_range`. Parameters
+    /// keep their scale, so the activations of every layer move with the input scale and the
+    /// calibrated input scale is the current one shifted by the smallest headroom of any layer.
+    /// # Arguments
+    /// * `samples` - Float inputs to a model with a single input, quantized at its input scale.
+    /// * `run_args` - [RunArgs]
+    pub fn calibrate(
+        &self,
+        samples: &[Tensor<f32>],
+        run_args: &RunArgs,
+    ) -> Result<QuantParams, GraphError> {
+        if self.graph.inputs.len() != 1 {
+            return Err(GraphError::Calibration(format!(
+                "expected a model with a single input, got {} inputs",
+                self.graph.inputs.len()
+            )));
+        }
+        if samples.is_empty() {
+            return Err(GraphError::Calibration("no samples".to_string()));
+        }
+        let input_scale = self.graph.get_input_scales()[0];
+
+        let mut ranges = crate::circuit::region::LayerRanges::default();
+        for sample in samples {
+            let quantized = sample
+                .iter()
+                .map(|x| {
+                    let x = super::quantize_float(&(*x as f64), 0.0, input_scale)?;
+                    Ok(ValType::Value(Value::known(integer_rep_to_felt::<Fp>(x))))
+                })
+                .collect::<Result<Vec<_>, GraphError>>()?;
+            let mut input: ValTensor<Fp> = quantized.into();
+            input.reshape(sample.dims())?;
+
+            let region_settings =
+                RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs)
+                    .with_range_tracking();
+            let res = self.dummy_layout(run_args, &[input], region_settings)?;
+            for layer in res.layer_ranges.layers {
+                ranges.record(layer);
+            }
+        }
+
+        // the number of times a value can be doubled before it leaves the table domain
+        let (lo, hi) = run_args.lookup_range;
+        let headroom = |value: IntegerRep, bound: IntegerRep| -> Option<crate::Scale> {
+            match value {
+                0 => None,
+                _ => Some((bound as f64 / value as f64).log2().floor() as crate::Scale),
+            }
+        };
+
+        let mut layers = vec![];
+        for range in ranges.layers {
+            // parameters don't move with the input scale
+            let node = self
+                .graph
+                .nodes
+                .get(&range.idx)
+                .ok_or(GraphError::MissingNode(range.idx))?;
+            if node.is_constant() {
+                continue;
+            }
+            let scale = node.out_scales()[0];
+            let headroom = [
+                headroom(range.max.max(0), hi),
+                headroom(range.min.min(0), lo),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+
+            let mult = super::scale_to_multiplier(scale) as f32;
+            layers.push((
+                headroom,
+                LayerCalibration {
+                    idx: range.idx,
+                    name: range.name,
+                    min: range.min as f32 / mult,
+                    max: range.max as f32 / mult,
+                    scale: headroom.map(|h| scale + h),
+                },
+            ));
+        }
+
+        let shift = layers.iter().filter_map(|(h, _)| *h).min().unwrap_or(0);
+        Ok(QuantParams {
+            input_scale: input_scale + shift,
+            layers: layers.into_iter().map(|(_, l)| l).collect(),
+        })
+    }
+
     /// Loads an Onnx model from a specified path.
     /// # Arguments
     /// * `reader` - A reader for an Onnx file.
@@ -702,6 +905,7 @@ impl Model {
         reader: &mut dyn std::io::Read,
         run_args: &RunArgs,
         visibility: &VarVisibility,
+        handlers: &OpHandlers,
     ) -> Result<ParsedNodes, GraphError> {
         let start_time = instant::Instant::now();
 
@@ -716,6 +920,7 @@ impl Model {
             &symbol_values,
             None,
             None,
+            handlers,
         )?;
 
         debug!("\n {}", model);
@@ -773,14 +978,184 @@ impl Model {
         format!("{} \n{}", string, table)
     }
 
+    /// Returns the total number of parameters (elements of constant nodes) in the model, including subgraphs.
+    pub fn num_params(&self) -> usize {
+        self.graph
+            .nodes
+            .values()
+            .map(|node| match node {
+                NodeType::Node(n) => n
+                    .opkind
+                    .get_constant()
+                    .map(|c| c.quantized_values.len())
+                    .unwrap_or(0),
+                NodeType::SubGraph { model, .. } => model.num_params(),
+            })
+            .sum()
+    }
+
+    /// Checks that the scale recorded for every node is the scale its op produces from the scales
+    /// of its inputs, e.g. that an einsum sums its input scales and that a rebase divides back down
+    /// to its target scale. A mismatch means the fixed point values flowing into some node are
+    /// interpreted at the wrong scale, which silently corrupts every downstream result.
+    ///
+    /// This runs whenever a model is loaded, after the rebases done while parsing, so the scales
+    /// checked are the ones the circuit is laid out with.
+    pub fn verify_scales(&self) -> Result<(), GraphError> {
+        for (idx, node) in &self.graph.nodes {
+            match node {
+                NodeType::Node(n) => {
+                    let in_scales = n
+                        .inputs
+                        .iter()
+                        .map(|(i, outlet)| {
+                            self.graph
+                                .nodes
+                                .get(i)
+                                .and_then(|input| input.out_scales().get(*outlet).copied())
+                                .ok_or(GraphError::MissingInput(*idx))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let expected = match &n.opkind {
+                        // a rebase can be folded into again after its target scale is recorded,
+                        // e.g. to match a subgraph's output scale, so work the scale out from
+                        // what the rebase actually divides by
+                        SupportedOp::RebaseScale(r) => {
+                            r.inner.out_scale(in_scales)? - multiplier_to_scale(r.multiplier)
+                        }
+                        op => op.out_scale(in_scales)?,
+                    };
+                    if expected != n.out_scale {
+                        return Err(GraphError::ScaleMismatch(*idx, expected, n.out_scale));
+                    }
+                }
+                NodeType::SubGraph { model, .. } => model.verify_scales()?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a summary of the model with one row per (non constant) node, listing the op,
+    /// its output shapes and the number of parameters it consumes from constant inputs.
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{:<6} {:<48} {:<24} {:>10}\n",
+            "idx", "layer", "output shape", "params"
+        );
+        for (idx, node) in &self.graph.nodes {
+            if node.is_constant() {
+                continue;
+            }
+            let params = match node {
+                NodeType::Node(_) => node
+                    .inputs()
+                    .iter()
+                    .filter_map(|(i, _)| match self.graph.nodes.get(i) {
+                        Some(NodeType::Node(n)) => {
+                            n.opkind.get_constant().map(|c| c.quantized_values.len())
+                        }
+                        _ => None,
+                    })
+                    .sum::<usize>(),
+                NodeType::SubGraph { model, .. } => model.num_params(),
+            };
+            let out_dims = node
+                .out_dims()
+                .iter()
+                .map(|d| format!("{:?}", d))
+                .join(", ");
+            summary.push_str(&format!(
+                "{:<6} {:<48} {:<24} {:>10}\n",
+                idx,
+                node.as_str(),
+                out_dims,
+                params
+            ));
+        }
+        summary.push_str(&format!("total params: {}\n", self.num_params()));
+        summary
+    }
+
+    /// Renders the cells each layer occupies as an ASCII grid, from a dummy layout with the given
+    /// [RunArgs]. Each line is a layer and each character of its bar a slice of the advice cells
+    /// in use, `#` where the layer is laid out and `|` wherever a layer spills into the next
+    /// block of advice columns. The fixed column is the number of constants the layer assigns.
+    pub fn render_layout(&self, run_args: &RunArgs) -> Result<String, GraphError> {
+        const WIDTH: usize = 64;
+
+        let inputs = self.dummy_inputs()?;
+        let res = self.dummy_layout(
+            run_args,
+            &inputs,
+            RegionSettings::all_false(run_args.decomp_base, run_args.decomp_legs),
+        )?;
+
+        let advice = VarTensor::dummy(run_args.logrows as usize, run_args.num_inner_cols);
+        let used = res.linear_coord.max(1);
+        let cells_per_char = used.div_ceil(WIDTH);
+        let num_chars = used.div_ceil(cells_per_char);
+
+        let mut grid = format!(
+            "advice: {} cells in {} block(s) of {} columns x {} rows, {} cells per char\n",
+            res.linear_coord,
+            res.linear_coord.div_ceil(advice.block_size()).max(1),
+            advice.num_inner_cols(),
+            advice.col_size(),
+            cells_per_char
+        );
+        grid.push_str(&format!(
+            "{:<6} {:<40} {:<width$} {:>8}\n",
+            "idx",
+            "layer",
+            "advice",
+            "fixed",
+            width = num_chars + 2
+        ));
+        for layer in &res.layer_footprints.layers {
+            let bar = (0..num_chars)
+                .map(|c| {
+                    let cells = c * cells_per_char..(c + 1) * cells_per_char;
+                    let occupied = cells.start < layer.advice.end && layer.advice.start < cells.end;
+                    // the last block boundary at or before the end of this slice
+                    let boundary = (cells.end - 1) / advice.block_size() * advice.block_size();
+                    let spills = occupied
+                        && cells.contains(&boundary)
+                        && layer.advice.start < boundary
+                        && boundary < layer.advice.end;
+                    match (occupied, spills) {
+                        (true, true) => '|',
+                        (true, false) => '#',
+                        _ => '.',
+                    }
+                })
+                .collect::<String>();
+            grid.push_str(&format!(
+                "{:<6} {:<40} [{}] {:>8}\n",
+                layer.idx, layer.name, bar, layer.constants
+            ));
+        }
+        let instance_shapes = self.instance_shapes()?;
+        grid.push_str(&format!(
+            "instance: {} column(s) holding {} cells\n",
+            instance_shapes.len(),
+            instance_shapes
+                .iter()
+                .map(|s| s.iter().product::<usize>())
+                .sum::<usize>()
+        ));
+        Ok(grid)
+    }
+
     /// Creates ezkl nodes from a tract graph
     /// # Arguments
     /// * `graph` - A tract graph.
     /// * `run_args` - [RunArgs]
     /// * `visibility` - Which inputs to the model are public and private (params, inputs, outputs) using [VarVisibility].
     /// * `input_scales` - The scales of the model's inputs.
+    /// * `handlers` - Custom handlers for onnx ops, see [OpHandlers].
 
     #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    #[allow(clippy::too_many_arguments)]
     pub fn nodes_from_graph(
         graph: &Graph<TypedFact, Box<dyn TypedOp>>,
         run_args: &RunArgs,
@@ -789,6 +1164,7 @@ impl Model {
         symbol_values: &SymbolValues,
         override_input_scales: Option<Vec<crate::Scale>>,
         override_output_scales: Option<HashMap<usize, crate::Scale>>,
+        handlers: &OpHandlers,
     ) -> Result<BTreeMap<usize, NodeType>, GraphError> {
         use crate::graph::node_output_shapes;
 
@@ -881,6 +1257,7 @@ impl Model {
                         symbol_values,
                         Some(input_scales.clone()),
                         Some(output_scale_override),
+                        handlers,
                     )?;
 
                     let subgraph = ParsedNodes {
@@ -934,8 +1311,15 @@ impl Model {
                     );
                 }
                 None => {
-                    let mut n =
-                        Node::new(n.clone(), &mut nodes, scales, i, symbol_values, run_args)?;
+                    let mut n = Node::new(
+                        n.clone(),
+                        &mut nodes,
+                        scales,
+                        i,
+                        symbol_values,
+                        run_args,
+                        handlers,
+                    )?;
                     if let Some(ref scales) = override_input_scales {
                         if let Some(inp) = n.opkind.get_input() {
                             let scale = scales[input_idx];
@@ -1048,7 +1432,6 @@ impl Model {
     ) -> Result<PolyConfig<Fp>, GraphError> {
         debug!("configuring model");
 
-        let lookup_range = settings.run_args.lookup_range;
         let logrows = settings.run_args.logrows as usize;
         let required_lookups = settings.required_lookups.clone();
         let required_range_checks = settings.required_range_checks.clone();
@@ -1068,6 +1451,7 @@ impl Model {
         let output = &vars.advices[2];
         let index = &vars.advices[1];
         for op in required_lookups {
+            let lookup_range = settings.table_range(&op);
             base_gate.configure_lookup(meta, input, output, index, lookup_range, logrows, &op)?;
         }
 
@@ -1137,8 +1521,7 @@ impl Model {
                 results.insert(*input_idx, vec![instance]);
                 vars.increment_instance_idx();
             } else {
-                let mut input = inputs[i].clone();
-                input.reshape(&input_shapes[i])?;
+                let input = self.shape_input(*input_idx, &inputs[i], &input_shapes[i])?;
                 results.insert(*input_idx, vec![input]);
             }
         }
@@ -1212,6 +1595,15 @@ impl Model {
                 // Then number of columns in the circuits
                 #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
                 thread_safe_region.debug_report();
+                info!(
+                    "constraints per layer:\n{}",
+                    thread_safe_region.layer_constraints()
+                );
+                #[cfg(feature = "timing")]
+                info!(
+                    "layer layout times:\n{}",
+                    thread_safe_region.layer_timings()
+                );
 
                 *constants = thread_safe_region.assigned_constants().clone();
 
@@ -1225,6 +1617,101 @@ impl Model {
         Ok(outputs)
     }
 
+    /// Lays out the nodes of the model in an existing region and returns, alongside the model's
+    /// outputs, the assigned output of every intermediate node. Extra constraints (e.g. auxiliary
+    /// losses on a hidden layer) can then be laid out in the same region with `config.base`.
+    /// Inputs are treated as private witnesses and, unlike [Model::layout], the tables and range
+    /// checks must already have been laid out by the caller.
+    pub fn layout_with_intermediates(
+        &self,
+        config: &mut ModelConfig,
+        region: &mut RegionCtx<Fp>,
+        inputs: &[ValTensor<Fp>],
+    ) -> Result<LaidOutModel, GraphError> {
+        if inputs.len() != self.graph.inputs.len() {
+            return Err(GraphError::InvalidDims(
+                0,
+                format!(
+                    "expected {} inputs, got {}",
+                    self.graph.inputs.len(),
+                    inputs.len()
+                ),
+            ));
+        }
+
+        let mut results = BTreeMap::<usize, Vec<ValTensor<Fp>>>::new();
+        let input_shapes = self.graph.input_shapes()?;
+        for (i, input_idx) in self.graph.inputs.iter().enumerate() {
+            let input = self.shape_input(*input_idx, &inputs[i], &input_shapes[i])?;
+            results.insert(*input_idx, vec![input]);
+        }
+
+        let outputs = self.layout_nodes(config, region, &mut results)?;
+
+        Ok(LaidOutModel {
+            outputs,
+            intermediates: results,
+        })
+    }
+
+    /// Reshapes an input fed to input node `idx` to the node's shape. Inputs have to be passed
+    /// in that shape or flattened, anything else (e.g. a `[3, 1]` input for a `[1, 3]` node) is
+    /// a wiring mistake and errors with [GraphError::ShapeMismatch] before any layer is laid out.
+    fn shape_input(
+        &self,
+        idx: usize,
+        input: &ValTensor<Fp>,
+        shape: &[usize],
+    ) -> Result<ValTensor<Fp>, GraphError> {
+        let flattened = input.dims().len() == 1 && input.len() == shape.iter().product::<usize>();
+        if input.dims() != shape && !flattened {
+            return Err(GraphError::ShapeMismatch {
+                layer: idx,
+                expected: shape.to_vec(),
+                got: input.dims().to_vec(),
+            });
+        }
+        let mut input = input.clone();
+        input.reshape(shape)?;
+        Ok(input)
+    }
+
+    /// Checks that the values a node is about to be laid out with have the shapes its inputs
+    /// were parsed with: the input node's own shape for inputs, the producing node's output shape
+    /// otherwise. Errors with [GraphError::ShapeMismatch] naming the node being laid out.
+    fn check_input_dims(
+        &self,
+        idx: usize,
+        node: &NodeType,
+        values: &[ValTensor<Fp>],
+    ) -> Result<(), GraphError> {
+        let expected = if node.is_input() {
+            vec![Some(node.out_dims()[0].clone())]
+        } else {
+            node.inputs()
+                .iter()
+                .map(|(i, _)| match self.graph.nodes.get(i) {
+                    Some(NodeType::Node(n)) => Some(n.out_dims.clone()),
+                    // subgraph outputs are stacked across iterations, their shapes are checked
+                    // by the subgraph's own nodes
+                    _ => None,
+                })
+                .collect()
+        };
+        for (value, expected) in values.iter().zip(expected) {
+            if let Some(expected) = expected {
+                if value.dims() != expected.as_slice() {
+                    return Err(GraphError::ShapeMismatch {
+                        layer: idx,
+                        expected,
+                        got: value.dims().to_vec(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn layout_nodes(
         &self,
         config: &mut ModelConfig,
@@ -1269,8 +1756,12 @@ impl Model {
                 "input dims {:?}",
                 values.iter().map(|v| v.dims()).collect_vec()
             );
+            self.check_input_dims(*idx, node, &values)?;
 
             let start = instant::Instant::now();
+            let constraints_before = region.constraint_count();
+            let coord_before = region.linear_coord();
+            let constants_before = region.total_constants();
             match &node {
                 NodeType::Node(n) => {
                     let res = if node.is_constant() && node.num_uses() == 1 {
@@ -1286,7 +1777,7 @@ impl Model {
                             .base
                             .layout(region, &values, n.opkind.clone_dyn())
                             .map_err(|e| {
-                                error!("{}", e);
+                                error!("node {} ({}): {}", idx, node.as_str(), e);
                                 halo2_proofs::plonk::Error::Synthesis
                             })?
                     };
@@ -1409,6 +1900,37 @@ impl Model {
                 }
             }
             debug!("------------ layout of {} took {:?}", idx, start.elapsed());
+            region.record_layer_constraints(
+                *idx,
+                &node.as_str(),
+                region.constraint_count() - constraints_before,
+            );
+            region.record_layer_footprint(LayerFootprint {
+                idx: *idx,
+                name: node.as_str(),
+                advice: coord_before..region.linear_coord(),
+                constants: region.total_constants() - constants_before,
+            });
+            if region.is_dummy() && region.witness_gen() && region.track_ranges() {
+                // every value is known when generating a witness, so keep track of their range
+                let evals = results
+                    .get(idx)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| v.int_evals().ok().filter(|evals| evals.len() == v.len()))
+                    .collect_vec();
+                if let Some((min, max)) = evals.iter().flat_map(|t| t.iter()).minmax().into_option()
+                {
+                    region.record_layer_range(LayerRange {
+                        idx: *idx,
+                        name: node.as_str(),
+                        min: *min,
+                        max: *max,
+                    });
+                }
+            }
+            #[cfg(feature = "timing")]
+            region.record_layer_time(*idx, &node.as_str(), start.elapsed());
         }
 
         // we do this so we can support multiple passes of the same model and have deterministic results (Non-assigned inputs etc... etc...)
@@ -1443,8 +1965,20 @@ impl Model {
 
         let mut results = BTreeMap::<usize, Vec<ValTensor<Fp>>>::new();
 
+        if inputs.len() != self.graph.inputs.len() {
+            return Err(GraphError::InvalidDims(
+                0,
+                format!(
+                    "expected {} inputs, got {}",
+                    self.graph.inputs.len(),
+                    inputs.len()
+                ),
+            ));
+        }
+        let input_shapes = self.graph.input_shapes()?;
         for (i, input_idx) in self.graph.inputs.iter().enumerate() {
-            results.insert(*input_idx, vec![inputs[i].clone()]);
+            let input = self.shape_input(*input_idx, &inputs[i], &input_shapes[i])?;
+            results.insert(*input_idx, vec![input]);
         }
 
         let mut dummy_config =
@@ -1511,6 +2045,7 @@ impl Model {
             max_dynamic_input_len: region.max_dynamic_input_len(),
             total_const_size: region.total_constants(),
             lookup_ops: region.used_lookups(),
+            lookup_ranges: region.lookup_ranges().into_iter().sorted().collect(),
             range_checks: region.used_range_checks(),
             max_lookup_inputs: region.max_lookup_inputs(),
             min_lookup_inputs: region.min_lookup_inputs(),
@@ -1520,6 +2055,11 @@ impl Model {
             num_shuffles: region.shuffle_index(),
             shuffle_col_coord: region.shuffle_col_coord(),
             outputs,
+            layer_constraints: region.layer_constraints().clone(),
+            layer_footprints: region.layer_footprints().clone(),
+            layer_ranges: region.layer_ranges().clone(),
+            #[cfg(feature = "timing")]
+            layer_timings: region.layer_timings().clone(),
         };
 
         Ok(res)
@@ -1612,3 +2152,817 @@ impl Model {
         Ok(instance_types)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::ops::poly::PolyOp;
+    use crate::circuit::region::ConstraintCount;
+    use crate::circuit::Constant;
+    use crate::graph::Visibility;
+
+    fn node(
+        opkind: SupportedOp,
+        idx: usize,
+        inputs: Vec<Outlet>,
+        out_dims: Vec<usize>,
+    ) -> NodeType {
+        NodeType::Node(Node {
+            opkind,
+            out_scale: 0,
+            inputs,
+            out_dims,
+            idx,
+            num_uses: 1,
+        })
+    }
+
+    fn constant(values: &[IntegerRep], dims: &[usize]) -> SupportedOp {
+        let mut quantized = Tensor::<Fp>::from(values.iter().map(|x| integer_rep_to_felt(*x)));
+        quantized.reshape(dims).unwrap();
+        quantized.set_visibility(&Visibility::Fixed);
+        quantized.set_scale(0);
+        let mut raw = Tensor::<f32>::from(values.iter().map(|x| *x as f32));
+        raw.reshape(dims).unwrap();
+        SupportedOp::Constant(Constant::new(quantized, raw, false))
+    }
+
+    fn mlp() -> Model {
+        mlp_with_weights(&[0; 12], &[0; 8])
+    }
+
+    // relu(w1 . relu(w0 . x)) with w0 of shape [4, 3] and w1 of shape [2, 4]
+    fn mlp_with_weights(w0: &[IntegerRep], w1: &[IntegerRep]) -> Model {
+        let relu = || {
+            SupportedOp::Linear(PolyOp::LeakyReLU {
+                slope: 0.0.into(),
+                scale: 1,
+            })
+        };
+        let affine = || {
+            SupportedOp::Linear(PolyOp::Einsum {
+                equation: "ij,kj->ik".to_string(),
+            })
+        };
+
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            0,
+            node(
+                SupportedOp::Input(Input {
+                    scale: 0,
+                    datum_type: InputType::F32,
+                    decomp: true,
+                }),
+                0,
+                vec![],
+                vec![1, 3],
+            ),
+        );
+        nodes.insert(1, node(constant(w0, &[4, 3]), 1, vec![], vec![4, 3]));
+        nodes.insert(2, node(affine(), 2, vec![(0, 0), (1, 0)], vec![1, 4]));
+        nodes.insert(3, node(relu(), 3, vec![(2, 0)], vec![1, 4]));
+        nodes.insert(4, node(constant(w1, &[2, 4]), 4, vec![], vec![2, 4]));
+        nodes.insert(5, node(affine(), 5, vec![(3, 0), (4, 0)], vec![1, 2]));
+        nodes.insert(6, node(relu(), 6, vec![(5, 0)], vec![1, 2]));
+
+        Model {
+            graph: ParsedNodes {
+                nodes,
+                inputs: vec![0],
+                outputs: vec![(6, 0)],
+                output_types: vec![InputType::F32],
+            },
+            visibility: VarVisibility::default(),
+        }
+    }
+
+    #[test]
+    fn test_mlp_summary() {
+        let model = mlp();
+        assert_eq!(model.num_params(), 20);
+
+        let summary = model.summary();
+        let rows = summary.lines().collect::<Vec<_>>();
+        // header, input, 2 x (affine, relu), total
+        assert_eq!(rows.len(), 7);
+        assert!(rows[1].contains("Input") && rows[1].contains("[1, 3]"));
+        assert!(
+            rows[2].contains("EINSUM") && rows[2].contains("[1, 4]") && rows[2].ends_with("12")
+        );
+        assert!(rows[3].contains("LEAKYRELU") && rows[3].contains("[1, 4]"));
+        assert!(rows[4].contains("EINSUM") && rows[4].contains("[1, 2]") && rows[4].ends_with("8"));
+        assert!(rows[5].contains("LEAKYRELU") && rows[5].contains("[1, 2]"));
+        assert_eq!(rows[6], "total params: 20");
+        assert_eq!(format!("{}", model), summary);
+    }
+
+    const W0: [IntegerRep; 12] = [1, 2, 3, -4, 0, 2, 5, -1, -1, 0, -3, 1];
+    const W1: [IntegerRep; 8] = [1, -1, 2, 0, -2, 3, 0, 1];
+
+    // the MLP with weights W0 and W1 over plain integers
+    fn mlp_reference(x: &[IntegerRep]) -> Vec<IntegerRep> {
+        let dense_relu = |w: &[IntegerRep], x: &[IntegerRep]| {
+            w.chunks(x.len())
+                .map(|row| {
+                    row.iter()
+                        .zip(x)
+                        .map(|(w, x)| w * x)
+                        .sum::<IntegerRep>()
+                        .max(0)
+                })
+                .collect::<Vec<_>>()
+        };
+        dense_relu(&W1, &dense_relu(&W0, x))
+    }
+
+    #[test]
+    fn test_forward_quantized() {
+        let model = mlp_with_weights(&W0, &W1);
+
+        // worked by hand, eg. for [3, -2, 2] the hidden layer is relu([5, -8, 15, 8]) and the
+        // output relu([35, -2])
+        let cases = [
+            ([3, -2, 2], [35, 0]),
+            ([1, 1, 1], [12, 0]),
+            ([-7, 5, 0], [0, 78]),
+            ([0, 0, 0], [0, 0]),
+        ];
+
+        let run_args = RunArgs::default();
+        for (x, expected) in cases {
+            assert_eq!(mlp_reference(&x), expected);
+
+            let input = Tensor::new(Some(&x), &[1, 3]).unwrap();
+            let outputs = model
+                .forward_quantized(&[input.clone()], &run_args)
+                .unwrap();
+            assert_eq!(outputs.len(), 1);
+            assert_eq!(outputs[0].dims(), &[1, 2]);
+            assert_eq!(outputs[0].to_vec(), expected);
+
+            // and these are the values the circuit witnesses for its outputs
+            let witnessed = model
+                .forward(
+                    &[input.map(integer_rep_to_felt)],
+                    &run_args,
+                    RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs),
+                )
+                .unwrap();
+            assert_eq!(
+                witnessed.outputs[0].map(felt_to_integer_rep).to_vec(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let model = mlp_with_weights(&W0, &W1);
+
+        let run_args = RunArgs {
+            lookup_range: (-1024, 1024),
+            ..RunArgs::default()
+        };
+        let samples = [[3., -2., 2.], [1., 1., 1.], [-7., 5., 0.]]
+            .iter()
+            .map(|x| Tensor::new(Some(x), &[1, 3]).unwrap())
+            .collect::<Vec<_>>();
+
+        let params = model.calibrate(&samples, &run_args).unwrap();
+        // the input, the two affines and the two relus
+        assert_eq!(params.layers.len(), 5);
+        assert_eq!(params.layers[0].min, -7.0);
+        assert_eq!(params.layers[0].max, 5.0);
+
+        // whether the activations of every layer stay in the table domain for the samples
+        let in_domain = |scale: crate::Scale| {
+            samples.iter().all(|x| {
+                let input = x.map(|x| {
+                    let x = crate::graph::quantize_float(&(x as f64), 0.0, scale).unwrap();
+                    ValType::Value(Value::known(integer_rep_to_felt::<Fp>(x)))
+                });
+                let res = model
+                    .dummy_layout(
+                        &run_args,
+                        &[input.into()],
+                        RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs)
+                            .with_range_tracking(),
+                    )
+                    .unwrap();
+                res.layer_ranges
+                    .layers
+                    .iter()
+                    .all(|l| l.min >= run_args.lookup_range.0 && l.max <= run_args.lookup_range.1)
+            })
+        };
+        assert!(params.input_scale > 0);
+        assert!(in_domain(params.input_scale));
+        // and no larger input scale does
+        assert!(!in_domain(params.input_scale + 1));
+
+        // other witness generation passes don't pay for tracking ranges
+        let input = Tensor::new(Some(&[3, -2, 2]), &[1, 3]).unwrap();
+        let res = model
+            .dummy_layout(
+                &run_args,
+                &[ValTensor::from_integer_rep_tensor(input)],
+                RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs),
+            )
+            .unwrap();
+        assert!(res.layer_ranges.layers.is_empty());
+    }
+
+    #[test]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    fn test_infer_and_prove() {
+        use crate::graph::GraphCircuit;
+        use crate::pfsys::{create_keys, srs::gen_srs, verify_proof_circuit};
+        use halo2_proofs::poly::commitment::Params;
+        use halo2_proofs::poly::kzg::{
+            commitment::KZGCommitmentScheme, multiopen::VerifierSHPLONK, strategy::SingleStrategy,
+        };
+        use halo2curves::bn256::Bn256;
+        use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+
+        // a small decomposition base keeps the range check tables within a few rows
+        let run_args = RunArgs {
+            logrows: 10,
+            decomp_base: 128,
+            decomp_legs: 2,
+            ..RunArgs::default()
+        };
+        let mut circuit = GraphCircuit::new(mlp_with_weights(&W0, &W1), &run_args).unwrap();
+
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(run_args.logrows);
+        let pk = create_keys::<KZGCommitmentScheme<Bn256>, GraphCircuit>(&circuit, &params, true)
+            .unwrap();
+
+        // at scale 0 these quantize to [3, -2, 2]
+        let input = Tensor::new(Some(&[3.2, -2.4, 1.6]), &[1, 3]).unwrap();
+        let (snark, outputs) = circuit.infer_and_prove(&[input], &params, &pk).unwrap();
+
+        let expected = mlp_reference(&[3, -2, 2])
+            .into_iter()
+            .map(|x| x as f32)
+            .collect::<Vec<_>>();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].to_vec(), expected);
+
+        let res = verify_proof_circuit::<
+            VerifierSHPLONK<'_, Bn256>,
+            KZGCommitmentScheme<Bn256>,
+            _,
+            _,
+            EvmTranscript<_, _, _, _>,
+        >(
+            &snark,
+            &params,
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            params.n(),
+        );
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    fn test_forward_cache() {
+        use crate::graph::GraphCircuit;
+        use crate::pfsys::{create_keys, srs::gen_srs};
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+        use halo2curves::bn256::Bn256;
+
+        let run_args = RunArgs {
+            logrows: 10,
+            decomp_base: 128,
+            decomp_legs: 2,
+            ..RunArgs::default()
+        };
+        let mut circuit = GraphCircuit::new(mlp_with_weights(&W0, &W1), &run_args).unwrap();
+        let region_settings = RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs);
+
+        // generating the witness runs the forward pass ...
+        let mut quantized = vec![Tensor::new(Some(&[3, -2, 2]), &[1, 3])
+            .unwrap()
+            .map(integer_rep_to_felt)];
+        circuit
+            .forward::<KZGCommitmentScheme<Bn256>>(&mut quantized, None, None, region_settings)
+            .unwrap();
+        assert_eq!(circuit.forward_cache.passes(), 1);
+
+        // ... which key generation and proving the same input reuse
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(run_args.logrows);
+        let pk = create_keys::<KZGCommitmentScheme<Bn256>, GraphCircuit>(&circuit, &params, true)
+            .unwrap();
+        let input = Tensor::new(Some(&[3.2, -2.4, 1.6]), &[1, 3]).unwrap();
+        circuit.infer_and_prove(&[input], &params, &pk).unwrap();
+        assert_eq!(circuit.forward_cache.passes(), 1);
+
+        // a new input invalidates the cached pass
+        let input = Tensor::new(Some(&[1.0, 1.0, 1.0]), &[1, 3]).unwrap();
+        let (_, outputs) = circuit.infer_and_prove(&[input], &params, &pk).unwrap();
+        assert_eq!(circuit.forward_cache.passes(), 2);
+        let expected = mlp_reference(&[1, 1, 1])
+            .into_iter()
+            .map(|x| x as f32)
+            .collect::<Vec<_>>();
+        assert_eq!(outputs[0].to_vec(), expected);
+    }
+
+    #[test]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    fn test_alternate_floor_planner() {
+        use crate::circuit::modules::{ModulePlanner, WithPlanner};
+        use crate::graph::GraphCircuit;
+        use halo2_proofs::circuit::SimpleFloorPlanner;
+        use halo2_proofs::dev::MockProver;
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+        use halo2curves::bn256::Bn256;
+
+        let run_args = RunArgs {
+            logrows: 10,
+            decomp_base: 128,
+            decomp_legs: 2,
+            ..RunArgs::default()
+        };
+        let mut circuit = GraphCircuit::new(mlp_with_weights(&W0, &W1), &run_args).unwrap();
+        let region_settings = RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs);
+
+        let mut quantized = vec![Tensor::new(Some(&[3, -2, 2]), &[1, 3])
+            .unwrap()
+            .map(integer_rep_to_felt)];
+        let witness = circuit
+            .forward::<KZGCommitmentScheme<Bn256>>(&mut quantized, None, None, region_settings)
+            .unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+        // the mlp has no modules, so both planners lay it out the same way
+        let simple = WithPlanner::<_, SimpleFloorPlanner>::new(circuit.clone());
+        let prover = MockProver::run(run_args.logrows, &simple, vec![public_inputs.clone()]);
+        assert!(prover.unwrap().verify().is_ok());
+
+        let module = WithPlanner::<_, ModulePlanner>::new(circuit);
+        let prover = MockProver::run(run_args.logrows, &module, vec![public_inputs]);
+        assert!(prover.unwrap().verify().is_ok());
+    }
+
+    #[test]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    fn test_intermediate_constraint() {
+        use crate::graph::GraphCircuit;
+        use halo2_proofs::circuit::SimpleFloorPlanner;
+        use halo2_proofs::dev::MockProver;
+        use halo2_proofs::plonk::{Circuit, Column, Error, Instance};
+
+        // constrains the sum of the first hidden layer to a public value, as an auxiliary loss
+        // on an intermediate output would be
+        #[derive(Clone)]
+        struct AuxCircuit {
+            model: Model,
+            settings: GraphSettings,
+            input: ValTensor<Fp>,
+        }
+
+        impl Circuit<Fp> for AuxCircuit {
+            type Config = (ModelConfig, Column<Instance>);
+            type FloorPlanner = SimpleFloorPlanner;
+            type Params = GraphSettings;
+
+            fn without_witnesses(&self) -> Self {
+                self.clone()
+            }
+
+            fn params(&self) -> Self::Params {
+                self.settings.clone()
+            }
+
+            fn configure_with_params(
+                cs: &mut ConstraintSystem<Fp>,
+                params: Self::Params,
+            ) -> Self::Config {
+                let vars = ModelVars::new(cs, &params);
+                let base = Model::configure(cs, &vars, &params).unwrap();
+                let aux_output = cs.instance_column();
+                cs.enable_equality(aux_output);
+                (ModelConfig { base, vars }, aux_output)
+            }
+
+            fn configure(_: &mut ConstraintSystem<Fp>) -> Self::Config {
+                unimplemented!("call configure_with_params instead")
+            }
+
+            fn synthesize(
+                &self,
+                (mut config, aux_output): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                config.base.layout_tables(&mut layouter).unwrap();
+                config.base.layout_range_checks(&mut layouter).unwrap();
+
+                let run_args = &self.settings.run_args;
+                let hidden_sum = layouter.assign_region(
+                    || "model",
+                    |region| {
+                        let mut region = RegionCtx::new(
+                            region,
+                            0,
+                            run_args.num_inner_cols,
+                            run_args.decomp_base,
+                            run_args.decomp_legs,
+                        );
+                        let laid_out = self
+                            .model
+                            .layout_with_intermediates(
+                                &mut config,
+                                &mut region,
+                                &[self.input.clone()],
+                            )
+                            .unwrap();
+                        // node 3 is the relu after the first einsum
+                        let hidden = laid_out.intermediates[&3][0].clone();
+                        Ok(config
+                            .base
+                            .layout(
+                                &mut region,
+                                &[hidden],
+                                Box::new(PolyOp::Sum { axes: vec![0, 1] }),
+                            )
+                            .unwrap()
+                            .unwrap())
+                    },
+                )?;
+
+                match hidden_sum.get_inner_tensor().unwrap()[0].clone() {
+                    ValType::PrevAssigned(v) => {
+                        layouter.constrain_instance(v.cell(), aux_output, 0)
+                    }
+                    _ => panic!("the sum should be assigned"),
+                }
+            }
+        }
+
+        let run_args = RunArgs {
+            logrows: 10,
+            decomp_base: 128,
+            decomp_legs: 2,
+            ..RunArgs::default()
+        };
+        let circuit = GraphCircuit::new(mlp_with_weights(&W0, &W1), &run_args).unwrap();
+        let input: ValTensor<Fp> = Tensor::new(Some(&[3, -2, 2]), &[1, 3])
+            .unwrap()
+            .map(|x: IntegerRep| ValType::Value(Value::known(integer_rep_to_felt(x))))
+            .into();
+        let aux = AuxCircuit {
+            model: circuit.model().clone(),
+            settings: circuit.settings().clone(),
+            input,
+        };
+
+        // relu(w0 . x) = [5, 0, 15, 8]
+        let prover = MockProver::run(run_args.logrows, &aux, vec![vec![Fp::from(28)]]).unwrap();
+        assert!(prover.verify().is_ok());
+
+        let prover = MockProver::run(run_args.logrows, &aux, vec![vec![Fp::from(27)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    fn test_table_strategy() {
+        use crate::circuit::TableStrategy;
+        use crate::graph::GraphCircuit;
+        use halo2_proofs::dev::MockProver;
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+        use halo2curves::bn256::Bn256;
+
+        // sigmoid(w1 . sigmoid(w0 . x)): the first table sees [5, -8, 15, 8], the second [3, -1]
+        let mut model = mlp_with_weights(&W0, &W1);
+        let sigmoid = || SupportedOp::Nonlinear(LookupOp::Sigmoid { scale: 1.0.into() });
+        let tanh = || SupportedOp::Nonlinear(LookupOp::Tanh { scale: 1.0.into() });
+        model
+            .graph
+            .nodes
+            .insert(3, node(sigmoid(), 3, vec![(2, 0)], vec![1, 4]));
+        model
+            .graph
+            .nodes
+            .insert(6, node(tanh(), 6, vec![(5, 0)], vec![1, 2]));
+
+        let table_rows = |table_strategy: TableStrategy| {
+            let run_args = RunArgs {
+                logrows: 10,
+                decomp_base: 128,
+                decomp_legs: 2,
+                table_strategy,
+                ..RunArgs::default()
+            };
+            let mut circuit = GraphCircuit::new(model.clone(), &run_args).unwrap();
+            let region_settings =
+                RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs);
+            let mut quantized = vec![Tensor::new(Some(&[3, -2, 2]), &[1, 3])
+                .unwrap()
+                .map(integer_rep_to_felt)];
+            let witness = circuit
+                .forward::<KZGCommitmentScheme<Bn256>>(&mut quantized, None, None, region_settings)
+                .unwrap();
+            circuit
+                .calc_min_logrows(
+                    (witness.min_lookup_inputs, witness.max_lookup_inputs),
+                    &witness.lookup_ranges,
+                    witness.max_range_size,
+                    Some(run_args.logrows),
+                    2.0,
+                )
+                .unwrap();
+
+            circuit.load_graph_witness(&witness).unwrap();
+            let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+            let prover = MockProver::run(
+                circuit.settings().run_args.logrows,
+                &circuit,
+                vec![public_inputs],
+            );
+            assert!(prover.unwrap().verify().is_ok());
+
+            circuit.settings().lookup_table_rows()
+        };
+
+        // both tables span the first layer's range when it is shared
+        let shared = table_rows(TableStrategy::Shared);
+        let per_layer = table_rows(TableStrategy::PerLayer);
+        assert_eq!(shared, 2 * 47);
+        assert_eq!(per_layer, 47 + 9);
+    }
+
+    #[test]
+    #[cfg(feature = "timing")]
+    fn test_layer_timings() {
+        let model = mlp_with_weights(&W0, &W1);
+        let run_args = RunArgs::default();
+        let input: ValTensor<Fp> = Tensor::new(Some(&[3, -2, 2]), &[1, 3])
+            .unwrap()
+            .map(|x: IntegerRep| ValType::Value(Value::known(integer_rep_to_felt(x))))
+            .into();
+        let res = model
+            .dummy_layout(
+                &run_args,
+                &[input],
+                RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs),
+            )
+            .unwrap();
+
+        let timings = res.layer_timings;
+        assert_eq!(
+            timings
+                .layers
+                .iter()
+                .map(|(idx, ..)| *idx)
+                .collect::<Vec<_>>(),
+            model.graph.nodes.keys().copied().collect::<Vec<_>>()
+        );
+        for ((_, name, duration), node) in timings.layers.iter().zip(model.graph.nodes.values()) {
+            assert_eq!(name, &node.as_str());
+            assert!(!duration.is_zero());
+        }
+        assert_eq!(timings.slowest().len(), timings.layers.len());
+
+        let report = timings.to_string();
+        assert_eq!(report.lines().count(), model.graph.nodes.len() + 1);
+        assert!(report.lines().last().unwrap().starts_with("total: "));
+    }
+
+    #[test]
+    fn test_layer_constraints() {
+        let model = mlp_with_weights(&W0, &W1);
+        let run_args = RunArgs::default();
+        let input: ValTensor<Fp> = Tensor::new(Some(&[3, -2, 2]), &[1, 3])
+            .unwrap()
+            .map(|x: IntegerRep| ValType::Value(Value::known(integer_rep_to_felt(x))))
+            .into();
+        let res = model
+            .dummy_layout(
+                &run_args,
+                &[input],
+                RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs),
+            )
+            .unwrap();
+
+        let constraints = res.layer_constraints;
+        assert_eq!(
+            constraints
+                .layers
+                .iter()
+                .map(|(idx, ..)| *idx)
+                .collect::<Vec<_>>(),
+            model.graph.nodes.keys().copied().collect::<Vec<_>>()
+        );
+
+        // each output of an affine layer is a dot product over its inputs, with one
+        // multiply-accumulate gate per row of num_inner_cols products
+        let macs =
+            |outputs: usize, inputs: usize| outputs * inputs.div_ceil(run_args.num_inner_cols);
+        let affine = constraints
+            .layers
+            .iter()
+            .filter(|(_, name, _)| name.starts_with("EINSUM"))
+            .map(|(_, _, count)| *count)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            affine,
+            vec![
+                ConstraintCount {
+                    gates: macs(4, 3),
+                    lookups: 0
+                },
+                ConstraintCount {
+                    gates: macs(2, 4),
+                    lookups: 0
+                },
+            ]
+        );
+
+        // the constants add nothing, every constraint is attributed to a layer
+        assert_eq!(constraints.layers[1].2, ConstraintCount::default());
+        assert_eq!(constraints.layers[4].2, ConstraintCount::default());
+        assert!(constraints.total().gates > macs(4, 3) + macs(2, 4));
+
+        let report = constraints.to_string();
+        assert_eq!(report.lines().count(), model.graph.nodes.len() + 1);
+        assert!(report.lines().last().unwrap().starts_with("total: "));
+    }
+
+    #[test]
+    fn test_render_layout() {
+        let model = mlp_with_weights(&W0, &W1);
+        let grid = model.render_layout(&RunArgs::default()).unwrap();
+        let rows = grid.lines().collect::<Vec<_>>();
+
+        // advice summary, header, one row per layer, instance summary
+        assert_eq!(rows.len(), model.graph.nodes.len() + 3);
+        assert!(rows[0].starts_with("advice: "));
+        assert!(rows.last().unwrap().starts_with("instance: "));
+        for (row, (idx, node)) in rows[2..].iter().zip(&model.graph.nodes) {
+            assert!(row.starts_with(&idx.to_string()));
+            assert!(row.contains(&node.as_str()));
+        }
+        // the affine layers occupy advice cells
+        for row in rows.iter().filter(|r| r.contains("EINSUM")) {
+            assert!(row.contains('#'));
+        }
+    }
+
+    #[test]
+    fn test_input_shape_mismatch() {
+        let model = mlp();
+        let run_args = RunArgs::default();
+        let region_settings = RegionSettings::all_true(run_args.decomp_base, run_args.decomp_legs);
+        let input = |values: &[IntegerRep], dims: &[usize]| -> ValTensor<Fp> {
+            ValTensor::from_integer_rep_tensor(Tensor::new(Some(values), dims).unwrap())
+        };
+
+        // l0 takes [1, 3]
+        match model.dummy_layout(
+            &run_args,
+            &[input(&[1, 2, 3, 4], &[1, 4])],
+            region_settings.clone(),
+        ) {
+            Err(GraphError::ShapeMismatch {
+                layer,
+                expected,
+                got,
+            }) => {
+                assert_eq!(layer, 0);
+                assert_eq!(expected, vec![1, 3]);
+                assert_eq!(got, vec![1, 4]);
+            }
+            other => panic!("expected a shape mismatch, got {:?}", other.err()),
+        }
+
+        // the right number of elements in the wrong shape is a wiring mistake too
+        match model.dummy_layout(
+            &run_args,
+            &[input(&[3, -2, 2], &[3, 1])],
+            region_settings.clone(),
+        ) {
+            Err(GraphError::ShapeMismatch {
+                layer,
+                expected,
+                got,
+            }) => assert_eq!((layer, expected, got), (0, vec![1, 3], vec![3, 1])),
+            other => panic!("expected a shape mismatch, got {:?}", other.err()),
+        }
+
+        // each node checks its inputs as it is laid out, even when the model's inputs weren't
+        let mut config = ModelConfig {
+            base: PolyConfig::dummy(run_args.logrows as usize, run_args.num_inner_cols),
+            vars: ModelVars::new_dummy(),
+        };
+        let mut region = RegionCtx::new_dummy(0, run_args.num_inner_cols, region_settings.clone());
+        let mut results = BTreeMap::from([(0, vec![input(&[3, -2, 2], &[3, 1])])]);
+        match model.layout_nodes(&mut config, &mut region, &mut results) {
+            Err(GraphError::ShapeMismatch {
+                layer,
+                expected,
+                got,
+            }) => assert_eq!((layer, expected, got), (0, vec![1, 3], vec![3, 1])),
+            other => panic!("expected a shape mismatch, got {:?}", other.err()),
+        }
+
+        // a flattened input is reshaped to the input node's shape
+        let res = model
+            .dummy_layout(&run_args, &[input(&[3, -2, 2], &[3])], region_settings)
+            .unwrap();
+        assert_eq!(res.outputs[0].dims(), &[1, 2]);
+    }
+
+    #[test]
+    fn test_verify_scales() {
+        let mut model = mlp();
+        assert!(model.verify_scales().is_ok());
+
+        // the second affine layer claims a scale its inputs can't produce
+        model.graph.nodes.get_mut(&5).unwrap().bump_scale(3);
+        match model.verify_scales() {
+            Err(GraphError::ScaleMismatch(idx, expected, found)) => {
+                assert_eq!((idx, expected, found), (5, 0, 3));
+            }
+            other => panic!("expected a scale mismatch, got {:?}", other),
+        }
+
+        // the relu after it consumes the bumped scale, so fixing the affine layer alone is enough
+        model.graph.nodes.get_mut(&5).unwrap().bump_scale(0);
+        assert!(model.verify_scales().is_ok());
+    }
+
+    #[test]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    fn test_verify_scales_on_load() {
+        // loading runs the check, it has to accept the rebases done while parsing (the bias of
+        // a conv is rebased to the scale of its product, the products back down to the input
+        // scale)
+        for path in [
+            "examples/onnx/1l_mlp/network.onnx",
+            "examples/onnx/1l_conv/network.onnx",
+        ] {
+            let mut file = std::fs::File::open(path).unwrap();
+            let model = Model::new(&mut file, &RunArgs::default()).unwrap();
+            assert!(model.verify_scales().is_ok());
+            assert!(model.graph.nodes.values().any(|n| match n {
+                NodeType::Node(n) => matches!(n.opkind, SupportedOp::RebaseScale(_)),
+                _ => false,
+            }));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+    fn test_custom_op_handler() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // swap the sigmoid lookup for an identity, counting how often the handler is called
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mut handlers = OpHandlers::default();
+        handlers.register("Sigmoid", move |ctx| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let out_scale = ctx.inputs[0].out_scales()[0];
+            Ok((
+                SupportedOp::Linear(PolyOp::Identity {
+                    out_scale: Some(out_scale),
+                }),
+                vec![],
+            ))
+        });
+
+        let mut file = std::fs::File::open("examples/onnx/1l_sigmoid/network.onnx").unwrap();
+        let model = Model::new_with_op_handlers(&mut file, &RunArgs::default(), &handlers).unwrap();
+        assert!(calls.load(Ordering::SeqCst) > 0);
+        assert!(model
+            .graph
+            .nodes
+            .values()
+            .all(|n| !n.as_str().contains("SIGMOID")));
+        assert!(model
+            .graph
+            .nodes
+            .values()
+            .any(|n| n.as_str().starts_with("IDENTITY")));
+
+        // the handlers only apply to the import they were passed to
+        let seen = calls.load(Ordering::SeqCst);
+        let mut file = std::fs::File::open("examples/onnx/1l_sigmoid/network.onnx").unwrap();
+        let model = Model::new(&mut file, &RunArgs::default()).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), seen);
+        assert!(model
+            .graph
+            .nodes
+            .values()
+            .any(|n| n.as_str().contains("SIGMOID")));
+
+        assert!(handlers.unregister("Sigmoid"));
+        assert!(!handlers.unregister("Sigmoid"));
+    }
+}
@@ -185,6 +185,7 @@ impl RebaseScale {
                     multiplier,
                     rebase_op: HybridOp::Div {
                         denom: crate::circuit::utils::F32((multiplier) as f32),
+                        clamp: None,
                     },
                     original_scale: op.original_scale,
                 })
@@ -195,6 +196,7 @@ impl RebaseScale {
                     multiplier,
                     rebase_op: HybridOp::Div {
                         denom: crate::circuit::utils::F32(multiplier as f32),
+                        clamp: None,
                     },
                     original_scale: op_out_scale,
                 })
@@ -229,6 +231,7 @@ impl RebaseScale {
                     original_scale: op.original_scale,
                     rebase_op: HybridOp::Div {
                         denom: crate::circuit::utils::F32((multiplier) as f32),
+                        clamp: None,
                     },
                 })
             } else {
@@ -239,6 +242,7 @@ impl RebaseScale {
                     original_scale: op_out_scale,
                     rebase_op: HybridOp::Div {
                         denom: crate::circuit::utils::F32(multiplier as f32),
+                        clamp: None,
                     },
                 })
             }
@@ -579,6 +583,7 @@ impl Node {
     /// * `idx` - Unique identifier for this node
     /// * `symbol_values` - ONNX symbol values
     /// * `run_args` - Runtime configuration arguments
+    /// * `handlers` - Custom handlers for onnx ops, see [super::OpHandlers]
     ///
     /// # Returns
     /// New Node instance or error if creation fails
@@ -591,6 +596,7 @@ impl Node {
         idx: usize,
         symbol_values: &SymbolValues,
         run_args: &crate::RunArgs,
+        handlers: &super::OpHandlers,
     ) -> Result<Self, GraphError> {
         trace!("Create {:?}", node);
         trace!("Create op {:?}", node.op);
@@ -634,6 +640,7 @@ impl Node {
             &mut inputs,
             symbol_values,
             run_args,
+            handlers,
         )?; // parses the op name
 
         // we can only take the inputs as mutable once -- so we need to collect them first
@@ -11,6 +11,7 @@ use crate::circuit::poly::PolyOp;
 use crate::circuit::Op;
 use crate::fieldutils::IntegerRep;
 use crate::tensor::{Tensor, TensorError, TensorType};
+use halo2_proofs::plonk::{Column, Instance};
 use halo2curves::bn256::Fr as Fp;
 use halo2curves::ff::PrimeField;
 use itertools::Itertools;
@@ -44,7 +45,7 @@ use tract_onnx::tract_hir::{
 };
 
 /// Quantizes an iterable of f64 to a [Tensor] of IntegerRep using a fixed point representation.
-/// NAN gets mapped to 0. INFINITY and NEG_INFINITY error out.
+/// NAN, INFINITY and NEG_INFINITY error out, as they usually point to a corrupt model file.
 /// Arguments
 ///
 /// * `elem` - the element to quantize.
@@ -55,6 +56,10 @@ pub fn quantize_float(
     shift: f64,
     scale: crate::Scale,
 ) -> Result<IntegerRep, TensorError> {
+    if !elem.is_finite() {
+        return Err(TensorError::NonFiniteValue(*elem));
+    }
+
     let mult = scale_to_multiplier(scale);
     let max_value = ((IntegerRep::MAX as f64 - shift) / mult).round(); // the maximum value that can be represented w/o sig bit truncation
 
@@ -257,6 +262,65 @@ fn load_op<C: tract_onnx::prelude::Op + Clone>(
     Ok(op.clone())
 }
 
+/// What an [OpHandler] gets to build the op of an onnx node from.
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+pub struct OpContext<'a> {
+    /// the index of the node in the graph
+    pub idx: usize,
+    /// the node as parsed by tract
+    pub node: &'a OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    /// the nodes the node takes as inputs, constant parameters can be rebased in place
+    pub inputs: &'a mut [super::NodeType],
+    /// the global (circuit) scales
+    pub scales: &'a VarScales,
+    /// the values of the symbolic dimensions of the graph
+    pub symbol_values: &'a SymbolValues,
+    /// [crate::RunArgs]
+    pub run_args: &'a crate::RunArgs,
+}
+
+/// Builds the [SupportedOp] for an onnx node, along with the indices of the inputs that are
+/// folded into the op (e.g. constant parameters) and so don't need to be laid out themselves.
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+pub type OpHandler =
+    Arc<dyn Fn(&mut OpContext) -> Result<(SupportedOp, Vec<usize>), GraphError> + Send + Sync>;
+
+/// Custom handlers for onnx ops, by op name. Handlers only apply to the imports they are passed
+/// to, see [crate::graph::Model::new_with_op_handlers].
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+#[derive(Clone, Default)]
+pub struct OpHandlers {
+    handlers: std::collections::HashMap<String, OpHandler>,
+}
+
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+impl OpHandlers {
+    /// Registers a handler for the onnx op named `name`, as tract names it (e.g. `"Sigmoid"`).
+    /// The importer builds every node of that op with the handler instead of its own conversion,
+    /// so handlers can add ops the crate doesn't support natively or replace how a supported op
+    /// is converted. Registering a handler for a name that already has one replaces it.
+    pub fn register(
+        &mut self,
+        name: &str,
+        handler: impl Fn(&mut OpContext) -> Result<(SupportedOp, Vec<usize>), GraphError>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.handlers.insert(name.to_string(), Arc::new(handler));
+    }
+
+    /// Removes the handler registered for the onnx op named `name`, returning whether there was
+    /// one.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.handlers.remove(name).is_some()
+    }
+
+    fn get(&self, name: &str) -> Option<&OpHandler> {
+        self.handlers.get(name)
+    }
+}
+
 /// Matches an onnx node to a [crate::circuit::Op].
 /// Arguments
 /// * `idx` - the index of the node in the graph.
@@ -272,11 +336,25 @@ pub fn new_op_from_onnx(
     inputs: &mut [super::NodeType],
     symbol_values: &SymbolValues,
     run_args: &crate::RunArgs,
+    handlers: &OpHandlers,
 ) -> Result<(SupportedOp, Vec<usize>), GraphError> {
     use crate::circuit::InputType;
     use std::f64::consts::E;
     use tract_onnx::tract_core::ops::array::Trilu;
 
+    // registered handlers take precedence over the ops supported natively
+    if let Some(handler) = handlers.get(node.op().name().as_ref()) {
+        debug!("Loading node {} with a custom handler: {:?}", idx, node);
+        return handler(&mut OpContext {
+            idx,
+            node: &node,
+            inputs,
+            scales,
+            symbol_values,
+            run_args,
+        });
+    }
+
     let input_scales = inputs
         .iter()
         .flat_map(|x| x.out_scales())
@@ -888,6 +966,12 @@ pub fn new_op_from_onnx(
         "HardSwish" => SupportedOp::Nonlinear(LookupOp::HardSwish {
             scale: scale_to_multiplier(input_scales[0]).into(),
         }),
+        "Mish" => SupportedOp::Nonlinear(LookupOp::Mish {
+            scale: scale_to_multiplier(input_scales[0]).into(),
+        }),
+        "Softplus" => SupportedOp::Nonlinear(LookupOp::Softplus {
+            scale: scale_to_multiplier(input_scales[0]).into(),
+        }),
         "Sigmoid" => SupportedOp::Nonlinear(LookupOp::Sigmoid {
             scale: scale_to_multiplier(input_scales[0]).into(),
         }),
@@ -1131,6 +1215,7 @@ pub fn new_op_from_onnx(
                 input_scale: scale_to_multiplier(in_scale).into(),
                 output_scale: scale_to_multiplier(max_scale).into(),
                 axes: softmax_op.axes.to_vec(),
+                temperature: 1.0.into(),
             })
         }
         "MaxPool" => {
@@ -1146,7 +1231,10 @@ pub fn new_op_from_onnx(
             let pool_spec: &PoolSpec = &sumpool_node.pool_spec;
 
             let stride = extract_strides(pool_spec)?;
-            let padding = extract_padding(pool_spec, &input_dims[0])?;
+            let padding = extract_padding(pool_spec, &input_dims[0])?
+                .iter()
+                .map(|(before, after)| (*before as isize, *after as isize))
+                .collect();
             let kernel_shape = &pool_spec.kernel_shape;
 
             SupportedOp::Hybrid(HybridOp::MaxPool {
@@ -1267,6 +1355,7 @@ pub fn new_op_from_onnx(
 
                     let op = SupportedOp::Hybrid(HybridOp::Div {
                         denom: denom.into(),
+                        clamp: None,
                     });
 
                     // if the input is scale 0 we re up to the max scale
@@ -1299,18 +1388,17 @@ pub fn new_op_from_onnx(
                 }
             };
 
-            if let Some(dilations) = &conv_node.pool_spec.dilations {
-                if dilations.iter().any(|x| *x != 1) {
-                    return Err(GraphError::MisformedParams(
-                        "non unit dilations not supported".to_string(),
-                    ));
-                }
-            }
-
             let pool_spec = &conv_node.pool_spec;
 
             let stride = extract_strides(pool_spec)?;
-            let padding = extract_padding(pool_spec, &input_dims[0])?;
+            let dilation = match &pool_spec.dilations {
+                Some(dilations) => dilations.to_vec(),
+                None => vec![1; stride.len()],
+            };
+            let padding = extract_padding(pool_spec, &input_dims[0])?
+                .iter()
+                .map(|(before, after)| (*before as isize, *after as isize))
+                .collect();
 
             // if bias exists then rescale it to the input + kernel scale
             if input_scales.len() == 3 {
@@ -1333,6 +1421,7 @@ pub fn new_op_from_onnx(
             SupportedOp::Linear(PolyOp::Conv {
                 padding,
                 stride,
+                dilation,
                 group,
                 data_format: conv_node.pool_spec.data_format.into(),
                 kernel_format: conv_node.kernel_fmt.into(),
@@ -1470,7 +1559,10 @@ pub fn new_op_from_onnx(
             let pool_spec: &PoolSpec = &sumpool_node.pool_spec;
 
             let stride = extract_strides(pool_spec)?;
-            let padding = extract_padding(pool_spec, &input_dims[0])?;
+            let padding = extract_padding(pool_spec, &input_dims[0])?
+                .iter()
+                .map(|(before, after)| (*before as isize, *after as isize))
+                .collect();
 
             SupportedOp::Hybrid(HybridOp::SumPool {
                 padding,
@@ -1550,6 +1642,20 @@ pub fn quantize_tensor<F: PrimeField + TensorType + PartialOrd>(
     const_value: Tensor<f32>,
     scale: crate::Scale,
     visibility: &Visibility,
+) -> Result<Tensor<F>, TensorError> {
+    quantize_tensor_generic(const_value, scale, visibility)
+}
+
+/// Like [quantize_tensor], for tensors of any float type. Each element is widened to an f64
+/// before it is quantized, so half precision weights (`half::f16` and `half::bf16` with the
+/// `half` feature) are quantized directly instead of being rounded through an f32 first.
+pub fn quantize_tensor_generic<
+    F: PrimeField + TensorType + PartialOrd,
+    T: TensorType + Into<f64> + Send + Sync,
+>(
+    const_value: Tensor<T>,
+    scale: crate::Scale,
+    visibility: &Visibility,
 ) -> Result<Tensor<F>, TensorError> {
     let mut value: Tensor<F> = const_value.par_enum_map(|_, x| {
         Ok::<_, TensorError>(crate::fieldutils::integer_rep_to_felt::<F>(quantize_float(
@@ -1564,6 +1670,83 @@ pub fn quantize_tensor<F: PrimeField + TensorType + PartialOrd>(
     Ok(value)
 }
 
+/// Assembles the instances of a circuit whose public values live at different scales and in
+/// different instance columns. Each call to [PublicInputs::add] quantizes a tensor at its own
+/// scale and appends it to its column, so values need only be added in the order they are
+/// constrained within each column. [PublicInputs::build] returns one vector per instance column,
+/// ordered by column index, as expected by the mock prover and the prover.
+#[derive(Clone, Debug, Default)]
+pub struct PublicInputs<F: PrimeField + TensorType + PartialOrd> {
+    columns: std::collections::BTreeMap<usize, Vec<F>>,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd> PublicInputs<F> {
+    /// Creates an empty set of instances.
+    pub fn new() -> Self {
+        Self {
+            columns: std::collections::BTreeMap::new(),
+        }
+    }
+
+    /// Quantizes `values` at `scale` and appends them to the instances of `column`.
+    pub fn add<T: TensorType + Into<f64> + Send + Sync>(
+        &mut self,
+        values: Tensor<T>,
+        scale: crate::Scale,
+        column: Column<Instance>,
+    ) -> Result<(), TensorError> {
+        let quantized: Tensor<F> = quantize_tensor(values, scale, &Visibility::Public)?;
+        self.columns
+            .entry(column.index())
+            .or_default()
+            .extend(quantized);
+        Ok(())
+    }
+
+    /// Returns the instances of every column up to the highest one added to, with columns that
+    /// were never added to left empty.
+    pub fn build(self) -> Vec<Vec<F>> {
+        let num_columns = self.columns.keys().last().map_or(0, |idx| idx + 1);
+        let mut instances = vec![vec![]; num_columns];
+        for (idx, values) in self.columns {
+            instances[idx] = values;
+        }
+        instances
+    }
+}
+
+/// Converts an integer output tensor to the values of an instance column, in the tensor's
+/// row-major order. The felts are mapped straight off the tensor's elements into the instance
+/// vector, which is the only allocation.
+pub fn integer_rep_tensor_to_instances<F: PrimeField>(values: &Tensor<IntegerRep>) -> Vec<F> {
+    values
+        .iter()
+        .map(|v| crate::fieldutils::integer_rep_to_felt(*v))
+        .collect()
+}
+
+/// Converts the values of an instance column back to an integer tensor of shape `dims`, the
+/// inverse of [integer_rep_tensor_to_instances]. The integers are collected straight into the
+/// tensor, which is then reshaped in place.
+pub fn instances_to_integer_rep_tensor<F: PrimeField + PartialOrd>(
+    instances: &[F],
+    dims: &[usize],
+) -> Result<Tensor<IntegerRep>, TensorError> {
+    if instances.len() != dims.iter().product::<usize>() {
+        return Err(TensorError::DimMismatch(format!(
+            "cannot shape {} instances as {:?}",
+            instances.len(),
+            dims
+        )));
+    }
+    let mut tensor: Tensor<IntegerRep> = instances
+        .iter()
+        .map(|f| crate::fieldutils::felt_to_integer_rep(*f))
+        .collect();
+    tensor.reshape(dims)?;
+    Ok(tensor)
+}
+
 use crate::tensor::ValTensor;
 /// Split a [ValTensor] into a vector of [ValTensor]s.
 pub(crate) fn split_valtensor(
@@ -1649,11 +1832,104 @@ pub mod tests {
         assert_eq!(quantized, reference);
     }
 
+    #[test]
+    fn test_public_inputs_across_scales() {
+        use crate::fieldutils::integer_rep_to_felt;
+        use halo2_proofs::plonk::ConstraintSystem;
+
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let columns = [cs.instance_column(), cs.instance_column()];
+
+        // a two output model: scores at scale 4 in the first column, followed by a class index
+        // at scale 0, and a regression output at scale 7 in the second column
+        let scores: Tensor<f32> = vec![0.5, -1.25].into_iter().into();
+        let class: Tensor<f32> = vec![1.0].into_iter().into();
+        let regression: Tensor<f32> = vec![0.25].into_iter().into();
+
+        let mut instances = PublicInputs::<Fp>::new();
+        // added out of column order
+        instances.add(regression, 7, columns[1]).unwrap();
+        instances.add(scores, 4, columns[0]).unwrap();
+        instances.add(class, 0, columns[0]).unwrap();
+
+        let felts = |values: &[IntegerRep]| {
+            values
+                .iter()
+                .map(|v| integer_rep_to_felt::<Fp>(*v))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(instances.build(), vec![felts(&[8, -20, 1]), felts(&[32])]);
+
+        // columns that are never added to are left empty
+        let mut instances = PublicInputs::<Fp>::new();
+        let class: Tensor<f32> = vec![3.0].into_iter().into();
+        instances.add(class, 0, columns[1]).unwrap();
+        assert_eq!(instances.build(), vec![vec![], felts(&[3])]);
+    }
+
+    #[test]
+    fn test_instance_conversion_roundtrip() {
+        use crate::fieldutils::integer_rep_to_felt;
+
+        let output = Tensor::<IntegerRep>::new(Some(&[3, -1, 0, 7, -128, 42]), &[2, 3]).unwrap();
+        let instances = integer_rep_tensor_to_instances::<Fp>(&output);
+        // row-major order, negative values wrap around the field
+        let expected = output
+            .iter()
+            .map(|v| integer_rep_to_felt::<Fp>(*v))
+            .collect::<Vec<_>>();
+        assert_eq!(instances, expected);
+        assert_eq!(instances[4], -Fp::from(128));
+
+        assert_eq!(
+            instances_to_integer_rep_tensor(&instances, &[2, 3]).unwrap(),
+            output
+        );
+        assert!(instances_to_integer_rep_tensor(&instances, &[4, 2]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "half")]
+    fn test_quantize_f16_tensor() {
+        use crate::fieldutils::{felt_to_integer_rep, integer_rep_to_felt};
+
+        let weights = [0.5, -1.25, 0.1, 3.0, -0.0003];
+        let tensor: Tensor<half::f16> = weights.iter().map(|x| half::f16::from_f64(*x)).into();
+        let quantized: Tensor<Fp> =
+            quantize_tensor_generic(tensor.clone(), 7, &Visibility::Fixed).unwrap();
+        assert_eq!(quantized.scale(), Some(7));
+
+        // the quantized values are those of the exact half precision weights
+        let reference = tensor
+            .iter()
+            .map(|x| integer_rep_to_felt((x.to_f64() * 128.0).round() as IntegerRep))
+            .collect::<Vec<Fp>>();
+        assert_eq!(quantized.to_vec(), reference);
+        assert_eq!(
+            quantized.map(felt_to_integer_rep).to_vec(),
+            vec![64, -160, 13, 384, 0]
+        );
+    }
+
     #[test]
     fn test_quantize_edge_cases() {
-        assert_eq!(quantize_float(&f64::NAN, 0.0, 0).unwrap(), 0);
-        assert!(quantize_float(&f64::INFINITY, 0.0, 0).is_err());
-        assert!(quantize_float(&f64::NEG_INFINITY, 0.0, 0).is_err());
+        for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                quantize_float(&x, 0.0, 0),
+                Err(TensorError::NonFiniteValue(_))
+            ));
+        }
+        assert!(matches!(
+            quantize_float(&1e40, 0.0, 0),
+            Err(TensorError::SigBitTruncationError)
+        ));
+    }
+
+    #[test]
+    fn test_quantize_tensor_nan() {
+        let tensor: Tensor<f32> = vec![0.5, f32::NAN, 2.0].into_iter().into();
+        let quantized = quantize_tensor::<Fp>(tensor, 4, &Visibility::Fixed);
+        assert!(matches!(quantized, Err(TensorError::NonFiniteValue(x)) if x.is_nan()));
     }
 
     #[test]
@@ -97,7 +97,7 @@ impl From<String> for EZKLError {
 
 use std::str::FromStr;
 
-use circuit::{table::Range, CheckMode};
+use circuit::{table::Range, CheckMode, TableStrategy};
 #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
 use clap::Args;
 use fieldutils::IntegerRep;
@@ -350,6 +350,11 @@ pub struct RunArgs {
         arg(long, default_value = "false")
     )]
     pub ignore_range_check_inputs_outputs: bool,
+    /// How lookup tables are sized: one shared range or a range per layer
+    /// Per-layer tables allow independent sizing at the cost of extra columns
+    #[cfg_attr(all(feature = "ezkl", not(target_arch = "wasm32")), arg(long, default_value = "shared", value_hint = clap::ValueHint::Other))]
+    #[serde(default)]
+    pub table_strategy: TableStrategy,
 }
 
 impl Default for RunArgs {
@@ -376,6 +381,7 @@ impl Default for RunArgs {
             decomp_base: 16384,
             decomp_legs: 2,
             ignore_range_check_inputs_outputs: false,
+            table_strategy: TableStrategy::Shared,
         }
     }
 }
@@ -24,4 +24,16 @@ pub enum PfsysError {
     /// Failed to load pk from file
     #[error("failed to load pk from file: {0}")]
     LoadPk(String),
+    /// Failed to export or use a standalone verifier
+    #[error("standalone verifier: {0}")]
+    ExportVerifier(String),
+    /// Failed to read or write the key cache
+    #[error("key cache: {0}")]
+    KeyCache(String),
+    /// The proof doesn't open with the expected commitments
+    #[error("proof commitments don't match the expected commitments")]
+    CommitmentMismatch,
+    /// The outputs can't be decoded from the instances of a proof
+    #[error("failed to decode the outputs: {0}")]
+    DecodeOutputs(String),
 }
@@ -0,0 +1,424 @@
+use super::{serde_format_from_str, verify_proof_circuit, PfsysError, Snark, TranscriptType};
+use crate::graph::dequantize;
+use crate::pfsys::evm::aggregation_kzg::PoseidonTranscript;
+use crate::tensor::Tensor;
+use crate::EZKL_KEY_FORMAT;
+use halo2_proofs::plonk::{Circuit, VerifyingKey};
+use halo2_proofs::poly::commitment::{Params, ParamsProver};
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_proofs::poly::VerificationStrategy;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use serde::{Deserialize, Serialize};
+use snark_verifier::loader::native::NativeLoader;
+use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+use std::io::Cursor;
+
+/// Where the outputs of a circuit sit in the instances of its proofs and how they are
+/// quantized, so a verifier can turn them back into floats.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputSpec {
+    /// the instance column holding the outputs
+    pub column: usize,
+    /// the row of the column the outputs start at
+    pub offset: usize,
+    /// the shape of the outputs
+    pub dims: Vec<usize>,
+    /// the fixed point scale (log base 2) the outputs are quantized at
+    pub scale: crate::Scale,
+}
+
+/// Everything needed to verify KZG proofs for a single circuit, without the model, the proving
+/// key or an SRS larger than the circuit. The bundle serializes to a single data blob and can be emitted as a Rust
+/// module with [VerifierBundle::to_rust_module] for embedding into a minimal verifier.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierBundle {
+    /// the serialized [VerifyingKey]
+    pub vk: Vec<u8>,
+    /// the serialized verifier parameters, downsized to the circuit's logrows
+    pub params: Vec<u8>,
+    /// log2 of the number of rows in the circuit
+    pub logrows: u32,
+    /// the transcript the proofs are generated with
+    pub transcript_type: TranscriptType,
+}
+
+impl VerifierBundle {
+    /// Creates a bundle from a verifying key and the parameters it was generated with. Parameters
+    /// for more rows than the circuit has are downsized to its logrows before they are bundled.
+    pub fn new(
+        vk: &VerifyingKey<G1Affine>,
+        params: &ParamsKZG<Bn256>,
+        transcript_type: TranscriptType,
+    ) -> Result<Self, PfsysError> {
+        let mut vk_bytes = vec![];
+        vk.write(&mut vk_bytes, serde_format_from_str(&EZKL_KEY_FORMAT))
+            .map_err(|e| PfsysError::ExportVerifier(format!("{}", e)))?;
+        let logrows = vk.get_domain().k();
+        let mut params = params.clone();
+        if params.k() > logrows {
+            params.downsize(logrows);
+        }
+        let mut params_bytes = vec![];
+        params
+            .verifier_params()
+            .write(&mut params_bytes)
+            .map_err(|e| PfsysError::ExportVerifier(format!("{}", e)))?;
+
+        Ok(Self {
+            vk: vk_bytes,
+            params: params_bytes,
+            logrows,
+            transcript_type,
+        })
+    }
+
+    /// Emits Rust source for a module holding the bundle as constants.
+    pub fn to_rust_module(&self) -> String {
+        format!(
+            "//! Verifier data for a single ezkl circuit. Generated, do not edit.\n\n\
+             /// log2 of the number of rows in the circuit\n\
+             pub const LOGROWS: u32 = {};\n\
+             /// the transcript the proofs are generated with\n\
+             pub const TRANSCRIPT: &str = \"{}\";\n\
+             /// the serialized verifying key\n\
+             pub const VK: &[u8] = &{:?};\n\
+             /// the serialized verifier parameters\n\
+             pub const PARAMS: &[u8] = &{:?};\n",
+            self.logrows, self.transcript_type, self.vk, self.params
+        )
+    }
+
+    /// Verifies `snark` against the bundled verifying key and parameters.
+    /// `circuit_params` are the [Circuit::Params] the verifying key was generated with.
+    pub fn verify<C: Circuit<Fr>>(
+        &self,
+        snark: &Snark<Fr, G1Affine>,
+        circuit_params: C::Params,
+    ) -> Result<(), PfsysError> {
+        if snark.transcript_type != self.transcript_type {
+            return Err(PfsysError::ExportVerifier(format!(
+                "proof uses a {} transcript but the verifier expects {}",
+                snark.transcript_type, self.transcript_type
+            )));
+        }
+
+        let params = ParamsKZG::<Bn256>::read(&mut Cursor::new(&self.params))
+            .map_err(|e| PfsysError::ExportVerifier(format!("{}", e)))?;
+        let vk = VerifyingKey::<G1Affine>::read::<_, C>(
+            &mut Cursor::new(&self.vk),
+            serde_format_from_str(&EZKL_KEY_FORMAT),
+            circuit_params,
+        )
+        .map_err(|e| PfsysError::LoadVk(format!("{}", e)))?;
+
+        verify_snark(snark, &params, &vk)
+    }
+
+    /// Verifies `snark` like [VerifierBundle::verify] and, if the proof is valid, returns the
+    /// outputs read from its instances and dequantized as described by `output_spec`.
+    pub fn verify_and_decode<C: Circuit<Fr>>(
+        &self,
+        snark: &Snark<Fr, G1Affine>,
+        circuit_params: C::Params,
+        output_spec: &OutputSpec,
+    ) -> Result<Tensor<f32>, PfsysError> {
+        self.verify::<C>(snark, circuit_params)?;
+        decode_outputs(&snark.instances, output_spec)
+    }
+}
+
+/// Verifies a KZG `proof` of `instances` and, if it is valid, returns the outputs read from the
+/// instances and dequantized as described by `output_spec`. Unlike
+/// [VerifierBundle::verify_and_decode] this takes the verifying key and parameters directly, the
+/// parameters being needed to check the proof's opening.
+pub fn verify_and_decode(
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsKZG<Bn256>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+    transcript_type: TranscriptType,
+    output_spec: &OutputSpec,
+) -> Result<Tensor<f32>, PfsysError> {
+    let snark = Snark::new(
+        None,
+        instances.to_vec(),
+        proof.to_vec(),
+        None,
+        transcript_type,
+        None,
+        None,
+        Some(crate::Commitments::KZG),
+    );
+    verify_snark(&snark, params, vk)?;
+    decode_outputs(instances, output_spec)
+}
+
+/// Verifies `snark` with the SHPLONK verifier for its transcript.
+fn verify_snark(
+    snark: &Snark<Fr, G1Affine>,
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+) -> Result<(), PfsysError> {
+    let strategy = SingleStrategy::new(params);
+    let orig_n = 1 << vk.get_domain().k();
+    match snark.transcript_type {
+        TranscriptType::EVM => verify_proof_circuit::<
+            VerifierSHPLONK<'_, Bn256>,
+            KZGCommitmentScheme<Bn256>,
+            _,
+            _,
+            EvmTranscript<G1Affine, _, _, _>,
+        >(snark, params, vk, strategy, orig_n)?,
+        TranscriptType::Poseidon => verify_proof_circuit::<
+            VerifierSHPLONK<'_, Bn256>,
+            KZGCommitmentScheme<Bn256>,
+            _,
+            _,
+            PoseidonTranscript<NativeLoader, _>,
+        >(snark, params, vk, strategy, orig_n)?,
+    };
+
+    Ok(())
+}
+
+/// Reads the outputs described by `output_spec` out of `instances` and dequantizes them.
+fn decode_outputs(
+    instances: &[Vec<Fr>],
+    output_spec: &OutputSpec,
+) -> Result<Tensor<f32>, PfsysError> {
+    let len = output_spec.dims.iter().product::<usize>();
+    let outputs = instances
+        .get(output_spec.column)
+        .and_then(|column| column.get(output_spec.offset..output_spec.offset + len))
+        .ok_or_else(|| {
+            PfsysError::DecodeOutputs(format!(
+                "{} outputs at row {} of instance column {} are out of bounds",
+                len, output_spec.offset, output_spec.column
+            ))
+        })?;
+
+    let outputs = outputs
+        .iter()
+        .map(|x| dequantize(*x, output_spec.scale, 0.0) as f32)
+        .collect::<Vec<_>>();
+    Tensor::new(Some(&outputs), &output_spec.dims)
+        .map_err(|e| PfsysError::DecodeOutputs(format!("{}", e)))
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::circuit::ops::layouts;
+    use crate::circuit::region::RegionCtx;
+    use crate::circuit::{BaseConfig, CheckMode};
+    use crate::fieldutils::integer_rep_to_felt;
+    use crate::pfsys::tests::AddCircuit;
+    use crate::pfsys::{create_keys, create_proof_circuit, srs::gen_srs};
+    use crate::tensor::{Tensor, ValTensor, VarTensor};
+    use crate::Commitments;
+    use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use halo2_proofs::plonk::{ConstraintSystem, Error};
+    use halo2_proofs::poly::kzg::multiopen::ProverSHPLONK;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[test]
+    fn test_verifier_bundle_roundtrip() {
+        let input = || {
+            ValTensor::from(Tensor::from(
+                (0..LEN).map(|i| Value::known(Fr::from(i as u64 + 1))),
+            ))
+        };
+        let circuit = AddCircuit {
+            inputs: [input(), input()],
+        };
+
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(K as u32);
+        let pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, AddCircuit>(&circuit, &params, true).unwrap();
+        let snark = create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            AddCircuit,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            circuit,
+            vec![],
+            &params,
+            &pk,
+            CheckMode::SAFE,
+            Commitments::KZG,
+            TranscriptType::EVM,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let bundle = VerifierBundle::new(pk.get_vk(), &params, TranscriptType::EVM).unwrap();
+        assert_eq!(bundle.logrows, K as u32);
+
+        // larger parameters are downsized to the circuit before they are bundled
+        let larger = gen_srs::<KZGCommitmentScheme<Bn256>>(K as u32 + 2);
+        let downsized = VerifierBundle::new(pk.get_vk(), &larger, TranscriptType::EVM).unwrap();
+        let downsized = ParamsKZG::<Bn256>::read(&mut Cursor::new(&downsized.params)).unwrap();
+        assert_eq!(downsized.k(), K as u32);
+
+        // the exported blob is all the verifier needs
+        let blob = serde_json::to_vec(&bundle).unwrap();
+        let bundle: VerifierBundle = serde_json::from_slice(&blob).unwrap();
+        assert!(bundle.verify::<AddCircuit>(&snark, ()).is_ok());
+
+        let mut tampered = snark.clone();
+        tampered.proof[0] ^= 1;
+        assert!(bundle.verify::<AddCircuit>(&tampered, ()).is_err());
+
+        let module = bundle.to_rust_module();
+        assert!(module.contains(&format!("pub const LOGROWS: u32 = {};", K)));
+        assert!(module.contains(&format!("pub const VK: &[u8] = &{:?};", bundle.vk)));
+    }
+
+    #[derive(Clone)]
+    struct PublicAddCircuit {
+        inputs: [ValTensor<Fr>; 2],
+    }
+
+    impl Circuit<Fr> for PublicAddCircuit {
+        type Config = (BaseConfig<Fr>, ValTensor<Fr>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let instance = ValTensor::new_instance(cs, vec![vec![LEN]], 2);
+            (
+                BaseConfig::configure(cs, &[a, b], &output, CheckMode::SAFE),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1, 128, 2);
+                    let sum = layouts::pairwise(
+                        &config,
+                        &mut region,
+                        &self.inputs,
+                        crate::circuit::ops::base::BaseOp::Add,
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    layouts::enforce_equality(&config, &mut region, &[sum, instance.clone()])
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_verify_and_decode() {
+        // [0.25, 0.5, -1, 1.5] at a scale of 2^2
+        let input = || {
+            ValTensor::from_integer_rep_tensor(Tensor::new(Some(&[1, 2, -4, 6]), &[LEN]).unwrap())
+        };
+        let circuit = PublicAddCircuit {
+            inputs: [input(), input()],
+        };
+        let outputs = [2, 4, -8, 12].map(integer_rep_to_felt::<Fr>).to_vec();
+
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(K as u32);
+        let pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, PublicAddCircuit>(&circuit, &params, true)
+                .unwrap();
+        let snark = create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            PublicAddCircuit,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            circuit,
+            vec![outputs],
+            &params,
+            &pk,
+            CheckMode::SAFE,
+            Commitments::KZG,
+            TranscriptType::EVM,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let bundle = VerifierBundle::new(pk.get_vk(), &params, TranscriptType::EVM).unwrap();
+        let spec = OutputSpec {
+            column: 0,
+            offset: 0,
+            dims: vec![2, 2],
+            scale: 2,
+        };
+        let decoded = bundle
+            .verify_and_decode::<PublicAddCircuit>(&snark, (), &spec)
+            .unwrap();
+        assert_eq!(
+            decoded,
+            Tensor::new(Some(&[0.5, 1.0, -2.0, 3.0]), &[2, 2]).unwrap()
+        );
+
+        // as does the free function, straight from the keys
+        let decoded_directly = verify_and_decode(
+            pk.get_vk(),
+            &params,
+            &snark.proof,
+            &snark.instances,
+            TranscriptType::EVM,
+            &spec,
+        )
+        .unwrap();
+        assert_eq!(decoded_directly, decoded);
+
+        // nothing is decoded from an invalid proof
+        let mut tampered = snark.clone();
+        tampered.proof[0] ^= 1;
+        assert!(bundle
+            .verify_and_decode::<PublicAddCircuit>(&tampered, (), &spec)
+            .is_err());
+        assert!(verify_and_decode(
+            pk.get_vk(),
+            &params,
+            &tampered.proof,
+            &tampered.instances,
+            TranscriptType::EVM,
+            &spec,
+        )
+        .is_err());
+
+        // or past the end of the instances
+        let spec = OutputSpec { offset: 1, ..spec };
+        assert!(matches!(
+            bundle.verify_and_decode::<PublicAddCircuit>(&snark, (), &spec),
+            Err(PfsysError::DecodeOutputs(_))
+        ));
+    }
+}
@@ -7,6 +7,9 @@ pub mod srs;
 /// errors related to pfsys
 pub mod errors;
 
+/// Exporting standalone verifiers for a single circuit
+pub mod export;
+
 pub use errors::PfsysError;
 
 use crate::circuit::CheckMode;
@@ -23,12 +26,14 @@ use halo2_proofs::poly::commitment::{CommitmentScheme, Params, ParamsProver, Pro
 use halo2_proofs::poly::ipa::commitment::IPACommitmentScheme;
 use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
 use halo2_proofs::poly::VerificationStrategy;
-use halo2_proofs::transcript::{EncodedChallenge, TranscriptReadBuffer, TranscriptWriterBuffer};
+use halo2_proofs::transcript::{
+    EncodedChallenge, Transcript, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
 use halo2curves::ff::{FromUniformBytes, PrimeField, WithSmallOrderMulGroup};
 use halo2curves::serde::SerdeObject;
 use halo2curves::CurveAffine;
 use instant::Instant;
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 #[cfg(not(feature = "det-prove"))]
 use rand::rngs::OsRng;
 #[cfg(feature = "det-prove")]
@@ -41,7 +46,7 @@ use snark_verifier::verifier::plonk::PlonkProtocol;
 use std::fs::File;
 use std::io::{self, BufReader, BufWriter, Cursor, Write};
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error as thisError;
 #[cfg(all(feature = "ezkl", not(target_arch = "wasm32")))]
 use tosubcommand::ToFlags;
@@ -291,6 +296,22 @@ pub struct PrettyElements {
     pub outputs: Vec<Vec<String>>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+/// A breakdown of the size of a proof, useful for reasoning about on-chain verification costs
+pub struct ProofSizeReport {
+    /// the length of the proof in bytes
+    pub proof_bytes: usize,
+    /// the proof bytes / 32, rounded up. this counts words, not proof elements: the EVM
+    /// transcript writes each point as two words
+    pub num_words: usize,
+    /// the number of public instances (these are not part of the proof bytes but are calldata)
+    pub num_instances: usize,
+    /// the number of witness commitments in the proof (only known if the protocol is attached)
+    pub num_commitments: Option<usize>,
+    /// the number of polynomial evaluations in the proof (only known if the protocol is attached)
+    pub num_evaluations: Option<usize>,
+}
+
 /// An application snark with proof and instance variables ready for aggregation (raw field element)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snark<F: PrimeField + SerdeObject, C: CurveAffine>
@@ -384,6 +405,21 @@ where
         }
     }
 
+    /// Reports the byte size of the proof and, if the protocol is attached, a breakdown of its contents
+    pub fn size_report(&self) -> ProofSizeReport {
+        let word_bytes = F::Repr::default().as_ref().len();
+        ProofSizeReport {
+            proof_bytes: self.proof.len(),
+            num_words: self.proof.len().div_ceil(word_bytes),
+            num_instances: self.instances.iter().map(|i| i.len()).sum(),
+            num_commitments: self
+                .protocol
+                .as_ref()
+                .map(|p| p.num_witness.iter().sum::<usize>()),
+            num_evaluations: self.protocol.as_ref().map(|p| p.evaluations.len()),
+        }
+    }
+
     /// create hex proof from proof
     pub fn create_hex_proof(&mut self) {
         let hex_proof = hex::encode(&self.proof);
@@ -549,6 +585,39 @@ where
     Ok(pk)
 }
 
+/// Like [create_keys], but reuses the proving key stored in `cache_dir` under `fingerprint` if
+/// there is one, and stores the generated key there otherwise. The fingerprint has to identify
+/// everything keygen depends on (e.g. [crate::graph::GraphCircuit::fingerprint] together with
+/// the SRS in use), so that a changed circuit misses the cache rather than reusing stale keys.
+/// Returns the key and whether it was loaded from the cache.
+pub fn create_keys_cached<Scheme: CommitmentScheme, C: Circuit<Scheme::Scalar>>(
+    circuit: &C,
+    params: &'_ Scheme::ParamsProver,
+    disable_selector_compression: bool,
+    cache_dir: &Path,
+    fingerprint: &str,
+) -> Result<(ProvingKey<Scheme::Curve>, bool), PfsysError>
+where
+    Scheme::Curve: SerdeObject + CurveAffine,
+    Scheme::Scalar: PrimeField + SerdeObject + FromUniformBytes<64>,
+{
+    let pk_path = cache_dir.join(format!("{}.pk", fingerprint));
+    if pk_path.exists() {
+        match load_pk::<Scheme, C>(pk_path.clone(), circuit.params()) {
+            Ok(pk) => {
+                debug!("reusing cached proving key for {}", fingerprint);
+                return Ok((pk, true));
+            }
+            Err(e) => warn!("regenerating unreadable cached proving key: {}", e),
+        }
+    }
+
+    let pk = create_keys::<Scheme, C>(circuit, params, disable_selector_compression)?;
+    std::fs::create_dir_all(cache_dir).map_err(|e| PfsysError::KeyCache(format!("{}", e)))?;
+    save_pk::<Scheme::Curve>(&pk_path, &pk).map_err(|e| PfsysError::KeyCache(format!("{}", e)))?;
+    Ok((pk, false))
+}
+
 /// a wrapper around halo2's create_proof
 #[allow(clippy::too_many_arguments)]
 pub fn create_proof_circuit<
@@ -572,6 +641,59 @@ pub fn create_proof_circuit<
     split: Option<ProofSplitCommit>,
     protocol: Option<PlonkProtocol<Scheme::Curve>>,
 ) -> Result<Snark<Scheme::Scalar, Scheme::Curve>, PfsysError>
+where
+    Scheme::ParamsVerifier: 'params,
+    Scheme::Scalar: Serialize
+        + DeserializeOwned
+        + SerdeObject
+        + PrimeField
+        + FromUniformBytes<64>
+        + WithSmallOrderMulGroup<3>,
+    Scheme::Curve: Serialize + DeserializeOwned + SerdeObject,
+    Scheme::ParamsProver: Send + Sync,
+{
+    create_proof_circuit_with_domain::<Scheme, C, P, V, Strategy, E, TW, TR>(
+        circuit,
+        instances,
+        params,
+        pk,
+        check_mode,
+        commitment,
+        transcript_type,
+        split,
+        protocol,
+        None,
+    )
+}
+
+/// Like [create_proof_circuit], but binds the proof to a domain separation label absorbed into
+/// the transcript before anything else. When several circuits are used in one system, giving
+/// each its own label keeps a proof for one from being replayed against another: the proof only
+/// verifies with [verify_proof_circuit_with_domain] under the same label. No label produces the
+/// same proofs as [create_proof_circuit], which is what the generated EVM verifiers expect.
+#[allow(clippy::too_many_arguments)]
+pub fn create_proof_circuit_with_domain<
+    'params,
+    Scheme: CommitmentScheme,
+    C: Circuit<Scheme::Scalar>,
+    P: Prover<'params, Scheme>,
+    V: Verifier<'params, Scheme>,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+    E: EncodedChallenge<Scheme::Curve>,
+    TW: TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+    TR: TranscriptReadBuffer<Cursor<Vec<u8>>, Scheme::Curve, E>,
+>(
+    circuit: C,
+    instances: Vec<Vec<Scheme::Scalar>>,
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    check_mode: CheckMode,
+    commitment: Commitments,
+    transcript_type: TranscriptType,
+    split: Option<ProofSplitCommit>,
+    protocol: Option<PlonkProtocol<Scheme::Curve>>,
+    domain: Option<&str>,
+) -> Result<Snark<Scheme::Scalar, Scheme::Curve>, PfsysError>
 where
     Scheme::ParamsVerifier: 'params,
     Scheme::Scalar: Serialize
@@ -585,6 +707,9 @@ where
 {
     let strategy = Strategy::new(params.verifier_params());
     let mut transcript = TranscriptWriterBuffer::<_, Scheme::Curve, _>::init(vec![]);
+    if let Some(domain) = domain {
+        absorb_domain(&mut transcript, domain).map_err(halo2_proofs::plonk::Error::Transcript)?;
+    }
     #[cfg(feature = "det-prove")]
     let mut rng = <StdRng as rand::SeedableRng>::from_seed([0u8; 32]);
     #[cfg(not(feature = "det-prove"))]
@@ -631,12 +756,13 @@ where
     if check_mode == CheckMode::SAFE {
         debug!("verifying generated proof");
         let verifier_params = params.verifier_params();
-        verify_proof_circuit::<V, Scheme, Strategy, E, TR>(
+        verify_proof_circuit_with_domain::<V, Scheme, Strategy, E, TR>(
             &checkable_pf,
             verifier_params,
             pk.get_vk(),
             strategy,
             verifier_params.n(),
+            domain,
         )?;
     }
     let elapsed = now.elapsed();
@@ -649,6 +775,26 @@ where
     Ok(checkable_pf)
 }
 
+/// Absorbs a domain separation label into a transcript, so every challenge squeezed after it,
+/// and hence the proof, depends on the label. The label is absorbed as its length followed by
+/// 31 byte chunks, each of which fits in a scalar without reduction.
+fn absorb_domain<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>>(
+    transcript: &mut T,
+    domain: &str,
+) -> io::Result<()>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    let bytes = domain.as_bytes();
+    transcript.common_scalar(C::Scalar::from(bytes.len() as u64))?;
+    for chunk in bytes.chunks(31) {
+        let mut wide = [0u8; 64];
+        wide[..chunk.len()].copy_from_slice(chunk);
+        transcript.common_scalar(C::Scalar::from_uniform_bytes(&wide))?;
+    }
+    Ok(())
+}
+
 /// Swaps the proof commitments to a new set in the proof
 pub fn swap_proof_commitments<
     Scheme: CommitmentScheme,
@@ -752,6 +898,68 @@ pub fn swap_proof_commitments_polycommit(
     Ok(proof)
 }
 
+/// Checks that the first commitments in the proof are `commitments`. With polycommit visibility
+/// these are the commitments to the committed advice (eg. the private weights), so a verifier can
+/// hold a proof to a published commitment without learning the committed values.
+pub fn check_proof_commitments<
+    Scheme: CommitmentScheme,
+    E: EncodedChallenge<Scheme::Curve>,
+    TW: TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+>(
+    snark: &Snark<Scheme::Scalar, Scheme::Curve>,
+    commitments: &[Scheme::Curve],
+) -> Result<(), PfsysError>
+where
+    Scheme::Scalar: SerdeObject
+        + PrimeField
+        + FromUniformBytes<64>
+        + WithSmallOrderMulGroup<3>
+        + Ord
+        + Serialize
+        + DeserializeOwned,
+    Scheme::Curve: Serialize + DeserializeOwned,
+{
+    let expected = get_proof_commitments::<Scheme, E, TW>(commitments)?;
+    if !snark.proof.starts_with(&expected) {
+        return Err(PfsysError::CommitmentMismatch);
+    }
+    Ok(())
+}
+
+/// Checks the proof commitments against a published set, see [check_proof_commitments]
+pub fn check_proof_commitments_polycommit(
+    snark: &Snark<Fr, G1Affine>,
+    commitments: &[G1Affine],
+) -> Result<(), PfsysError> {
+    match snark.commitment {
+        Some(Commitments::KZG) => match snark.transcript_type {
+            TranscriptType::EVM => check_proof_commitments::<
+                KZGCommitmentScheme<Bn256>,
+                _,
+                EvmTranscript<G1Affine, _, _, _>,
+            >(snark, commitments),
+            TranscriptType::Poseidon => check_proof_commitments::<
+                KZGCommitmentScheme<Bn256>,
+                _,
+                PoseidonTranscript<NativeLoader, _>,
+            >(snark, commitments),
+        },
+        Some(Commitments::IPA) => match snark.transcript_type {
+            TranscriptType::EVM => check_proof_commitments::<
+                IPACommitmentScheme<G1Affine>,
+                _,
+                EvmTranscript<G1Affine, _, _, _>,
+            >(snark, commitments),
+            TranscriptType::Poseidon => check_proof_commitments::<
+                IPACommitmentScheme<G1Affine>,
+                _,
+                PoseidonTranscript<NativeLoader, _>,
+            >(snark, commitments),
+        },
+        None => Err(PfsysError::InvalidCommitmentScheme),
+    }
+}
+
 /// A wrapper around halo2's verify_proof
 pub fn verify_proof_circuit<
     'params,
@@ -767,6 +975,37 @@ pub fn verify_proof_circuit<
     strategy: Strategy,
     orig_n: u64,
 ) -> Result<Strategy::Output, halo2_proofs::plonk::Error>
+where
+    Scheme::Scalar: SerdeObject
+        + PrimeField
+        + FromUniformBytes<64>
+        + WithSmallOrderMulGroup<3>
+        + Serialize
+        + DeserializeOwned,
+    Scheme::Curve: Serialize + DeserializeOwned,
+{
+    verify_proof_circuit_with_domain::<V, Scheme, Strategy, E, TR>(
+        snark, params, vk, strategy, orig_n, None,
+    )
+}
+
+/// Like [verify_proof_circuit], but only accepts proofs made by
+/// [create_proof_circuit_with_domain] under the same domain separation label.
+pub fn verify_proof_circuit_with_domain<
+    'params,
+    V: Verifier<'params, Scheme>,
+    Scheme: CommitmentScheme,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+    E: EncodedChallenge<Scheme::Curve>,
+    TR: TranscriptReadBuffer<Cursor<Vec<u8>>, Scheme::Curve, E>,
+>(
+    snark: &Snark<Scheme::Scalar, Scheme::Curve>,
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    orig_n: u64,
+    domain: Option<&str>,
+) -> Result<Strategy::Output, halo2_proofs::plonk::Error>
 where
     Scheme::Scalar: SerdeObject
         + PrimeField
@@ -785,6 +1024,9 @@ where
     trace!("instances {:?}", instances);
 
     let mut transcript = TranscriptReadBuffer::init(Cursor::new(snark.proof.clone()));
+    if let Some(domain) = domain {
+        absorb_domain(&mut transcript, domain)?;
+    }
     verify_proof::<Scheme, V, _, TR, _>(params, vk, strategy, instances, &mut transcript, orig_n)
 }
 
@@ -903,6 +1145,110 @@ mod tests {
         assert!(res.is_ok())
     }
 
+    /// `a + b` over four cells, shared by the tests of the proof system modules. Either input can
+    /// hold constants, which are assigned to the constant column.
+    #[derive(Clone)]
+    pub(super) struct AddCircuit {
+        pub(super) inputs: [crate::tensor::ValTensor<Fr>; 2],
+    }
+
+    impl Circuit<Fr> for AddCircuit {
+        type Config = crate::circuit::BaseConfig<Fr>;
+        type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut halo2_proofs::plonk::ConstraintSystem<Fr>) -> Self::Config {
+            let vars = (0..3)
+                .map(|_| crate::tensor::VarTensor::new_advice(cs, 6, 1, 4))
+                .collect::<Vec<_>>();
+            let _constants = crate::tensor::VarTensor::constant_cols(cs, 6, 4, false);
+            Self::Config::configure(
+                cs,
+                &[vars[0].clone(), vars[1].clone()],
+                &vars[2],
+                CheckMode::SAFE,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+        ) -> Result<(), halo2_proofs::plonk::Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = crate::circuit::region::RegionCtx::new(region, 0, 1, 128, 2);
+                    config
+                        .layout(
+                            &mut region,
+                            &self.inputs,
+                            Box::new(crate::circuit::ops::poly::PolyOp::Add),
+                        )
+                        .map_err(|_| halo2_proofs::plonk::Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_create_keys_cached() {
+        let tmp_dir = Builder::new().prefix("key_cache").tempdir().unwrap();
+        let cache_dir = tmp_dir.path().join("keys");
+
+        let input = || {
+            crate::tensor::ValTensor::from(crate::tensor::Tensor::from(
+                (0..4).map(|i| Value::known(Fr::from(i as u64))),
+            ))
+        };
+        let circuit = AddCircuit {
+            inputs: [input(), input()],
+        };
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+
+        let (pk, cached) = create_keys_cached::<KZGCommitmentScheme<Bn256>, AddCircuit>(
+            &circuit, &params, true, &cache_dir, "add",
+        )
+        .unwrap();
+        assert!(!cached);
+        assert!(cache_dir.join("add.pk").exists());
+
+        // the same architecture hits the cache and gets the same keys back
+        let (cached_pk, cached) = create_keys_cached::<KZGCommitmentScheme<Bn256>, AddCircuit>(
+            &circuit, &params, true, &cache_dir, "add",
+        )
+        .unwrap();
+        assert!(cached);
+        assert_eq!(
+            cached_pk.get_vk().transcript_repr(),
+            pk.get_vk().transcript_repr()
+        );
+
+        // a new fingerprint misses the cache
+        let (_, cached) = create_keys_cached::<KZGCommitmentScheme<Bn256>, AddCircuit>(
+            &circuit, &params, true, &cache_dir, "add_v2",
+        )
+        .unwrap();
+        assert!(!cached);
+    }
+
+    #[test]
+    fn test_graph_fingerprint() {
+        let mut circuit = crate::graph::GraphCircuit::default();
+        let fingerprint = circuit.fingerprint().unwrap();
+
+        circuit.settings_mut().timestamp = Some(1);
+        assert_eq!(circuit.fingerprint().unwrap(), fingerprint);
+
+        circuit.settings_mut().run_args.logrows += 1;
+        assert_ne!(circuit.fingerprint().unwrap(), fingerprint);
+    }
+
     #[test]
     fn test_snark_serialization_roundtrip() {
         let snark = Snark::<Fr, G1Affine> {
@@ -929,4 +1275,211 @@ mod tests {
         assert_eq!(snark.proof, snark2.proof);
         assert_eq!(snark.transcript_type, snark2.transcript_type);
     }
+
+    #[test]
+    fn test_snark_size_report() {
+        use halo2_proofs::poly::kzg::{
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        };
+        use snark_verifier::system::halo2::{compile, Config};
+
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+        let circuit = |offset: u64| {
+            let input = || {
+                crate::tensor::ValTensor::from(crate::tensor::Tensor::from(
+                    (0..4).map(|i| Value::known(Fr::from(i + offset))),
+                ))
+            };
+            AddCircuit {
+                inputs: [input(), input()],
+            }
+        };
+        let pk = create_keys::<KZGCommitmentScheme<Bn256>, AddCircuit>(&circuit(0), &params, true)
+            .unwrap();
+        let protocol = compile(
+            &params,
+            pk.get_vk(),
+            Config::kzg().with_num_instance(vec![]),
+        );
+
+        let prove = |circuit: AddCircuit| {
+            create_proof_circuit::<
+                KZGCommitmentScheme<_>,
+                _,
+                ProverSHPLONK<_>,
+                VerifierSHPLONK<_>,
+                SingleStrategy<_>,
+                _,
+                EvmTranscript<_, _, _, _>,
+                EvmTranscript<_, _, _, _>,
+            >(
+                circuit,
+                vec![],
+                &params,
+                &pk,
+                CheckMode::SAFE,
+                Commitments::KZG,
+                TranscriptType::EVM,
+                None,
+                Some(protocol.clone()),
+            )
+            .unwrap()
+        };
+        let snark = prove(circuit(0));
+        let report = snark.size_report();
+
+        // the evm transcript writes every scalar as one 32 byte word and every point as two
+        assert!(report.proof_bytes > 0);
+        assert_eq!(report.proof_bytes % 32, 0);
+        assert_eq!(report.num_words * 32, report.proof_bytes);
+        assert_eq!(report.num_instances, 0);
+
+        // on top of the witness commitments and evaluations the proof carries the quotient
+        // commitments and the opening proof
+        let num_commitments = report.num_commitments.unwrap();
+        let num_evaluations = report.num_evaluations.unwrap();
+        assert!(num_commitments > 0 && num_evaluations > 0);
+        assert!(report.proof_bytes > 64 * num_commitments + 32 * num_evaluations);
+
+        // the size depends on the circuit, not on the witness
+        assert_eq!(prove(circuit(5)).size_report(), report);
+    }
+
+    #[test]
+    fn test_proof_domain_separation() {
+        use halo2_proofs::poly::kzg::{
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        };
+
+        let input = || {
+            crate::tensor::ValTensor::from(crate::tensor::Tensor::from(
+                (0..4).map(|i| Value::known(Fr::from(i as u64))),
+            ))
+        };
+        let circuit = AddCircuit {
+            inputs: [input(), input()],
+        };
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+        let pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, AddCircuit>(&circuit, &params, true).unwrap();
+
+        let snark = create_proof_circuit_with_domain::<
+            KZGCommitmentScheme<_>,
+            _,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            SingleStrategy<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            EvmTranscript<_, _, _, _>,
+        >(
+            circuit,
+            vec![],
+            &params,
+            &pk,
+            // safe mode checks the proof verifies under its own label
+            CheckMode::SAFE,
+            Commitments::KZG,
+            TranscriptType::EVM,
+            None,
+            None,
+            Some("modelA"),
+        )
+        .unwrap();
+
+        let verify = |domain: Option<&str>| {
+            verify_proof_circuit_with_domain::<
+                VerifierSHPLONK<'_, Bn256>,
+                KZGCommitmentScheme<Bn256>,
+                _,
+                _,
+                EvmTranscript<_, _, _, _>,
+            >(
+                &snark,
+                &params,
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                params.n(),
+                domain,
+            )
+        };
+        assert!(verify(Some("modelA")).is_ok());
+        assert!(verify(Some("modelB")).is_err());
+        assert!(verify(None).is_err());
+    }
+
+    #[test]
+    fn test_frozen_params_are_committed() {
+        use crate::fieldutils::IntegerRep;
+        use crate::tensor::{Tensor, ValTensor};
+        use halo2_proofs::poly::kzg::{
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        };
+
+        let circuit = |frozen: &[IntegerRep], trainable: &[IntegerRep]| {
+            let param = |values: &[IntegerRep], trainable: bool| {
+                ValTensor::from_integer_rep_param(
+                    Tensor::new(Some(values), &[values.len()]).unwrap(),
+                    trainable,
+                )
+            };
+            AddCircuit {
+                inputs: [param(frozen, false), param(trainable, true)],
+            }
+        };
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+        let pk = create_keys::<KZGCommitmentScheme<Bn256>, AddCircuit>(
+            &circuit(&[1, 2, 3, 4], &[0, 0, 0, 0]),
+            &params,
+            true,
+        )
+        .unwrap();
+
+        let prove_and_verify = |circuit: AddCircuit| {
+            let snark = create_proof_circuit::<
+                KZGCommitmentScheme<_>,
+                _,
+                ProverSHPLONK<_>,
+                VerifierSHPLONK<_>,
+                SingleStrategy<_>,
+                _,
+                EvmTranscript<_, _, _, _>,
+                EvmTranscript<_, _, _, _>,
+            >(
+                circuit,
+                vec![],
+                &params,
+                &pk,
+                CheckMode::UNSAFE,
+                Commitments::KZG,
+                TranscriptType::EVM,
+                None,
+                None,
+            )
+            .unwrap();
+            verify_proof_circuit::<
+                VerifierSHPLONK<'_, Bn256>,
+                KZGCommitmentScheme<Bn256>,
+                _,
+                _,
+                EvmTranscript<_, _, _, _>,
+            >(
+                &snark,
+                &params,
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                params.n(),
+            )
+            .is_ok()
+        };
+
+        // a step that only updates the trainable params proves under the same keys
+        assert!(prove_and_verify(circuit(&[1, 2, 3, 4], &[0, 0, 0, 0])));
+        assert!(prove_and_verify(circuit(&[1, 2, 3, 4], &[5, -1, 2, 7])));
+        // but the frozen params are fixed by the verifying key
+        assert!(!prove_and_verify(circuit(&[1, 2, 3, 5], &[0, 0, 0, 0])));
+    }
 }
@@ -17,6 +17,9 @@ pub enum TensorError {
     /// Significant bit truncation when instantiating
     #[error("significant bit truncation when instantiating, try lowering the scale")]
     SigBitTruncationError,
+    /// A NaN or infinite value was quantized
+    #[error("cannot quantize the non-finite value {0}, check the model and its inputs for NaN or infinite entries")]
+    NonFiniteValue(f64),
     /// Failed to convert to field element tensor
     #[error("failed to convert to field element tensor")]
     FeltError,
@@ -61,6 +61,31 @@ pub trait TensorType: Clone + Debug + 'static {
     }
 }
 
+/// A [TensorType] that supports addition and multiplication, and whose [TensorType::zero] and
+/// [TensorType::one] are defined. Ops that accumulate values are bound on this rather than on
+/// [TensorType], so that tensors of non-numeric elements (eg. of [Column]s) stay valid tensors
+/// that just don't have the numeric ops.
+///
+/// This is implemented for every [TensorType] with the arithmetic bounds, which includes
+/// [IntegerRep], the field types and [Value]s of those.
+/// ```
+/// use ezkl::tensor::{ops::accumulated::sum, Tensor};
+/// use ezkl::fieldutils::IntegerRep;
+///
+/// let x = Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[3]).unwrap();
+/// assert_eq!(sum(&x, 1).unwrap().last(), Some(&6));
+/// ```
+/// `()` is a [TensorType] but has no arithmetic, so it can't be summed:
+/// ```compile_fail
+/// use ezkl::tensor::{ops::accumulated::sum, Tensor};
+///
+/// let x = Tensor::<()>::new(Some(&[(), ()]), &[2]).unwrap();
+/// sum(&x, 1).unwrap();
+/// ```
+pub trait NumericTensorType: TensorType + Add<Output = Self> + Mul<Output = Self> {}
+
+impl<T: TensorType + Add<Output = T> + Mul<Output = T>> NumericTensorType for T {}
+
 macro_rules! tensor_type {
     ($rust_type:ty, $tensor_type:ident, $zero:expr_2021, $one:expr_2021) => {
         impl TensorType for $rust_type {
@@ -101,6 +126,28 @@ impl TensorType for f32 {
     }
 }
 
+#[cfg(feature = "half")]
+impl TensorType for half::f16 {
+    fn zero() -> Option<Self> {
+        Some(half::f16::ZERO)
+    }
+
+    fn tmax(&self, other: &Self) -> Option<Self> {
+        Some(self.max(*other))
+    }
+}
+
+#[cfg(feature = "half")]
+impl TensorType for half::bf16 {
+    fn zero() -> Option<Self> {
+        Some(half::bf16::ZERO)
+    }
+
+    fn tmax(&self, other: &Self) -> Option<Self> {
+        Some(self.max(*other))
+    }
+}
+
 impl TensorType for f64 {
     fn zero() -> Option<Self> {
         Some(0.0)
@@ -139,6 +186,16 @@ impl<T: TensorType> TensorType for Tensor<T> {
     }
 }
 
+impl<T: TensorType> TensorType for Option<T> {
+    fn zero() -> Option<Self> {
+        Some(T::zero())
+    }
+
+    fn one() -> Option<Self> {
+        Some(T::one())
+    }
+}
+
 impl<T: TensorType> TensorType for Value<T> {
     fn zero() -> Option<Self> {
         Some(Value::known(T::zero().unwrap()))
@@ -458,6 +515,246 @@ impl<T: Clone + TensorType> Tensor<T> {
         }
     }
 
+    /// Builds a tensor of shape `dims` by evaluating `f` at each coordinate in row-major order.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::from_fn(&[2, 3], |coord| (10 * coord[0] + coord[1]) as IntegerRep);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[0, 1, 2, 10, 11, 12]), &[2, 3]).unwrap();
+    /// assert_eq!(a, expected);
+    /// ```
+    pub fn from_fn(dims: &[usize], mut f: impl FnMut(&[usize]) -> T) -> Self {
+        let inner = if dims.is_empty() {
+            vec![]
+        } else {
+            dims.iter()
+                .map(|d| 0..*d)
+                .multi_cartesian_product()
+                .map(|coord| f(&coord))
+                .collect()
+        };
+        Tensor {
+            inner,
+            dims: Vec::from(dims),
+            scale: None,
+            visibility: None,
+        }
+    }
+
+    /// Stacks tensors of the same shape along a new axis inserted at `axis`, unlike
+    /// [crate::tensor::ops::concat] which joins them along an existing one. Stacking single
+    /// samples along axis 0 batches them.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2]), &[2]).unwrap();
+    /// let b = Tensor::<IntegerRep>::new(Some(&[3, 4]), &[2]).unwrap();
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 3, 2, 4]), &[2, 2]).unwrap();
+    /// assert_eq!(Tensor::stack(&[a.clone(), b.clone()], 1).unwrap(), expected);
+    ///
+    /// let c = Tensor::<IntegerRep>::new(Some(&[5, 6, 7]), &[3]).unwrap();
+    /// assert!(Tensor::stack(&[a.clone(), c], 0).is_err());
+    /// assert!(Tensor::stack(&[a, b], 2).is_err());
+    /// ```
+    pub fn stack(tensors: &[Tensor<T>], axis: usize) -> Result<Self, TensorError> {
+        let first = tensors.first().ok_or_else(|| {
+            TensorError::DimError("cannot stack an empty list of tensors".to_string())
+        })?;
+        if let Some(t) = tensors.iter().find(|t| t.dims() != first.dims()) {
+            return Err(TensorError::DimMismatch(format!(
+                "stack expects tensors of the same shape, got {:?} and {:?}",
+                first.dims(),
+                t.dims()
+            )));
+        }
+        if axis > first.dims().len() {
+            return Err(TensorError::DimError(format!(
+                "cannot stack along axis {} of tensors with {} dims",
+                axis,
+                first.dims().len()
+            )));
+        }
+
+        let mut dims = first.dims().to_vec();
+        dims.insert(axis, tensors.len());
+        Ok(Tensor::from_fn(&dims, |coord| {
+            let mut coord = coord.to_vec();
+            let i = coord.remove(axis);
+            tensors[i].get(&coord)
+        }))
+    }
+
+    /// Splits the tensor into tiles of shape `tile_dims`, each returned with the ranges of the
+    /// tensor it covers, in row-major order of the tile origins. Every dim must be a multiple of
+    /// the tile's so that the tiles partition the tensor, which lets an input too large for a
+    /// single circuit be proven a tile at a time and then reassembled with [Tensor::from_tiles].
+    ///
+    /// The tiles are proven independently, so nothing in a tile's proof ties it to the rest of
+    /// the input: proofs of tiles from different inputs pass just as well. To bind them to one
+    /// input, make each tile's hash public (e.g. with the Poseidon module) and check the hashes
+    /// against a commitment to the whole input, such as the hash of all the tile hashes in order.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&(0..16).collect::<Vec<_>>()), &[4, 4]).unwrap();
+    ///
+    /// let tiles = a.tiles(&[2, 2]).unwrap();
+    /// assert_eq!(tiles.len(), 4);
+    /// assert_eq!(tiles[1].0, vec![0..2, 2..4]);
+    /// assert_eq!(tiles[1].1, Tensor::<IntegerRep>::new(Some(&[2, 3, 6, 7]), &[2, 2]).unwrap());
+    ///
+    /// assert!(a.tiles(&[3, 2]).is_err());
+    /// assert!(a.tiles(&[2]).is_err());
+    /// ```
+    pub fn tiles(
+        &self,
+        tile_dims: &[usize],
+    ) -> Result<Vec<(Vec<Range<usize>>, Tensor<T>)>, TensorError>
+    where
+        T: Send + Sync,
+    {
+        if tile_dims.len() != self.dims.len()
+            || tile_dims
+                .iter()
+                .zip(self.dims.iter())
+                .any(|(t, d)| *t == 0 || d % t != 0)
+        {
+            return Err(TensorError::DimMismatch(format!(
+                "tiles of shape {:?} do not evenly divide a tensor of shape {:?}",
+                tile_dims, self.dims
+            )));
+        }
+
+        self.dims
+            .iter()
+            .zip(tile_dims.iter())
+            .map(|(d, t)| (0..*d).step_by(*t).map(move |start| start..start + t))
+            .multi_cartesian_product()
+            .map(|ranges| {
+                let tile = self.get_slice(&ranges)?;
+                Ok((ranges, tile))
+            })
+            .collect()
+    }
+
+    /// Reassembles a tensor of shape `dims` from tiles and the ranges they cover, as returned by
+    /// [Tensor::tiles]. Errors unless the tiles cover every element exactly once, see
+    /// [crate::tensor::ops::check_tiling].
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&(0..12).collect::<Vec<_>>()), &[2, 6]).unwrap();
+    ///
+    /// let mut tiles = a.tiles(&[2, 3]).unwrap();
+    /// tiles.reverse();
+    /// assert_eq!(Tensor::from_tiles(&[2, 6], &tiles).unwrap(), a);
+    ///
+    /// assert!(Tensor::from_tiles(&[2, 6], &tiles[..1]).is_err());
+    /// ```
+    pub fn from_tiles(
+        dims: &[usize],
+        tiles: &[(Vec<Range<usize>>, Tensor<T>)],
+    ) -> Result<Self, TensorError>
+    where
+        T: Send + Sync,
+    {
+        let ranges = tiles.iter().map(|(r, _)| r.clone()).collect::<Vec<_>>();
+        ops::check_tiling(dims, &ranges)?;
+
+        let mut output = Tensor::new(None, dims)?;
+        for (ranges, tile) in tiles {
+            let tile_dims = ranges.iter().map(|r| r.end - r.start).collect::<Vec<_>>();
+            if tile.dims() != tile_dims.as_slice() {
+                return Err(TensorError::DimMismatch(format!(
+                    "tile of shape {:?} does not match the ranges {:?} it covers",
+                    tile.dims(),
+                    ranges
+                )));
+            }
+            output.set_slice(ranges, tile)?;
+        }
+        Ok(output)
+    }
+
+    /// Selects elementwise between `a` where `mask` is set and `b` where it is not. All three
+    /// tensors must have the same shape. This is the data side of a masked layer, handy for
+    /// building the expected outputs of gated ops.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let mask = Tensor::<bool>::new(Some(&[true, false, false, true]), &[2, 2]).unwrap();
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4]), &[2, 2]).unwrap();
+    /// let b = Tensor::<IntegerRep>::new(Some(&[-1, -2, -3, -4]), &[2, 2]).unwrap();
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, -2, -3, 4]), &[2, 2]).unwrap();
+    /// assert_eq!(Tensor::where_mask(&mask, &a, &b).unwrap(), expected);
+    ///
+    /// let c = Tensor::<IntegerRep>::new(Some(&[1, 2]), &[2]).unwrap();
+    /// assert!(Tensor::where_mask(&mask, &a, &c).is_err());
+    /// ```
+    pub fn where_mask(
+        mask: &Tensor<bool>,
+        a: &Tensor<T>,
+        b: &Tensor<T>,
+    ) -> Result<Self, TensorError> {
+        if mask.dims() != a.dims() || a.dims() != b.dims() {
+            return Err(TensorError::DimMismatch(format!(
+                "where_mask expects a mask and tensors of the same shape, got {:?}, {:?} and {:?}",
+                mask.dims(),
+                a.dims(),
+                b.dims()
+            )));
+        }
+
+        let mut output = a.clone();
+        for (i, selected) in mask.iter().enumerate() {
+            if !selected {
+                output[i] = b[i].clone();
+            }
+        }
+        Ok(output)
+    }
+
+    /// Cumulative sum along `axis`: each element of the output is the sum of the elements of the
+    /// tensor up to and including it along that axis.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap();
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 3, 6, 4, 9, 15]), &[2, 3]).unwrap();
+    /// assert_eq!(a.cumsum(1).unwrap(), expected);
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 5, 7, 9]), &[2, 3]).unwrap();
+    /// assert_eq!(a.cumsum(0).unwrap(), expected);
+    ///
+    /// assert!(a.cumsum(2).is_err());
+    /// ```
+    pub fn cumsum(&self, axis: usize) -> Result<Self, TensorError>
+    where
+        T: Add<Output = T>,
+    {
+        if axis >= self.dims.len() {
+            return Err(TensorError::DimError(format!(
+                "cannot take the cumsum along axis {} of a tensor with {} dims",
+                axis,
+                self.dims.len()
+            )));
+        }
+
+        // consecutive elements along the axis are a stride apart, and in row-major order each
+        // one comes after the running sum it is added to
+        let stride = self.dims[axis + 1..].iter().product::<usize>();
+        let mut output = self.clone();
+        for i in 0..output.len() {
+            if (i / stride) % self.dims[axis] != 0 {
+                output[i] = output[i - stride].clone() + self[i].clone();
+            }
+        }
+        Ok(output)
+    }
+
     /// set the tensor's (optional) scale parameter
     pub fn set_scale(&mut self, scale: crate::Scale) {
         self.scale = Some(scale)
@@ -525,6 +822,32 @@ impl<T: Clone + TensorType> Tensor<T> {
         self[index].clone()
     }
 
+    /// Get a single value from the Tensor, or `fill` if any of the (signed) `coords` falls
+    /// outside the tensor. Useful for boundary conditions, e.g. zero or constant padding, when
+    /// preprocessing data.
+    ///
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap();
+    ///
+    /// assert_eq!(a.get_padded(&[1, 2], -1), 6);
+    /// assert_eq!(a.get_padded(&[-1, 0], -1), -1);
+    /// assert_eq!(a.get_padded(&[0, 3], -1), -1);
+    /// assert_eq!(a.get_padded(&[2, 0], 0), 0);
+    /// ```
+    pub fn get_padded(&self, coords: &[isize], fill: T) -> T {
+        assert_eq!(self.dims.len(), coords.len());
+        let mut indices = Vec::with_capacity(coords.len());
+        for (c, d) in coords.iter().zip(self.dims.iter()) {
+            if *c < 0 || *c as usize >= *d {
+                return fill;
+            }
+            indices.push(*c as usize);
+        }
+        self.get(&indices)
+    }
+
     /// Get a mutable array index from rows / columns indices.
     ///
     /// ```
@@ -650,6 +973,64 @@ impl<T: Clone + TensorType> Tensor<T> {
         Tensor::new(Some(&res), &dims)
     }
 
+    /// Get a strided slice from the Tensor, keeping every `steps[i]`-th index of `indices[i]`
+    /// along axis `i`. Axes past `indices` are kept whole.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[1, 6]).unwrap();
+    ///
+    /// // every other element along the last axis
+    /// let b = Tensor::<IntegerRep>::new(Some(&[1, 3, 5]), &[1, 3]).unwrap();
+    /// assert_eq!(a.get_slice_strided(&[0..1, 0..6], &[1, 2]).unwrap(), b);
+    ///
+    /// // the last element is kept when the step lands on it
+    /// let c = Tensor::<IntegerRep>::new(Some(&[2, 5]), &[1, 2]).unwrap();
+    /// assert_eq!(a.get_slice_strided(&[0..1, 1..6], &[1, 3]).unwrap(), c);
+    ///
+    /// assert!(a.get_slice_strided(&[0..1, 0..6], &[1, 0]).is_err());
+    /// ```
+    pub fn get_slice_strided(
+        &self,
+        indices: &[Range<usize>],
+        steps: &[usize],
+    ) -> Result<Tensor<T>, TensorError>
+    where
+        T: Send + Sync,
+    {
+        if indices.len() != steps.len() || steps.iter().any(|&s| s == 0) {
+            return Err(TensorError::DimError(format!(
+                "expected a positive step for each of the slice ranges {:?}, got {:?}",
+                indices, steps
+            )));
+        }
+        if steps.iter().all(|&s| s == 1) {
+            return self.get_slice(indices);
+        }
+        if self.dims.len() < indices.len() {
+            return Err(TensorError::DimError(format!(
+                "The dimensionality of the slice {:?} is greater than the tensor's {:?}",
+                indices, self.dims
+            )));
+        }
+
+        let mut full_indices = indices
+            .iter()
+            .zip(steps)
+            .map(|(range, step)| range.clone().step_by(*step))
+            .collect::<Vec<_>>();
+        full_indices.extend((indices.len()..self.dims.len()).map(|i| (0..self.dims[i]).step_by(1)));
+
+        let dims: Vec<usize> = full_indices.iter().map(|e| e.len()).collect();
+        let res = full_indices
+            .into_iter()
+            .multi_cartesian_product()
+            .map(|coord| self[self.get_index(&coord)].clone())
+            .collect::<Vec<_>>();
+
+        Tensor::new(Some(&res), &dims)
+    }
+
     /// Set a slice of the Tensor.
     /// ```
     /// use ezkl::tensor::Tensor;
@@ -909,6 +1290,16 @@ impl<T: Clone + TensorType> Tensor<T> {
         &self.dims
     }
 
+    /// Returns the tensor's shape, the same as [Tensor::dims].
+    pub fn shape(&self) -> &[usize] {
+        &self.dims
+    }
+
+    /// Returns the number of dimensions of the tensor.
+    pub fn ndim(&self) -> usize {
+        self.dims.len()
+    }
+
     ///Reshape the tensor
     /// ```
     /// use ezkl::tensor::Tensor;
@@ -944,6 +1335,64 @@ impl<T: Clone + TensorType> Tensor<T> {
         Ok(())
     }
 
+    /// Removes size-1 dims: only `axis` if one is given, otherwise all of them. This only changes
+    /// the shape, the underlying values are untouched.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[1, 3, 1]).unwrap();
+    /// assert_eq!(a.squeeze(None).unwrap().dims(), &[3]);
+    /// assert_eq!(a.squeeze(Some(2)).unwrap().dims(), &[1, 3]);
+    /// assert_eq!(a.squeeze(None).unwrap(), Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[3]).unwrap());
+    ///
+    /// // only size-1 dims can be squeezed
+    /// assert!(a.squeeze(Some(1)).is_err());
+    /// assert!(a.squeeze(Some(3)).is_err());
+    /// ```
+    pub fn squeeze(&self, axis: Option<usize>) -> Result<Self, TensorError> {
+        let dims = match axis {
+            Some(axis) => {
+                if self.dims.get(axis) != Some(&1) {
+                    return Err(TensorError::DimError(format!(
+                        "cannot squeeze axis {} of a tensor with dims {:?}",
+                        axis, self.dims
+                    )));
+                }
+                let mut dims = self.dims.clone();
+                dims.remove(axis);
+                dims
+            }
+            None => self.dims.iter().copied().filter(|d| *d != 1).collect(),
+        };
+        let mut output = self.clone();
+        output.reshape(&dims)?;
+        Ok(output)
+    }
+
+    /// Inserts a size-1 dim at `axis`. This only changes the shape, the underlying values are
+    /// untouched.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[3]).unwrap();
+    /// assert_eq!(a.unsqueeze(0).unwrap().dims(), &[1, 3]);
+    /// assert_eq!(a.unsqueeze(1).unwrap().dims(), &[3, 1]);
+    /// assert!(a.unsqueeze(2).is_err());
+    /// ```
+    pub fn unsqueeze(&self, axis: usize) -> Result<Self, TensorError> {
+        if axis > self.dims.len() {
+            return Err(TensorError::DimError(format!(
+                "cannot unsqueeze axis {} of a tensor with dims {:?}",
+                axis, self.dims
+            )));
+        }
+        let mut dims = self.dims.clone();
+        dims.insert(axis, 1);
+        let mut output = self.clone();
+        output.reshape(&dims)?;
+        Ok(output)
+    }
+
     /// Move axis of the tensor
     /// ```
     /// use ezkl::tensor::Tensor;
@@ -1088,6 +1537,40 @@ impl<T: Clone + TensorType> Tensor<T> {
         Ok(output)
     }
 
+    /// Cyclically shifts the elements by `shift` positions along `axis`, elements shifted past
+    /// the end wrap around to the start. A negative shift rolls towards the start.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap();
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[3, 1, 2, 6, 4, 5]), &[2, 3]).unwrap();
+    /// assert_eq!(a.roll(1, 1).unwrap(), expected);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[4, 5, 6, 1, 2, 3]), &[2, 3]).unwrap();
+    /// assert_eq!(a.roll(-3, 0).unwrap(), expected);
+    /// assert!(a.roll(1, 2).is_err());
+    /// ```
+    pub fn roll(&self, shift: isize, axis: usize) -> Result<Self, TensorError> {
+        if axis >= self.dims.len() {
+            return Err(TensorError::DimError(format!(
+                "cannot roll axis {} of a tensor with dims {:?}",
+                axis, self.dims
+            )));
+        }
+        let len = self.dims[axis] as isize;
+        if len == 0 {
+            return Ok(self.clone());
+        }
+
+        let mut output = Tensor::from_fn(&self.dims, |coord| {
+            let mut source = coord.to_vec();
+            source[axis] = (coord[axis] as isize - shift).rem_euclid(len) as usize;
+            self.get(&source)
+        });
+        output.scale = self.scale;
+        output.visibility = self.visibility.clone();
+        Ok(output)
+    }
+
     /// Broadcasts the tensor to a given shape
     /// ```
     /// use ezkl::tensor::Tensor;
@@ -1152,6 +1635,211 @@ impl<T: Clone + TensorType> Tensor<T> {
         Ok(output)
     }
 
+    /// Broadcasts the tensor to `target_dims` following numpy's rules: the dims are aligned from
+    /// the right, missing leading dims are added, and each dim must either match the target or be
+    /// 1, in which case its values are repeated. Unlike [Tensor::expand] this errors on any dim
+    /// that can't be broadcast.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[1, 3]).unwrap();
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3]), &[4, 3]).unwrap();
+    /// assert_eq!(a.expand_to(&[4, 3]).unwrap(), expected);
+    ///
+    /// // leading dims are added
+    /// assert_eq!(a.expand_to(&[2, 4, 3]).unwrap().dims(), &[2, 4, 3]);
+    ///
+    /// // a dim of 3 can't be broadcast to 4, nor can a tensor lose dims
+    /// assert!(a.expand_to(&[4, 4]).is_err());
+    /// assert!(a.expand_to(&[3]).is_err());
+    /// ```
+    pub fn expand_to(&self, target_dims: &[usize]) -> Result<Self, TensorError> {
+        let offset = target_dims.len().checked_sub(self.dims.len());
+        let compatible = offset.is_some_and(|offset| {
+            self.dims
+                .iter()
+                .zip(&target_dims[offset..])
+                .all(|(d, t)| d == t || *d == 1)
+        });
+        let offset = match (offset, compatible) {
+            (Some(offset), true) => offset,
+            _ => {
+                return Err(TensorError::DimMismatch(format!(
+                    "cannot broadcast a tensor with dims {:?} to {:?}",
+                    self.dims, target_dims
+                )))
+            }
+        };
+
+        let mut output = Tensor::from_fn(target_dims, |coord| {
+            let source = self
+                .dims
+                .iter()
+                .zip(&coord[offset..])
+                .map(|(d, c)| if *d == 1 { 0 } else { *c })
+                .collect::<Vec<_>>();
+            self.get(&source)
+        });
+        output.scale = self.scale;
+        output.visibility = self.visibility.clone();
+        Ok(output)
+    }
+
+    /// Repeats the tensor `reps[i]` times along each axis `i`, following numpy's `tile`.
+    /// If `reps` and the tensor have a different number of dims, the shorter of the two is
+    /// padded with leading 1s.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[1, 3]).unwrap();
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 1, 2, 3]), &[2, 3]).unwrap();
+    /// assert_eq!(a.tile(&[2, 1]).unwrap(), expected);
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 1, 2, 3]), &[1, 6]).unwrap();
+    /// assert_eq!(a.tile(&[2]).unwrap(), expected);
+    ///
+    /// let b = Tensor::<IntegerRep>::new(Some(&[1, 2]), &[2]).unwrap();
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 2, 1, 2, 1, 2, 1, 2]), &[2, 4]).unwrap();
+    /// assert_eq!(b.tile(&[2, 2]).unwrap(), expected);
+    /// ```
+    pub fn tile(&self, reps: &[usize]) -> Result<Self, TensorError> {
+        let num_dims = std::cmp::max(self.dims().len(), reps.len());
+        let pad = |v: &[usize]| {
+            let mut padded = vec![1; num_dims - v.len()];
+            padded.extend_from_slice(v);
+            padded
+        };
+        let dims = pad(self.dims());
+        let reps = pad(reps);
+
+        let mut input = self.clone();
+        input.reshape(&dims)?;
+
+        let shape = dims
+            .iter()
+            .zip(reps.iter())
+            .map(|(d, r)| d * r)
+            .collect::<Vec<_>>();
+
+        let cartesian_coords = shape
+            .iter()
+            .map(|d| 0..*d)
+            .multi_cartesian_product()
+            .collect::<Vec<Vec<usize>>>();
+
+        let mut output = Tensor::new(None, &shape)?;
+
+        for coord in cartesian_coords {
+            let old_coord = coord
+                .iter()
+                .zip(dims.iter())
+                .map(|(c, d)| c % d)
+                .collect::<Vec<_>>();
+            output.set(&coord, input.get(&old_coord));
+        }
+
+        Ok(output)
+    }
+
+    /// Reverses the order of elements along a given axis
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap();
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[3, 2, 1, 6, 5, 4]), &[2, 3]).unwrap();
+    /// assert_eq!(a.flip(1).unwrap(), expected);
+    ///
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[4, 5, 6, 1, 2, 3]), &[2, 3]).unwrap();
+    /// assert_eq!(a.flip(0).unwrap(), expected);
+    ///
+    /// assert!(a.flip(2).is_err());
+    /// ```
+    pub fn flip(&self, axis: usize) -> Result<Self, TensorError> {
+        if axis >= self.dims.len() {
+            return Err(TensorError::DimError(format!(
+                "Cannot flip axis {} of a tensor with {} dims",
+                axis,
+                self.dims.len()
+            )));
+        }
+
+        let cartesian_coords = self
+            .dims
+            .iter()
+            .map(|d| 0..*d)
+            .multi_cartesian_product()
+            .collect::<Vec<Vec<usize>>>();
+
+        let mut output = Tensor::new(None, &self.dims)?;
+
+        for coord in cartesian_coords {
+            let mut old_coord = coord.clone();
+            old_coord[axis] = self.dims[axis] - 1 - coord[axis];
+            output.set(&coord, self.get(&old_coord));
+        }
+
+        output.scale = self.scale;
+        output.visibility = self.visibility.clone();
+
+        Ok(output)
+    }
+
+    /// Physically reorders a 4D tensor from NCHW (channels first) to NHWC (channels last) layout.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(
+    ///     Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]),
+    ///     &[1, 3, 2, 2],
+    /// ).unwrap();
+    /// let b = a.to_nhwc().unwrap();
+    /// let expected = Tensor::<IntegerRep>::new(
+    ///     Some(&[1, 5, 9, 2, 6, 10, 3, 7, 11, 4, 8, 12]),
+    ///     &[1, 2, 2, 3],
+    /// ).unwrap();
+    /// assert_eq!(b, expected);
+    ///
+    /// // converting back round-trips
+    /// assert_eq!(b.to_nchw().unwrap(), a);
+    ///
+    /// assert!(Tensor::<IntegerRep>::new(None, &[3, 2, 2]).unwrap().to_nhwc().is_err());
+    /// ```
+    pub fn to_nhwc(&self) -> Result<Self, TensorError> {
+        if self.dims.len() != 4 {
+            return Err(TensorError::DimError(format!(
+                "Expected a 4D NCHW tensor, got dims {:?}",
+                self.dims
+            )));
+        }
+        self.clone().move_axis(1, 3)
+    }
+
+    /// Physically reorders a 4D tensor from NHWC (channels last) to NCHW (channels first) layout.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(
+    ///     Some(&[1, 5, 9, 2, 6, 10, 3, 7, 11, 4, 8, 12]),
+    ///     &[1, 2, 2, 3],
+    /// ).unwrap();
+    /// let expected = Tensor::<IntegerRep>::new(
+    ///     Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]),
+    ///     &[1, 3, 2, 2],
+    /// ).unwrap();
+    /// assert_eq!(a.to_nchw().unwrap(), expected);
+    /// ```
+    pub fn to_nchw(&self) -> Result<Self, TensorError> {
+        if self.dims.len() != 4 {
+            return Err(TensorError::DimError(format!(
+                "Expected a 4D NHWC tensor, got dims {:?}",
+                self.dims
+            )));
+        }
+        self.clone().move_axis(3, 1)
+    }
+
     ///Flatten the tensor shape
     /// ```
     /// use ezkl::tensor::Tensor;
@@ -1336,6 +2024,134 @@ impl<T: Clone + TensorType> Tensor<Tensor<T>> {
     }
 }
 
+impl<T: Clone + TensorType + PartialOrd> Tensor<T> {
+    /// Returns the index of the largest element along `axis`, removing that axis from the
+    /// output. Ties go to the first occurrence, as in numpy's `argmax`. Reducing a 1D tensor
+    /// gives a tensor of shape `[1]`.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 7, 3, 9, -2, 9]), &[2, 3]).unwrap();
+    ///
+    /// let expected = Tensor::<usize>::new(Some(&[1, 0]), &[2]).unwrap();
+    /// assert_eq!(a.argmax_axis(1).unwrap(), expected);
+    ///
+    /// let expected = Tensor::<usize>::new(Some(&[1, 0, 1]), &[3]).unwrap();
+    /// assert_eq!(a.argmax_axis(0).unwrap(), expected);
+    ///
+    /// assert!(a.argmax_axis(2).is_err());
+    /// ```
+    pub fn argmax_axis(&self, axis: usize) -> Result<Tensor<usize>, TensorError> {
+        self.arg_reduce_axis(axis, |candidate, best| candidate > best)
+    }
+
+    /// Returns the index of the smallest element along `axis`, removing that axis from the
+    /// output. Ties go to the first occurrence, as in numpy's `argmin`.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 7, 1, 9, -2, 9]), &[2, 3]).unwrap();
+    ///
+    /// let expected = Tensor::<usize>::new(Some(&[0, 1]), &[2]).unwrap();
+    /// assert_eq!(a.argmin_axis(1).unwrap(), expected);
+    /// ```
+    pub fn argmin_axis(&self, axis: usize) -> Result<Tensor<usize>, TensorError> {
+        self.arg_reduce_axis(axis, |candidate, best| candidate < best)
+    }
+
+    fn arg_reduce_axis(
+        &self,
+        axis: usize,
+        replaces: impl Fn(&T, &T) -> bool,
+    ) -> Result<Tensor<usize>, TensorError> {
+        if axis >= self.dims().len() {
+            return Err(TensorError::DimError(format!(
+                "cannot reduce along axis {} of a tensor with {} dims",
+                axis,
+                self.dims().len()
+            )));
+        }
+        if self.dims()[axis] == 0 {
+            return Err(TensorError::DimError(
+                "cannot reduce along an empty axis".to_string(),
+            ));
+        }
+
+        let mut out_dims = self.dims().to_vec();
+        out_dims.remove(axis);
+
+        let indices = out_dims
+            .iter()
+            .map(|d| 0..*d)
+            .multi_cartesian_product()
+            .map(|mut coord| {
+                coord.insert(axis, 0);
+                let mut best = (0, self.get(&coord));
+                for i in 1..self.dims()[axis] {
+                    coord[axis] = i;
+                    let candidate = self.get(&coord);
+                    if replaces(&candidate, &best.1) {
+                        best = (i, candidate);
+                    }
+                }
+                best.0
+            })
+            .collect::<Vec<_>>();
+
+        if out_dims.is_empty() {
+            out_dims = vec![1];
+        }
+        Tensor::new(Some(&indices), &out_dims)
+    }
+
+    /// Elementwise `self > other`, broadcasting the two tensors against each other. Unlike the
+    /// derived [PartialOrd], which orders whole tensors, this returns a mask of the shape of the
+    /// broadcast.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 5, 3, 2]), &[2, 2]).unwrap();
+    /// let b = Tensor::<IntegerRep>::new(Some(&[2, 2]), &[2]).unwrap();
+    /// let expected = Tensor::<bool>::new(Some(&[false, true, true, false]), &[2, 2]).unwrap();
+    /// assert_eq!(a.gt(&b).unwrap(), expected);
+    ///
+    /// let c = Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[3]).unwrap();
+    /// assert!(a.gt(&c).is_err());
+    /// ```
+    pub fn gt(&self, other: &Self) -> Result<Tensor<bool>, TensorError> {
+        self.compare(other, |a, b| a > b)
+    }
+
+    /// Elementwise `self >= other`, broadcasting the two tensors against each other.
+    pub fn ge(&self, other: &Self) -> Result<Tensor<bool>, TensorError> {
+        self.compare(other, |a, b| a >= b)
+    }
+
+    /// Elementwise `self < other`, broadcasting the two tensors against each other.
+    pub fn lt(&self, other: &Self) -> Result<Tensor<bool>, TensorError> {
+        self.compare(other, |a, b| a < b)
+    }
+
+    /// Elementwise `self <= other`, broadcasting the two tensors against each other.
+    pub fn le(&self, other: &Self) -> Result<Tensor<bool>, TensorError> {
+        self.compare(other, |a, b| a <= b)
+    }
+
+    fn compare(
+        &self,
+        other: &Self,
+        op: impl Fn(&T, &T) -> bool,
+    ) -> Result<Tensor<bool>, TensorError> {
+        let broadcasted_shape = get_broadcasted_shape(self.dims(), other.dims())?;
+        let lhs = self.expand(&broadcasted_shape)?;
+        let rhs = other.expand(&broadcasted_shape)?;
+
+        let mut res: Tensor<bool> = lhs.iter().zip(rhs.iter()).map(|(a, b)| op(a, b)).collect();
+        res.reshape(&broadcasted_shape)?;
+        Ok(res)
+    }
+}
+
 impl<T: TensorType + Add<Output = T> + std::marker::Send + std::marker::Sync> Add for Tensor<T> {
     type Output = Result<Tensor<T>, TensorError>;
     /// Adds tensors.
@@ -1606,6 +2422,93 @@ impl<T: TensorType + Mul<Output = T> + std::marker::Send + std::marker::Sync> Te
         // needless overflow.
         acc.mul(base)
     }
+
+    /// Outer product of two 1D tensors.
+    /// # Arguments
+    ///
+    /// * `self` - 1D Tensor of length `m`
+    /// * `other` - 1D Tensor of length `n`
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3]), &[3]).unwrap();
+    /// let b = Tensor::<IntegerRep>::new(Some(&[4, 5, 6]), &[3]).unwrap();
+    /// let result = a.outer(&b).unwrap();
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[4, 5, 6, 8, 10, 12, 12, 15, 18]), &[3, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn outer(&self, other: &Self) -> Result<Self, TensorError> {
+        if self.dims().len() != 1 || other.dims().len() != 1 {
+            return Err(TensorError::DimError(format!(
+                "outer product requires 1D tensors, got {:?} and {:?}",
+                self.dims(),
+                other.dims()
+            )));
+        }
+
+        let inner = self
+            .inner
+            .iter()
+            .flat_map(|a| other.inner.iter().map(move |b| a.clone() * b.clone()))
+            .collect::<Vec<_>>();
+
+        Tensor::new(Some(&inner), &[self.len(), other.len()])
+    }
+
+    /// Kronecker product of two tensors with the same number of dimensions.
+    /// # Arguments
+    ///
+    /// * `self` - Tensor of shape `[a_0, ..., a_k]`
+    /// * `other` - Tensor of shape `[b_0, ..., b_k]`
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4]), &[2, 2]).unwrap();
+    /// let b = Tensor::<IntegerRep>::new(Some(&[0, 1, 1, 0]), &[2, 2]).unwrap();
+    /// let result = a.kron(&b).unwrap();
+    /// let expected = Tensor::<IntegerRep>::new(
+    ///     Some(&[0, 1, 0, 2, 1, 0, 2, 0, 0, 3, 0, 4, 3, 0, 4, 0]),
+    ///     &[4, 4],
+    /// ).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn kron(&self, other: &Self) -> Result<Self, TensorError> {
+        if self.dims().len() != other.dims().len() {
+            return Err(TensorError::DimError(format!(
+                "kronecker product requires tensors with the same number of dims, got {:?} and {:?}",
+                self.dims(),
+                other.dims()
+            )));
+        }
+
+        let output_dims = self
+            .dims()
+            .iter()
+            .zip(other.dims())
+            .map(|(a, b)| a * b)
+            .collect::<Vec<_>>();
+
+        let mut output = Tensor::new(None, &output_dims)?;
+
+        let cartesian_coords = output_dims
+            .iter()
+            .map(|d| 0..*d)
+            .multi_cartesian_product()
+            .collect::<Vec<Vec<usize>>>();
+
+        for coord in cartesian_coords {
+            let (a_coord, b_coord): (Vec<usize>, Vec<usize>) = coord
+                .iter()
+                .zip(other.dims())
+                .map(|(c, d)| (c / d, c % d))
+                .unzip();
+            output.set(&coord, self.get(&a_coord) * other.get(&b_coord));
+        }
+
+        Ok(output)
+    }
 }
 
 impl<T: TensorType + Div<Output = T> + std::marker::Send + std::marker::Sync> Div for Tensor<T> {
@@ -2026,4 +2929,78 @@ mod tests {
         let b = Tensor::<IntegerRep>::new(Some(&[1, 4]), &[2, 1]).unwrap();
         assert_eq!(a.get_slice(&[0..2, 0..1]).unwrap(), b);
     }
+
+    #[test]
+    fn tensor_roll() {
+        let a = Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4]), &[1, 4]).unwrap();
+        let right = Tensor::<IntegerRep>::new(Some(&[4, 1, 2, 3]), &[1, 4]).unwrap();
+        let left = Tensor::<IntegerRep>::new(Some(&[2, 3, 4, 1]), &[1, 4]).unwrap();
+        assert_eq!(a.roll(1, 1).unwrap(), right);
+        assert_eq!(a.roll(-1, 1).unwrap(), left);
+        // shifts wrap around the axis
+        assert_eq!(a.roll(5, 1).unwrap(), right);
+        assert_eq!(a.roll(4, 1).unwrap(), a);
+        // rolling a length 1 axis is a no-op
+        assert_eq!(a.roll(1, 0).unwrap(), a);
+    }
+
+    #[test]
+    fn tensor_from_fn_identity() {
+        let identity =
+            Tensor::<IntegerRep>::from_fn(&[3, 3], |coord| (coord[0] == coord[1]) as IntegerRep);
+        assert_eq!(identity.dims(), &[3, 3]);
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_eq!(identity.get(&[i, j]), (i == j) as IntegerRep);
+            }
+        }
+        assert_eq!(identity.iter().sum::<IntegerRep>(), 3);
+    }
+
+    #[test]
+    fn tensor_compare_scalar() {
+        let a = Tensor::<IntegerRep>::new(Some(&[-1, 3, 2, 0]), &[2, 2]).unwrap();
+        let threshold = Tensor::<IntegerRep>::new(Some(&[2]), &[1]).unwrap();
+
+        let mask = |m: &[bool]| Tensor::<bool>::new(Some(m), &[2, 2]).unwrap();
+        assert_eq!(
+            a.gt(&threshold).unwrap(),
+            mask(&[false, true, false, false])
+        );
+        assert_eq!(a.ge(&threshold).unwrap(), mask(&[false, true, true, false]));
+        assert_eq!(a.lt(&threshold).unwrap(), mask(&[true, false, false, true]));
+        assert_eq!(a.le(&threshold).unwrap(), mask(&[true, false, true, true]));
+        // the scalar can be on either side
+        assert_eq!(threshold.lt(&a).unwrap(), a.gt(&threshold).unwrap());
+    }
+
+    #[test]
+    fn tensor_stack_batch() {
+        let samples = [[1, 2], [3, 4], [5, 6]]
+            .iter()
+            .map(|x| Tensor::<IntegerRep>::new(Some(x), &[2]).unwrap())
+            .collect::<Vec<_>>();
+
+        let batch = Tensor::stack(&samples, 0).unwrap();
+        assert_eq!(batch.dims(), &[3, 2]);
+        assert_eq!(
+            batch,
+            Tensor::<IntegerRep>::new(Some(&[1, 2, 3, 4, 5, 6]), &[3, 2]).unwrap()
+        );
+        for (i, sample) in samples.iter().enumerate() {
+            let mut row = batch.get_slice(&[i..i + 1]).unwrap();
+            row.reshape(&[2]).unwrap();
+            assert_eq!(&row, sample);
+        }
+    }
+
+    #[test]
+    fn tensor_shape_utilities() {
+        let a = Tensor::<IntegerRep>::new(Some(&(0..24).collect::<Vec<_>>()), &[2, 3, 4]).unwrap();
+        assert_eq!(a.len(), 24);
+        assert_eq!(a.ndim(), 3);
+        assert_eq!(a.shape(), &[2, 3, 4]);
+        assert_eq!(a.shape(), a.dims());
+        assert!(!a.is_empty());
+    }
 }
@@ -1,10 +1,11 @@
 use super::TensorError;
 use crate::{
     fieldutils::IntegerRep,
-    tensor::{Tensor, TensorType},
+    tensor::{NumericTensorType, Tensor, TensorType},
 };
 use itertools::Itertools;
 use maybe_rayon::{iter::ParallelIterator, prelude::IntoParallelRefIterator};
+use std::ops::Range;
 pub use std::ops::{Add, Mul, Neg, Sub};
 
 #[derive(Debug, Clone, PartialEq, thiserror::Error)]
@@ -1384,6 +1385,68 @@ pub fn concat<T: TensorType + Send + Sync>(
     Ok(output)
 }
 
+/// Checks that tiles, given as the ranges of the tensor each covers, partition a tensor of shape
+/// `dims`: every tile lies within the tensor and every element is covered by exactly one tile.
+/// # Examples
+/// ```
+/// use ezkl::tensor::ops::check_tiling;
+/// let tiles = vec![vec![0..2, 0..2], vec![0..2, 2..3], vec![2..4, 0..3]];
+/// assert!(check_tiling(&[4, 3], &tiles).is_ok());
+///
+/// // overlapping tiles
+/// let tiles = vec![vec![0..2, 0..3], vec![1..4, 0..3]];
+/// assert!(check_tiling(&[4, 3], &tiles).is_err());
+///
+/// // a gap in the last row
+/// let tiles = vec![vec![0..3, 0..3]];
+/// assert!(check_tiling(&[4, 3], &tiles).is_err());
+///
+/// // a tile out of bounds
+/// let tiles = vec![vec![0..4, 0..3], vec![4..5, 0..3]];
+/// assert!(check_tiling(&[4, 3], &tiles).is_err());
+/// ```
+pub fn check_tiling(dims: &[usize], tiles: &[Vec<Range<usize>>]) -> Result<(), TensorError> {
+    let mut coverage = Tensor::<usize>::new(None, dims)?;
+
+    for tile in tiles {
+        if tile.len() != dims.len()
+            || tile
+                .iter()
+                .zip(dims.iter())
+                .any(|(r, d)| r.start >= r.end || r.end > *d)
+        {
+            return Err(TensorError::DimMismatch(format!(
+                "tile {:?} is not a non-empty region of a tensor of shape {:?}",
+                tile, dims
+            )));
+        }
+        for coord in tile.iter().cloned().multi_cartesian_product() {
+            let count = coverage.get(&coord);
+            if count > 0 {
+                return Err(TensorError::InvalidArgument(format!(
+                    "tiles overlap at {:?}",
+                    coord
+                )));
+            }
+            coverage.set(&coord, count + 1);
+        }
+    }
+
+    if let Some(coord) = dims
+        .iter()
+        .map(|d| 0..*d)
+        .multi_cartesian_product()
+        .find(|coord| coverage.get(coord) == 0)
+    {
+        return Err(TensorError::InvalidArgument(format!(
+            "tiles leave a gap at {:?}",
+            coord
+        )));
+    }
+
+    Ok(())
+}
+
 /// Slices a tensor from start to end along a given axis
 ///
 /// /// # Examples
@@ -1665,6 +1728,138 @@ pub mod nonlinearities {
         .unwrap()
     }
 
+    /// Elementwise applies mish, `x * tanh(softplus(x))`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// use ezkl::tensor::ops::nonlinearities::mish;
+    /// let x = Tensor::<IntegerRep>::new(
+    ///     Some(&[-768, -256, 0, 128, 256, 1024]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = mish(&x, 256.0);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[-37, -78, 0, 96, 221, 1023]), &[2, 3]).unwrap();
+    ///
+    /// assert_eq!(result, expected);
+    ///
+    /// ```
+    pub fn mish(a: &Tensor<IntegerRep>, scale_input: f64) -> Tensor<IntegerRep> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let softplus = kix.exp().ln_1p();
+            let rounded = (kix * softplus.tanh() * scale_input).round();
+            Ok::<_, TensorError>(rounded as IntegerRep)
+        })
+        .unwrap()
+    }
+
+    /// Elementwise applies softplus, `ln(1 + exp(x))`, to a tensor of integers.
+    /// Past `x = 20` softplus and `x` agree to within f64 precision, so large inputs are passed
+    /// through as is rather than overflowing `exp`.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// use ezkl::tensor::ops::nonlinearities::softplus;
+    /// let x = Tensor::<IntegerRep>::new(
+    ///     Some(&[-768, -256, 0, 128, 256, 1024]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = softplus(&x, 256.0);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[12, 80, 177, 249, 336, 1029]), &[2, 3]).unwrap();
+    ///
+    /// assert_eq!(result, expected);
+    ///
+    /// // exp(10000) overflows an f64
+    /// let x = Tensor::<IntegerRep>::new(Some(&[10000]), &[1]).unwrap();
+    /// assert_eq!(softplus(&x, 1.0), x);
+    /// ```
+    pub fn softplus(a: &Tensor<IntegerRep>, scale_input: f64) -> Tensor<IntegerRep> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let fout = if kix > 20.0 { kix } else { kix.exp().ln_1p() };
+            let rounded = (fout * scale_input).round();
+            Ok::<_, TensorError>(rounded as IntegerRep)
+        })
+        .unwrap()
+    }
+
+    /// Elementwise applies swish, `x * sigmoid(x)`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// use ezkl::tensor::ops::nonlinearities::swish;
+    /// let x = Tensor::<IntegerRep>::new(
+    ///     Some(&[-768, -256, 0, 128, 256, 1024]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = swish(&x, 256.0);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[-36, -69, 0, 80, 187, 1006]), &[2, 3]).unwrap();
+    ///
+    /// assert_eq!(result, expected);
+    ///
+    /// ```
+    pub fn swish(a: &Tensor<IntegerRep>, scale_input: f64) -> Tensor<IntegerRep> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let rounded = (kix / (1.0 + (-kix).exp()) * scale_input).round();
+            Ok::<_, TensorError>(rounded as IntegerRep)
+        })
+        .unwrap()
+    }
+
+    /// Elementwise applies a thresholded relu to a tensor of integers.
+    /// The output is `x` when `x > threshold` and 0 otherwise.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `threshold` - Single value (in the unscaled domain)
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// use ezkl::tensor::ops::nonlinearities::thresholded_relu;
+    /// let x = Tensor::<IntegerRep>::new(
+    ///     Some(&[-1, 0, 1, 2, 3, 4]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = thresholded_relu(&x, 1.0, 2.0);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[0, 0, 0, 0, 3, 4]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    ///
+    /// // the threshold is applied in the unscaled domain
+    /// let result = thresholded_relu(&x, 2.0, 1.0);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[0, 0, 0, 0, 3, 4]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn thresholded_relu(
+        a: &Tensor<IntegerRep>,
+        scale_input: f64,
+        threshold: f64,
+    ) -> Tensor<IntegerRep> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let res = if kix > threshold { a_i } else { 0 };
+            Ok::<_, TensorError>(res)
+        })
+        .unwrap()
+    }
+
     /// Elementwise applies exponential to a tensor of integers.
     /// # Arguments
     ///
@@ -2244,6 +2439,12 @@ pub mod nonlinearities {
     /// let result = const_div(&x, k);
     /// let expected = Tensor::<IntegerRep>::new(Some(&[1, 1, 1, 4, 1, 1]), &[2, 3]).unwrap();
     /// assert_eq!(result, expected);
+    ///
+    /// // negative numerators give signed quotients
+    /// let x = Tensor::<IntegerRep>::new(Some(&[-256, -192, -63]), &[3]).unwrap();
+    /// let result = const_div(&x, 128.0);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[-2, -2, 0]), &[3]).unwrap();
+    /// assert_eq!(result, expected);
     /// ```
     pub fn const_div(a: &Tensor<IntegerRep>, denom: f64) -> Tensor<IntegerRep> {
         a.par_enum_map(|_, a_i| {
@@ -2282,6 +2483,46 @@ pub mod nonlinearities {
         .unwrap()
     }
 
+    /// Elementwise reciprocal of positive fixed point values, `round(scale^2 / x)`, so that the
+    /// output is at the same scale as the input. Inputs that are not strictly positive map to 0;
+    /// callers are expected to constrain them away.
+    ///
+    /// Precision is worst for small `x`: a single quantization step there moves the output by a
+    /// large amount (`x = 1` and `x = 2` map to `scale^2` and `scale^2 / 2`), and so the result is
+    /// only meaningful once `x` spans several quantization steps. For `x > 2 * scale^2` the result
+    /// rounds to 0.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::fieldutils::IntegerRep;
+    /// use ezkl::tensor::ops::nonlinearities::reciprocal;
+    /// let scale = 64;
+    /// let x = Tensor::<IntegerRep>::new(Some(&[scale, 1, 2, 32, 128, 9000]), &[6]).unwrap();
+    /// let result = reciprocal(&x, scale as f64);
+    /// let expected = Tensor::<IntegerRep>::new(Some(&[scale, 4096, 2048, 128, 32, 0]), &[6]).unwrap();
+    /// assert_eq!(result, expected);
+    ///
+    /// // decreasing over the positive domain
+    /// let x = Tensor::<IntegerRep>::new(Some(&(1..1000).collect::<Vec<_>>()), &[999]).unwrap();
+    /// let result = reciprocal(&x, scale as f64);
+    /// assert!(result.iter().zip(result.iter().skip(1)).all(|(a, b)| a >= b));
+    /// assert!(result[0] > result[998]);
+    /// ```
+    pub fn reciprocal(a: &Tensor<IntegerRep>, scale: f64) -> Tensor<IntegerRep> {
+        a.par_enum_map(|_, a_i| {
+            if a_i <= 0 {
+                return Ok::<_, TensorError>(0);
+            }
+            let d_inv_x = (scale * scale) / (a_i as f64);
+            Ok::<_, TensorError>(d_inv_x.round() as IntegerRep)
+        })
+        .unwrap()
+    }
+
     /// Elementwise inverse.
     /// # Arguments
     /// * `out_scale` - Single value
@@ -2312,6 +2553,98 @@ pub mod nonlinearities {
 pub mod accumulated {
     use super::*;
 
+    /// The order in which a dot product accumulates its terms. Sums in the field don't depend on
+    /// the order, but floating point sums do, so a float reference has to accumulate like the
+    /// circuit for the two to agree to the last bit.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum AccumulationOrder {
+        /// one term at a time, in row-major order of the reduced axis
+        #[default]
+        Sequential,
+        /// in row-major order in blocks of the given size, each block summed before it is added
+        /// to the running sum. This is the order of the circuit's dot products, with one block
+        /// per row of the inner columns.
+        Blocked(usize),
+    }
+
+    impl AccumulationOrder {
+        /// The number of terms summed before each update of the running sum.
+        pub fn block_size(&self) -> usize {
+            match self {
+                AccumulationOrder::Sequential => 1,
+                AccumulationOrder::Blocked(n) => (*n).max(1),
+            }
+        }
+    }
+
+    /// Reference forward pass of an affine layer, `x W^T + b` for an input `inputs[0]` of shape
+    /// `[rows, in]`, weights `inputs[1]` of shape `[out, in]` and an optional bias `inputs[2]`
+    /// of length `out`. Each output starts from its bias and accumulates its products in
+    /// `order`, as [crate::circuit::ops::layouts::affine] does.
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::accumulated::{affine, AccumulationOrder};
+    ///
+    /// let x = Tensor::<f32>::new(Some(&[1e8, 1.0, -1e8, 1.0]), &[1, 4]).unwrap();
+    /// let w = Tensor::<f32>::new(Some(&[1.0, 1.0, 1.0, 1.0]), &[1, 4]).unwrap();
+    ///
+    /// // one at a time only the first 1 is lost to rounding, as it is added to 1e8, in blocks of
+    /// // two both are
+    /// let sequential = affine(&[x.clone(), w.clone()], AccumulationOrder::Sequential).unwrap();
+    /// assert_eq!(sequential[0], 1.0);
+    /// let blocked = affine(&[x.clone(), w.clone()], AccumulationOrder::Blocked(2)).unwrap();
+    /// assert_eq!(blocked[0], 0.0);
+    ///
+    /// // the bias starts the accumulation, so it is lost to rounding too rather than added to
+    /// // the final 1
+    /// let b = Tensor::<f32>::new(Some(&[1.0]), &[1]).unwrap();
+    /// let biased = affine(&[x, w, b], AccumulationOrder::Sequential).unwrap();
+    /// assert_eq!(biased[0], 1.0);
+    /// ```
+    pub fn affine<T: NumericTensorType>(
+        inputs: &[Tensor<T>],
+        order: AccumulationOrder,
+    ) -> Result<Tensor<T>, TensorError> {
+        if inputs.len() != 2 && inputs.len() != 3 {
+            return Err(TensorError::DimMismatch("affine".to_string()));
+        }
+        let (x, w) = (&inputs[0], &inputs[1]);
+        if x.dims().len() != 2 || w.dims().len() != 2 || x.dims()[1] != w.dims()[1] {
+            return Err(TensorError::DimMismatch("affine".to_string()));
+        }
+        let (num_rows, in_features, out_features) = (x.dims()[0], x.dims()[1], w.dims()[0]);
+        let bias = inputs.get(2);
+        if bias.is_some_and(|b| b.len() != out_features) {
+            return Err(TensorError::DimMismatch("affine bias".to_string()));
+        }
+
+        let mut output = Tensor::new(None, &[num_rows, out_features])?;
+        for i in 0..num_rows {
+            let row = x.get_slice(&[i..i + 1, 0..in_features])?;
+            for k in 0..out_features {
+                let col = w.get_slice(&[k..k + 1, 0..in_features])?;
+                let init = match bias {
+                    Some(bias) => bias[k].clone(),
+                    None => T::zero().unwrap(),
+                };
+                let acc = row
+                    .iter()
+                    .zip(col.iter())
+                    .chunks(order.block_size())
+                    .into_iter()
+                    .fold(init, |acc, block| {
+                        let block = block.fold(T::zero().unwrap(), |sum, (a, b)| {
+                            sum + a.clone() * b.clone()
+                        });
+                        acc + block
+                    });
+                output.set(&[i, k], acc);
+            }
+        }
+        Ok(output)
+    }
+
     /// Dot product of two tensors.
     /// # Arguments
     ///
@@ -2336,7 +2669,7 @@ pub mod accumulated {
     /// ).unwrap();
     /// assert_eq!(dot(&[x, y], 1).unwrap(), expected);
     /// ```
-    pub fn dot<T: TensorType + Mul<Output = T> + Add<Output = T>>(
+    pub fn dot<T: NumericTensorType>(
         inputs: &[Tensor<T>; 2],
         chunk_size: usize,
     ) -> Result<Tensor<T>, TensorError> {
@@ -2382,7 +2715,7 @@ pub mod accumulated {
     /// ).unwrap();
     /// assert_eq!(result, expected);
     /// ```
-    pub fn sum<T: TensorType + Mul<Output = T> + Add<Output = T>>(
+    pub fn sum<T: NumericTensorType>(
         a: &Tensor<T>,
         chunk_size: usize,
     ) -> Result<Tensor<T>, TensorError> {
@@ -2420,7 +2753,7 @@ pub mod accumulated {
     /// ).unwrap();
     /// assert_eq!(result, expected);
     /// ```
-    pub fn prod<T: TensorType + Mul<Output = T> + Add<Output = T>>(
+    pub fn prod<T: NumericTensorType>(
         a: &Tensor<T>,
         chunk_size: usize,
     ) -> Result<Tensor<T>, TensorError> {
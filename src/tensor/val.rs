@@ -373,6 +373,30 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> ValTensor<F> {
         inner.into()
     }
 
+    /// Creates a parameter tensor from integer representations. Frozen parameters (`trainable`
+    /// false) are constants, assigned to fixed columns and so committed to by the verifying key,
+    /// a prover can't alter them without the proof failing to verify. Trainable parameters are
+    /// private advice the prover is free to choose, e.g. the weights a proven training step
+    /// updates.
+    ///
+    /// This is for circuits laid out directly with the circuit API. Models loaded into
+    /// [crate::graph] still place all of their parameters by `RunArgs::param_visibility`.
+    ///
+    /// # Arguments
+    /// * `t` - Tensor of integer values to convert to field elements
+    /// * `trainable` - Whether the parameters are witnessed as advice rather than fixed
+    pub fn from_integer_rep_param(t: Tensor<IntegerRep>, trainable: bool) -> ValTensor<F> {
+        let inner = t.map(|x| {
+            let x = integer_rep_to_felt(x);
+            if trainable {
+                ValType::Value(Value::known(x))
+            } else {
+                ValType::Constant(x)
+            }
+        });
+        inner.into()
+    }
+
     /// Creates a new public input instance column
     ///
     /// # Arguments
@@ -428,6 +452,43 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> ValTensor<F> {
         }
     }
 
+    /// Maps a coordinate in the `instance`-th tensor of an instance column to its row in the
+    /// column. Each tensor occupies a contiguous block after the initial offset and the tensors
+    /// before it, laid out in row-major order, so for a `[C, H, W]` tensor `(c, h, w)` sits at
+    /// `c * H * W + h * W + w` within its block.
+    pub fn instance_row(&self, instance: usize, coord: &[usize]) -> Result<usize, TensorError> {
+        match self {
+            ValTensor::Instance {
+                dims,
+                initial_offset,
+                ..
+            } => {
+                let shape = dims
+                    .get(instance)
+                    .ok_or(TensorError::IndexOutOfBounds(instance, dims.len()))?;
+                if coord.len() != shape.len() {
+                    return Err(TensorError::DimMismatch(format!(
+                        "coordinate {:?} for an instance of shape {:?}",
+                        coord, shape
+                    )));
+                }
+                let mut row = 0;
+                for (c, d) in coord.iter().zip(shape) {
+                    if c >= d {
+                        return Err(TensorError::IndexOutOfBounds(*c, *d));
+                    }
+                    row = row * d + c;
+                }
+                let preceding = dims[..instance]
+                    .iter()
+                    .map(|x| x.iter().product::<usize>())
+                    .sum::<usize>();
+                Ok(initial_offset + preceding + row)
+            }
+            _ => Err(TensorError::WrongMethod),
+        }
+    }
+
     /// Returns true if this is an Instance tensor
     pub fn is_instance(&self) -> bool {
         matches!(self, ValTensor::Instance { .. })
@@ -608,6 +669,36 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> ValTensor<F> {
         Ok(res)
     }
 
+    /// Gets the value of each cell, e.g. to check intermediate values of a circuit under the
+    /// MockProver. Unlike [ValTensor::get_felt_evals] the result keeps the shape of the tensor and
+    /// cells whose value is unknown (as in keygen) are `None`.
+    ///
+    /// # Errors
+    /// Returns an error if called on an Instance tensor
+    /// ```
+    /// use ezkl::tensor::{Tensor, ValTensor, ValType};
+    /// use halo2_proofs::circuit::Value;
+    /// use halo2curves::bn256::Fr as Fp;
+    ///
+    /// let x: ValTensor<Fp> = Tensor::from(
+    ///     vec![ValType::Value(Value::known(Fp::from(3))), ValType::Value(Value::unknown())].into_iter(),
+    /// )
+    /// .into();
+    /// assert_eq!(x.felt_values().unwrap().to_vec(), vec![Some(Fp::from(3)), None]);
+    /// assert_eq!(x.int_values().unwrap().to_vec(), vec![Some(3), None]);
+    /// ```
+    pub fn felt_values(&self) -> Result<Tensor<Option<F>>, TensorError> {
+        match self {
+            ValTensor::Value { inner: v, .. } => Ok(v.map(|vaf| vaf.get_felt_eval())),
+            _ => Err(TensorError::WrongMethod),
+        }
+    }
+
+    /// Gets the value of each cell as an integer, see [ValTensor::felt_values].
+    pub fn int_values(&self) -> Result<Tensor<Option<IntegerRep>>, TensorError> {
+        Ok(self.felt_values()?.map(|f| f.map(felt_to_integer_rep)))
+    }
+
     /// Checks if this is a singleton tensor (1 element)
     pub fn is_singleton(&self) -> bool {
         match self {